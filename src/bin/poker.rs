@@ -0,0 +1,551 @@
+use std::env;
+use std::fs::File;
+use std::io::{BufReader, Write};
+use std::process;
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+use misc::poker::allin::{AllInConfig, AllInTable};
+use misc::poker::atomic;
+use misc::poker::audit;
+use misc::poker::backend::{self, Backend, Evaluator, Packed};
+use misc::poker::call;
+use misc::poker::chubukov::{ChubukovConfig, ChubukovTable};
+use misc::poker::cli::{Command, Flag, Program, Shell, Value};
+use misc::poker::config::{Config, OutputFormat};
+use misc::poker::dataset::{self, Schema};
+use misc::poker::fairness::{self, Source};
+use misc::poker::{Board, Cards};
+use misc::poker::history::{self, Columns, Field};
+use misc::poker::json::Json;
+use misc::poker::kuhn::{self, Kuhn};
+use misc::poker::matchups::{MatchupConfig, MatchupTable};
+use misc::poker::range::Range;
+use misc::poker::semibluff::Bet;
+use misc::poker::serve;
+use misc::poker::starting::StartingHand;
+use misc::poker::stats::Watch;
+
+const USAGE: &str = "\
+usage: poker [--config FILE] <command> [options]
+       poker --describe-json
+
+commands:
+  allin --out FILE [--samples N] [--callers K] [--seed S] [--hand AKs]
+  audit FILE
+  call --hole CARDS [--board CARDS] --pot P --to-call C
+       (--equity E | --range R [--samples N] [--seed S])
+  chubukov --out FILE [--samples N] [--seed S] [--hand AKs]
+  completions bash|zsh|fish
+  dataset --spots N --schema showdown --out FILE [--seed S]
+  fairness --deals N [--rng chacha8|std|os] [--seed S]
+  import --from FILE --out FILE [--tsv] [--columns field=Name,...]
+  kuhn --iterations N [--csv FILE] [--trace FILE] [--trace-every K]
+  matchups --out FILE [--samples N] [--seed S] [--hand AKs --against QQ]
+  semibluff --hole CARDS [--board CARDS] --pot P --bet B
+            (--fold F --equity E | --range R --calls R [--samples N] [--seed S])
+  serve
+  stats watch DIR [--interval SECS] [--out FILE] [--state FILE] [--once]
+  verify [--backend lookup|packed|twoplustwo] [--table FILE]
+
+settings are read from ~/.config/poker/config.toml unless --config is given;
+see src/poker/config.rs for what it can hold.
+";
+
+// The commands as data, for completions and --describe-json. Kept in step
+// with USAGE and the flags each command reads.
+const SAMPLES: Flag = Flag::value("--samples", Value::new("N"), "samples to draw");
+const SEED: Flag = Flag::value("--seed", Value::new("S"), "random seed, the clock if not given");
+const HOLE: Flag = Flag::value("--hole", Value::new("CARDS"), "the two hole cards");
+const BOARD: Flag = Flag::value("--board", Value::new("CARDS"), "the board so far");
+const POT: Flag = Flag::value("--pot", Value::new("P"), "the pot before the bet");
+const RANGE: Flag = Flag::value("--range", Value::new("R"), "the opponent's range");
+const EQUITY: Flag = Flag::value("--equity", Value::new("E"), "equity when called");
+const OUT: Flag = Flag::value("--out", Value::new("FILE"), "file to write");
+const STARTING: Flag = Flag::value("--hand", Value::new("HAND"), "one starting hand, such as AKs");
+
+const PROGRAM: Program = Program{
+    name: "poker",
+    flags: &[
+        Flag::value("--config", Value::new("FILE"), "settings file to read"),
+        Flag::switch("--describe-json", "print the commands and flags as JSON"),
+    ],
+    commands: &[
+        Command::new("allin", "all in equities against random callers").flags(&[
+            OUT, SAMPLES,
+            Flag::value("--callers", Value::new("K"), "number of callers"),
+            SEED, STARTING,
+        ]),
+        Command::new("audit", "check a hand history file").args(&[Value::new("FILE")]),
+        Command::new("call", "EV of calling an all in").flags(&[
+            HOLE, BOARD, POT,
+            Flag::value("--to-call", Value::new("C"), "the amount to call"),
+            EQUITY, RANGE, SAMPLES, SEED,
+        ]),
+        Command::new("chubukov", "Sklansky-Chubukov numbers").flags(&[OUT, SAMPLES, SEED, STARTING]),
+        Command::new("completions", "print a shell completion script")
+            .args(&[Value::one_of("SHELL", Shell::NAMES)]),
+        Command::new("dataset", "generate a training dataset").flags(&[
+            Flag::value("--spots", Value::new("N"), "number of spots"),
+            Flag::value("--schema", Value::one_of("SCHEMA", &["showdown"]), "what each row holds"),
+            OUT, SEED,
+        ]),
+        Command::new("fairness", "test a shuffle for bias").flags(&[
+            Flag::value("--deals", Value::new("N"), "number of deals"),
+            Flag::value("--rng", Value::one_of("RNG", &["chacha8", "std", "os"]), "the generator"),
+            SEED,
+        ]),
+        Command::new("import", "import hands from another CSV or TSV layout").flags(&[
+            Flag::value("--from", Value::new("FILE"), "file to read"),
+            OUT,
+            Flag::switch("--tsv", "read tab separated values"),
+            Flag::value("--columns", Value::new("MAP"), "field=Name pairs for the columns"),
+        ]),
+        Command::new("kuhn", "solve Kuhn poker").flags(&[
+            Flag::value("--iterations", Value::new("N"), "solver iterations"),
+            Flag::value("--csv", Value::new("FILE"), "write the strategy as CSV"),
+            Flag::value("--trace", Value::new("FILE"), "write snapshots as training goes"),
+            Flag::value("--trace-every", Value::new("K"), "iterations between trace rows"),
+        ]),
+        Command::new("matchups", "preflop equity of every starting hand against every other").flags(&[
+            OUT, SAMPLES, SEED, STARTING,
+            Flag::value("--against", Value::new("HAND"), "the other starting hand"),
+        ]),
+        Command::new("semibluff", "EV of a bet that may be called").flags(&[
+            HOLE, BOARD, POT,
+            Flag::value("--bet", Value::new("B"), "the bet"),
+            Flag::value("--fold", Value::new("F"), "how often the bet is folded to"),
+            EQUITY, RANGE,
+            Flag::value("--calls", Value::new("R"), "the part of the range that calls"),
+            SAMPLES, SEED,
+        ]),
+        Command::new("serve", "answer JSON requests on standard input"),
+        Command::new("stats", "statistics over hand histories").subcommands(&[
+            Command::new("watch", "follow a directory of histories").args(&[Value::new("DIR")]).flags(&[
+                Flag::value("--interval", Value::new("SECS"), "seconds between scans"),
+                Flag::value("--out", Value::new("FILE"), "file to write the statistics to"),
+                Flag::value("--state", Value::new("FILE"), "file to carry the totals over restarts in"),
+                Flag::switch("--once", "scan once and stop"),
+            ]),
+        ]),
+        Command::new("verify", "check an evaluator against the naive one on every hand").flags(&[
+            Flag::value("--backend", Value::one_of("BACKEND", &["naive", "lookup", "packed", "twoplustwo"]), "the evaluator to check"),
+            Flag::value("--table", Value::new("FILE"), "a twoplustwo table to load instead of building one"),
+        ]),
+    ],
+};
+
+fn main() {
+    let mut args: Vec<String> = env::args().skip(1).collect();
+
+    if args.first().map(String::as_str) == Some("--describe-json") {
+        println!("{}", PROGRAM.describe());
+        return;
+    }
+
+    let config = match args.first().map(String::as_str) {
+        Some("--config") if args.len() > 1 => {
+            let path = args[1].clone();
+            args.drain(..2);
+            Config::load(&path).map_err(|e| format!("{}: {}", path, e))
+        },
+        _ => Config::load_default().map_err(|e| format!("config: {}", e)),
+    };
+    let config = match config {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("{}", e);
+            process::exit(1);
+        },
+    };
+
+    let result = match args.first().map(String::as_str) {
+        Some("allin") => cmd_allin(&args[1..], &config),
+        Some("audit") => cmd_audit(&args[1..]),
+        Some("call") => cmd_call(&args[1..], &config),
+        Some("chubukov") => cmd_chubukov(&args[1..], &config),
+        Some("completions") => cmd_completions(&args[1..]),
+        Some("dataset") => cmd_dataset(&args[1..], &config),
+        Some("fairness") => cmd_fairness(&args[1..], &config),
+        Some("import") => cmd_import(&args[1..]),
+        Some("kuhn") => cmd_kuhn(&args[1..], &config),
+        Some("matchups") => cmd_matchups(&args[1..], &config),
+        Some("semibluff") => cmd_semibluff(&args[1..], &config),
+        Some("serve") => cmd_serve(&config),
+        Some("stats") => cmd_stats(&args[1..]),
+        Some("verify") => cmd_verify(&args[1..]),
+        _ => Err(USAGE.to_string()),
+    };
+
+    if let Err(e) = result {
+        eprintln!("{}", e);
+        process::exit(1);
+    }
+}
+
+fn flag<'a>(args: &'a [String], name: &str) -> Option<&'a str> {
+    args.iter()
+        .position(|a| a == name)
+        .and_then(|i| args.get(i + 1))
+        .map(String::as_str)
+}
+
+// The flag, the config file's setting, or the clock, in that order.
+fn seed(args: &[String], config: &Config) -> Result<u64, String> {
+    match flag(args, "--seed") {
+        Some(s) => s.parse().map_err(|e| format!("--seed: {}", e)),
+        None => Ok(config.seed.unwrap_or_else(|| SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs())),
+    }
+}
+
+fn samples(args: &[String], config: &Config, default: usize) -> Result<usize, String> {
+    match flag(args, "--samples") {
+        Some(s) => s.parse().map_err(|e| format!("--samples: {}", e)),
+        None => Ok(config.samples.unwrap_or(default)),
+    }
+}
+
+// A table path from the flag or the config file.
+fn table_path(args: &[String], configured: &Option<std::path::PathBuf>) -> Result<std::path::PathBuf, String> {
+    match flag(args, "--out") {
+        Some(path) => Ok(path.into()),
+        None => configured.clone().ok_or_else(|| "missing --out".to_string()),
+    }
+}
+
+// Results as one line of text, with the EV colored by sign, or as JSON.
+fn print_results(config: &Config, fields: &[(&str, f64)]) {
+    match config.format {
+        OutputFormat::Json => {
+            println!("{}", Json::object(fields.iter().map(|&(k, v)| (k, v.into())).collect()));
+        },
+        OutputFormat::Text => {
+            let text: Vec<String> = fields.iter()
+                .map(|&(k, v)| {
+                    let n = format!("{:.3}", v);
+                    format!("{} {}", k, if k == "ev" { config.paint(&n, v >= 0.0) } else { n })
+                })
+                .collect();
+            println!("{}", text.join(" "));
+        },
+    }
+}
+
+fn cmd_allin(args: &[String], settings: &Config) -> Result<(), String> {
+    let mut config = AllInConfig::default();
+    config.samples = samples(args, settings, config.samples)?;
+    if let Some(s) = flag(args, "--callers") {
+        config.callers = s.parse().map_err(|e| format!("--callers: {}", e))?;
+    }
+    if let Some(s) = flag(args, "--seed") {
+        config.seed = s.parse().map_err(|e| format!("--seed: {}", e))?;
+    } else if let Some(seed) = settings.seed {
+        config.seed = seed;
+    }
+    let out = table_path(args, &settings.allin_table)?;
+
+    let table = AllInTable::load_or_generate(out, config).map_err(|e| e.to_string())?;
+
+    if let Some(s) = flag(args, "--hand") {
+        let hand: StartingHand = s.parse().map_err(|e| format!("--hand: {}", e))?;
+        for callers in 1..=config.callers {
+            println!("{} vs {}: {:.3}", hand, callers, table.equity(hand, callers).unwrap());
+        }
+    }
+    Ok(())
+}
+
+fn cmd_audit(args: &[String]) -> Result<(), String> {
+    let path = args.first().ok_or("missing FILE")?;
+
+    let f = File::open(path).map_err(|e| e.to_string())?;
+    let hands = history::read_csv(BufReader::new(f)).map_err(|e| format!("{}: {}", path, e))?;
+    let issues = audit::audit(&hands);
+
+    for issue in &issues {
+        println!("{}", issue);
+    }
+    eprintln!("{} hands, {} issues", hands.len(), issues.len());
+    Ok(())
+}
+
+fn cmd_call(args: &[String], config: &Config) -> Result<(), String> {
+    let options = config.parse_options();
+    let hole = Cards::parse_with(flag(args, "--hole").ok_or("missing --hole")?, &options, 2..=2)
+        .map_err(|e| format!("--hole: {}", e))?;
+    let board = Board::parse_with(flag(args, "--board").unwrap_or(""), &options)
+        .map_err(|e| format!("--board: {}", e))?;
+    let (pot, to_call) = (number(args, "--pot")?, number(args, "--to-call")?);
+
+    let r = match flag(args, "--range") {
+        Some(range) => {
+            let range = Range::parse_with(range, &options).map_err(|e| format!("--range: {}", e))?;
+            let samples = samples(args, config, 10_000)?;
+
+            let mut rng = ChaCha8Rng::seed_from_u64(seed(args, config)?);
+            call::ev_call([hole[0], hole[1]], &range, pot, to_call, &board, samples, &mut rng)
+                .map_err(|e| e.to_string())?
+        },
+        None => {
+            let equity = number(args, "--equity")?;
+            call::CallEv{equity, break_even: call::break_even_equity(pot, to_call), ev: call::call_ev(pot, to_call, equity)}
+        },
+    };
+
+    print_results(config, &[("equity", r.equity), ("break-even", r.break_even), ("ev", r.ev)]);
+    Ok(())
+}
+
+fn cmd_chubukov(args: &[String], settings: &Config) -> Result<(), String> {
+    let mut config = ChubukovConfig::default();
+    config.samples = samples(args, settings, config.samples)?;
+    if let Some(s) = flag(args, "--seed") {
+        config.seed = s.parse().map_err(|e| format!("--seed: {}", e))?;
+    } else if let Some(seed) = settings.seed {
+        config.seed = seed;
+    }
+    let out = table_path(args, &settings.chubukov_table)?;
+
+    let table = ChubukovTable::load_or_generate(out, config).map_err(|e| e.to_string())?;
+
+    match flag(args, "--hand") {
+        Some(s) => {
+            let hand: StartingHand = s.parse().map_err(|e| format!("--hand: {}", e))?;
+            println!("{}: {:.1}", hand, table.number(hand));
+        },
+        None => println!("{}", table.numbers().map(|n| format!("{:.0}", n))),
+    }
+    Ok(())
+}
+
+fn cmd_completions(args: &[String]) -> Result<(), String> {
+    let name = args.first().ok_or("missing shell")?;
+    let shell = Shell::parse(name).ok_or_else(|| format!("unknown shell '{}'", name))?;
+    print!("{}", PROGRAM.completions(shell));
+    Ok(())
+}
+
+fn cmd_dataset(args: &[String], config: &Config) -> Result<(), String> {
+    let spots: usize = flag(args, "--spots")
+        .ok_or("missing --spots")?
+        .parse()
+        .map_err(|e| format!("--spots: {}", e))?;
+    let schema: Schema = flag(args, "--schema")
+        .unwrap_or("showdown")
+        .parse()
+        .map_err(|e| format!("--schema: {}", e))?;
+    let out = flag(args, "--out").ok_or("missing --out")?;
+    let seed = seed(args, config)?;
+
+    let data = dataset::generate(spots, schema, seed);
+    data.write_to(out).map_err(|e| e.to_string())?;
+
+    eprintln!("wrote {} {} rows to {} (seed {})", spots, schema, out, seed);
+    Ok(())
+}
+
+fn cmd_fairness(args: &[String], config: &Config) -> Result<(), String> {
+    let deals: usize = flag(args, "--deals")
+        .ok_or("missing --deals")?
+        .parse()
+        .map_err(|e| format!("--deals: {}", e))?;
+    if deals == 0 { return Err("--deals: must be at least 1".to_string()); }
+    let seed = seed(args, config)?;
+    let rng = flag(args, "--rng").unwrap_or("chacha8");
+    let source = Source::parse(rng, seed).ok_or_else(|| format!("--rng: unknown generator '{}'", rng))?;
+
+    println!("{:?}", source);
+    print!("{}", fairness::run(source, deals));
+    Ok(())
+}
+
+fn cmd_import(args: &[String]) -> Result<(), String> {
+    let from = flag(args, "--from").ok_or("missing --from")?;
+    let out = flag(args, "--out").ok_or("missing --out")?;
+
+    let mut columns = if args.iter().any(|a| a == "--tsv") { Columns::tsv() } else { Columns::default() };
+    for mapping in flag(args, "--columns").unwrap_or("").split(',').filter(|m| !m.is_empty()) {
+        let (field, name) = mapping.split_once('=').ok_or(format!("--columns: bad mapping '{}'", mapping))?;
+        let field: Field = field.parse().map_err(|e| format!("--columns: {}", e))?;
+        columns = columns.column(field, name);
+    }
+
+    let io = |e: std::io::Error| e.to_string();
+    let hands = columns
+        .import(BufReader::new(File::open(from).map_err(io)?))
+        .map_err(|e| format!("{}: {}", from, e))?;
+    history::write_to(&hands, out).map_err(io)?;
+
+    eprintln!("imported {} hands from {} to {}", hands.len(), from, out);
+    Ok(())
+}
+
+fn cmd_kuhn(args: &[String], config: &Config) -> Result<(), String> {
+    let iterations: usize = match flag(args, "--iterations") {
+        Some(s) => s.parse().map_err(|e| format!("--iterations: {}", e))?,
+        None => config.iterations.ok_or("missing --iterations")?,
+    };
+    let trace_every: usize = match flag(args, "--trace-every") {
+        Some(s) => s.parse().map_err(|e| format!("--trace-every: {}", e))?,
+        None => (iterations / 100).max(1),
+    };
+
+    let mut solver = Kuhn::new();
+    let trace = solver.train(iterations, trace_every);
+    let table = solver.average();
+
+    print!("{}", table);
+    println!("exploitability {:.6}", kuhn::exploitability(&table));
+
+    let io = |e: std::io::Error| e.to_string();
+    if let Some(path) = flag(args, "--csv") {
+        atomic::write(path, |w| table.write_csv(w)).map_err(io)?;
+    }
+    if let Some(path) = flag(args, "--trace") {
+        atomic::write(path, |w| trace.write_csv(w, &table.game)).map_err(io)?;
+    }
+    Ok(())
+}
+
+fn number(args: &[String], name: &str) -> Result<f64, String> {
+    flag(args, name)
+        .ok_or(format!("missing {}", name))?
+        .parse()
+        .map_err(|e| format!("{}: {}", name, e))
+}
+
+fn cmd_matchups(args: &[String], settings: &Config) -> Result<(), String> {
+    let mut config = MatchupConfig::default();
+    config.samples = samples(args, settings, config.samples)?;
+    if let Some(s) = flag(args, "--seed") {
+        config.seed = s.parse().map_err(|e| format!("--seed: {}", e))?;
+    } else if let Some(seed) = settings.seed {
+        config.seed = seed;
+    }
+    let out = table_path(args, &settings.matchups_table)?;
+
+    let table = MatchupTable::load_or_generate(out, config).map_err(|e| e.to_string())?;
+
+    if let (Some(a), Some(b)) = (flag(args, "--hand"), flag(args, "--against")) {
+        let a: StartingHand = a.parse().map_err(|e| format!("--hand: {}", e))?;
+        let b: StartingHand = b.parse().map_err(|e| format!("--against: {}", e))?;
+        println!("{} vs {}: {:.3}", a, b, table.equity(a, b));
+    }
+    Ok(())
+}
+
+fn cmd_semibluff(args: &[String], config: &Config) -> Result<(), String> {
+    let options = config.parse_options();
+    let hole = Cards::parse_with(flag(args, "--hole").ok_or("missing --hole")?, &options, 2..=2)
+        .map_err(|e| format!("--hole: {}", e))?;
+    let board = Board::parse_with(flag(args, "--board").unwrap_or(""), &options)
+        .map_err(|e| format!("--board: {}", e))?;
+
+    let bet = Bet{
+        hole: [hole[0], hole[1]],
+        board,
+        pot: number(args, "--pot")?,
+        bet: number(args, "--bet")?,
+    };
+
+    let (fold, equity, ev) = match flag(args, "--range") {
+        Some(range) => {
+            let range = Range::parse_with(range, &options).map_err(|e| format!("--range: {}", e))?;
+            let calls = Range::parse_with(flag(args, "--calls").ok_or("missing --calls")?, &options)
+                .map_err(|e| format!("--calls: {}", e))?;
+            let samples = samples(args, config, 10_000)?;
+
+            let mut rng = ChaCha8Rng::seed_from_u64(seed(args, config)?);
+            let r = bet.against_range(&range, &calls, samples, &mut rng).map_err(|e| e.to_string())?;
+            (r.fold, r.equity, r.ev)
+        },
+        None => {
+            let (fold, equity) = (number(args, "--fold")?, number(args, "--equity")?);
+            (fold, equity, bet.ev(fold, equity))
+        },
+    };
+
+    print_results(config, &[("fold", fold), ("equity", equity), ("ev", ev)]);
+    Ok(())
+}
+
+fn cmd_serve(config: &Config) -> Result<(), String> {
+    let stdin = std::io::stdin();
+    let stdout = std::io::stdout();
+    serve::serve_with(stdin.lock(), &mut stdout.lock(), &config.parse_options()).map_err(|e| e.to_string())
+}
+
+fn cmd_stats(args: &[String]) -> Result<(), String> {
+    let dir = match args {
+        [watch, dir, ..] if watch == "watch" => dir,
+        _ => return Err(USAGE.to_string()),
+    };
+    let interval: u64 = match flag(args, "--interval") {
+        Some(s) => s.parse().map_err(|e| format!("--interval: {}", e))?,
+        None => 2,
+    };
+    let once = args.iter().any(|a| a == "--once");
+
+    let io = |e: std::io::Error| e.to_string();
+    let state = flag(args, "--state");
+    let mut watch = match state {
+        Some(path) => Watch::load(dir, path).map_err(|e| format!("--state: {}", e))?,
+        None => Watch::new(dir),
+    };
+    let mut first = true;
+    loop {
+        let poll = watch.poll().map_err(io)?;
+        for (path, e) in &poll.failed {
+            eprintln!("{}: {}, no longer watched", path.display(), e);
+        }
+
+        if poll.hands > 0 || first {
+            if !once { print!("\x1b[2J\x1b[H"); }
+            print!("{}", watch.stats);
+            std::io::stdout().flush().map_err(io)?;
+
+            if let Some(path) = flag(args, "--out") {
+                atomic::write(path, |w| watch.stats.write_csv(w)).map_err(io)?;
+            }
+            if let Some(path) = state {
+                watch.save(path).map_err(io)?;
+            }
+        }
+        if once { return Ok(()); }
+
+        first = false;
+        thread::sleep(Duration::from_secs(interval));
+    }
+}
+
+fn cmd_verify(args: &[String]) -> Result<(), String> {
+    let name = flag(args, "--backend").unwrap_or("lookup");
+    let evaluator: Box<dyn Evaluator> = match name {
+        "packed" => Box::new(Packed),
+        "twoplustwo" => twoplustwo_table(flag(args, "--table"))?,
+        _ => Box::new(Backend::parse(name).ok_or_else(|| format!("--backend: unknown evaluator '{}'", name))?),
+    };
+
+    let hands = backend::verify(evaluator.as_ref()).map_err(|e| e.to_string())?;
+    println!("{} agrees with {} on all {} hands", evaluator.name(), Backend::Naive, hands);
+    Ok(())
+}
+
+#[cfg(feature = "twoplustwo")]
+fn twoplustwo_table(path: Option<&str>) -> Result<Box<dyn Evaluator>, String> {
+    use misc::poker::twoplustwo::TwoPlusTwo;
+
+    let table = match path {
+        Some(path) => TwoPlusTwo::load(path).map_err(|e| format!("--table: {}", e))?,
+        None => TwoPlusTwo::build(5),
+    };
+    Ok(Box::new(table))
+}
+
+#[cfg(not(feature = "twoplustwo"))]
+fn twoplustwo_table(_: Option<&str>) -> Result<Box<dyn Evaluator>, String> {
+    Err("--backend: twoplustwo needs the twoplustwo feature".to_string())
+}
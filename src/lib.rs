@@ -1 +1 @@
-mod poker;
+pub mod poker;
@@ -0,0 +1,222 @@
+//! Preflop all-in equity tables for push/fold play.
+//!
+//! For each of the 169 starting hands and each number of callers, the table
+//! holds the hand's share of the pot when everyone is all in before the
+//! flop against callers holding random cards. Filling the table takes a
+//! while at useful sample counts, so `load_or_generate` keeps it on disk and
+//! only rebuilds it when the file is missing or was made with other
+//! settings.
+
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::Path;
+
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+use super::Card;
+use super::atomic;
+use super::deal::winners;
+use super::deck::Deck;
+use super::format::Header;
+use super::starting::StartingHand;
+
+const KIND: &str = "allin";
+const VERSION: u32 = 1;
+
+// Six-max: at most five callers.
+pub const MAX_CALLERS: usize = 5;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AllInConfig {
+    pub samples: usize,
+    pub callers: usize,
+    pub seed: u64,
+}
+
+/// Equity of each starting hand, indexed by `StartingHand::index`, then by
+/// the number of callers less one.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AllInTable {
+    pub config: AllInConfig,
+    equity: Vec<Vec<f64>>,
+}
+
+impl Default for AllInConfig {
+    fn default() -> Self {
+        AllInConfig{samples: 10_000, callers: MAX_CALLERS, seed: 0}
+    }
+}
+
+impl AllInTable {
+    pub fn generate(config: AllInConfig) -> Self {
+        assert!((1..=MAX_CALLERS).contains(&config.callers), "one to five callers");
+
+        let equity = StartingHand::all().iter().enumerate().map(|(i, hand)| {
+            (1..=config.callers).map(|callers| {
+                // Each cell has its own stream, so cells can be regenerated alone.
+                let mut rng = ChaCha8Rng::seed_from_u64(config.seed);
+                rng.set_stream((i * MAX_CALLERS + callers) as u64);
+                sample_equity(hand, callers, config.samples, &mut rng)
+            }).collect()
+        }).collect();
+
+        AllInTable{config, equity}
+    }
+
+    pub fn equity(&self, hand: StartingHand, callers: usize) -> Option<f64> {
+        if callers == 0 { return None; }
+        self.equity[hand.index()].get(callers - 1).cloned()
+    }
+
+    /// Expected profit in big blinds of shoving `stack` and being called by
+    /// `callers` players who each cover it, with `dead` money already in
+    /// the pot from players who folded.
+    pub fn ev(&self, hand: StartingHand, callers: usize, stack: f64, dead: f64) -> Option<f64> {
+        let pot = stack * (callers + 1) as f64 + dead;
+        self.equity(hand, callers).map(|e| e * pot - stack)
+    }
+
+    pub fn write<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        Header::new(KIND, VERSION)
+            .field("samples", self.config.samples)
+            .field("callers", self.config.callers)
+            .field("seed", self.config.seed)
+            .write(w)?;
+
+        write!(w, "hand")?;
+        for c in 1..=self.config.callers { write!(w, ",callers{}", c)?; }
+        writeln!(w)?;
+
+        for (hand, row) in StartingHand::all().iter().zip(&self.equity) {
+            write!(w, "{}", hand)?;
+            for e in row { write!(w, ",{}", e)?; }
+            writeln!(w)?;
+        }
+        Ok(())
+    }
+
+    pub fn read<R: BufRead>(r: R) -> io::Result<Self> {
+        let invalid = |e: String| io::Error::new(io::ErrorKind::InvalidData, e);
+
+        let mut lines = r.lines();
+        let first = lines.next().ok_or_else(|| invalid("empty file".to_string()))??;
+        let header = Header::parse(&first).map_err(|e| invalid(e.to_string()))?;
+        header.check(KIND, VERSION).map_err(|e| invalid(e.to_string()))?;
+
+        let field = |key: &str| header.get(key).and_then(|v| v.parse().ok())
+            .ok_or_else(|| invalid(format!("missing {}", key)));
+        let config = AllInConfig{
+            samples: field("samples")? as usize,
+            callers: field("callers")? as usize,
+            seed: field("seed")?,
+        };
+
+        let all = StartingHand::all();
+        let mut equity = vec![vec![]; all.len()];
+        for (n, line) in lines.enumerate().skip(1) {
+            let line = line?;
+            let bad = || invalid(format!("malformed row on line {}", n + 2));
+
+            let mut fields = line.split(',');
+            let hand: StartingHand = fields.next().and_then(|h| h.parse().ok()).ok_or_else(bad)?;
+            let row = fields.map(|f| f.parse().ok()).collect::<Option<Vec<f64>>>().ok_or_else(bad)?;
+            if row.len() != config.callers { return Err(bad()); }
+            equity[hand.index()] = row;
+        }
+        if equity.iter().any(|row| row.is_empty()) {
+            return Err(invalid("missing starting hands".to_string()));
+        }
+
+        Ok(AllInTable{config, equity})
+    }
+
+    // Reads the table at `path` if it was made with `config`, otherwise
+    // generates it and writes it there.
+    pub fn load_or_generate<P: AsRef<Path>>(path: P, config: AllInConfig) -> io::Result<Self> {
+        let path = path.as_ref();
+        atomic::remove_stale(path)?;
+
+        if let Ok(file) = File::open(path) {
+            if let Ok(table) = AllInTable::read(BufReader::new(file)) {
+                if table.config == config { return Ok(table); }
+            }
+        }
+
+        let table = AllInTable::generate(config);
+        atomic::write(path, |w| table.write(w))?;
+        Ok(table)
+    }
+}
+
+fn sample_equity(hand: &StartingHand, callers: usize, samples: usize, rng: &mut ChaCha8Rng) -> f64 {
+    let combos = hand.combos();
+    let mut won = 0.0;
+
+    for i in 0..samples {
+        let hero = combos[i % combos.len()];
+        let mut deck = Deck::without(hero);
+        deck.shuffle(rng);
+
+        let mut holes = vec![hero];
+        for _ in 0..callers {
+            holes.push([deck.deal().unwrap(), deck.deal().unwrap()]);
+        }
+        let board: Vec<Card> = (0..5).map(|_| deck.deal().unwrap()).collect();
+        let board = [board[0], board[1], board[2], board[3], board[4]];
+
+        let w = winners(&holes, &board);
+        if w.contains(&0) {
+            won += 1.0 / w.len() as f64;
+        }
+    }
+    won / samples.max(1) as f64
+}
+
+#[cfg(test)]
+mod allin_tests {
+    use super::*;
+
+    fn small() -> AllInConfig {
+        AllInConfig{samples: 12, callers: 2, seed: 5}
+    }
+
+    #[test]
+    fn test_generate() {
+        let table = AllInTable::generate(small());
+        let aa: StartingHand = "AA".parse().unwrap();
+        let trash: StartingHand = "72o".parse().unwrap();
+
+        assert!(table.equity(aa, 1).unwrap() > table.equity(trash, 1).unwrap());
+        assert!(table.equity(aa, 1).unwrap() > table.equity(aa, 2).unwrap());
+        assert_eq!(table.equity(aa, 3), None);
+        assert_eq!(table.equity(aa, 0), None);
+        assert_eq!(table, AllInTable::generate(small()));
+    }
+
+    #[test]
+    fn test_ev() {
+        let table = AllInTable::generate(small());
+        let aa: StartingHand = "AA".parse().unwrap();
+        let e = table.equity(aa, 1).unwrap();
+
+        assert!((table.ev(aa, 1, 10.0, 1.5).unwrap() - (e * 21.5 - 10.0)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_round_trip_and_cache() {
+        let table = AllInTable::generate(small());
+        let mut out = vec![];
+        table.write(&mut out).unwrap();
+
+        assert!(out.starts_with(b"# misc-poker allin v1 samples=12 callers=2 seed=5\nhand,callers1,callers2\nAA,"));
+        assert_eq!(AllInTable::read(&out[..]).unwrap(), table);
+        assert!(AllInTable::read(&out[..out.len() / 2]).is_err());
+
+        let path = std::env::temp_dir().join(format!("allin-{}.csv", std::process::id()));
+        std::fs::write(&path, "# misc-poker allin v1 samples=1 callers=2 seed=5\n").unwrap();
+        assert_eq!(AllInTable::load_or_generate(&path, small()).unwrap(), table);
+        assert_eq!(AllInTable::read(BufReader::new(File::open(&path).unwrap())).unwrap(), table);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
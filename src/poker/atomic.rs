@@ -0,0 +1,119 @@
+//! Writing files so that a crash or power cut leaves either the old file or
+//! the new one, never half of the new one. Everything the crate persists
+//! goes through `write`: the file is written under a temporary name beside
+//! the target, synced to disk, renamed over the target, and the directory
+//! synced so that the rename itself survives.
+//!
+//! A write that dies part way leaves only the temporary file, which the
+//! next write to the same path overwrites and `remove_stale` clears up.
+//! Readers still check what they read, since files may come from elsewhere:
+//! tables and datasets count their rows in the header and a short file is
+//! rejected, and caches read through `load_or_generate` are rebuilt.
+
+use std::ffi::OsString;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+/// Writes `path` through `f`, all or nothing.
+pub fn write<P, F>(path: P, f: F) -> io::Result<()>
+where
+    P: AsRef<Path>,
+    F: FnOnce(&mut BufWriter<File>) -> io::Result<()>,
+{
+    let path = path.as_ref();
+    let temp = temp_path(path);
+
+    let result = (|| {
+        let mut w = BufWriter::new(File::create(&temp)?);
+        f(&mut w)?;
+        w.flush()?;
+        w.get_ref().sync_all()?;
+        fs::rename(&temp, path)
+    })();
+    if result.is_err() {
+        let _ = fs::remove_file(&temp);
+    }
+    result?;
+
+    sync_dir(path)
+}
+
+/// Where `write` puts the file before it is complete: `.name.tmp` in the
+/// same directory, so that the rename never crosses file systems.
+pub fn temp_path(path: &Path) -> PathBuf {
+    let mut name = OsString::from(".");
+    name.push(path.file_name().unwrap_or_default());
+    name.push(".tmp");
+    path.with_file_name(name)
+}
+
+/// Removes what an interrupted `write` to `path` left behind, returning
+/// whether there was anything.
+pub fn remove_stale<P: AsRef<Path>>(path: P) -> io::Result<bool> {
+    match fs::remove_file(temp_path(path.as_ref())) {
+        Ok(()) => Ok(true),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+// Directories can only be synced on Unix; elsewhere the rename is as
+// durable as the file system makes it.
+#[cfg(unix)]
+fn sync_dir(path: &Path) -> io::Result<()> {
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    File::open(dir)?.sync_all()
+}
+
+#[cfg(not(unix))]
+fn sync_dir(_path: &Path) -> io::Result<()> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod atomic_tests {
+    use super::*;
+
+    fn path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("atomic-{}-{}", std::process::id(), name))
+    }
+
+    #[test]
+    fn test_write() {
+        let p = path("write.csv");
+        write(&p, |w| w.write_all(b"first\n")).unwrap();
+        write(&p, |w| w.write_all(b"second\n")).unwrap();
+
+        assert_eq!(fs::read_to_string(&p).unwrap(), "second\n");
+        assert!(!temp_path(&p).exists());
+        fs::remove_file(&p).unwrap();
+    }
+
+    #[test]
+    fn test_failed_write() {
+        let p = path("failed.csv");
+        write(&p, |w| w.write_all(b"kept\n")).unwrap();
+
+        let failed = write(&p, |w| {
+            w.write_all(b"half")?;
+            Err(io::Error::other("interrupted"))
+        });
+        assert!(failed.is_err());
+        assert_eq!(fs::read_to_string(&p).unwrap(), "kept\n");
+        assert!(!temp_path(&p).exists());
+        fs::remove_file(&p).unwrap();
+    }
+
+    #[test]
+    fn test_remove_stale() {
+        let p = path("stale.csv");
+        fs::write(temp_path(&p), "half").unwrap();
+        assert!(remove_stale(&p).unwrap());
+        assert!(!remove_stale(&p).unwrap());
+        assert_eq!(temp_path(Path::new("t.csv")), Path::new(".t.csv.tmp"));
+    }
+}
@@ -0,0 +1,201 @@
+//! Consistency checks on imported hand histories, so that hands corrupted
+//! on export or edited by hand are found before they are counted.
+//!
+//! Every hand is checked for cards dealt twice and, where the history
+//! records them, for a pot that matches the chips put in and for winnings
+//! and rake that add up to the pot. Across hands at the same table each
+//! player's starting stack should be what they ended the last hand with.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use super::Card;
+use super::board::Street;
+use super::history::{HandHistory, Play};
+
+// Amounts are money, so anything under half a cent is rounding.
+const EPSILON: f64 = 0.005;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Issue {
+    DuplicateCard { hand: String, card: Card },
+    // The recorded pot is not what the actions put in.
+    Pot { hand: String, recorded: f64, actions: f64 },
+    // What was collected plus the rake is not the pot.
+    Payout { hand: String, pot: f64, paid: f64 },
+    Stack { hand: String, player: String, expected: f64, found: f64 },
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Issue::DuplicateCard { hand, card } => {
+                write!(f, "hand {}: {} dealt twice", hand, card)
+            },
+            Issue::Pot { hand, recorded, actions } => {
+                write!(f, "hand {}: pot recorded as {} but actions put in {}", hand, recorded, actions)
+            },
+            Issue::Payout { hand, pot, paid } => {
+                write!(f, "hand {}: pot of {} but {} collected with rake", hand, pot, paid)
+            },
+            Issue::Stack { hand, player, expected, found } => write!(
+                f,
+                "hand {}: {} starts with {}, expected {} from the last hand",
+                hand, player, found, expected
+            ),
+        }
+    }
+}
+
+pub fn audit(hands: &[HandHistory]) -> Vec<Issue> {
+    let mut issues = vec![];
+    // The stack each player left the last hand at a table with.
+    let mut tables: HashMap<&str, HashMap<&str, f64>> = HashMap::new();
+
+    for hand in hands {
+        let id = || hand.id.clone();
+
+        let mut cards: Vec<Card> = hand.board.to_vec();
+        for seat in &hand.seats {
+            cards.extend(seat.hole.iter().flat_map(|h| h.iter()));
+        }
+        for (i, card) in cards.iter().enumerate() {
+            if cards[i + 1..].contains(card) {
+                issues.push(Issue::DuplicateCard{hand: id(), card: *card});
+            }
+        }
+
+        let invested = invested(hand);
+        let actions: f64 = invested.values().sum();
+        if let Some(recorded) = hand.pot {
+            if (recorded - actions).abs() > EPSILON {
+                issues.push(Issue::Pot{hand: id(), recorded, actions});
+            }
+        }
+
+        let collected = |player: &str| -> f64 {
+            hand.actions.iter()
+                .filter(|a| a.player == player)
+                .filter_map(|a| match a.play { Play::Collect(x) => Some(x), _ => None })
+                .sum()
+        };
+        if hand.actions.iter().any(|a| matches!(a.play, Play::Collect(_))) {
+            let pot = hand.pot.unwrap_or(actions);
+            let paid = hand.seats.iter().map(|s| collected(&s.player)).sum::<f64>()
+                + hand.rake.unwrap_or(0.0);
+            if (pot - paid).abs() > EPSILON {
+                issues.push(Issue::Payout{hand: id(), pot, paid});
+            }
+        }
+
+        let table = match &hand.table {
+            Some(table) => tables.entry(table.as_str()).or_default(),
+            None => continue,
+        };
+        for seat in &hand.seats {
+            let player = seat.player.as_str();
+            let found = match seat.stack {
+                Some(found) => found,
+                None => {
+                    table.remove(player);
+                    continue;
+                },
+            };
+            if let Some(&expected) = table.get(player) {
+                if (expected - found).abs() > EPSILON {
+                    issues.push(Issue::Stack{hand: id(), player: player.to_string(), expected, found});
+                }
+            }
+            let put_in = invested.get(player).copied().unwrap_or(0.0);
+            table.insert(player, found - put_in + collected(player));
+        }
+    }
+    issues
+}
+
+// What each player put in the pot, less anything given back. Raises are to
+// a total for the street, so they replace what the player had in already.
+fn invested(hand: &HandHistory) -> HashMap<&str, f64> {
+    let mut streets: HashMap<(&str, Street), f64> = HashMap::new();
+    let mut refunds: HashMap<&str, f64> = HashMap::new();
+
+    for action in &hand.actions {
+        let player = action.player.as_str();
+        let street = streets.entry((player, action.street)).or_default();
+        match action.play {
+            Play::Post(a) | Play::Call(a) | Play::Bet(a) => *street += a,
+            Play::Raise(a) => *street = a,
+            Play::Refund(a) => *refunds.entry(player).or_default() += a,
+            Play::Fold | Play::Check | Play::Collect(_) => (),
+        }
+    }
+
+    let mut invested: HashMap<&str, f64> = HashMap::new();
+    for ((player, _), a) in streets {
+        *invested.entry(player).or_default() += a;
+    }
+    for (player, a) in refunds {
+        *invested.entry(player).or_default() -= a;
+    }
+    invested
+}
+
+#[cfg(test)]
+mod audit_tests {
+    use super::*;
+    use super::super::history::Columns;
+
+    const TITLES: &str = "hand,table,player,stack,street,action,amount,hole,board,pot,rake\n";
+
+    fn audit_text(rows: &str) -> Vec<Issue> {
+        let hands = Columns::default().import(format!("{}{}", TITLES, rows).as_bytes()).unwrap();
+        audit(&hands)
+    }
+
+    const GOOD: &str = "\
+1,t1,a,100,preflop,post,1,AS KD,,7,0.5
+1,t1,b,100,preflop,post,2,QH QC,,,
+1,t1,a,,preflop,raise,6,,,,
+1,t1,b,,preflop,call,4,,,,
+1,t1,b,,flop,bet,5,,2C 7D 9H,,
+1,t1,a,,flop,fold,,,,,
+1,t1,b,,flop,refund,5,,,,
+1,t1,b,,flop,collect,11.5,,,,
+2,t1,a,94,preflop,post,1,,,,
+2,t1,b,105.5,preflop,post,2,,,,
+2,t1,a,,preflop,fold,,,,,
+2,t1,b,,preflop,collect,3,,,,
+";
+
+    #[test]
+    fn test_clean() {
+        let good = GOOD.replace(",7,0.5", ",12,0.5");
+        assert_eq!(audit_text(&good), vec![]);
+    }
+
+    #[test]
+    fn test_pot_and_payout() {
+        let issues = audit_text(GOOD);
+
+        assert_eq!(issues, vec![
+            Issue::Pot{hand: "1".to_string(), recorded: 7.0, actions: 12.0},
+            Issue::Payout{hand: "1".to_string(), pot: 7.0, paid: 12.0},
+        ]);
+        assert_eq!(issues[0].to_string(), "hand 1: pot recorded as 7 but actions put in 12");
+    }
+
+    #[test]
+    fn test_duplicates_and_stacks() {
+        let rows = GOOD.replace(",7,0.5", ",12,0.5").replace("QH QC", "QH AS").replace("2,t1,a,94", "2,t1,a,95");
+        let issues = audit_text(&rows);
+
+        assert_eq!(issues, vec![
+            Issue::DuplicateCard{hand: "1".to_string(), card: Card::from_code("AS").unwrap()},
+            Issue::Stack{hand: "2".to_string(), player: "a".to_string(), expected: 94.0, found: 95.0},
+        ]);
+
+        // Stacks are only followed within a table.
+        let other = GOOD.replace(",7,0.5", ",12,0.5").replace("2,t1,a,94", "2,t2,a,95").replace("2,t1,", "2,t2,");
+        assert_eq!(audit_text(&other), vec![]);
+    }
+}
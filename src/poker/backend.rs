@@ -0,0 +1,241 @@
+//! The five card evaluators the crate has, to pick between where a caller
+//! cares: `Hand::value`, which scores the cards directly, and the tables
+//! of `lookup`. Both give the same value for every hand, which `verify`
+//! checks over every hand a deck can deal, for them and for the other
+//! evaluators: `batch::classify_packed`, vector path and all, and a
+//! `TwoPlusTwo` table.
+
+use std::fmt;
+
+use super::{Card, Category, Hand};
+use super::batch;
+use super::deck::Deck;
+use super::explain;
+use super::lookup;
+#[cfg(feature = "twoplustwo")]
+use super::twoplustwo::TwoPlusTwo;
+use super::value::HandValue;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Default)]
+pub enum Backend {
+    // Rank masks and counts, as `Hand::new` does.
+    #[default]
+    Naive,
+    // Cactus Kev tables, for enumerating many hands.
+    Lookup,
+}
+
+impl Backend {
+    pub const ALL: [Backend; 2] = [Backend::Naive, Backend::Lookup];
+
+    pub fn evaluate(self, cards: &[Card; 5]) -> HandValue {
+        match self {
+            Backend::Naive => Hand::new(*cards).value(),
+            Backend::Lookup => lookup::evaluate(cards),
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Backend::Naive => "naive",
+            Backend::Lookup => "lookup",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Backend> {
+        Backend::ALL.iter().copied().find(|b| b.name() == name)
+    }
+}
+
+impl fmt::Display for Backend {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// Something that values five card hands, for `verify` to check. Hands
+/// come a chunk at a time so batch evaluators are checked the way they
+/// are run.
+pub trait Evaluator {
+    fn name(&self) -> String;
+
+    /// The value of each of `hands`, in order, written to `out`, which is
+    /// as long.
+    fn evaluate_chunk(&self, hands: &[[Card; 5]], out: &mut [HandValue]);
+}
+
+impl Evaluator for Backend {
+    fn name(&self) -> String {
+        Backend::name(*self).to_string()
+    }
+
+    fn evaluate_chunk(&self, hands: &[[Card; 5]], out: &mut [HandValue]) {
+        for (cards, value) in hands.iter().zip(out.iter_mut()) {
+            *value = self.evaluate(cards);
+        }
+    }
+}
+
+/// `batch::classify_packed`, which with the `simd` feature does every
+/// whole group of eight hands on the vector path.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Default)]
+pub struct Packed;
+
+impl Evaluator for Packed {
+    fn name(&self) -> String {
+        "packed".to_string()
+    }
+
+    fn evaluate_chunk(&self, hands: &[[Card; 5]], out: &mut [HandValue]) {
+        let indices: Vec<u8> = hands.iter()
+            .flat_map(|cards| cards.iter().map(|c| batch::index(c).expect("a card from the deck")))
+            .collect();
+        let mut classes = vec![0u16; hands.len()];
+        batch::classify_packed(&indices, &mut classes);
+
+        for (&class, value) in classes.iter().zip(out.iter_mut()) {
+            // A class for every hand of distinct cards; none found is as
+            // wrong as a wrong one, so it is reported as high card nothing.
+            *value = match class {
+                lookup::EMPTY => HandValue::new(Category::HighCard, &[]),
+                class => lookup::value(class),
+            };
+        }
+    }
+}
+
+#[cfg(feature = "twoplustwo")]
+impl Evaluator for TwoPlusTwo {
+    fn name(&self) -> String {
+        format!("twoplustwo ({} cards)", self.cards())
+    }
+
+    fn evaluate_chunk(&self, hands: &[[Card; 5]], out: &mut [HandValue]) {
+        for (cards, value) in hands.iter().zip(out.iter_mut()) {
+            *value = self.evaluate(cards);
+        }
+    }
+}
+
+/// A hand an evaluator values differently from `Backend::Naive`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Divergence {
+    pub cards: [Card; 5],
+    // The name of the evaluator under test.
+    pub evaluator: String,
+    // What `Backend::Naive` gives.
+    pub expected: HandValue,
+    pub found: HandValue,
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for card in &self.cards {
+            write!(f, "{} ", card)?;
+        }
+        write!(f, "is {} by {} but {} by {}",
+            explain::describe(&self.expected), Backend::Naive,
+            explain::describe(&self.found), self.evaluator)
+    }
+}
+
+impl std::error::Error for Divergence {}
+
+// Hands handed to an evaluator at once; a multiple of eight, so the
+// vector path sees whole groups.
+const CHUNK: usize = 1024;
+
+/// Values all 2,598,960 five card hands of a deck with `Backend::Naive`
+/// and with `evaluator`, in deck order, and gives the first hand they
+/// disagree on, or how many hands agreed.
+pub fn verify<E: Evaluator + ?Sized>(evaluator: &E) -> Result<u64, Divergence> {
+    verify_with(Deck::new().cards(), evaluator)
+}
+
+fn verify_with<E: Evaluator + ?Sized>(deck: &[Card], evaluator: &E) -> Result<u64, Divergence> {
+    let mut chunk = Vec::with_capacity(CHUNK);
+    let mut found = vec![HandValue::new(Category::HighCard, &[]); CHUNK];
+    let mut checked = 0;
+
+    let mut check = |chunk: &mut Vec<[Card; 5]>| {
+        let found = &mut found[..chunk.len()];
+        evaluator.evaluate_chunk(chunk, found);
+        for (&cards, found) in chunk.iter().zip(found.iter()) {
+            let expected = Backend::Naive.evaluate(&cards);
+            if expected != *found {
+                return Err(Divergence{cards, evaluator: evaluator.name(), expected, found: *found});
+            }
+        }
+        checked += chunk.len() as u64;
+        chunk.clear();
+        Ok(())
+    };
+
+    let n = deck.len();
+    for a in 0..n {
+        for b in a + 1..n {
+            for c in b + 1..n {
+                for d in c + 1..n {
+                    for e in d + 1..n {
+                        chunk.push([deck[a], deck[b], deck[c], deck[d], deck[e]]);
+                        if chunk.len() == CHUNK {
+                            check(&mut chunk)?;
+                        }
+                    }
+                }
+            }
+        }
+    }
+    check(&mut chunk)?;
+    Ok(checked)
+}
+
+#[cfg(test)]
+mod backend_tests {
+    use super::*;
+    use super::super::Rank;
+
+    // Misses flushes.
+    struct Broken;
+
+    impl Evaluator for Broken {
+        fn name(&self) -> String {
+            "broken".to_string()
+        }
+
+        fn evaluate_chunk(&self, hands: &[[Card; 5]], out: &mut [HandValue]) {
+            for (cards, value) in hands.iter().zip(out.iter_mut()) {
+                *value = match Backend::Naive.evaluate(cards) {
+                    v if v.category() == Category::Flush => HandValue::new(Category::HighCard, v.tiebreaks()),
+                    v => v,
+                };
+            }
+        }
+    }
+
+    #[test]
+    fn test_verify() {
+        let full = Deck::new();
+        let deck = &full.cards()[..12];
+        assert_eq!(verify_with(deck, &Backend::Lookup), Ok(792));
+
+        // Past one chunk, with a partial one at the end.
+        assert_eq!(verify_with(&full.cards()[..20], &Packed), Ok(15504));
+        assert_eq!(verify_with(&full.cards()[32..], &Packed), Ok(15504));
+
+        // A backend that misses flushes is caught at the first one.
+        let divergence = verify_with(deck, &Broken).unwrap_err();
+        assert_eq!(divergence.expected.category(), Category::Flush);
+        assert_eq!(divergence.cards, [deck[0], deck[1], deck[2], deck[3], deck[5]]);
+        assert!(divergence.to_string().ends_with("by naive but High Card, Seven by broken"));
+        assert_eq!(divergence.found.tiebreaks()[0], Rank::Seven);
+    }
+
+    #[test]
+    #[cfg(feature = "twoplustwo")]
+    fn test_verify_twoplustwo() {
+        let table = TwoPlusTwo::build(5);
+        assert_eq!(verify_with(&Deck::new().cards()[..20], &table), Ok(15504));
+        assert_eq!(table.name(), "twoplustwo (5 cards)");
+    }
+}
@@ -0,0 +1,132 @@
+//! Badugi, a draw game played with four cards where the lowest hand wins.
+//! A hand plays only its cards of different ranks and different suits, so
+//! out of four cards it may make a four card badugi, or three, two or one
+//! card hand when some ranks or suits clash. Any four card hand beats any
+//! three card one, and so on; among hands of as many cards the lower wins,
+//! compared from the highest card down. Aces are low and straights do not
+//! count, so A-2-3-4 of four suits is the best hand there is.
+
+use std::cmp::Ordering;
+use std::fmt;
+
+use super::{Card, Rank};
+
+/// The cards a Badugi hand plays. Better hands compare greater, as with
+/// `HandValue`, so the winner of several is the `max`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct BadugiValue {
+    // Aces as 1, highest first; unused slots are 0.
+    ranks: [u8; 4],
+    len: u8,
+}
+
+impl BadugiValue {
+    /// The best hand that `cards` play.
+    pub fn new(cards: &[Card; 4]) -> Self {
+        let mut best: Option<BadugiValue> = None;
+
+        // Every subset of the four, by bits.
+        for picked in 1..16u8 {
+            let subset: Vec<Card> = (0..4).filter(|i| picked & 1 << i != 0).map(|i| cards[i]).collect();
+            let distinct = subset.iter().enumerate().all(|(i, a)| {
+                subset[i + 1..].iter().all(|b| rank(a.rank) != rank(b.rank) && a.suit != b.suit)
+            });
+            if !distinct { continue; }
+
+            let mut ranks = [0; 4];
+            for (slot, card) in ranks.iter_mut().zip(&subset) {
+                *slot = rank(card.rank);
+            }
+            ranks.sort_unstable_by(|a, b| b.cmp(a));
+            best = best.max(Some(BadugiValue{ranks, len: subset.len() as u8}));
+        }
+        best.unwrap()
+    }
+
+    /// How many cards play, from 1 to 4.
+    pub fn card_count(&self) -> usize {
+        self.len as usize
+    }
+
+    pub fn is_badugi(&self) -> bool {
+        self.len == 4
+    }
+
+    /// The ranks that play, aces as 1, highest first.
+    pub fn ranks(&self) -> &[u8] {
+        &self.ranks[..self.len as usize]
+    }
+}
+
+impl Ord for BadugiValue {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.len.cmp(&other.len).then_with(|| other.ranks.cmp(&self.ranks))
+    }
+}
+
+impl PartialOrd for BadugiValue {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+// As the hands are called, highest card first: "8-5-3-A".
+impl fmt::Display for BadugiValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let names: Vec<String> = self.ranks().iter().map(|&r| name(r)).collect();
+        write!(f, "{}", names.join("-"))
+    }
+}
+
+// 1 for an ace up to 13 for a king.
+fn rank(rank: Rank) -> u8 {
+    match rank {
+        Rank::One | Rank::Ace => 1,
+        // `Rank::One` is 0, so each rank sits one below its number.
+        r => r as u8 + 1,
+    }
+}
+
+fn name(rank: u8) -> String {
+    match rank {
+        1 => "A".to_string(),
+        10 => "T".to_string(),
+        11 => "J".to_string(),
+        12 => "Q".to_string(),
+        13 => "K".to_string(),
+        r => r.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod badugi_tests {
+    use super::*;
+
+    fn value(s: &str) -> BadugiValue {
+        let cards: Vec<Card> = s.split_whitespace().map(|c| Card::from_code(c).unwrap()).collect();
+        BadugiValue::new(&[cards[0], cards[1], cards[2], cards[3]])
+    }
+
+    #[test]
+    fn test_value() {
+        let best = value("AH 2D 3C 4S");
+        assert!(best.is_badugi());
+        assert_eq!(best.to_string(), "4-3-2-A");
+
+        // A pair and a suit clash each cost a card.
+        assert_eq!(value("AH AD 3C 4S").to_string(), "4-3-A");
+        assert_eq!(value("KH 2H 3C 4S").to_string(), "4-3-2");
+        assert_eq!(value("KH QH JH TH").to_string(), "T");
+        assert_eq!(value("KH KD 2H 2D").card_count(), 2);
+        assert_eq!(value("KH KD 2H 2D").to_string(), "K-2");
+    }
+
+    #[test]
+    fn test_compare() {
+        assert!(value("KH QD JC TS") > value("AH 2H 3C 4S"));
+        assert!(value("8H 5D 3C AS") > value("8H 6D 3C AS"));
+        assert!(value("7H 6D 5C 4S") > value("8H 3D 2C AS"));
+        assert_eq!(value("4H 3D 2C AS"), value("4D 3C 2S AH"));
+        assert!(value("AH 2H 3C 4S") > value("AH 2H 5C 4S"));
+    }
+}
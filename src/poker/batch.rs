@@ -0,0 +1,175 @@
+//! Evaluating many hands at once. The lookup tables are fetched once per
+//! batch instead of once per hand, and the packed form skips building a
+//! `Hand` at all: a simulation keeps its cards as indices into the deck
+//! and hands over a flat slice of them.
+//!
+//! A card index runs from 0 to 51, four to a rank from the twos up, the
+//! suits in the order Hearts, Diamonds, Clubs, Spades, so `index / 4` is
+//! the rank above a two and `index % 4` the suit.
+
+use super::{Card, Hand, Rank, Suit};
+use super::lookup::{self, Lookup};
+use super::value::HandValue;
+
+// The suit bits `lookup::code` gives, in the same order.
+pub(crate) const SUIT_BITS: [u32; 4] = [0x2000, 0x4000, 0x8000, 0x1000];
+
+/// The index of a card, `None` for `Rank::One`.
+pub fn index(card: &Card) -> Option<u8> {
+    let r = (card.rank as u8).checked_sub(Rank::Two as u8)?;
+    Some(r * 4 + card.suit as u8)
+}
+
+/// The card at an index below 52.
+pub fn card(index: u8) -> Card {
+    Card{rank: Rank::DECK[index as usize / 4], suit: Suit::ALL[index as usize % 4]}
+}
+
+/// The value of each hand, in order, as `Hand::value` gives it.
+pub fn evaluate_batch(hands: &[Hand]) -> Vec<HandValue> {
+    let lookup = Lookup::get();
+
+    hands.iter()
+        .map(|hand| {
+            let c = hand.cards();
+            let codes = [lookup::code(&c[0]), lookup::code(&c[1]), lookup::code(&c[2]), lookup::code(&c[3]), lookup::code(&c[4])];
+            match codes {
+                [Some(a), Some(b), Some(c), Some(d), Some(e)] => match lookup.class([a, b, c, d, e]) {
+                    Some(class) => lookup::value(class),
+                    None => hand.value(),
+                },
+                _ => hand.value(),
+            }
+        })
+        .collect()
+}
+
+/// The `lookup` class of each hand of five card indices laid end to end in
+/// `indices`, written to `out`, `lookup::EMPTY` for a hand that repeats a
+/// card. With the `simd` feature, on a processor with AVX2, eight hands
+/// are done at a time.
+///
+/// # Panics
+///
+/// If `indices` is not five for every slot of `out`, or an index is 52 or
+/// more.
+pub fn classify_packed(indices: &[u8], out: &mut [u16]) {
+    assert_eq!(indices.len(), out.len() * 5, "five indices per hand");
+    assert!(indices.iter().fold(0, |m, &i| m.max(i)) < 52, "card indices below 52");
+
+    #[cfg(all(feature = "simd", target_arch = "x86_64"))]
+    let done = super::simd::classify(indices, out);
+    #[cfg(not(all(feature = "simd", target_arch = "x86_64")))]
+    let done = 0;
+
+    let lookup = Lookup::get();
+    let codes: [u32; 52] = std::array::from_fn(|i| lookup::pack(i as u32 / 4, SUIT_BITS[i % 4]));
+    for (hand, class) in indices[done * 5..].chunks_exact(5).zip(out[done..].iter_mut()) {
+        let class_of = lookup.class([
+            codes[hand[0] as usize],
+            codes[hand[1] as usize],
+            codes[hand[2] as usize],
+            codes[hand[3] as usize],
+            codes[hand[4] as usize],
+        ]);
+        *class = class_of.unwrap_or(lookup::EMPTY);
+    }
+}
+
+/// Evaluates hands of five card indices laid end to end in `indices`,
+/// writing each value to `out`. Nothing is allocated, so one buffer can be
+/// used for every batch of a simulation.
+///
+/// # Panics
+///
+/// If `indices` is not five for every slot of `out`, an index is 52 or
+/// more, or a hand repeats a card.
+pub fn evaluate_packed(indices: &[u8], out: &mut [HandValue]) {
+    assert_eq!(indices.len(), out.len() * 5, "five indices per hand");
+
+    let mut classes = [0u16; 64];
+    for (hands, values) in indices.chunks(64 * 5).zip(out.chunks_mut(64)) {
+        let classes = &mut classes[..values.len()];
+        classify_packed(hands, classes);
+        for (value, &class) in values.iter_mut().zip(classes.iter()) {
+            assert!(class != lookup::EMPTY, "no card repeated");
+            *value = lookup::value(class);
+        }
+    }
+}
+
+/// `evaluate_packed` into a new vector.
+pub fn evaluate_indices(indices: &[u8]) -> Vec<HandValue> {
+    let mut out = vec![lookup::value(1); indices.len() / 5];
+    evaluate_packed(indices, &mut out);
+    out
+}
+
+#[cfg(test)]
+mod batch_tests {
+    use super::*;
+    use super::super::deck::Deck;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    #[test]
+    fn test_index() {
+        let deck = Deck::new();
+        for c in deck.cards() {
+            assert_eq!(card(index(c).unwrap()), *c);
+        }
+        assert_eq!(index(&Card{rank: Rank::Two, suit: Suit::Hearts}), Some(0));
+        assert_eq!(index(&Card{rank: Rank::Ace, suit: Suit::Spades}), Some(51));
+        assert_eq!(index(&Card{rank: Rank::One, suit: Suit::Spades}), None);
+    }
+
+    #[test]
+    fn test_matches_value() {
+        let mut rng = ChaCha8Rng::seed_from_u64(11);
+        let hands: Vec<Hand> = (0..5_000).map(|_| {
+            let mut deck = Deck::new();
+            deck.shuffle(&mut rng);
+            deck.deal_hand().unwrap()
+        }).collect();
+        let expected: Vec<HandValue> = hands.iter().map(Hand::value).collect();
+
+        assert_eq!(evaluate_batch(&hands), expected);
+
+        let indices: Vec<u8> = hands.iter().flat_map(|h| h.cards().iter().map(|c| index(c).unwrap())).collect();
+        assert_eq!(evaluate_indices(&indices), expected);
+    }
+
+    #[test]
+    fn test_classify_packed() {
+        let mut rng = ChaCha8Rng::seed_from_u64(12);
+        // Not a whole number of eights, so some go the scalar way.
+        let hands: Vec<Hand> = (0..1_003).map(|_| {
+            let mut deck = Deck::new();
+            deck.shuffle(&mut rng);
+            deck.deal_hand().unwrap()
+        }).collect();
+        let mut indices: Vec<u8> = hands.iter().flat_map(|h| h.cards().iter().map(|c| index(c).unwrap())).collect();
+        // A repeated card in the first group of eight.
+        indices[5..10].copy_from_slice(&[0, 0, 1, 2, 3]);
+
+        let mut classes = vec![0; hands.len()];
+        classify_packed(&indices, &mut classes);
+        assert_eq!(classes[1], lookup::EMPTY);
+        for (i, h) in hands.iter().enumerate().filter(|&(i, _)| i != 1) {
+            assert_eq!(lookup::value(classes[i]), h.value(), "{}", h);
+        }
+    }
+
+    #[test]
+    fn test_outside_the_deck() {
+        let wheel: Hand = "1H 2D 3C 4S 5H".parse().unwrap();
+        let value = wheel.value();
+        assert_eq!(evaluate_batch(&[wheel]), vec![value]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_short() {
+        evaluate_indices(&[0, 1, 2, 3]);
+    }
+}
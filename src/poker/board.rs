@@ -0,0 +1,213 @@
+use std::fmt;
+use std::ops::Deref;
+use std::str::FromStr;
+
+use super::{parse_codes, Card, DuplicateCard, HandParseError};
+use super::parse::ParseOptions;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Street {
+    Preflop,
+    Flop,
+    Turn,
+    River,
+}
+
+impl Street {
+    // Community cards out by this street.
+    pub fn cards(&self) -> usize {
+        match self {
+            Street::Preflop => 0,
+            Street::Flop => 3,
+            Street::Turn => 4,
+            Street::River => 5,
+        }
+    }
+
+    pub fn next(&self) -> Option<Street> {
+        match self {
+            Street::Preflop => Some(Street::Flop),
+            Street::Flop => Some(Street::Turn),
+            Street::Turn => Some(Street::River),
+            Street::River => None,
+        }
+    }
+}
+
+impl FromStr for Street {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "preflop" | "pre-flop" | "p" => Ok(Street::Preflop),
+            "flop" | "f" => Ok(Street::Flop),
+            "turn" | "t" => Ok(Street::Turn),
+            "river" | "r" => Ok(Street::River),
+            _ => Err(()),
+        }
+    }
+}
+
+impl fmt::Display for Street {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Street::Preflop => write!(f, "preflop"),
+            Street::Flop => write!(f, "flop"),
+            Street::Turn => write!(f, "turn"),
+            Street::River => write!(f, "river"),
+        }
+    }
+}
+
+/// The community cards of a Hold'em hand: none preflop, then three, four
+/// or five, all different. Parsing accepts the streets run together or
+/// split by '|', in either case: "AhKs2d | 9c | 2s".
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Board {
+    cards: Vec<Card>,
+}
+
+impl Board {
+    pub fn new() -> Self {
+        Board::default()
+    }
+
+    pub fn try_new(cards: Vec<Card>) -> Result<Self, HandParseError> {
+        if ![0, 3, 4, 5].contains(&cards.len()) {
+            return Err(HandParseError::WrongCount{min: 3, max: 5, found: cards.len()});
+        }
+        for (i, card) in cards.iter().enumerate() {
+            if cards[i + 1..].contains(card) { return Err(DuplicateCard(*card).into()); }
+        }
+        Ok(Board{cards})
+    }
+
+    pub fn parse_with(s: &str, options: &ParseOptions) -> Result<Self, HandParseError> {
+        Board::try_new(parse_codes(s, options, 5)?)
+    }
+
+    pub fn street(&self) -> Street {
+        match self.cards.len() {
+            0 => Street::Preflop,
+            3 => Street::Flop,
+            4 => Street::Turn,
+            _ => Street::River,
+        }
+    }
+
+    /// Deals the next street, three cards for the flop and one after.
+    pub fn deal(&mut self, cards: &[Card]) -> Result<Street, HandParseError> {
+        let next = self.street().next().ok_or(HandParseError::TooManyCards)?;
+        let want = next.cards() - self.cards.len();
+        if cards.len() != want {
+            return Err(HandParseError::WrongCount{min: want, max: want, found: cards.len()});
+        }
+        if let Some(card) = cards.iter().enumerate()
+            .find(|&(i, c)| self.cards.contains(c) || cards[i + 1..].contains(c))
+            .map(|(_, c)| *c)
+        {
+            return Err(DuplicateCard(card).into());
+        }
+
+        self.cards.extend_from_slice(cards);
+        Ok(next)
+    }
+
+    pub fn flop(&self) -> Option<&[Card]> {
+        self.cards.get(..3)
+    }
+
+    pub fn turn(&self) -> Option<Card> {
+        self.cards.get(3).copied()
+    }
+
+    pub fn river(&self) -> Option<Card> {
+        self.cards.get(4).copied()
+    }
+
+    pub fn into_vec(self) -> Vec<Card> {
+        self.cards
+    }
+}
+
+impl FromStr for Board {
+    type Err = HandParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Board::parse_with(s, &ParseOptions::default().lenient(true))
+    }
+}
+
+impl Deref for Board {
+    type Target = [Card];
+
+    fn deref(&self) -> &[Card] {
+        &self.cards
+    }
+}
+
+// "AH KS 2D | 9C | 2S", which parses back.
+impl fmt::Display for Board {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, card) in self.cards.iter().enumerate() {
+            match i {
+                0 => (),
+                3 | 4 => write!(f, " | ")?,
+                _ => write!(f, " ")?,
+            }
+            write!(f, "{}", card)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod board_tests {
+    use super::*;
+
+    fn card(s: &str) -> Card {
+        Card::from_code(s).unwrap()
+    }
+
+    #[test]
+    fn test_parse() {
+        let board: Board = "AhKs2d | 9c | 2s".parse().unwrap();
+
+        assert_eq!(board.street(), Street::River);
+        assert_eq!(board.flop(), Some(&[card("AH"), card("KS"), card("2D")][..]));
+        assert_eq!(board.turn(), Some(card("9C")));
+        assert_eq!(board.to_string(), "AH KS 2D | 9C | 2S");
+        assert_eq!(board.to_string().parse::<Board>(), Ok(board));
+
+        let flop: Board = "AH KS 2D".parse().unwrap();
+        assert_eq!(flop.street(), Street::Flop);
+        assert_eq!(flop.turn(), None);
+        assert_eq!("".parse::<Board>().unwrap().street(), Street::Preflop);
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(
+            "AH KS".parse::<Board>(),
+            Err(HandParseError::WrongCount{min: 3, max: 5, found: 2})
+        );
+        assert_eq!("AH KS AH".parse::<Board>(), Err(HandParseError::DuplicateCard(card("AH"))));
+        assert_eq!("AH KS 2D 3D 4D 5D".parse::<Board>(), Err(HandParseError::TooManyCards));
+    }
+
+    #[test]
+    fn test_deal() {
+        let mut board = Board::new();
+
+        assert_eq!(
+            board.deal(&[card("AH")]),
+            Err(HandParseError::WrongCount{min: 3, max: 3, found: 1})
+        );
+        assert_eq!(board.deal(&[card("AH"), card("KS"), card("2D")]), Ok(Street::Flop));
+        assert_eq!(board.deal(&[card("KS")]), Err(HandParseError::DuplicateCard(card("KS"))));
+        assert_eq!(board.deal(&[card("9C")]), Ok(Street::Turn));
+        assert_eq!(board.deal(&[card("2S")]), Ok(Street::River));
+        assert_eq!(board.deal(&[card("3S")]), Err(HandParseError::TooManyCards));
+        assert_eq!(board.len(), 5);
+    }
+}
@@ -0,0 +1,89 @@
+use std::cmp::Reverse;
+
+use super::{Card, Category, Hand};
+
+/// Which cards of a hand make its category and which are only kickers.
+/// `made` is ordered as the hand would be read out (the trips before the
+/// pair of a full house, the higher pair first), `kickers` high to low.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HandBreakdown {
+    pub category: Category,
+    pub made: Vec<Card>,
+    pub kickers: Vec<Card>,
+}
+
+impl Hand {
+    pub fn breakdown(&self) -> HandBreakdown {
+        let value = self.value();
+        let category = value.category();
+
+        let mut cards = self.cards().to_vec();
+        cards.sort_by_key(|c| Reverse(c.rank));
+
+        let made_ranks = match category {
+            Category::Straight
+            | Category::Flush
+            | Category::StraightFlush
+            | Category::RoyalFlush => return HandBreakdown{category, made: cards, kickers: vec![]},
+            _ => value.primary_ranks(),
+        };
+
+        let mut made = vec![];
+        for rank in made_ranks {
+            made.extend(cards.iter().filter(|c| c.rank == *rank));
+        }
+        let kickers = cards.into_iter().filter(|c| !made_ranks.contains(&c.rank)).collect();
+
+        HandBreakdown{category, made, kickers}
+    }
+}
+
+#[cfg(test)]
+mod breakdown_tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn codes(cards: &[Card]) -> Vec<String> {
+        cards.iter().map(|c| c.to_string()).collect()
+    }
+
+    fn breakdown(s: &str) -> (Category, Vec<String>, Vec<String>) {
+        let b = Hand::from_str(s).unwrap().breakdown();
+        (b.category, codes(&b.made), codes(&b.kickers))
+    }
+
+    #[test]
+    fn test_pairs() {
+        let (category, made, kickers) = breakdown("8C 8S KC 9H 9S");
+        assert_eq!(category, Category::TwoPairs);
+        assert_eq!(made, vec!["9H", "9S", "8C", "8S"]);
+        assert_eq!(kickers, vec!["KC"]);
+
+        let (category, made, kickers) = breakdown("4D 6S 9H QH QC");
+        assert_eq!(category, Category::OnePair);
+        assert_eq!(made, vec!["QH", "QC"]);
+        assert_eq!(kickers, vec!["9H", "6S", "4D"]);
+    }
+
+    #[test]
+    fn test_full_house() {
+        let (category, made, kickers) = breakdown("2H 2D 4C 4D 4S");
+
+        assert_eq!(category, Category::FullHouse);
+        assert_eq!(made, vec!["4C", "4D", "4S", "2H", "2D"]);
+        assert!(kickers.is_empty());
+    }
+
+    #[test]
+    fn test_high_card_and_flush() {
+        let (category, made, kickers) = breakdown("7D 2S 5D 3S AC");
+        assert_eq!(category, Category::HighCard);
+        assert_eq!(made, vec!["AC"]);
+        assert_eq!(kickers, vec!["7D", "5D", "3S", "2S"]);
+
+        let (category, made, kickers) = breakdown("3D 6D 7D TD QD");
+        assert_eq!(category, Category::Flush);
+        assert_eq!(made, vec!["QD", "TD", "7D", "6D", "3D"]);
+        assert!(kickers.is_empty());
+    }
+}
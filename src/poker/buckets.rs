@@ -0,0 +1,202 @@
+use std::fmt;
+
+use super::{Board, Card, Cards, Category, Rank};
+use super::board::Street;
+use super::deal::best_of;
+use super::range::Range;
+
+/// Named made-hand classes of a combo on a board, strongest first. Pairs
+/// and better only count when the hole cards make them; a pair on the board
+/// does not.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Bucket {
+    StraightOrBetter,
+    Set,
+    Trips,
+    TwoPair,
+    Overpair,
+    // Top pair with the best kicker left.
+    TopPairTopKicker,
+    // Top pair with a Ten or better kicker.
+    TopPairGoodKicker,
+    TopPairWeakKicker,
+    // Middle or bottom pair, or a pocket pair below the top card.
+    WeakPair,
+    Draw,
+    Air,
+}
+
+impl fmt::Display for Bucket {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Bucket::StraightOrBetter => "straight or better",
+            Bucket::Set => "set",
+            Bucket::Trips => "trips",
+            Bucket::TwoPair => "two pair",
+            Bucket::Overpair => "overpair",
+            Bucket::TopPairTopKicker => "top pair, top kicker",
+            Bucket::TopPairGoodKicker => "top pair, good kicker",
+            Bucket::TopPairWeakKicker => "top pair, weak kicker",
+            Bucket::WeakPair => "weak pair",
+            Bucket::Draw => "draw",
+            Bucket::Air => "air",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// How a range divides into buckets. Rows are strongest first and only
+/// list buckets that hold a combo; combos blocked by the board are left out.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RangeBreakdown {
+    pub rows: Vec<BucketRow>,
+    pub weight: f64,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct BucketRow {
+    pub bucket: Bucket,
+    pub combos: usize,
+    pub weight: f64,
+}
+
+impl RangeBreakdown {
+    pub fn percent(&self, bucket: Bucket) -> f64 {
+        if self.weight == 0.0 { return 0.0; }
+        let weight = self.rows.iter().find(|r| r.bucket == bucket).map_or(0.0, |r| r.weight);
+        100.0 * weight / self.weight
+    }
+}
+
+impl fmt::Display for RangeBreakdown {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for row in &self.rows {
+            writeln!(
+                f,
+                "{:<22} {:>5} {:>6.1}%",
+                row.bucket.to_string(),
+                row.combos,
+                self.percent(row.bucket)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+pub fn bucket(hole: &[Card; 2], board: &Board) -> Bucket {
+    assert!(board.street() != Street::Preflop, "a flop, turn or river board");
+
+    let all: Vec<Card> = hole.iter().chain(board.iter()).cloned().collect();
+    let category = best_of(&all).value().category();
+
+    let board_ranks: Vec<Rank> = board.iter().map(|c| c.rank).collect();
+    let top = *board_ranks.iter().max().unwrap();
+    let on_board = |r: Rank| board_ranks.contains(&r);
+    let pocket = hole[0].rank == hole[1].rank;
+
+    let board_plays = board.street() == Street::River && best_of(board).value() >= best_of(&all).value();
+    if category >= Category::Straight && !board_plays {
+        return Bucket::StraightOrBetter;
+    }
+    if category == Category::ThreeOfAKind {
+        if pocket && on_board(hole[0].rank) { return Bucket::Set; }
+        if hole.iter().any(|c| board_ranks.iter().filter(|&&r| r == c.rank).count() == 2) {
+            return Bucket::Trips;
+        }
+    }
+    if !pocket && on_board(hole[0].rank) && on_board(hole[1].rank) {
+        return Bucket::TwoPair;
+    }
+    if pocket {
+        return if hole[0].rank > top { Bucket::Overpair } else { Bucket::WeakPair };
+    }
+
+    if let Some(i) = (0..2).find(|&i| hole[i].rank == top) {
+        let kicker = hole[1 - i].rank;
+        let best_kicker = if top == Rank::Ace { Rank::King } else { Rank::Ace };
+        return if kicker == best_kicker {
+            Bucket::TopPairTopKicker
+        } else if kicker >= Rank::Ten {
+            Bucket::TopPairGoodKicker
+        } else {
+            Bucket::TopPairWeakKicker
+        };
+    }
+    if hole.iter().any(|c| on_board(c.rank)) {
+        return Bucket::WeakPair;
+    }
+
+    let hole = Cards::try_new(hole.to_vec()).unwrap();
+    if hole.draws(board).is_empty() { Bucket::Air } else { Bucket::Draw }
+}
+
+impl Range {
+    pub fn breakdown(&self, board: &Board) -> RangeBreakdown {
+        let live = self.without_blocked(board);
+        let mut rows: Vec<BucketRow> = vec![];
+
+        for combo in live.combos() {
+            let b = bucket(&combo.cards, board);
+            match rows.iter_mut().find(|r| r.bucket == b) {
+                Some(row) => {
+                    row.combos += 1;
+                    row.weight += combo.weight;
+                },
+                None => rows.push(BucketRow{bucket: b, combos: 1, weight: combo.weight}),
+            }
+        }
+        rows.sort_by_key(|r| r.bucket);
+
+        RangeBreakdown{rows, weight: live.weight()}
+    }
+}
+
+#[cfg(test)]
+mod buckets_tests {
+    use super::*;
+
+    fn bucket_of(hole: &str, board: &str) -> Bucket {
+        let hole = Cards::hole(hole).unwrap();
+        bucket(&[hole[0], hole[1]], &board.parse().unwrap())
+    }
+
+    #[test]
+    fn test_buckets() {
+        let board = "KH 9D 4C";
+
+        assert_eq!(bucket_of("9S 9C", board), Bucket::Set);
+        assert_eq!(bucket_of("KS 9C", board), Bucket::TwoPair);
+        assert_eq!(bucket_of("AS AC", board), Bucket::Overpair);
+        assert_eq!(bucket_of("AS KC", board), Bucket::TopPairTopKicker);
+        assert_eq!(bucket_of("KS QC", board), Bucket::TopPairGoodKicker);
+        assert_eq!(bucket_of("KS 5C", board), Bucket::TopPairWeakKicker);
+        assert_eq!(bucket_of("9S 8C", board), Bucket::WeakPair);
+        assert_eq!(bucket_of("5S 5C", board), Bucket::WeakPair);
+        assert_eq!(bucket_of("QD JD", "KH 9D 4D"), Bucket::Draw);
+        assert_eq!(bucket_of("QS JC", board), Bucket::Draw);
+        assert_eq!(bucket_of("7S 2C", board), Bucket::Air);
+        assert_eq!(bucket_of("JS TC", "KH QD 9C"), Bucket::StraightOrBetter);
+        assert_eq!(bucket_of("9S 5C", "9H 9D 4C"), Bucket::Trips);
+    }
+
+    #[test]
+    fn test_board_plays() {
+        // The board's straight is shared by everyone.
+        assert_eq!(bucket_of("2S 3C", "9H TD JC QS KD"), Bucket::Air);
+        assert_eq!(bucket_of("AS 3C", "9H TD JC QS KD"), Bucket::StraightOrBetter);
+    }
+
+    #[test]
+    fn test_breakdown() {
+        let range: Range = "AA,KK,AK,72o".parse().unwrap();
+        let board: Board = "KH 9D 4C".parse().unwrap();
+        let b = range.breakdown(&board);
+
+        assert_eq!(b.weight, 6.0 + 3.0 + 12.0 + 12.0);
+        assert_eq!(b.rows[0], BucketRow{bucket: Bucket::Set, combos: 3, weight: 3.0});
+        assert_eq!(b.rows.iter().map(|r| r.bucket).collect::<Vec<_>>(),
+            vec![Bucket::Set, Bucket::Overpair, Bucket::TopPairTopKicker, Bucket::Air]);
+        assert!((b.percent(Bucket::Air) - 100.0 * 12.0 / 33.0).abs() < 1e-9);
+        assert!(b.to_string().starts_with("set"));
+    }
+}
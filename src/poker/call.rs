@@ -0,0 +1,95 @@
+//! Expected value of calling an all in: the shove is already in the pot,
+//! so calling `C` into a pot of `P` wins `P + C` with equity `e` and
+//!
+//! ```text
+//! EV = e * (P + C) - C
+//! ```
+//!
+//! against folding, which is worth zero. Calling breaks even at an equity
+//! of `C / (P + C)`, the pot odds.
+
+use rand::Rng;
+
+use super::{Board, Card, DuplicateCard};
+use super::equity::Simulator;
+use super::range::Range;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CallEv {
+    // Against the shoving range, weighted by combo.
+    pub equity: f64,
+    pub break_even: f64,
+    pub ev: f64,
+}
+
+pub fn call_ev(pot: f64, to_call: f64, equity: f64) -> f64 {
+    equity * (pot + to_call) - to_call
+}
+
+pub fn break_even_equity(pot: f64, to_call: f64) -> f64 {
+    to_call / (pot + to_call)
+}
+
+/// The EV of calling `to_call` into `pot`, which holds the shove, with
+/// `hero` against `shove_range` on `board`. Combos blocked by the hole
+/// cards or board are left out. Equity against each combo is enumerated
+/// when there are at most `samples` runouts and sampled otherwise.
+pub fn ev_call<R: Rng + ?Sized>(
+    hero: [Card; 2],
+    shove_range: &Range,
+    pot: f64,
+    to_call: f64,
+    board: &Board,
+    samples: usize,
+    rng: &mut R,
+) -> Result<CallEv, DuplicateCard> {
+    let dead: Vec<Card> = hero.iter().chain(board.iter()).cloned().collect();
+    let range = shove_range.without_blocked(&dead);
+
+    let simulator = Simulator::new(samples).auto_enumerate(true);
+    let mut equity = 0.0;
+    for combo in range.combos() {
+        let e = simulator.run(&[hero, combo.cards], board, rng)?;
+        equity += combo.weight * e.shares[0];
+    }
+    if range.weight() > 0.0 { equity /= range.weight(); }
+
+    Ok(CallEv{equity, break_even: break_even_equity(pot, to_call), ev: call_ev(pot, to_call, equity)})
+}
+
+#[cfg(test)]
+mod call_tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    fn hole(s: &str) -> [Card; 2] {
+        let c: Vec<Card> = s.split_whitespace().map(|c| Card::from_code(c).unwrap()).collect();
+        [c[0], c[1]]
+    }
+
+    #[test]
+    fn test_call_ev() {
+        assert_eq!(call_ev(15.0, 10.0, 0.4), 0.0);
+        assert_eq!(break_even_equity(15.0, 10.0), 0.4);
+        assert_eq!(call_ev(10.0, 10.0, 1.0), 10.0);
+        assert_eq!(call_ev(10.0, 10.0, 0.0), -10.0);
+    }
+
+    #[test]
+    fn test_against_range() {
+        // A flush draw against a shove of sets and top pair on the turn.
+        let board: Board = "KH 9H 2C 7S".parse().unwrap();
+        let range: Range = "KK,99,AK".parse().unwrap();
+        let r = ev_call(hole("AH 5H"), &range, 30.0, 20.0, &board, 1000, &mut ChaCha8Rng::seed_from_u64(1)).unwrap();
+
+        assert_eq!(r.break_even, 0.4);
+        assert!(r.equity > 0.1 && r.equity < 0.3);
+        assert!((r.ev - call_ev(30.0, 20.0, r.equity)).abs() < 1e-9);
+        assert!(r.ev < 0.0);
+
+        // Every combo blocked leaves nothing to call against.
+        let r = ev_call(hole("AH KD"), &"AHAC".parse().unwrap(), 30.0, 20.0, &board, 10, &mut ChaCha8Rng::seed_from_u64(1)).unwrap();
+        assert_eq!(r.equity, 0.0);
+    }
+}
@@ -0,0 +1,115 @@
+use super::{Card, Hand, Rank, Suit};
+use super::features::{card_index, CARDS};
+
+/// A hand's representative under suit isomorphism, together with an index
+/// that is equal for exactly the hands that are isomorphic. The index is
+/// stable across versions but not dense: it is the colex rank of the
+/// canonical hand's cards among all five card sets.
+#[derive(Clone, Debug)]
+pub struct Canonical {
+    pub hand: Hand,
+    pub index: u32,
+}
+
+impl Hand {
+    // Of the 24 relabellings of the suits, the canonical one gives the
+    // smallest card indices once sorted, so a suited AK comes out in hearts.
+    pub fn canonical(&self) -> Canonical {
+        let mut best: Option<[usize; 5]> = None;
+
+        for perm in permutations() {
+            let mut indices = [0; 5];
+            for (i, card) in self.cards().iter().enumerate() {
+                indices[i] = card_index(&relabel(card, &perm));
+            }
+            indices.sort_unstable();
+            if best.map_or(true, |b| indices < b) {
+                best = Some(indices);
+            }
+        }
+
+        let indices = best.unwrap();
+        let cards = [
+            from_index(indices[0]),
+            from_index(indices[1]),
+            from_index(indices[2]),
+            from_index(indices[3]),
+            from_index(indices[4]),
+        ];
+
+        Canonical{hand: Hand::new(cards), index: colex(&indices)}
+    }
+
+    pub fn is_isomorphic(&self, other: &Hand) -> bool {
+        self.canonical().index == other.canonical().index
+    }
+}
+
+fn relabel(card: &Card, perm: &[usize; 4]) -> Card {
+    Card{rank: card.rank, suit: Suit::ALL[perm[card.suit.index()]]}
+}
+
+fn from_index(index: usize) -> Card {
+    Card{rank: Rank::ALL[index / 4], suit: Suit::ALL[index % 4]}
+}
+
+fn permutations() -> Vec<[usize; 4]> {
+    let mut out = vec![];
+    for a in 0..4 {
+        for b in 0..4 {
+            for c in 0..4 {
+                for d in 0..4 {
+                    if a != b && a != c && a != d && b != c && b != d && c != d {
+                        out.push([a, b, c, d]);
+                    }
+                }
+            }
+        }
+    }
+    out
+}
+
+fn choose(n: usize, k: usize) -> u32 {
+    if k > n { return 0; }
+    (0..k).fold(1u64, |acc, i| acc * (n - i) as u64 / (i + 1) as u64) as u32
+}
+
+fn colex(sorted: &[usize; 5]) -> u32 {
+    debug_assert!(sorted.iter().all(|&i| i < CARDS));
+    sorted.iter().enumerate().map(|(k, &i)| choose(i, k + 1)).sum()
+}
+
+#[cfg(test)]
+mod canonical_tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn canonical(s: &str) -> Canonical {
+        Hand::from_str(s).unwrap().canonical()
+    }
+
+    #[test]
+    fn test_isomorphic() {
+        let a = canonical("AH KH 7D 7C 2S");
+        let b = canonical("AS KS 7C 7H 2D");
+
+        assert_eq!(a.index, b.index);
+        assert_eq!(a.hand.cards(), b.hand.cards());
+        assert_ne!(a.index, canonical("AH KD 7D 7C 2S").index);
+    }
+
+    #[test]
+    fn test_canonical_form() {
+        let c = canonical("AS KS QS JS TS");
+
+        assert!(c.hand.iter().all(|card| card.suit == Suit::Hearts));
+        assert_eq!(c.hand.value(), Hand::from_str("AS KS QS JS TS").unwrap().value());
+    }
+
+    #[test]
+    fn test_choose() {
+        assert_eq!(choose(5, 2), 10);
+        assert_eq!(choose(3, 4), 0);
+        assert_eq!(choose(CARDS, 5), 3_819_816);
+    }
+}
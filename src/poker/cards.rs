@@ -0,0 +1,127 @@
+use std::fmt;
+use std::ops::{Deref, RangeInclusive};
+
+use super::{parse_codes, Card, DuplicateCard, HandParseError};
+use super::parse::ParseOptions;
+
+/// A list of distinct cards of any size, for the card groups that are not a
+/// five card hand: hole cards, Omaha and stud hands, boards. Parsing goes
+/// through the same `ParseOptions` as `Hand`, with the accepted sizes given
+/// by the caller.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Cards {
+    cards: Vec<Card>,
+}
+
+impl Cards {
+    pub fn try_new(cards: Vec<Card>) -> Result<Self, DuplicateCard> {
+        for (i, card) in cards.iter().enumerate() {
+            if cards[i + 1..].contains(card) { return Err(DuplicateCard(*card)); }
+        }
+        Ok(Cards{cards})
+    }
+
+    pub fn parse_with(
+        s: &str,
+        options: &ParseOptions,
+        sizes: RangeInclusive<usize>,
+    ) -> Result<Self, HandParseError> {
+        let (min, max) = (*sizes.start(), *sizes.end());
+
+        let cards = parse_codes(s, options, usize::MAX)?;
+        if !sizes.contains(&cards.len()) {
+            return Err(HandParseError::WrongCount{min, max, found: cards.len()});
+        }
+        Ok(Cards{cards})
+    }
+
+    pub fn hole(s: &str) -> Result<Self, HandParseError> {
+        Cards::parse_with(s, &ParseOptions::default(), 2..=2)
+    }
+
+    pub fn omaha(s: &str) -> Result<Self, HandParseError> {
+        Cards::parse_with(s, &ParseOptions::default(), 4..=4)
+    }
+
+    pub fn stud(s: &str) -> Result<Self, HandParseError> {
+        Cards::parse_with(s, &ParseOptions::default(), 7..=7)
+    }
+
+    // A flop, turn or river board.
+    pub fn board(s: &str) -> Result<Self, HandParseError> {
+        Cards::parse_with(s, &ParseOptions::default(), 3..=5)
+    }
+
+    pub fn into_vec(self) -> Vec<Card> {
+        self.cards
+    }
+}
+
+impl Deref for Cards {
+    type Target = [Card];
+
+    fn deref(&self) -> &[Card] {
+        &self.cards
+    }
+}
+
+impl fmt::Display for Cards {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, card) in self.cards.iter().enumerate() {
+            if i > 0 { write!(f, " ")?; }
+            write!(f, "{}", card)?;
+        }
+        Ok(())
+    }
+}
+
+impl ParseOptions {
+    pub fn parse_cards(&self, s: &str, sizes: RangeInclusive<usize>) -> Result<Cards, HandParseError> {
+        Cards::parse_with(s, self, sizes)
+    }
+}
+
+#[cfg(test)]
+mod cards_tests {
+    use super::*;
+
+    #[test]
+    fn test_sizes() {
+        assert_eq!(Cards::hole("AS KD").unwrap().len(), 2);
+        assert_eq!(Cards::omaha("AS KD QH 2C").unwrap().len(), 4);
+        assert_eq!(Cards::stud("AS KD QH 2C 3C 4C 5C").unwrap().len(), 7);
+        assert_eq!(Cards::board("AS KD QH").unwrap().len(), 3);
+        assert_eq!(Cards::board("AS KD QH 2C 3C").unwrap().len(), 5);
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(
+            Cards::hole("AS KD QH"),
+            Err(HandParseError::WrongCount{min: 2, max: 2, found: 3})
+        );
+        assert_eq!(
+            Cards::board("AS KD"),
+            Err(HandParseError::WrongCount{min: 3, max: 5, found: 2})
+        );
+        assert_eq!(
+            Cards::board("AS KD QH 2C 3C 4C 5C").unwrap_err().to_string(),
+            "expected 3 to 5 cards, found 7"
+        );
+        assert_eq!(
+            Cards::hole("AS AS"),
+            Err(HandParseError::DuplicateCard(Card::from_code("AS").unwrap()))
+        );
+        assert!(matches!(Cards::hole("AS ZZ"), Err(HandParseError::BadCard{index: 1, ..})));
+    }
+
+    #[test]
+    fn test_options_and_display() {
+        let opts = ParseOptions::german().lenient(true);
+        let cards = opts.parse_cards("bh, dd", 2..=2).unwrap();
+
+        assert_eq!(cards.to_string(), "JH QD");
+        assert_eq!(cards[0], Card::from_code("JH").unwrap());
+        assert!(Cards::try_new(cards.into_vec().repeat(2)).is_err());
+    }
+}
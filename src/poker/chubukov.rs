@@ -0,0 +1,275 @@
+//! Sklansky-Chubukov numbers: for each starting hand, the deepest stack, in
+//! big blinds, at which moving all in from the small blind heads up is
+//! worth more than folding, even against a big blind who sees the hand
+//! and calls exactly when calling pays.
+//!
+//! The shover folds away half a blind or shoves `S` blinds. The caller
+//! folds a hand `h` for a loss of their blind, or calls and takes back
+//! `(1 - e) * 2S` of the pot, `e` being the shover's equity against `h`;
+//! so they call when `S * (1 - 2e) > -1`. The shover then makes
+//! `S * (2e - 1)` when called and 1 when not, averaged over the caller's
+//! combos, and the number is the largest `S` where that is at least -0.5.
+//! A hand nothing profitably calls, Aces, has no limit.
+//!
+//! Equities against each of the 169 classes are sampled, but for the
+//! hand's own class, which is taken as an even split. The numbers move a
+//! little with the sample count; like `allin`, the table is kept on disk
+//! by `load_or_generate`.
+
+use std::cmp::Ordering;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::Path;
+
+use rand::SeedableRng;
+use rand::seq::SliceRandom;
+use rand_chacha::ChaCha8Rng;
+
+use super::Card;
+use super::atomic;
+use super::deck::Deck;
+use super::format::Header;
+use super::lookup;
+use super::preflop::PreflopMatrix;
+use super::starting::StartingHand;
+
+const KIND: &str = "sklansky-chubukov";
+const VERSION: u32 = 1;
+
+// Stacks beyond this count as no limit.
+const UNLIMITED: f64 = 1e9;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ChubukovConfig {
+    // Showdowns against each class of the caller's hands.
+    pub samples: usize,
+    pub seed: u64,
+}
+
+impl Default for ChubukovConfig {
+    fn default() -> Self {
+        ChubukovConfig{samples: 2_000, seed: 0}
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ChubukovTable {
+    pub config: ChubukovConfig,
+    numbers: PreflopMatrix<f64>,
+}
+
+/// The number for a shover whose equity against each of the caller's
+/// holdings is given with its weight, the combos it stands for.
+/// `f64::INFINITY` when the shove never does worse than folding.
+pub fn sc_number(matchups: &[(f64, f64)]) -> f64 {
+    let total: f64 = matchups.iter().map(|&(w, _)| w).sum();
+    let ev = |stack: f64| {
+        matchups.iter()
+            .map(|&(w, e)| {
+                let called = stack * (1.0 - 2.0 * e) > -1.0;
+                w * if called { stack * (2.0 * e - 1.0) } else { 1.0 }
+            })
+            .sum::<f64>() / total
+    };
+
+    if ev(1.0) < -0.5 { return 0.0; }
+    if ev(UNLIMITED) >= -0.5 { return f64::INFINITY; }
+
+    // The EV only falls as the stack grows, so bisect for where it
+    // crosses -0.5.
+    let (mut low, mut high) = (1.0, UNLIMITED);
+    while high - low > 1e-9 * high {
+        let mid = (low + high) / 2.0;
+        if ev(mid) >= -0.5 { low = mid; } else { high = mid; }
+    }
+    low
+}
+
+impl ChubukovTable {
+    pub fn generate(config: ChubukovConfig) -> Self {
+        let all = StartingHand::all();
+        let mut numbers = PreflopMatrix::from_fn(|_| 0.0);
+
+        for (i, &hand) in all.iter().enumerate() {
+            // Each hand has its own stream, so hands can be regenerated alone.
+            let mut rng = ChaCha8Rng::seed_from_u64(config.seed);
+            rng.set_stream(i as u64);
+
+            let hero = hand.combos()[0];
+            let matchups: Vec<(f64, f64)> = all.iter()
+                .filter_map(|villain| {
+                    let combos: Vec<[Card; 2]> = villain.combos().into_iter()
+                        .filter(|c| !c.iter().any(|card| hero.contains(card)))
+                        .collect();
+                    if combos.is_empty() { return None; }
+                    // The same hand splits, and sampling noise either side
+                    // of a half would decide whether it is called.
+                    let equity = if *villain == hand { 0.5 } else { sample_equity(hero, &combos, config.samples, &mut rng) };
+                    Some((combos.len() as f64, equity))
+                })
+                .collect();
+
+            numbers.set(hand, sc_number(&matchups));
+        }
+
+        ChubukovTable{config, numbers}
+    }
+
+    pub fn number(&self, hand: StartingHand) -> f64 {
+        self.numbers[hand]
+    }
+
+    pub fn numbers(&self) -> &PreflopMatrix<f64> {
+        &self.numbers
+    }
+
+    pub fn write<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        Header::new(KIND, VERSION)
+            .field("samples", self.config.samples)
+            .field("seed", self.config.seed)
+            .write(w)?;
+
+        writeln!(w, "hand,number")?;
+        for hand in StartingHand::all() {
+            writeln!(w, "{},{}", hand, self.numbers[hand])?;
+        }
+        Ok(())
+    }
+
+    pub fn read<R: BufRead>(r: R) -> io::Result<Self> {
+        let invalid = |e: String| io::Error::new(io::ErrorKind::InvalidData, e);
+
+        let mut lines = r.lines();
+        let first = lines.next().ok_or_else(|| invalid("empty file".to_string()))??;
+        let header = Header::parse(&first).map_err(|e| invalid(e.to_string()))?;
+        header.check(KIND, VERSION).map_err(|e| invalid(e.to_string()))?;
+
+        let field = |key: &str| header.get(key).and_then(|v| v.parse().ok())
+            .ok_or_else(|| invalid(format!("missing {}", key)));
+        let config = ChubukovConfig{samples: field("samples")? as usize, seed: field("seed")?};
+
+        let mut numbers = PreflopMatrix::from_fn(|_| None);
+        for (n, line) in lines.enumerate().skip(1) {
+            let line = line?;
+            let bad = || invalid(format!("malformed row on line {}", n + 2));
+
+            let (hand, number) = line.split_once(',').ok_or_else(bad)?;
+            let hand: StartingHand = hand.parse().map_err(|_| bad())?;
+            numbers[hand] = Some(number.parse::<f64>().map_err(|_| bad())?);
+        }
+        if numbers.iter().any(|(_, n)| n.is_none()) {
+            return Err(invalid("missing starting hands".to_string()));
+        }
+
+        Ok(ChubukovTable{config, numbers: numbers.map(|n| n.unwrap())})
+    }
+
+    // Reads the table at `path` if it was made with `config`, otherwise
+    // generates it and writes it there.
+    pub fn load_or_generate<P: AsRef<Path>>(path: P, config: ChubukovConfig) -> io::Result<Self> {
+        let path = path.as_ref();
+        atomic::remove_stale(path)?;
+
+        if let Ok(file) = File::open(path) {
+            if let Ok(table) = ChubukovTable::read(BufReader::new(file)) {
+                if table.config == config { return Ok(table); }
+            }
+        }
+
+        let table = ChubukovTable::generate(config);
+        atomic::write(path, |w| table.write(w))?;
+        Ok(table)
+    }
+}
+
+// The hero's share of the pot against the villain combos in turn, scored
+// by `lookup` classes since there are many showdowns to get through. The
+// smaller class is the stronger hand.
+fn sample_equity(hero: [Card; 2], villains: &[[Card; 2]], samples: usize, rng: &mut ChaCha8Rng) -> f64 {
+    let code = |c: &Card| lookup::code(c).unwrap();
+    let mut won = 0.0;
+
+    for i in 0..samples {
+        let villain = villains[i % villains.len()];
+        let deck = Deck::without([hero[0], hero[1], villain[0], villain[1]]);
+
+        let mut a = [0; 7];
+        let mut b = [0; 7];
+        for (k, c) in deck.cards().choose_multiple(rng, 5).enumerate() {
+            a[k + 2] = code(c);
+            b[k + 2] = code(c);
+        }
+        a[..2].copy_from_slice(&[code(&hero[0]), code(&hero[1])]);
+        b[..2].copy_from_slice(&[code(&villain[0]), code(&villain[1])]);
+
+        won += match lookup::best_class(&b).cmp(&lookup::best_class(&a)) {
+            Ordering::Greater => 1.0,
+            Ordering::Equal => 0.5,
+            Ordering::Less => 0.0,
+        };
+    }
+    won / samples.max(1) as f64
+}
+
+#[cfg(test)]
+mod chubukov_tests {
+    use super::*;
+    use std::sync::OnceLock;
+
+    fn small() -> ChubukovConfig {
+        ChubukovConfig{samples: 24, seed: 3}
+    }
+
+    // Generating takes a while in a debug build, so the tests share one.
+    fn table() -> &'static ChubukovTable {
+        static TABLE: OnceLock<ChubukovTable> = OnceLock::new();
+        TABLE.get_or_init(|| ChubukovTable::generate(small()))
+    }
+
+    #[test]
+    fn test_sc_number() {
+        // Nothing calls a hand that is ahead of everything.
+        assert_eq!(sc_number(&[(1.0, 0.8), (3.0, 0.6)]), f64::INFINITY);
+
+        // Against one hand at 40%, the shove loses 0.2S and folding loses
+        // half a blind, so they break even at 2.5.
+        assert!((sc_number(&[(1.0, 0.4)]) - 2.5).abs() < 1e-6);
+
+        // Half the time the caller folds for a win of 1, so the shover can
+        // lose 2 when called: S * 0.2 = 2.
+        assert!((sc_number(&[(1.0, 0.4), (1.0, 0.9)]) - 10.0).abs() < 1e-6);
+        assert_eq!(sc_number(&[(1.0, 0.0)]), 0.0);
+    }
+
+    #[test]
+    fn test_generate() {
+        let n = |s: &str| table().number(s.parse().unwrap());
+
+        assert_eq!(n("AA"), f64::INFINITY);
+        assert!(n("KK") > n("AKs"));
+        assert!(n("AKs") > n("72o"));
+        assert!(n("72o") < 5.0);
+    }
+
+    #[test]
+    fn test_round_trip_and_cache() {
+        let table = table();
+        let mut out = vec![];
+        table.write(&mut out).unwrap();
+
+        assert!(out.starts_with(b"# misc-poker sklansky-chubukov v1 samples=24 seed=3\nhand,number\nAA,inf\n"));
+        assert_eq!(&ChubukovTable::read(&out[..]).unwrap(), table);
+        assert!(ChubukovTable::read(&out[..out.len() / 2]).is_err());
+
+        // A table on disk made with the same settings is read, not redone.
+        let mut changed = table.clone();
+        changed.numbers.set("32o".parse().unwrap(), 1.5);
+        let mut out = vec![];
+        changed.write(&mut out).unwrap();
+
+        let path = std::env::temp_dir().join(format!("chubukov-{}.csv", std::process::id()));
+        std::fs::write(&path, out).unwrap();
+        assert_eq!(ChubukovTable::load_or_generate(&path, small()).unwrap(), changed);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,397 @@
+//! A description of a command line, its commands and their flags, kept as
+//! data so that shell completions and a JSON dump for wrappers can be made
+//! from it rather than written by hand and left to drift. The binary keeps
+//! its own table; nothing here knows which commands it has.
+//!
+//! Values named `FILE` or `DIR` complete to paths, values with choices to
+//! those choices, and any other value to nothing.
+
+use super::json::Json;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Value {
+    // How usage shows it, such as "FILE" or "N".
+    pub name: &'static str,
+    pub choices: &'static [&'static str],
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Flag {
+    pub name: &'static str,
+    // None for a switch.
+    pub value: Option<Value>,
+    pub about: &'static str,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Command {
+    pub name: &'static str,
+    pub about: &'static str,
+    // Positional arguments, in order.
+    pub args: &'static [Value],
+    pub flags: &'static [Flag],
+    pub subcommands: &'static [Command],
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Program {
+    pub name: &'static str,
+    // Flags taken before the command.
+    pub flags: &'static [Flag],
+    pub commands: &'static [Command],
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+impl Shell {
+    pub const NAMES: &'static [&'static str] = &["bash", "zsh", "fish"];
+
+    pub fn parse(name: &str) -> Option<Shell> {
+        match name {
+            "bash" => Some(Shell::Bash),
+            "zsh" => Some(Shell::Zsh),
+            "fish" => Some(Shell::Fish),
+            _ => None,
+        }
+    }
+}
+
+impl Value {
+    pub const fn new(name: &'static str) -> Self {
+        Value{name, choices: &[]}
+    }
+
+    pub const fn one_of(name: &'static str, choices: &'static [&'static str]) -> Self {
+        Value{name, choices}
+    }
+
+    fn is_path(&self) -> bool {
+        self.name == "FILE" || self.name == "DIR"
+    }
+
+    fn to_json(self) -> Json {
+        Json::object(vec![
+            ("name", self.name.into()),
+            ("choices", Json::Array(self.choices.iter().map(|&c| c.into()).collect())),
+        ])
+    }
+}
+
+impl Flag {
+    pub const fn switch(name: &'static str, about: &'static str) -> Self {
+        Flag{name, value: None, about}
+    }
+
+    pub const fn value(name: &'static str, value: Value, about: &'static str) -> Self {
+        Flag{name, value: Some(value), about}
+    }
+
+    fn to_json(self) -> Json {
+        Json::object(vec![
+            ("name", self.name.into()),
+            ("value", self.value.map_or(Json::Null, Value::to_json)),
+            ("about", self.about.into()),
+        ])
+    }
+}
+
+impl Command {
+    pub const fn new(name: &'static str, about: &'static str) -> Self {
+        Command{name, about, args: &[], flags: &[], subcommands: &[]}
+    }
+
+    pub const fn args(self, args: &'static [Value]) -> Self {
+        Command{args, ..self}
+    }
+
+    pub const fn flags(self, flags: &'static [Flag]) -> Self {
+        Command{flags, ..self}
+    }
+
+    pub const fn subcommands(self, subcommands: &'static [Command]) -> Self {
+        Command{subcommands, ..self}
+    }
+
+    fn to_json(self) -> Json {
+        Json::object(vec![
+            ("name", self.name.into()),
+            ("about", self.about.into()),
+            ("args", Json::Array(self.args.iter().map(|a| a.to_json()).collect())),
+            ("flags", Json::Array(self.flags.iter().map(|f| f.to_json()).collect())),
+            ("commands", Json::Array(self.subcommands.iter().map(|c| c.to_json()).collect())),
+        ])
+    }
+}
+
+// A command with the names leading to it, "stats/watch" for a subcommand.
+struct Node {
+    path: String,
+    flags: &'static [Flag],
+    args: &'static [Value],
+    children: Vec<&'static Command>,
+}
+
+impl Program {
+    /// The whole tree as one JSON document:
+    /// `{"name", "flags": [...], "commands": [{"name", "about", "args",
+    /// "flags", "commands"}]}`, each flag with its `value`, null for a
+    /// switch.
+    pub fn describe(&self) -> Json {
+        Json::object(vec![
+            ("name", self.name.into()),
+            ("flags", Json::Array(self.flags.iter().map(|f| f.to_json()).collect())),
+            ("commands", Json::Array(self.commands.iter().map(|c| c.to_json()).collect())),
+        ])
+    }
+
+    /// A completion script to be sourced by, or installed for, `shell`.
+    pub fn completions(&self, shell: Shell) -> String {
+        match shell {
+            Shell::Bash => self.bash(),
+            Shell::Zsh => self.zsh(),
+            Shell::Fish => self.fish(),
+        }
+    }
+
+    // The program and every command under it, parents first.
+    fn nodes(&self) -> Vec<Node> {
+        fn walk(prefix: &str, commands: &'static [Command], out: &mut Vec<Node>) {
+            for c in commands {
+                let path = if prefix.is_empty() { c.name.to_string() } else { format!("{}/{}", prefix, c.name) };
+                out.push(Node{path: path.clone(), flags: c.flags, args: c.args, children: c.subcommands.iter().collect()});
+                walk(&path, c.subcommands, out);
+            }
+        }
+
+        let mut out = vec![Node{path: String::new(), flags: self.flags, args: &[], children: self.commands.iter().collect()}];
+        walk("", self.commands, &mut out);
+        out
+    }
+
+    // Every flag that takes a value, which the shell scripts skip past
+    // along with the value when working out the command.
+    fn value_flags(&self) -> Vec<&'static str> {
+        let mut names: Vec<&str> = self.nodes().iter()
+            .flat_map(|n| n.flags.iter().filter(|f| f.value.is_some()).map(|f| f.name))
+            .collect();
+        names.sort_unstable();
+        names.dedup();
+        names
+    }
+
+    // The shell case patterns that step from one command to the next, such
+    // as "/stats" and "stats/watch".
+    fn steps(&self) -> String {
+        let steps: Vec<String> = self.nodes().iter()
+            .flat_map(|n| n.children.iter().map(move |c| format!("\"{}/{}\"", n.path, c.name)))
+            .collect();
+        steps.join("|")
+    }
+
+    fn bash(&self) -> String {
+        let f = self.name.replace('-', "_");
+        let mut s = format!("_{}() {{\n", f);
+        s += "    local cur=${COMP_WORDS[COMP_CWORD]} prev=${COMP_WORDS[COMP_CWORD-1]}\n";
+        s += "    local cmd=\"\" i\n";
+        s += "    for ((i = 1; i < COMP_CWORD; i++)); do\n";
+        s += "        case \"${COMP_WORDS[i]}\" in\n";
+        s += &format!("            {}) ((i++)); continue ;;\n", self.value_flags().join("|"));
+        s += "        esac\n";
+        s += "        case \"$cmd/${COMP_WORDS[i]}\" in\n";
+        s += &format!("            {}) cmd=\"$cmd/${{COMP_WORDS[i]}}\"; cmd=${{cmd#/}} ;;\n", self.steps());
+        s += "        esac\n";
+        s += "    done\n\n";
+
+        s += "    case \"$cmd $prev\" in\n";
+        for node in self.nodes() {
+            for flag in node.flags {
+                if let Some(value) = flag.value {
+                    let reply = if value.is_path() {
+                        format!("compgen -{} -- \"$cur\"", if value.name == "DIR" { "d" } else { "f" })
+                    } else {
+                        format!("compgen -W \"{}\" -- \"$cur\"", value.choices.join(" "))
+                    };
+                    s += &format!("        \"{} {}\") COMPREPLY=($({})); return ;;\n", node.path, flag.name, reply);
+                }
+            }
+        }
+        s += "    esac\n\n";
+
+        s += "    case \"$cmd\" in\n";
+        for node in self.nodes() {
+            let words = words(&node);
+            match node.args.iter().find(|a| a.is_path()) {
+                Some(arg) => {
+                    let kind = if arg.name == "DIR" { "d" } else { "f" };
+                    s += &format!("        \"{}\") [[ $cur == -* ]] && COMPREPLY=($(compgen -W \"{}\" -- \"$cur\")) || COMPREPLY=($(compgen -{} -- \"$cur\")) ;;\n", node.path, words, kind);
+                },
+                None => s += &format!("        \"{}\") COMPREPLY=($(compgen -W \"{}\" -- \"$cur\")) ;;\n", node.path, words),
+            }
+        }
+        s += "    esac\n}\n";
+        s += &format!("complete -F _{} {}\n", f, self.name);
+        s
+    }
+
+    fn zsh(&self) -> String {
+        // `path` is special in zsh, so the command is kept in `cmd`.
+        let f = self.name.replace('-', "_");
+        let mut s = format!("#compdef {}\n\n_{}() {{\n", self.name, f);
+        s += "    local cmd=\"\" i\n";
+        s += "    for ((i = 2; i < CURRENT; i++)); do\n";
+        s += "        case \"${words[i]}\" in\n";
+        s += &format!("            {}) ((i++)); continue ;;\n", self.value_flags().join("|"));
+        s += "        esac\n";
+        s += "        case \"$cmd/${words[i]}\" in\n";
+        s += &format!("            {}) cmd=\"$cmd/${{words[i]}}\"; cmd=${{cmd#/}} ;;\n", self.steps());
+        s += "        esac\n";
+        s += "    done\n\n";
+
+        s += "    case \"$cmd ${words[CURRENT-1]}\" in\n";
+        for node in self.nodes() {
+            for flag in node.flags {
+                if let Some(value) = flag.value {
+                    let reply = if value.is_path() {
+                        if value.name == "DIR" { "_files -/".to_string() } else { "_files".to_string() }
+                    } else {
+                        format!("compadd -- {}", value.choices.join(" "))
+                    };
+                    s += &format!("        \"{} {}\") {}; return ;;\n", node.path, flag.name, reply);
+                }
+            }
+        }
+        s += "    esac\n\n";
+
+        s += "    case \"$cmd\" in\n";
+        for node in self.nodes() {
+            let mut reply = format!("compadd -- {}", words(&node));
+            if let Some(arg) = node.args.iter().find(|a| a.is_path()) {
+                reply += if arg.name == "DIR" { "; _files -/" } else { "; _files" };
+            }
+            s += &format!("        \"{}\") {} ;;\n", node.path, reply);
+        }
+        s += "    esac\n}\n\n";
+        s += &format!("_{} \"$@\"\n", f);
+        s
+    }
+
+    fn fish(&self) -> String {
+        let mut s = format!("complete -c {} -f\n", self.name);
+
+        for node in self.nodes() {
+            // A command is being completed when its name has been seen and
+            // none of its subcommands' have.
+            let mut condition = match node.path.rsplit('/').next() {
+                Some("") | None => "__fish_use_subcommand".to_string(),
+                Some(name) => format!("__fish_seen_subcommand_from {}", name),
+            };
+            if !node.children.is_empty() && !node.path.is_empty() {
+                let names: Vec<&str> = node.children.iter().map(|c| c.name).collect();
+                condition += &format!("; and not __fish_seen_subcommand_from {}", names.join(" "));
+            }
+            let head = format!("complete -c {} -n {}", self.name, quote(&condition));
+
+            for c in &node.children {
+                s += &format!("{} -a {} -d {}\n", head, c.name, quote(c.about));
+            }
+            for arg in node.args {
+                if arg.is_path() {
+                    s += &format!("{} -F\n", head);
+                } else if !arg.choices.is_empty() {
+                    s += &format!("{} -a {}\n", head, quote(&arg.choices.join(" ")));
+                }
+            }
+            for flag in node.flags {
+                s += &format!("{} -l {}", head, flag.name.trim_start_matches('-'));
+                match flag.value {
+                    Some(v) if v.is_path() => s += " -r -F",
+                    Some(v) if !v.choices.is_empty() => s += &format!(" -x -a {}", quote(&v.choices.join(" "))),
+                    Some(_) => s += " -x",
+                    None => {},
+                }
+                s += &format!(" -d {}\n", quote(flag.about));
+            }
+        }
+        s
+    }
+}
+
+// The words that can come next under a command: its flags, subcommands
+// and the choices for its arguments.
+fn words(node: &Node) -> String {
+    let words: Vec<&str> = node.flags.iter().map(|f| f.name)
+        .chain(node.children.iter().map(|c| c.name))
+        .chain(node.args.iter().flat_map(|a| a.choices.iter().cloned()))
+        .collect();
+    words.join(" ")
+}
+
+// Single quoted for fish, which allows \' and \\ inside.
+fn quote(s: &str) -> String {
+    format!("'{}'", s.replace('\\', "\\\\").replace('\'', "\\'"))
+}
+
+#[cfg(test)]
+mod cli_tests {
+    use super::*;
+
+    const PROGRAM: Program = Program{
+        name: "tool",
+        flags: &[Flag::value("--config", Value::new("FILE"), "settings")],
+        commands: &[
+            Command::new("run", "runs it").flags(&[
+                Flag::value("--rng", Value::one_of("NAME", &["std", "os"]), "the generator"),
+                Flag::switch("--once", "stop after one"),
+            ]),
+            Command::new("stats", "watches").subcommands(&[
+                Command::new("watch", "a directory").args(&[Value::new("DIR")]),
+            ]),
+        ],
+    };
+
+    #[test]
+    fn test_describe() {
+        let json = PROGRAM.describe();
+        let run = match json.get("commands") {
+            Some(Json::Array(commands)) => &commands[0],
+            _ => panic!("no commands"),
+        };
+
+        assert_eq!(json.get("name").and_then(Json::as_str), Some("tool"));
+        assert_eq!(
+            run.to_string(),
+            "{\"name\":\"run\",\"about\":\"runs it\",\"args\":[],\"flags\":[\
+             {\"name\":\"--rng\",\"value\":{\"name\":\"NAME\",\"choices\":[\"std\",\"os\"]},\"about\":\"the generator\"},\
+             {\"name\":\"--once\",\"value\":null,\"about\":\"stop after one\"}],\"commands\":[]}"
+        );
+        assert_eq!(Json::parse(&json.to_string()).unwrap(), json);
+    }
+
+    #[test]
+    fn test_completions() {
+        let bash = PROGRAM.completions(Shell::Bash);
+        assert!(bash.contains("            --config|--rng) ((i++)); continue ;;\n"));
+        assert!(bash.contains("\"/run\"|\"/stats\"|\"stats/watch\")"));
+        assert!(bash.contains("\"run --rng\") COMPREPLY=($(compgen -W \"std os\" -- \"$cur\")); return ;;"));
+        assert!(bash.contains("\"\") COMPREPLY=($(compgen -W \"--config run stats\" -- \"$cur\")) ;;"));
+        assert!(bash.ends_with("complete -F _tool tool\n"));
+
+        let zsh = PROGRAM.completions(Shell::Zsh);
+        assert!(zsh.starts_with("#compdef tool\n"));
+        assert!(zsh.contains("\"stats/watch\") compadd -- ; _files -/ ;;"));
+
+        let fish = PROGRAM.completions(Shell::Fish);
+        assert!(fish.contains("complete -c tool -n '__fish_use_subcommand' -a run -d 'runs it'\n"));
+        assert!(fish.contains("complete -c tool -n '__fish_seen_subcommand_from stats; and not __fish_seen_subcommand_from watch' -a watch -d 'a directory'\n"));
+        assert!(fish.contains("complete -c tool -n '__fish_seen_subcommand_from run' -l rng -x -a 'std os' -d 'the generator'\n"));
+        assert!(fish.contains("complete -c tool -n '__fish_seen_subcommand_from watch' -F\n"));
+
+        assert_eq!(Shell::parse("zsh"), Some(Shell::Zsh));
+        assert_eq!(Shell::parse("csh"), None);
+    }
+}
@@ -0,0 +1,111 @@
+//! Card encodings used by other evaluators, for moving data in and out and
+//! for cross-checking results.
+//!
+//! treys (and deuces, which it forked) packs a card into 32 bits as
+//!
+//! ```text
+//! xxxbbbbb bbbbbbbb cdhsrrrr xxpppppp
+//! ```
+//!
+//! where `b` is one bit per rank, `cdhs` one bit per suit, `r` the rank from
+//! 0 (deuce) to 12 (ace) and `p` the rank's prime, 2 for a deuce up to 41
+//! for an ace. PokerStove numbers cards 0 to 51 as `rank * 4 + suit`, ranks
+//! from the deuce and suits in the order clubs, diamonds, hearts, spades.
+//!
+//! The low ace `Rank::One` has no counterpart in either and converts to
+//! `None`.
+
+use super::{Card, Rank, Suit};
+
+const PRIMES: [u32; 13] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41];
+
+// Deck ranks, deuce to ace.
+fn rank_index(rank: Rank) -> Option<u32> {
+    match rank {
+        Rank::One => None,
+        r => Some(r as u32 - 1),
+    }
+}
+
+fn rank_from_index(i: u32) -> Option<Rank> {
+    Rank::DECK.get(i as usize).cloned()
+}
+
+fn treys_suit(suit: Suit) -> u32 {
+    match suit {
+        Suit::Spades => 1,
+        Suit::Hearts => 2,
+        Suit::Diamonds => 4,
+        Suit::Clubs => 8,
+    }
+}
+
+const STOVE_SUITS: [Suit; 4] = [Suit::Clubs, Suit::Diamonds, Suit::Hearts, Suit::Spades];
+
+pub fn to_treys(card: &Card) -> Option<u32> {
+    let r = rank_index(card.rank)?;
+    Some(1 << (16 + r) | treys_suit(card.suit) << 12 | r << 8 | PRIMES[r as usize])
+}
+
+// Every field has to agree, so stray bits are rejected rather than ignored.
+pub fn from_treys(code: u32) -> Option<Card> {
+    let rank = rank_from_index((code >> 8) & 0xF)?;
+    let suit = [Suit::Spades, Suit::Hearts, Suit::Diamonds, Suit::Clubs].iter()
+        .find(|&&s| treys_suit(s) == (code >> 12) & 0xF)
+        .cloned()?;
+
+    let card = Card{rank, suit};
+    if to_treys(&card) == Some(code) { Some(card) } else { None }
+}
+
+pub fn to_pokerstove(card: &Card) -> Option<u8> {
+    let r = rank_index(card.rank)?;
+    let s = STOVE_SUITS.iter().position(|&s| s == card.suit).unwrap();
+    Some((r * 4) as u8 + s as u8)
+}
+
+pub fn from_pokerstove(code: u8) -> Option<Card> {
+    if code >= 52 { return None; }
+    Some(Card{rank: rank_from_index(code as u32 / 4)?, suit: STOVE_SUITS[code as usize % 4]})
+}
+
+#[cfg(test)]
+mod compat_tests {
+    use super::*;
+    use super::super::deck::Deck;
+
+    fn card(s: &str) -> Card {
+        Card::from_code(s).unwrap()
+    }
+
+    #[test]
+    fn test_treys() {
+        // QH is the example in the treys documentation.
+        assert_eq!(to_treys(&card("QH")), Some(67119647));
+        assert_eq!(to_treys(&card("KD")), Some(134236965));
+        assert_eq!(to_treys(&card("AS")), Some(268442665));
+        assert_eq!(to_treys(&card("2C")), Some(98306));
+        assert_eq!(to_treys(&card("1C")), None);
+
+        assert_eq!(from_treys(67119647), Some(card("QH")));
+        assert_eq!(from_treys(67119647 ^ 1), None);
+        assert_eq!(from_treys(0), None);
+    }
+
+    #[test]
+    fn test_pokerstove() {
+        assert_eq!(to_pokerstove(&card("2C")), Some(0));
+        assert_eq!(to_pokerstove(&card("2S")), Some(3));
+        assert_eq!(to_pokerstove(&card("AS")), Some(51));
+        assert_eq!(from_pokerstove(4), Some(card("3C")));
+        assert_eq!(from_pokerstove(52), None);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for c in Deck::new().cards() {
+            assert_eq!(from_treys(to_treys(c).unwrap()), Some(*c));
+            assert_eq!(from_pokerstove(to_pokerstove(c).unwrap()), Some(*c));
+        }
+    }
+}
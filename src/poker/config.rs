@@ -0,0 +1,358 @@
+//! Settings for the command line tool and the server, read from
+//! `~/.config/poker/config.toml` (or `$XDG_CONFIG_HOME/poker/config.toml`)
+//! so that flags given on every run can be set once:
+//!
+//! ```toml
+//! [defaults]
+//! samples = 20000       # sampled runouts, or showdowns per cell
+//! iterations = 100000   # for solvers such as kuhn
+//! seed = 7              # instead of the clock
+//!
+//! [tables]
+//! allin = "~/.cache/poker/allin.csv"
+//! chubukov = "~/.cache/poker/sc.csv"
+//! matchups = "~/.cache/poker/matchups.csv"
+//!
+//! [output]
+//! format = "json"       # or "text"
+//! color = "auto"        # "always" or "never"
+//! theme = "colorblind"  # "default", "colorblind" or "plain"
+//! locale = "de"         # card letters: "en", "de", "fr" or "nl"
+//! ```
+//!
+//! Only this much of TOML is read: sections, `key = value` lines, and
+//! strings and integers as values. Everything is optional, and
+//! flags on the command line win over the file. Unknown keys are errors,
+//! so a misspelt setting does not go quietly unused.
+
+use std::env;
+use std::fmt;
+use std::fs;
+use std::io::{self, IsTerminal};
+use std::path::{Path, PathBuf};
+
+use super::notation::CardNotation;
+use super::parse::ParseOptions;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum OutputFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum ColorMode {
+    // Color when standard output is a terminal.
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+/// ANSI styles for good and bad numbers, such as a positive or negative EV.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Theme {
+    pub good: &'static str,
+    pub bad: &'static str,
+}
+
+impl Theme {
+    pub const DEFAULT: Theme = Theme{good: "32", bad: "31"};
+    // Blue and orange, told apart with the common kinds of color blindness.
+    pub const COLORBLIND: Theme = Theme{good: "34", bad: "38;5;208"};
+    // Bold and underline, for terminals without color.
+    pub const PLAIN: Theme = Theme{good: "1", bad: "4"};
+
+    fn named(name: &str) -> Option<Theme> {
+        match name {
+            "default" => Some(Theme::DEFAULT),
+            "colorblind" => Some(Theme::COLORBLIND),
+            "plain" => Some(Theme::PLAIN),
+            _ => None,
+        }
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme::DEFAULT
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Default)]
+pub struct Config {
+    pub samples: Option<usize>,
+    pub iterations: Option<usize>,
+    pub seed: Option<u64>,
+    pub allin_table: Option<PathBuf>,
+    pub chubukov_table: Option<PathBuf>,
+    pub matchups_table: Option<PathBuf>,
+    pub format: OutputFormat,
+    pub color: ColorMode,
+    pub theme: Theme,
+    // None for English.
+    pub locale: Option<CardNotation>,
+}
+
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum ConfigError {
+    Io(io::Error),
+    Syntax { line: usize },
+    UnknownKey { line: usize, key: String },
+    BadValue { line: usize, key: String },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConfigError::Io(e) => write!(f, "{}", e),
+            ConfigError::Syntax { line } => write!(f, "line {}: expected [section] or key = value", line),
+            ConfigError::UnknownKey { line, key } => write!(f, "line {}: unknown setting '{}'", line, key),
+            ConfigError::BadValue { line, key } => write!(f, "line {}: bad value for '{}'", line, key),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ConfigError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for ConfigError {
+    fn from(e: io::Error) -> Self {
+        ConfigError::Io(e)
+    }
+}
+
+// A value as written: strings are unquoted, anything else is left as is.
+enum Value {
+    Str(String),
+    Bare(String),
+}
+
+impl Config {
+    /// Where the file is looked for, if a home directory is known.
+    pub fn default_path() -> Option<PathBuf> {
+        let base = match env::var_os("XDG_CONFIG_HOME").filter(|v| !v.is_empty()) {
+            Some(dir) => PathBuf::from(dir),
+            None => PathBuf::from(env::var_os("HOME")?).join(".config"),
+        };
+        Some(base.join("poker").join("config.toml"))
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Config, ConfigError> {
+        Config::parse(&fs::read_to_string(path)?)
+    }
+
+    /// The file at `default_path`, or the defaults when there is none.
+    pub fn load_default() -> Result<Config, ConfigError> {
+        match Config::default_path() {
+            Some(path) if path.exists() => Config::load(path),
+            _ => Ok(Config::default()),
+        }
+    }
+
+    pub fn parse(text: &str) -> Result<Config, ConfigError> {
+        let mut config = Config::default();
+        let mut section = String::new();
+
+        for (i, raw) in text.lines().enumerate() {
+            let line = i + 1;
+            let content = strip_comment(raw).trim();
+            if content.is_empty() { continue; }
+
+            if let Some(name) = content.strip_prefix('[').and_then(|c| c.strip_suffix(']')) {
+                section = name.trim().to_string();
+                continue;
+            }
+
+            let (key, value) = content.split_once('=').ok_or(ConfigError::Syntax{line})?;
+            let key = format!("{}.{}", section, key.trim());
+            let value = parse_value(value.trim()).ok_or(ConfigError::Syntax{line})?;
+            config.set(&key, value).map_err(|e| match e {
+                SetError::Unknown => ConfigError::UnknownKey{line, key: key.clone()},
+                SetError::Bad => ConfigError::BadValue{line, key: key.clone()},
+            })?;
+        }
+        Ok(config)
+    }
+
+    fn set(&mut self, key: &str, value: Value) -> Result<(), SetError> {
+        let number = |v: &Value| -> Result<u64, SetError> {
+            match v {
+                Value::Bare(s) => s.replace('_', "").parse().map_err(|_| SetError::Bad),
+                Value::Str(_) => Err(SetError::Bad),
+            }
+        };
+        let string = |v: &Value| match v {
+            Value::Str(s) => Ok(s.clone()),
+            Value::Bare(_) => Err(SetError::Bad),
+        };
+
+        match key {
+            "defaults.samples" => self.samples = Some(number(&value)? as usize),
+            "defaults.iterations" => self.iterations = Some(number(&value)? as usize),
+            "defaults.seed" => self.seed = Some(number(&value)?),
+            "tables.allin" => self.allin_table = Some(expand_home(&string(&value)?)),
+            "tables.chubukov" => self.chubukov_table = Some(expand_home(&string(&value)?)),
+            "tables.matchups" => self.matchups_table = Some(expand_home(&string(&value)?)),
+            "output.format" => self.format = match string(&value)?.as_str() {
+                "text" => OutputFormat::Text,
+                "json" => OutputFormat::Json,
+                _ => return Err(SetError::Bad),
+            },
+            "output.color" => self.color = match string(&value)?.as_str() {
+                "auto" => ColorMode::Auto,
+                "always" => ColorMode::Always,
+                "never" => ColorMode::Never,
+                _ => return Err(SetError::Bad),
+            },
+            "output.theme" => self.theme = Theme::named(&string(&value)?).ok_or(SetError::Bad)?,
+            "output.locale" => self.locale = match string(&value)?.as_str() {
+                "en" => None,
+                "de" => Some(CardNotation::GERMAN),
+                "fr" => Some(CardNotation::FRENCH),
+                "nl" => Some(CardNotation::DUTCH),
+                _ => return Err(SetError::Bad),
+            },
+            _ => return Err(SetError::Unknown),
+        }
+        Ok(())
+    }
+
+    /// Lenient parsing in the configured card letters, for cards typed by
+    /// people.
+    pub fn parse_options(&self) -> ParseOptions {
+        ParseOptions::default()
+            .notation(self.locale.unwrap_or(CardNotation::ENGLISH))
+            .lenient(true)
+    }
+
+    pub fn use_color(&self) -> bool {
+        match self.color {
+            ColorMode::Always => true,
+            ColorMode::Never => false,
+            ColorMode::Auto => io::stdout().is_terminal(),
+        }
+    }
+
+    /// `text` styled as good or bad in the theme, when color is on.
+    pub fn paint(&self, text: &str, good: bool) -> String {
+        if !self.use_color() { return text.to_string(); }
+        let style = if good { self.theme.good } else { self.theme.bad };
+        format!("\x1b[{}m{}\x1b[0m", style, text)
+    }
+}
+
+enum SetError {
+    Unknown,
+    Bad,
+}
+
+// Cuts a '#' comment, unless the '#' is inside a string.
+fn strip_comment(line: &str) -> &str {
+    let mut quoted = false;
+    let mut escaped = false;
+    for (i, c) in line.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if quoted => escaped = true,
+            '"' => quoted = !quoted,
+            '#' if !quoted => return &line[..i],
+            _ => {},
+        }
+    }
+    line
+}
+
+fn parse_value(s: &str) -> Option<Value> {
+    let inner = match s.strip_prefix('"') {
+        Some(rest) => rest.strip_suffix('"')?,
+        None if !s.is_empty() && !s.contains(char::is_whitespace) => return Some(Value::Bare(s.to_string())),
+        None => return None,
+    };
+
+    let mut out = String::new();
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next()? {
+                '"' => out.push('"'),
+                '\\' => out.push('\\'),
+                'n' => out.push('\n'),
+                't' => out.push('\t'),
+                _ => return None,
+            },
+            '"' => return None,
+            c => out.push(c),
+        }
+    }
+    Some(Value::Str(out))
+}
+
+fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), env::var_os("HOME")) {
+        (Some(rest), Some(home)) => PathBuf::from(home).join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+#[cfg(test)]
+mod config_tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let text = "\
+            # poker settings\n\
+            [defaults]\n\
+            samples = 20_000\n\
+            seed = 7 # fixed\n\
+            \n\
+            [tables]\n\
+            allin = \"/tmp/allin # 1.csv\"\n\
+            [output]\n\
+            format = \"json\"\n\
+            color = \"never\"\n\
+            theme = \"colorblind\"\n\
+            locale = \"de\"\n";
+        let config = Config::parse(text).unwrap();
+
+        assert_eq!(config.samples, Some(20_000));
+        assert_eq!(config.seed, Some(7));
+        assert_eq!(config.iterations, None);
+        assert_eq!(config.allin_table, Some(PathBuf::from("/tmp/allin # 1.csv")));
+        assert_eq!(config.format, OutputFormat::Json);
+        assert_eq!(config.theme, Theme::COLORBLIND);
+        assert!(config.parse_options().parse_card("bh").is_some());
+        assert_eq!(config.paint("+1.5", true), "+1.5");
+
+        assert_eq!(Config::parse("").unwrap(), Config::default());
+    }
+
+    #[test]
+    fn test_errors() {
+        let err = |text: &str| Config::parse(text).unwrap_err().to_string();
+
+        assert_eq!(err("[defaults]\nsample = 10"), "line 2: unknown setting 'defaults.sample'");
+        assert_eq!(err("[output]\n\nformat = \"xml\""), "line 3: bad value for 'output.format'");
+        assert_eq!(err("[defaults]\nsamples = \"many\""), "line 2: bad value for 'defaults.samples'");
+        assert_eq!(err("samples"), "line 1: expected [section] or key = value");
+        assert_eq!(err("[tables]\nallin = \"open"), "line 2: expected [section] or key = value");
+        assert!(matches!(Config::load("/nonexistent/config.toml"), Err(ConfigError::Io(_))));
+    }
+
+    #[test]
+    fn test_paint() {
+        let config = Config{color: ColorMode::Always, ..Config::default()};
+
+        assert_eq!(config.paint("-2.0", false), "\x1b[31m-2.0\x1b[0m");
+        assert_eq!(Config{theme: Theme::PLAIN, ..config}.paint("x", true), "\x1b[1mx\x1b[0m");
+    }
+}
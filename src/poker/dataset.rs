@@ -0,0 +1,343 @@
+use std::fmt;
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+use std::str::FromStr;
+
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+use super::Card;
+use super::atomic;
+use super::deal::evaluate_seven;
+use super::deck::Deck;
+use super::features::{one_hot, rank_histogram, suit_histogram, HAND_CATEGORY};
+use super::format::{FormatError, Header};
+
+const KIND: &str = "dataset";
+
+// v0 files predate the format header and start with "# schema=...".
+const VERSION: u32 = 1;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Schema {
+    // Seven dealt cards, labelled with the class index of their best five
+    // (`HandValue::class_index`, 1 for a royal flush to 7462).
+    Showdown,
+}
+
+impl FromStr for Schema {
+    type Err = DatasetError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "showdown" => Ok(Schema::Showdown),
+            _ => Err(DatasetError::UnknownSchema(s.to_string())),
+        }
+    }
+}
+
+impl fmt::Display for Schema {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Schema::Showdown => write!(f, "showdown"),
+        }
+    }
+}
+
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum DatasetError {
+    UnknownSchema(String),
+    UnsupportedFormat(String),
+    Io(io::Error),
+    Format(FormatError),
+    Malformed { line: usize },
+    // Fewer rows than the header counts, from a write cut short.
+    Truncated { expected: usize, found: usize },
+}
+
+impl fmt::Display for DatasetError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DatasetError::UnknownSchema(s) => write!(f, "unknown schema '{}'", s),
+            DatasetError::UnsupportedFormat(s) => {
+                write!(f, "unsupported output format '{}', expected .csv", s)
+            },
+            DatasetError::Io(e) => write!(f, "{}", e),
+            DatasetError::Format(e) => write!(f, "{}", e),
+            DatasetError::Malformed { line } => write!(f, "malformed dataset row on line {}", line),
+            DatasetError::Truncated { expected, found } => {
+                write!(f, "truncated dataset: {} of {} rows", found, expected)
+            },
+        }
+    }
+}
+
+impl std::error::Error for DatasetError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            DatasetError::Io(e) => Some(e),
+            DatasetError::Format(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for DatasetError {
+    fn from(e: io::Error) -> Self {
+        DatasetError::Io(e)
+    }
+}
+
+impl From<FormatError> for DatasetError {
+    fn from(e: FormatError) -> Self {
+        DatasetError::Format(e)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Row {
+    pub cards: Vec<Card>,
+    pub features: Vec<f32>,
+    pub label: usize,
+}
+
+/// A generated dataset. Row `i` is drawn from a ChaCha8 stream `i` keyed by
+/// `seed`, so any single row can be regenerated from the seed and its index.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Dataset {
+    pub schema: Schema,
+    pub seed: u64,
+    pub rows: Vec<Row>,
+}
+
+pub fn generate(spots: usize, schema: Schema, seed: u64) -> Dataset {
+    let rows = (0..spots).map(|i| showdown_row(seed, i as u64)).collect();
+
+    Dataset{schema, seed, rows}
+}
+
+fn showdown_row(seed: u64, index: u64) -> Row {
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+    rng.set_stream(index);
+
+    let mut deck = Deck::new();
+    deck.shuffle(&mut rng);
+    let cards: [Card; 7] = std::array::from_fn(|_| deck.deal().unwrap());
+
+    // The card, rank and suit blocks of `encode_hand`, over all seven cards.
+    let mut features = Vec::with_capacity(HAND_CATEGORY);
+    features.extend_from_slice(&one_hot(&cards));
+    features.extend_from_slice(&rank_histogram(&cards));
+    features.extend_from_slice(&suit_histogram(&cards));
+    let label = evaluate_seven(&cards).class_index().unwrap() as usize;
+
+    Row{
+        cards: cards.to_vec(),
+        features,
+        label,
+    }
+}
+
+impl Dataset {
+    pub fn write_csv<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        Header::new(KIND, VERSION)
+            .field("schema", self.schema)
+            .field("seed", self.seed)
+            .field("rows", self.rows.len())
+            .write(w)?;
+
+        if let Some(first) = self.rows.first() {
+            write!(w, "row")?;
+            for i in 0..first.cards.len() { write!(w, ",c{}", i)?; }
+            for i in 0..first.features.len() { write!(w, ",f{}", i)?; }
+            writeln!(w, ",label")?;
+        }
+
+        for (i, row) in self.rows.iter().enumerate() {
+            write!(w, "{}", i)?;
+            for c in &row.cards { write!(w, ",{}", c)?; }
+            for f in &row.features { write!(w, ",{}", f)?; }
+            writeln!(w, ",{}", row.label)?;
+        }
+        Ok(())
+    }
+
+    pub fn read_csv<R: BufRead>(r: R) -> Result<Dataset, DatasetError> {
+        let mut lines = r.lines();
+        let first = lines.next().ok_or(FormatError::MissingHeader)??;
+
+        let header = match Header::parse(&first) {
+            Ok(header) => header,
+            Err(FormatError::MissingHeader) => migrate_v0(&first)?,
+            Err(e) => return Err(e.into()),
+        };
+        header.check(KIND, VERSION)?;
+
+        let malformed = |line| DatasetError::Malformed{line};
+        let schema: Schema = header.get("schema").ok_or_else(|| malformed(1))?.parse()?;
+        let seed = header.get("seed").and_then(|s| s.parse().ok()).ok_or_else(|| malformed(1))?;
+
+        let mut rows = vec![];
+        let mut cards_len = 0;
+        for (i, line) in lines.enumerate() {
+            let line = line?;
+            let n = i + 2;
+
+            if line.starts_with("row,") {
+                cards_len = line.split(',').filter(|c| c.starts_with('c')).count();
+                continue;
+            }
+
+            let fields: Vec<&str> = line.split(',').collect();
+            if fields.len() < cards_len + 2 { return Err(malformed(n)); }
+
+            let cards = fields[1..1 + cards_len].iter()
+                .map(|c| Card::from_code(c))
+                .collect::<Option<Vec<Card>>>()
+                .ok_or_else(|| malformed(n))?;
+            let features = fields[1 + cards_len..fields.len() - 1].iter()
+                .map(|f| f.parse().ok())
+                .collect::<Option<Vec<f32>>>()
+                .ok_or_else(|| malformed(n))?;
+            let label = fields[fields.len() - 1].parse().map_err(|_| malformed(n))?;
+
+            rows.push(Row{cards, features, label});
+        }
+
+        // Files from before the count was written are taken as they are.
+        if let Some(expected) = header.get("rows").and_then(|r| r.parse().ok()) {
+            if rows.len() != expected { return Err(DatasetError::Truncated{expected, found: rows.len()}); }
+        }
+
+        Ok(Dataset{schema, seed, rows})
+    }
+
+    pub fn write_to<P: AsRef<Path>>(&self, path: P) -> Result<(), DatasetError> {
+        let path = path.as_ref();
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("csv") => (),
+            other => {
+                return Err(DatasetError::UnsupportedFormat(other.unwrap_or("").to_string()));
+            },
+        }
+
+        atomic::write(path, |w| self.write_csv(w))?;
+        Ok(())
+    }
+}
+
+// The v0 header carried the same fields without the magic and version.
+fn migrate_v0(line: &str) -> Result<Header, FormatError> {
+    let fields = line.strip_prefix("# schema=").ok_or(FormatError::MissingHeader)?;
+    Header::parse(&format!("# misc-poker {} v0 schema={}", KIND, fields))
+}
+
+#[cfg(test)]
+mod dataset_tests {
+    use super::*;
+    use super::super::HandValue;
+    use super::super::features::CARDS;
+
+    #[test]
+    fn test_generate_reproducible() {
+        let a = generate(20, Schema::Showdown, 42);
+        let b = generate(20, Schema::Showdown, 42);
+        let c = generate(20, Schema::Showdown, 43);
+
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+        assert_eq!(generate(5, Schema::Showdown, 42).rows[..], a.rows[..5]);
+    }
+
+    #[test]
+    fn test_labels() {
+        let data = generate(50, Schema::Showdown, 1);
+
+        for row in &data.rows {
+            assert_eq!(row.cards.len(), 7);
+            assert_eq!(row.features.len(), HAND_CATEGORY);
+            assert_eq!(row.features.iter().take(CARDS).sum::<f32>(), 7.0);
+            assert!((1..=7462).contains(&row.label));
+
+            let cards: [Card; 7] = std::array::from_fn(|i| row.cards[i]);
+            assert_eq!(HandValue::from_class_index(row.label as u16), Some(evaluate_seven(&cards)));
+        }
+    }
+
+    #[test]
+    fn test_unsupported() {
+        assert!(matches!(
+            generate(1, Schema::Showdown, 0).write_to("out.parquet"),
+            Err(DatasetError::UnsupportedFormat(_))
+        ));
+        assert!("river".parse::<Schema>().is_err());
+        assert!(matches!("decision".parse::<Schema>(), Err(DatasetError::UnknownSchema(_))));
+    }
+
+    #[test]
+    fn test_write_csv() {
+        let data = generate(2, Schema::Showdown, 9);
+        let mut out = Vec::new();
+        data.write_csv(&mut out).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+
+        assert_eq!(lines[0], "# misc-poker dataset v1 schema=showdown seed=9 rows=2");
+        assert!(lines[1].starts_with("row,c0,c1,c2,c3,c4,c5,c6,f0,"));
+        assert!(lines[1].ends_with(",label"));
+        assert_eq!(lines.len(), 4);
+    }
+
+    #[test]
+    fn test_read_csv() {
+        let data = generate(3, Schema::Showdown, 9);
+        let mut out = Vec::new();
+        data.write_csv(&mut out).unwrap();
+
+        assert_eq!(Dataset::read_csv(&out[..]).unwrap(), data);
+
+        // Cut off after the second row.
+        let text = String::from_utf8(out).unwrap();
+        let cut: Vec<&str> = text.lines().take(4).collect();
+        assert!(matches!(
+            Dataset::read_csv(cut.join("\n").as_bytes()),
+            Err(DatasetError::Truncated{expected: 3, found: 2})
+        ));
+    }
+
+    #[test]
+    fn test_read_v0() {
+        let v0 = "# schema=showdown seed=9 rows=1\n\
+                  row,c0,c1,c2,c3,c4,f0,label\n\
+                  0,QH,3D,JC,KD,2S,1,0\n";
+        let data = Dataset::read_csv(v0.as_bytes()).unwrap();
+
+        assert_eq!(data.seed, 9);
+        assert_eq!(data.rows[0].cards[0], Card::from_code("QH").unwrap());
+        assert_eq!(data.rows[0].features, vec![1.0]);
+    }
+
+    #[test]
+    fn test_read_rejects() {
+        let newer = "# misc-poker dataset v2 schema=showdown seed=9 rows=0\n";
+        assert!(matches!(
+            Dataset::read_csv(newer.as_bytes()),
+            Err(DatasetError::Format(FormatError::Unsupported{version: 2, ..}))
+        ));
+
+        let other = "# misc-poker table v1\n";
+        assert!(matches!(
+            Dataset::read_csv(other.as_bytes()),
+            Err(DatasetError::Format(FormatError::WrongKind{..}))
+        ));
+
+        let bad = "# misc-poker dataset v1 schema=showdown seed=9 rows=1\n\
+                   row,c0,label\n\
+                   0,ZZ,1\n";
+        assert!(matches!(
+            Dataset::read_csv(bad.as_bytes()),
+            Err(DatasetError::Malformed{line: 3})
+        ));
+    }
+}
@@ -0,0 +1,405 @@
+use std::fmt;
+use std::str::FromStr;
+
+use super::{Card, Hand, HandValue};
+use super::deck::Deck;
+use super::notation::CardNotation;
+
+/// A complete Hold'em deal: two hole cards per seat, the three burn cards
+/// and the five board cards, all in the order they came off the deck.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Deal {
+    pub holes: Vec<[Card; 2]>,
+    pub burns: [Card; 3],
+    pub board: [Card; 5],
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum DealParseError {
+    Malformed(String),
+    // The same card appears twice across the holes, burns and board.
+    DuplicateCard(Card),
+}
+
+impl fmt::Display for DealParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DealParseError::Malformed(s) => write!(f, "malformed deal: {}", s),
+            DealParseError::DuplicateCard(card) => write!(f, "duplicate card {}", card),
+        }
+    }
+}
+
+impl std::error::Error for DealParseError {}
+
+impl Deck {
+    // Hole cards go round the table one at a time starting from seat 0,
+    // then burn and flop, burn and turn, burn and river.
+    pub fn deal_holdem(&mut self, players: usize) -> Option<Deal> {
+        if players == 0 || self.len() < players * 2 + 8 { return None; }
+
+        let first: Vec<Card> = (0..players).map(|_| self.deal()).collect::<Option<_>>()?;
+        let second: Vec<Card> = (0..players).map(|_| self.deal()).collect::<Option<_>>()?;
+        let holes = first.into_iter().zip(second).map(|(a, b)| [a, b]).collect();
+
+        let burn = self.burn()?;
+        let flop = [self.deal()?, self.deal()?, self.deal()?];
+        let burn_turn = self.burn()?;
+        let turn = self.deal()?;
+        let burn_river = self.burn()?;
+        let river = self.deal()?;
+
+        Some(Deal {
+            holes,
+            burns: [burn, burn_turn, burn_river],
+            board: [flop[0], flop[1], flop[2], turn, river],
+        })
+    }
+}
+
+impl Deal {
+    pub fn players(&self) -> usize {
+        self.holes.len()
+    }
+
+    // Every card visible to someone, for use as known or dead cards.
+    pub fn known_cards(&self) -> Vec<Card> {
+        self.holes.iter().flatten().chain(self.board.iter()).cloned().collect()
+    }
+
+    pub fn best_hand(&self, player: usize) -> Hand {
+        best_holdem(&self.holes[player], &self.board)
+    }
+
+    // Seats holding the best hand, more than one on a split pot.
+    pub fn showdown(&self) -> Vec<usize> {
+        winners(&self.holes, &self.board)
+    }
+}
+
+pub(crate) fn best_holdem(hole: &[Card; 2], board: &[Card; 5]) -> Hand {
+    best_of(&[hole[0], hole[1], board[0], board[1], board[2], board[3], board[4]])
+}
+
+pub(crate) fn winners(holes: &[[Card; 2]], board: &[Card; 5]) -> Vec<usize> {
+    let hands: Vec<Hand> = holes.iter().map(|h| best_holdem(h, board)).collect();
+    let best = hands.iter().max().unwrap();
+
+    (0..holes.len()).filter(|&p| hands[p] == *best).collect()
+}
+
+/// The value of the best five card hand out of seven, for Hold'em hole
+/// cards with the board or a Stud hand. Tries all 21 ways to pick five.
+pub fn evaluate_seven(cards: &[Card; 7]) -> HandValue {
+    best_of(cards).value()
+}
+
+/// The value of an Omaha hand, which must be made from exactly two of the
+/// four hole cards and three of the board. Running `evaluate_seven` or
+/// `best_hand` on all nine cards gets this wrong: four suited hole cards
+/// with one on the board make no flush, and a board straight does not play
+/// on its own. Tries all 60 ways to pick two and three.
+pub fn evaluate_omaha(hole: &[Card; 4], board: &[Card; 5]) -> HandValue {
+    best_omaha(hole, board).value()
+}
+
+/// The best Omaha hand, two of `hole` and three of `board`, for boards of
+/// three cards or more and any number of hole cards from two: PLO5 deals
+/// five and Big O six.
+///
+/// # Panics
+///
+/// If there are fewer than two hole cards or three board cards.
+pub fn best_omaha(hole: &[Card], board: &[Card]) -> Hand {
+    omaha_hands(hole, board).map(Hand::new)
+        .reduce(|best, hand| if hand > best { hand } else { best })
+        .unwrap()
+}
+
+// Every pick of two of `hole` and three of `board`.
+pub(crate) fn omaha_hands<'a>(hole: &'a [Card], board: &'a [Card]) -> impl Iterator<Item = [Card; 5]> + 'a {
+    assert!(hole.len() >= 2 && board.len() >= 3, "two hole cards and three board cards");
+
+    pairs(hole.len()).flat_map(move |(i, j)| {
+        triples(board.len()).map(move |(a, b, c)| [hole[i], hole[j], board[a], board[b], board[c]])
+    })
+}
+
+fn pairs(n: usize) -> impl Iterator<Item = (usize, usize)> {
+    (0..n).flat_map(move |i| (i + 1..n).map(move |j| (i, j)))
+}
+
+fn triples(n: usize) -> impl Iterator<Item = (usize, usize, usize)> {
+    pairs(n).flat_map(move |(i, j)| (j + 1..n).map(move |k| (i, j, k)))
+}
+
+/// The best five card hand out of any number of cards, five or more, with
+/// its value. Every way to pick five is tried, so six cards for Pineapple
+/// cost 6 evaluations and seven cost 21.
+///
+/// # Panics
+///
+/// If there are fewer than five cards.
+pub fn best_hand(cards: &[Card]) -> (Hand, HandValue) {
+    let hand = best_of(cards);
+    let value = hand.value();
+    (hand, value)
+}
+
+pub(crate) fn best_of(cards: &[Card]) -> Hand {
+    // The first of equally strong hands is kept.
+    fives(cards).reduce(|best, hand| if hand > best { hand } else { best }).unwrap()
+}
+
+// Every hand of five out of `cards`, the positions picked stepped through
+// in order.
+pub(crate) fn fives(cards: &[Card]) -> impl Iterator<Item = Hand> + '_ {
+    assert!(cards.len() >= 5, "at least five cards");
+    let n = cards.len();
+    let mut picked = Some([0, 1, 2, 3, 4]);
+
+    std::iter::from_fn(move || {
+        let current = picked?;
+        picked = (0..5).rev().find(|&i| current[i] < n - 5 + i).map(|i| {
+            let mut next = current;
+            next[i] += 1;
+            for j in i + 1..5 {
+                next[j] = next[j - 1] + 1;
+            }
+            next
+        });
+        Some(Hand::new(current.map(|i| cards[i])))
+    })
+}
+
+fn write_cards(f: &mut fmt::Formatter, cards: &[Card]) -> fmt::Result {
+    for card in cards {
+        write!(f, "{}", card)?;
+    }
+    Ok(())
+}
+
+fn parse_cards(s: &str) -> Result<Vec<Card>, DealParseError> {
+    let chars: Vec<char> = CardNotation::ENGLISH.tens(s).chars().collect();
+    if chars.len() % 2 != 0 {
+        return Err(DealParseError::Malformed(s.to_string()));
+    }
+
+    chars.chunks(2)
+        .map(|c| Card::from_code(&c.iter().collect::<String>()).ok_or_else(|| DealParseError::Malformed(s.to_string())))
+        .collect()
+}
+
+// Written as `holes=ASKD,7H2C burns=3D4C5H board=9STSJSQS2D`.
+impl fmt::Display for Deal {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "holes=")?;
+        for (i, hole) in self.holes.iter().enumerate() {
+            if i > 0 { write!(f, ",")?; }
+            write_cards(f, hole)?;
+        }
+        write!(f, " burns=")?;
+        write_cards(f, &self.burns)?;
+        write!(f, " board=")?;
+        write_cards(f, &self.board)
+    }
+}
+
+impl FromStr for Deal {
+    type Err = DealParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || DealParseError::Malformed(s.to_string());
+
+        let mut holes = vec![];
+        let mut burns = None;
+        let mut board = None;
+
+        for word in s.split_whitespace() {
+            let (key, value) = word.split_once('=').ok_or_else(err)?;
+            match key {
+                "holes" => {
+                    for hole in value.split(',') {
+                        let cards = parse_cards(hole)?;
+                        if cards.len() != 2 { return Err(err()); }
+                        holes.push([cards[0], cards[1]]);
+                    }
+                },
+                "burns" => burns = Some(parse_cards(value)?),
+                "board" => board = Some(parse_cards(value)?),
+                _ => return Err(err()),
+            }
+        }
+
+        let burns = burns.filter(|b| b.len() == 3).ok_or_else(err)?;
+        let board = board.filter(|b| b.len() == 5).ok_or_else(err)?;
+        if holes.is_empty() { return Err(err()); }
+
+        let mut seen: Vec<Card> = vec![];
+        for &card in holes.iter().flatten().chain(&burns).chain(&board) {
+            if seen.contains(&card) { return Err(DealParseError::DuplicateCard(card)); }
+            seen.push(card);
+        }
+
+        Ok(Deal {
+            holes,
+            burns: [burns[0], burns[1], burns[2]],
+            board: [board[0], board[1], board[2], board[3], board[4]],
+        })
+    }
+}
+
+#[cfg(test)]
+mod deal_tests {
+    use super::*;
+    use super::super::{Category, Rank};
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    fn cards<const N: usize>(s: &str) -> [Card; N] {
+        let cards: Vec<Card> = s.split_whitespace().map(|c| Card::from_code(c).unwrap()).collect();
+        std::convert::TryInto::try_into(cards).unwrap()
+    }
+
+    #[test]
+    fn test_omaha() {
+        // Four hearts in the hand and one on the board: no flush.
+        let hole = cards("AH KH QH JH");
+        let board = cards("2H 7C 9D 3S 4C");
+        assert_eq!(evaluate_omaha(&hole, &board).category(), Category::HighCard);
+        let all: [Card; 9] = cards("AH KH QH JH 2H 7C 9D 3S 4C");
+        assert_eq!(best_hand(&all).1.category(), Category::Flush);
+
+        // A straight on the board needs two hole cards to play.
+        let board = cards("6C 7D 8S 9H TC");
+        assert_eq!(evaluate_omaha(&cards("AH AD KC KS"), &board).category(), Category::OnePair);
+        assert_eq!(evaluate_omaha(&cards("JH QD 2C 2S"), &board).tiebreaks(), &[Rank::Queen]);
+
+        // Nor does one hole card of the suit with four on the board.
+        let board = cards("2H 5H 8H JH 3C");
+        assert_eq!(evaluate_omaha(&cards("AH KC QC QD"), &board).category(), Category::OnePair);
+        assert_eq!(evaluate_omaha(&cards("AH KH QC QD"), &board).category(), Category::Flush);
+
+        let five = cards::<5>("AH AD 2C 2S KH");
+        assert_eq!(omaha_hands(&five, &board).count(), 100);
+    }
+
+    #[test]
+    fn test_deal_order() {
+        let mut deck = Deck::new();
+        let top: Vec<Card> = deck.cards().iter().rev().cloned().collect();
+        let deal = deck.deal_holdem(2).unwrap();
+
+        assert_eq!(deal.holes, vec![[top[0], top[2]], [top[1], top[3]]]);
+        assert_eq!(deal.burns, [top[4], top[8], top[10]]);
+        assert_eq!(deal.board, [top[5], top[6], top[7], top[9], top[11]]);
+        assert_eq!(deck.len(), 52 - 12);
+    }
+
+    #[test]
+    fn test_too_many_players() {
+        assert!(Deck::new().deal_holdem(22).is_some());
+        assert!(Deck::new().deal_holdem(23).is_none());
+        assert!(Deck::new().deal_holdem(0).is_none());
+    }
+
+    #[test]
+    fn test_known_cards() {
+        let mut deck = Deck::new();
+        deck.shuffle(&mut ChaCha8Rng::seed_from_u64(1));
+        let deal = deck.deal_holdem(6).unwrap();
+        let known = deal.known_cards();
+
+        assert_eq!(known.len(), 17);
+        assert!(!deal.burns.iter().any(|b| known.contains(b)));
+    }
+
+    #[test]
+    fn test_showdown() {
+        let deal: Deal = "holes=ASAD,KSKD,AHAC burns=2C3C4C board=7S8D9HJC2D".parse().unwrap();
+
+        assert_eq!(deal.showdown(), vec![0, 2]);
+        assert_eq!(deal.best_hand(1).value().category(), super::super::Category::OnePair);
+
+        let deal: Deal = "holes=ASAD,KSKD burns=2C3C4C board=KH8D9HJC2D".parse().unwrap();
+        assert_eq!(deal.showdown(), vec![1]);
+    }
+
+    #[test]
+    fn test_evaluate_seven() {
+        let cards = |s: &str| -> [Card; 7] {
+            let v: Vec<Card> = s.split_whitespace().map(|c| Card::from_code(c).unwrap()).collect();
+            [v[0], v[1], v[2], v[3], v[4], v[5], v[6]]
+        };
+        let hand = |s: &str| s.parse::<Hand>().unwrap().value();
+
+        assert_eq!(evaluate_seven(&cards("AS AD 7S 8D 9H JC 2D")), hand("AS AD JC 9H 8D"));
+        assert_eq!(evaluate_seven(&cards("5H 6H 7S 8D 9H TH 2H")), hand("5H 6H 9H TH 2H"));
+        assert_eq!(evaluate_seven(&cards("KS KD KH 2C 2D 2S 9C")), hand("KS KD KH 2C 2D"));
+    }
+
+    #[test]
+    fn test_wheel() {
+        let deal: Deal = "holes=5SKD,KSKC burns=2C3C4C board=AH2D3H4SJC".parse().unwrap();
+        assert_eq!(deal.showdown(), vec![0]);
+        assert_eq!(deal.best_hand(0), "AH 2D 3H 4S 5S".parse::<Hand>().unwrap());
+
+        // The five makes a steel wheel in Stud.
+        let value = evaluate_seven(&cards::<7>("AD 2D 3D 4D 5D 6C 6S"));
+        assert_eq!(value.category(), super::super::Category::StraightFlush);
+        assert_eq!(value.tiebreaks(), &[super::super::Rank::Five]);
+    }
+
+    #[test]
+    fn test_best_hand() {
+        let hand = |s: &str| s.parse::<Hand>().unwrap();
+
+        let (best, value) = best_hand(&cards_of("AS KS 9D 9C 2H 2S"));
+        assert_eq!(best, hand("AS 9D 9C 2H 2S"));
+        assert_eq!(value, best.value());
+
+        assert_eq!(best_hand(&cards_of("AS KS QS JS TS")).0, hand("AS KS QS JS TS"));
+        assert_eq!(best_hand(&cards_of("2C 3D 4H 5S 7C 8D 9H JS KC 6C")).0, hand("5S 6C 7C 8D 9H"));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_best_hand_too_few() {
+        best_hand(&cards_of("AS KS QS JS"));
+    }
+
+    fn cards_of(s: &str) -> Vec<Card> {
+        s.split_whitespace().map(|c| Card::from_code(c).unwrap()).collect()
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut deck = Deck::new();
+        deck.shuffle(&mut ChaCha8Rng::seed_from_u64(2));
+        let deal = deck.deal_holdem(3).unwrap();
+        let text = deal.to_string();
+
+        assert!(text.starts_with("holes="));
+        assert_eq!(text.parse::<Deal>(), Ok(deal));
+        assert!("holes=AS burns=2C3C4C board=7S8D9HJC2D".parse::<Deal>().is_err());
+        assert!("holes=ASAD burns=2C3C board=7S8D9HJC2D".parse::<Deal>().is_err());
+        assert_eq!(
+            "holes=AS10D burns=2C3C4C board=7S8D9HJC10S".parse::<Deal>().unwrap().to_string(),
+            "holes=ASTD burns=2C3C4C board=7S8D9HJCTS"
+        );
+    }
+
+    #[test]
+    fn test_parse_duplicates() {
+        let parse = |s: &str| s.parse::<Deal>();
+        let dup = |c: &str| Err(DealParseError::DuplicateCard(Card::from_code(c).unwrap()));
+
+        assert_eq!(parse("holes=ASAS burns=2C3C4C board=7S8D9HJC2D"), dup("AS"));
+        assert_eq!(parse("holes=ASAD,KSAD burns=2C3C4C board=7S8D9HJC2D"), dup("AD"));
+        assert_eq!(parse("holes=ASAD burns=2C3C4C board=7S8D9HJCAS"), dup("AS"));
+        assert_eq!(parse("holes=ASAD burns=2C3C4C board=7S8D9HJC2C"), dup("2C"));
+        assert_eq!(parse("holes=ASAD burns=2C2C4C board=7S8D9HJC2D"), dup("2C"));
+        assert!(parse("holes=ASAD burns=2C3C4C board=7S8D9HJC2D").is_ok());
+    }
+}
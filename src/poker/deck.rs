@@ -0,0 +1,634 @@
+use rand::{Rng, SeedableRng};
+use rand::seq::SliceRandom;
+use rand_chacha::ChaCha8Rng;
+
+use std::fmt;
+
+use super::{Card, Hand, Rank, Suit};
+use super::batch;
+use super::json::Json;
+
+// One character per card, by `batch::index`.
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// A standard 52 card deck, Two through Ace in each suit. Cards are dealt
+/// from the top, which is the end of the underlying vector.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Deck {
+    cards: Vec<Card>,
+}
+
+// A deal asked for more cards than the deck had left.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NotEnoughCards {
+    pub needed: usize,
+    pub left: usize,
+}
+
+impl fmt::Display for NotEnoughCards {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "needed {} cards, {} left in the deck", self.needed, self.left)
+    }
+}
+
+impl std::error::Error for NotEnoughCards {}
+
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum DeckDecodeError {
+    // Positions count from 0, from the bottom card.
+    BadChar { index: usize },
+    BadCard { index: usize },
+    DuplicateCard(Card),
+    NotAnArray,
+    // The copies of each card before the cards are not a positive number.
+    BadCopies,
+}
+
+impl fmt::Display for DeckDecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DeckDecodeError::BadChar { index } => write!(f, "not a base64 character at position {}", index),
+            DeckDecodeError::BadCard { index } => write!(f, "not a card at position {}", index),
+            DeckDecodeError::DuplicateCard(card) => write!(f, "duplicate card {}", card),
+            DeckDecodeError::NotAnArray => write!(f, "expected an array of cards"),
+            DeckDecodeError::BadCopies => write!(f, "the copies of each card are not a positive number"),
+        }
+    }
+}
+
+impl std::error::Error for DeckDecodeError {}
+
+// One base64 character per card, as `Deck::encode` writes them; `None`
+// if a card is `Rank::One`.
+pub(crate) fn encode_cards(cards: &[Card]) -> Option<String> {
+    cards.iter().map(|c| batch::index(c).map(|i| BASE64[i as usize] as char)).collect()
+}
+
+// The cards `encode_cards` gave, none more than `copies` times.
+pub(crate) fn decode_cards(s: &str, copies: usize) -> Result<Vec<Card>, DeckDecodeError> {
+    let cards = s.chars().enumerate()
+        .map(|(index, c)| match BASE64.iter().position(|&b| b as char == c) {
+            Some(i) if i < 52 => Ok(batch::card(i as u8)),
+            Some(_) => Err(DeckDecodeError::BadCard{index}),
+            None => Err(DeckDecodeError::BadChar{index}),
+        })
+        .collect::<Result<Vec<Card>, _>>()?;
+    check_copies(cards, copies)
+}
+
+// Card codes as `Deck::to_json` writes them, none more than `copies` times.
+pub(crate) fn cards_from_json(codes: &[Json], copies: usize) -> Result<Vec<Card>, DeckDecodeError> {
+    let cards = codes.iter().enumerate()
+        .map(|(index, code)| code.as_str().and_then(Card::from_code).ok_or(DeckDecodeError::BadCard{index}))
+        .collect::<Result<Vec<Card>, _>>()?;
+    check_copies(cards, copies)
+}
+
+fn check_copies(cards: Vec<Card>, copies: usize) -> Result<Vec<Card>, DeckDecodeError> {
+    for (i, card) in cards.iter().enumerate() {
+        if cards[..i].iter().filter(|&c| c == card).count() >= copies {
+            return Err(DeckDecodeError::DuplicateCard(*card));
+        }
+    }
+    Ok(cards)
+}
+
+impl Default for Deck {
+    fn default() -> Self {
+        Deck::new()
+    }
+}
+
+impl Deck {
+    pub fn new() -> Self {
+        Deck::with_ranks(&Rank::DECK)
+    }
+
+    /// The 52 card deck, in order; the same as `new`.
+    pub fn standard() -> Self {
+        Deck::new()
+    }
+
+    /// The 36 cards Six through Ace of short-deck Hold'em.
+    pub fn short_deck() -> Self {
+        Deck::with_ranks(&Rank::DECK[4..])
+    }
+
+    /// The 32 cards Seven through Ace of piquet, belote and skat.
+    pub fn piquet() -> Self {
+        Deck::with_ranks(&Rank::DECK[5..])
+    }
+
+    /// The 24 cards Nine through Ace of euchre.
+    pub fn euchre() -> Self {
+        Deck::with_ranks(&Rank::DECK[7..])
+    }
+
+    /// The 48 cards of pinochle: two of every card Nine through Ace, so
+    /// each card appears twice.
+    pub fn pinochle() -> Self {
+        let mut deck = Deck::euchre();
+        deck.cards.extend(Deck::euchre().cards);
+        deck
+    }
+
+    // Every suit of each of `ranks`, such as Six through Ace for a short
+    // deck; see `Rules::deck`.
+    pub fn with_ranks(ranks: &[Rank]) -> Self {
+        let mut cards = Vec::with_capacity(4 * ranks.len());
+
+        for &suit in Suit::ALL.iter() {
+            for &rank in ranks {
+                cards.push(Card{rank, suit});
+            }
+        }
+
+        Deck{cards}
+    }
+
+    pub fn len(&self) -> usize {
+        self.cards.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cards.is_empty()
+    }
+
+    pub fn cards(&self) -> &[Card] {
+        &self.cards
+    }
+
+    // For reordering in place, as the physical shuffles do.
+    pub(crate) fn cards_mut(&mut self) -> &mut Vec<Card> {
+        &mut self.cards
+    }
+
+    /// Shuffles with any generator; a seeded one gives the same order on
+    /// every run.
+    pub fn shuffle<R: Rng + ?Sized>(&mut self, rng: &mut R) {
+        self.cards.shuffle(rng);
+    }
+
+    /// The cards left in random order, each drawn as it is asked for, so a
+    /// runout needing a few cards costs a few draws rather than a shuffle
+    /// of the whole deck. Nothing is dealt: drawn cards are swapped to the
+    /// top as they come, and the deck keeps every card for the next trial.
+    pub fn sample_iter<'a, R: Rng + ?Sized>(&'a mut self, rng: &'a mut R) -> SampleIter<'a, R> {
+        let left = self.cards.len();
+        SampleIter{cards: &mut self.cards, rng, left}
+    }
+
+    /// A standard deck shuffled by ChaCha8 from `seed`.
+    ///
+    /// The order for a seed is fixed: it does not change between runs,
+    /// platforms or releases of this crate. It depends on the ChaCha8
+    /// stream and on the shuffle in `rand`, so moving to a new major
+    /// version of either would change it, and such a release says so.
+    pub fn shuffled_with_seed(seed: u64) -> Self {
+        let mut deck = Deck::new();
+        deck.shuffle(&mut ChaCha8Rng::seed_from_u64(seed));
+        deck
+    }
+
+    /// A standard deck less `cards`: hole cards, the board and any others
+    /// seen, such as folded cards, so that none of them can be dealt again.
+    pub fn without<I: IntoIterator<Item = Card>>(cards: I) -> Self {
+        let dead: Vec<Card> = cards.into_iter().collect();
+        let mut deck = Deck::new();
+        deck.remove(&dead);
+        deck
+    }
+
+    // Takes cards already known to be out, such as hole cards or a board.
+    pub fn remove(&mut self, cards: &[Card]) {
+        self.cards.retain(|c| !cards.contains(c));
+    }
+
+    /// The chance that the next card dealt is one `predicate` holds for,
+    /// given only which cards are left, not their order; 0 for an empty
+    /// deck.
+    pub fn draw_probability<F: Fn(&Card) -> bool>(&self, predicate: F) -> f64 {
+        if self.cards.is_empty() { return 0.0; }
+        self.cards.iter().filter(|c| predicate(c)).count() as f64 / self.cards.len() as f64
+    }
+
+    pub fn rank_probability(&self, rank: Rank) -> f64 {
+        self.draw_probability(|c| c.rank == rank)
+    }
+
+    pub fn suit_probability(&self, suit: Suit) -> f64 {
+        self.draw_probability(|c| c.suit == suit)
+    }
+
+    pub fn card_probability(&self, card: Card) -> f64 {
+        self.draw_probability(|&c| c == card)
+    }
+
+    /// The chance that at least one of the next `draws` cards is one
+    /// `predicate` holds for, such as hitting one of a draw's outs by the
+    /// river with two cards to come. 1 if `draws` is the whole deck and a
+    /// card matches.
+    pub fn draw_probability_within<F: Fn(&Card) -> bool>(&self, predicate: F, draws: usize) -> f64 {
+        let n = self.cards.len();
+        let blanks = n - self.cards.iter().filter(|c| predicate(c)).count();
+        let draws = draws.min(n);
+
+        // Missing every time: C(blanks, draws) / C(n, draws), one draw at a
+        // time.
+        let miss = (0..draws).fold(1.0, |p, i| p * blanks.saturating_sub(i) as f64 / (n - i) as f64);
+        1.0 - miss
+    }
+
+    /// The exact order of the cards left, one base64 character per card
+    /// from the bottom up, for saving a deck part way through a hand. A
+    /// full deck takes 52 characters. A deck holding some card more than
+    /// once, such as pinochle, is prefixed with the most copies of any
+    /// card and a star: "2*" and 48 characters. Decks holding `Rank::One`
+    /// cannot be encoded.
+    pub fn encode(&self) -> Option<String> {
+        let cards = encode_cards(&self.cards)?;
+        match self.copies() {
+            1 => Some(cards),
+            copies => Some(format!("{}*{}", copies, cards)),
+        }
+    }
+
+    /// The deck `encode` gave.
+    pub fn decode(s: &str) -> Result<Deck, DeckDecodeError> {
+        let (copies, s) = match s.split_once('*') {
+            Some((n, rest)) if !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit()) => {
+                (n.parse().ok().filter(|&n| n > 0).ok_or(DeckDecodeError::BadCopies)?, rest)
+            }
+            _ => (1, s),
+        };
+        Ok(Deck{cards: decode_cards(s, copies)?})
+    }
+
+    /// The cards left as an array of card codes, bottom first. A deck
+    /// holding some card more than once gives `{"copies": 2, "cards": [..]}`
+    /// instead, as `encode` prefixes the copies.
+    pub fn to_json(&self) -> Json {
+        let cards = Json::Array(self.cards.iter().map(|c| c.to_string().into()).collect());
+        match self.copies() {
+            1 => cards,
+            copies => Json::object(vec![("copies", Json::Number(copies as f64)), ("cards", cards)]),
+        }
+    }
+
+    pub fn from_json(json: &Json) -> Result<Deck, DeckDecodeError> {
+        let (codes, copies) = match json {
+            Json::Array(codes) => (codes, 1),
+            Json::Object(_) => match (json.get("cards"), json.get("copies").and_then(Json::as_f64)) {
+                (Some(Json::Array(codes)), Some(n)) if n >= 1.0 && n.fract() == 0.0 => (codes, n as usize),
+                (Some(Json::Array(_)), _) => return Err(DeckDecodeError::BadCopies),
+                _ => return Err(DeckDecodeError::NotAnArray),
+            },
+            _ => return Err(DeckDecodeError::NotAnArray),
+        };
+        Ok(Deck{cards: cards_from_json(codes, copies)?})
+    }
+
+    // The most times any one card is held, at least 1.
+    fn copies(&self) -> usize {
+        self.cards.iter().map(|card| self.cards.iter().filter(|&c| c == card).count()).max().unwrap_or(1)
+    }
+
+    pub fn deal(&mut self) -> Option<Card> {
+        self.cards.pop()
+    }
+
+    // The next `n` cards in the order they come off, or none of them if
+    // fewer are left.
+    pub fn deal_n(&mut self, n: usize) -> Option<Vec<Card>> {
+        if n > self.cards.len() { return None; }
+        let rest = self.cards.len() - n;
+        Some(self.cards.drain(rest..).rev().collect())
+    }
+
+    // Takes the top card out of play face down, before each street. It is
+    // returned for dealers that show or record it.
+    pub fn burn(&mut self) -> Option<Card> {
+        self.cards.pop()
+    }
+
+    // How many cards are left to deal.
+    pub fn remaining(&self) -> usize {
+        self.cards.len()
+    }
+
+    /// Deals `players` hands of `each` cards, one card at a time round the
+    /// table from the first hand, and gives them with what is left of the
+    /// deck for the board or draws. A count too large to add up is reported
+    /// as needing `usize::MAX` cards.
+    pub fn deal_hands(mut self, players: usize, each: usize) -> Result<(Vec<Vec<Card>>, Deck), NotEnoughCards> {
+        let needed = players.saturating_mul(each);
+        if needed > self.len() {
+            return Err(NotEnoughCards{needed, left: self.len()});
+        }
+
+        let mut hands = vec![Vec::with_capacity(each); players];
+        for _ in 0..each {
+            for hand in hands.iter_mut() {
+                hand.extend(self.deal());
+            }
+        }
+        Ok((hands, self))
+    }
+
+    /// The next five cards as a hand. None, with the deck left as it was,
+    /// if fewer than five are left or they hold a card twice, as the top of
+    /// a pinochle deck may.
+    pub fn deal_hand(&mut self) -> Option<Hand> {
+        let rest = self.cards.len().checked_sub(5)?;
+        let hand: Hand = self.cards[rest..].iter().rev().copied().collect::<Result<_, _>>().ok()?;
+        self.cards.truncate(rest);
+        Some(hand)
+    }
+}
+
+/// Cards drawn without replacement, from `Deck::sample_iter`.
+pub struct SampleIter<'a, R: ?Sized> {
+    cards: &'a mut Vec<Card>,
+    rng: &'a mut R,
+    // The cards below this have not been drawn yet.
+    left: usize,
+}
+
+impl<R: Rng + ?Sized> Iterator for SampleIter<'_, R> {
+    type Item = Card;
+
+    // One step of a Fisher-Yates shuffle from the top down.
+    fn next(&mut self) -> Option<Card> {
+        if self.left == 0 { return None; }
+        let i = self.rng.gen_range(0..self.left);
+        self.left -= 1;
+        self.cards.swap(i, self.left);
+        Some(self.cards[self.left])
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.left, Some(self.left))
+    }
+}
+
+impl<R: Rng + ?Sized> ExactSizeIterator for SampleIter<'_, R> {}
+
+impl Hand {
+    pub fn random<R: Rng + ?Sized>(rng: &mut R) -> Hand {
+        let mut deck = Deck::new();
+        deck.shuffle(rng);
+        deck.deal_hand().unwrap()
+    }
+}
+
+// Non-overlapping hands from one fresh deck; at most ten fit.
+pub fn deal_random_hands<R: Rng + ?Sized>(n: usize, rng: &mut R) -> Option<Vec<Hand>> {
+    let mut deck = Deck::new();
+    deck.shuffle(rng);
+    (0..n).map(|_| deck.deal_hand()).collect()
+}
+
+#[cfg(test)]
+mod deck_tests {
+    use super::*;
+    use super::super::Rules;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    #[test]
+    fn test_new() {
+        let deck = Deck::new();
+
+        assert_eq!(deck.len(), 52);
+        assert!(!deck.cards().iter().any(|c| c.rank == Rank::One));
+        for (i, a) in deck.cards().iter().enumerate() {
+            assert!(!deck.cards()[i + 1..].contains(a));
+        }
+    }
+
+    #[test]
+    fn test_stripped() {
+        let lowest = |deck: &Deck| deck.cards().iter().map(|c| c.rank).min();
+
+        assert_eq!(Deck::short_deck().len(), 36);
+        assert_eq!(Deck::short_deck(), Rules::ShortDeck.deck());
+        assert_eq!((Deck::piquet().len(), lowest(&Deck::piquet())), (32, Some(Rank::Seven)));
+        assert_eq!((Deck::euchre().len(), lowest(&Deck::euchre())), (24, Some(Rank::Nine)));
+
+        let pinochle = Deck::pinochle();
+        assert_eq!((pinochle.len(), lowest(&pinochle)), (48, Some(Rank::Nine)));
+        for card in Deck::euchre().cards() {
+            assert_eq!(pinochle.cards().iter().filter(|&c| c == card).count(), 2);
+        }
+    }
+
+    #[test]
+    fn test_draw_probability() {
+        let hole = [Card::from_code("AH").unwrap(), Card::from_code("KH").unwrap()];
+        let flop = ["7H", "2H", "9C"].iter().map(|c| Card::from_code(c).unwrap());
+        let deck = Deck::without(hole.iter().copied().chain(flop));
+        assert_eq!(deck.len(), 47);
+
+        assert_eq!(deck.suit_probability(Suit::Hearts), 9.0 / 47.0);
+        assert_eq!(deck.rank_probability(Rank::Ace), 3.0 / 47.0);
+        assert_eq!(deck.card_probability(hole[0]), 0.0);
+        assert_eq!(deck.draw_probability(|c| c.rank > Rank::Ten), 14.0 / 47.0);
+
+        // Nine outs twice: 1 - 38/47 * 37/46, about 35%.
+        let by_river = deck.draw_probability_within(|c| c.suit == Suit::Hearts, 2);
+        assert!((by_river - (1.0 - 38.0 * 37.0 / (47.0 * 46.0))).abs() < 1e-12);
+        assert_eq!(deck.draw_probability_within(|c| c.suit == Suit::Hearts, 0), 0.0);
+        assert_eq!(deck.draw_probability_within(|c| c.suit == Suit::Hearts, 47), 1.0);
+        assert_eq!(Deck::without(Deck::new().cards().to_vec()).draw_probability(|_| true), 0.0);
+    }
+
+    #[test]
+    fn test_shuffle_deal() {
+        let mut a = Deck::new();
+        let mut b = Deck::new();
+        a.shuffle(&mut ChaCha8Rng::seed_from_u64(7));
+        b.shuffle(&mut ChaCha8Rng::seed_from_u64(7));
+
+        assert_eq!(a, b);
+        assert_ne!(a, Deck::new());
+
+        let top = *a.cards().last().unwrap();
+        assert_eq!(a.deal(), Some(top));
+        assert_eq!(a.len(), 51);
+    }
+
+    #[test]
+    fn test_shuffled_with_seed() {
+        let deck = Deck::shuffled_with_seed(7);
+        let mut same = Deck::new();
+        same.shuffle(&mut ChaCha8Rng::seed_from_u64(7));
+        assert_eq!(deck, same);
+
+        // Pinned so that a change to the order for a seed is noticed.
+        let top: Vec<String> = Deck::shuffled_with_seed(2024).cards().iter().rev().take(5).map(|c| c.to_string()).collect();
+        assert_eq!(top.join(" "), "2S TH JS QS 8C");
+    }
+
+    #[test]
+    fn test_deal_n_burn() {
+        let mut deck = Deck::standard();
+        let top: Vec<Card> = deck.cards().iter().rev().take(4).cloned().collect();
+
+        assert_eq!(deck.burn(), Some(top[0]));
+        assert_eq!(deck.deal_n(3), Some(top[1..].to_vec()));
+        assert_eq!(deck.remaining(), 48);
+        assert_eq!(deck.deal_n(49), None);
+        assert_eq!(deck.remaining(), 48);
+        assert_eq!(deck.deal_n(48).map(|c| c.len()), Some(48));
+        assert_eq!(deck.burn(), None);
+    }
+
+    #[test]
+    fn test_remove() {
+        let mut deck = Deck::new();
+        let gone = [deck.cards()[0], deck.cards()[30]];
+        deck.remove(&gone);
+
+        assert_eq!(deck.len(), 50);
+        assert!(!deck.cards().contains(&gone[0]));
+        assert!(!deck.cards().contains(&gone[1]));
+    }
+
+    #[test]
+    fn test_deal_hands() {
+        let deck = Deck::shuffled_with_seed(4);
+        let top: Vec<Card> = deck.cards().iter().rev().cloned().collect();
+
+        let (hands, rest) = deck.clone().deal_hands(3, 4).unwrap();
+        assert_eq!(hands.len(), 3);
+        assert_eq!(hands[0], vec![top[0], top[3], top[6], top[9]]);
+        assert_eq!(hands[2], vec![top[2], top[5], top[8], top[11]]);
+        assert_eq!(rest.len(), 40);
+        assert!(hands.iter().flatten().all(|c| !rest.cards().contains(c)));
+
+        assert_eq!(deck.clone().deal_hands(13, 4).unwrap().1.len(), 0);
+        let err = deck.clone().deal_hands(9, 6).unwrap_err();
+        assert_eq!(err, NotEnoughCards{needed: 54, left: 52});
+        assert_eq!(err.to_string(), "needed 54 cards, 52 left in the deck");
+
+        let err = deck.clone().deal_hands(usize::MAX, 2).unwrap_err();
+        assert_eq!(err, NotEnoughCards{needed: usize::MAX, left: 52});
+        assert!(deck.clone().deal_hands(2, usize::MAX).is_err());
+    }
+
+    #[test]
+    fn test_encode() {
+        let mut deck = Deck::shuffled_with_seed(12);
+        let full = deck.encode().unwrap();
+        assert_eq!(full.len(), 52);
+        assert_eq!(&Deck::new().encode().unwrap()[..4], "AEIM");
+
+        deck.deal_n(9);
+        let saved = deck.encode().unwrap();
+        assert_eq!(Deck::decode(&saved).unwrap(), deck);
+        assert_eq!(Deck::from_json(&Json::parse(&deck.to_json().to_string()).unwrap()).unwrap(), deck);
+
+        assert_eq!(Deck::decode("AB*"), Err(DeckDecodeError::BadChar{index: 2}));
+        assert_eq!(Deck::decode("AB/"), Err(DeckDecodeError::BadCard{index: 2}));
+        assert_eq!(Deck::decode("ABA"), Err(DeckDecodeError::DuplicateCard(batch::card(0))));
+        assert_eq!(Deck::from_json(&Json::parse("[\"AH\", 3]").unwrap()), Err(DeckDecodeError::BadCard{index: 1}));
+        assert_eq!(Deck::decode("").unwrap().len(), 0);
+    }
+
+    #[test]
+    fn test_encode_presets() {
+        let mut rng = ChaCha8Rng::seed_from_u64(13);
+        let presets = [Deck::new(), Deck::short_deck(), Deck::piquet(), Deck::euchre(), Deck::pinochle()];
+        for mut deck in presets {
+            deck.shuffle(&mut rng);
+            for dealt in [0, 5] {
+                deck.deal_n(dealt);
+                assert_eq!(Deck::decode(&deck.encode().unwrap()).unwrap(), deck);
+                assert_eq!(Deck::from_json(&Json::parse(&deck.to_json().to_string()).unwrap()).unwrap(), deck);
+            }
+        }
+
+        // Pinochle holds each card twice, and no more.
+        let pinochle = Deck::pinochle().encode().unwrap();
+        assert!(pinochle.starts_with("2*") && pinochle.len() == 50);
+        assert_eq!(Deck::decode("2*AAB").unwrap().len(), 3);
+        assert_eq!(Deck::decode("2*AAA"), Err(DeckDecodeError::DuplicateCard(batch::card(0))));
+        assert_eq!(Deck::decode("0*AB"), Err(DeckDecodeError::BadCopies));
+        assert_eq!(Deck::decode("99999999999999999999*AB"), Err(DeckDecodeError::BadCopies));
+        let json = Json::parse(r#"{"copies": 2, "cards": ["9H", "9H", "9H"]}"#).unwrap();
+        assert_eq!(Deck::from_json(&json), Err(DeckDecodeError::DuplicateCard(Card::from_code("9H").unwrap())));
+        assert_eq!(Deck::from_json(&Json::parse(r#"{"cards": ["9H"]}"#).unwrap()), Err(DeckDecodeError::BadCopies));
+    }
+
+    #[test]
+    fn test_sample_iter() {
+        let mut rng = ChaCha8Rng::seed_from_u64(6);
+        let mut deck = Deck::without(Deck::new().cards()[..40].to_vec());
+
+        let drawn: Vec<Card> = deck.sample_iter(&mut rng).take(5).collect();
+        assert_eq!(deck.len(), 12);
+        assert!(drawn.iter().all(|c| deck.cards().contains(c)));
+        assert!(drawn.iter().enumerate().all(|(i, c)| !drawn[i + 1..].contains(c)));
+
+        let mut all: Vec<Card> = deck.sample_iter(&mut rng).collect();
+        assert_eq!(all.len(), 12);
+        all.sort_by_key(|c| (c.suit, c.rank));
+        assert_eq!(all, Deck::new().cards()[40..].to_vec());
+
+        // Every card comes first about as often.
+        let mut firsts = [0; 12];
+        for _ in 0..12_000 {
+            let first = deck.sample_iter(&mut rng).next().unwrap();
+            firsts[all.iter().position(|&c| c == first).unwrap()] += 1;
+        }
+        assert!(firsts.iter().all(|&n| (800..1200).contains(&n)), "{:?}", firsts);
+        assert_eq!(deck.sample_iter(&mut rng).len(), 12);
+    }
+
+    #[test]
+    fn test_without() {
+        let dead: Vec<Card> = "AH KD 7C 7S 2H".split_whitespace().map(|c| Card::from_code(c).unwrap()).collect();
+        let deck = Deck::without(dead.iter().copied());
+
+        assert_eq!(deck.len(), 47);
+        assert!(dead.iter().all(|c| !deck.cards().contains(c)));
+        assert_eq!(Deck::without(vec![]), Deck::new());
+    }
+
+    #[test]
+    fn test_random() {
+        let mut rng = ChaCha8Rng::seed_from_u64(11);
+        let hand = Hand::random(&mut rng);
+        assert!(Hand::try_new(*hand.cards()).is_ok());
+
+        let hands = deal_random_hands(10, &mut rng).unwrap();
+        assert_eq!(hands.len(), 10);
+        for (i, a) in hands.iter().enumerate() {
+            for b in &hands[i + 1..] {
+                assert_eq!(a.shares_cards(b), None);
+            }
+        }
+        assert!(deal_random_hands(11, &mut rng).is_none());
+    }
+
+    #[test]
+    fn test_deal_hand() {
+        let mut deck = Deck::new();
+
+        for _ in 0..10 {
+            assert!(deck.deal_hand().is_some());
+        }
+        assert_eq!(deck.len(), 2);
+        assert!(deck.deal_hand().is_none());
+
+        let mut pinochle = Deck::pinochle();
+        pinochle.cards.push(pinochle.cards[45]);
+        let before = pinochle.clone();
+        assert!(pinochle.deal_hand().is_none());
+        assert_eq!(pinochle, before);
+        pinochle.deal();
+        assert!(pinochle.deal_hand().is_some());
+        assert_eq!(pinochle.len(), 43);
+    }
+}
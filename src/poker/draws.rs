@@ -0,0 +1,144 @@
+use std::fmt;
+
+use super::{Board, Card, Cards, Rank, Suit};
+
+/// An unmade hand that one more card could complete. Draws are only
+/// reported when the hole cards take part in them, not for draws on the
+/// board alone.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Draw {
+    // Four cards to a flush in this suit.
+    Flush(Suit),
+    // Four in a row with both ends open.
+    OpenEnded,
+    // Two ranks complete a straight, but not four in a row.
+    DoubleGutshot,
+    // One rank completes a straight.
+    Gutshot,
+    // A flush draw together with a straight draw.
+    Combo,
+}
+
+impl fmt::Display for Draw {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Draw::Flush(suit) => write!(f, "flush draw ({:?})", suit),
+            Draw::OpenEnded => write!(f, "open-ended straight draw"),
+            Draw::DoubleGutshot => write!(f, "double gutshot"),
+            Draw::Gutshot => write!(f, "gutshot"),
+            Draw::Combo => write!(f, "combo draw"),
+        }
+    }
+}
+
+impl Cards {
+    // Draws of these hole cards on a flop or turn; a river has none left.
+    pub fn draws(&self, board: &Board) -> Vec<Draw> {
+        if board.river().is_some() { return vec![]; }
+
+        let all: Vec<Card> = self.iter().chain(board.iter()).cloned().collect();
+        let mut out = vec![];
+
+        for &suit in Suit::ALL.iter() {
+            let count = |cards: &[Card]| cards.iter().filter(|c| c.suit == suit).count();
+            if count(&all) == 4 && count(self) > 0 {
+                out.push(Draw::Flush(suit));
+            }
+        }
+
+        let held = rank_mask(&all);
+        if !has_straight(held) {
+            let board_outs = straight_outs(rank_mask(board));
+            let outs: Vec<usize> = straight_outs(held).into_iter()
+                .filter(|v| !board_outs.contains(v))
+                .collect();
+
+            match outs.len() {
+                0 => (),
+                1 => out.push(Draw::Gutshot),
+                _ if open_ended(held, &outs) => out.push(Draw::OpenEnded),
+                _ => out.push(Draw::DoubleGutshot),
+            }
+        }
+
+        let flush = out.iter().any(|d| matches!(d, Draw::Flush(_)));
+        if flush && out.len() > 1 {
+            out.push(Draw::Combo);
+        }
+        out
+    }
+}
+
+// Bit `v` is set when rank value `v` is held, the ace counting as both the
+// lowest and highest value.
+fn rank_mask(cards: &[Card]) -> u16 {
+    let mut mask = 0;
+    for card in cards {
+        mask |= card.rank.bit();
+        if card.rank == Rank::Ace { mask |= 1; }
+    }
+    mask
+}
+
+fn has_straight(mask: u16) -> bool {
+    (0..Rank::ALL.len() - 4).any(|low| (mask >> low) & 0b11111 == 0b11111)
+}
+
+// Deck rank values, Two to Ace, that would complete a straight.
+fn straight_outs(mask: u16) -> Vec<usize> {
+    (1..Rank::ALL.len())
+        .filter(|&v| mask & (1 << v) == 0)
+        .filter(|&v| {
+            let mut with = mask | 1 << v;
+            if v == Rank::ALL.len() - 1 { with |= 1; }
+            has_straight(with)
+        })
+        .collect()
+}
+
+// Four consecutive held ranks with both neighbours among the outs. Below
+// a Two the neighbour is the Ace.
+fn open_ended(mask: u16, outs: &[usize]) -> bool {
+    (1..Rank::ALL.len() - 4).any(|low| {
+        let below = if low == 1 { Rank::ALL.len() - 1 } else { low - 1 };
+        (mask >> low) & 0b1111 == 0b1111 && outs.contains(&below) && outs.contains(&(low + 4))
+    })
+}
+
+#[cfg(test)]
+mod draws_tests {
+    use super::*;
+
+    fn draws(hole: &str, board: &str) -> Vec<Draw> {
+        Cards::hole(hole).unwrap().draws(&board.parse().unwrap())
+    }
+
+    #[test]
+    fn test_flush_draw() {
+        assert_eq!(draws("AH 5H", "KH 9H 2C"), vec![Draw::Flush(Suit::Hearts)]);
+        assert_eq!(draws("AS 5C", "KH 9H 2H 3H"), vec![Draw::Gutshot]);
+        assert_eq!(draws("AH 5H", "KH 9H 2H"), vec![]);
+    }
+
+    #[test]
+    fn test_straight_draws() {
+        assert_eq!(draws("8C 9D", "TH JS 2C"), vec![Draw::OpenEnded]);
+        assert_eq!(draws("8C 9D", "JH QS 2C"), vec![Draw::Gutshot]);
+        assert_eq!(draws("7C 9D", "JH TS 5C"), vec![Draw::Gutshot]);
+        assert_eq!(draws("7C 9D", "5H 8S JC"), vec![Draw::DoubleGutshot]);
+        assert_eq!(draws("2C 3D", "4H 5S KC"), vec![Draw::OpenEnded]);
+        assert_eq!(draws("AC 2D", "3H 4S 9C"), vec![Draw::Gutshot]);
+        assert_eq!(draws("KC QD", "AH JS 2C"), vec![Draw::Gutshot]);
+        assert_eq!(draws("8C 9D", "TH JS QC"), vec![]);
+    }
+
+    #[test]
+    fn test_combo_and_river() {
+        assert_eq!(
+            draws("8H 9H", "TH JS 2H"),
+            vec![Draw::Flush(Suit::Hearts), Draw::OpenEnded, Draw::Combo]
+        );
+        assert_eq!(draws("8H 9H", "TH JS 2H 3C 4D"), vec![]);
+        assert_eq!(draws("2C 3D", "8H 9S TH JC"), vec![]);
+    }
+}
@@ -0,0 +1,480 @@
+use std::cmp::Ordering;
+use std::fmt;
+
+use rand::Rng;
+
+use super::{DuplicateCard, Hand};
+use super::deck::Deck;
+
+// Heads-up, no-limit, single betting round over five-card hands. Seat 0 is
+// the button: it posts the small blind and acts first.
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Config {
+    pub stack: u32,
+    pub small_blind: u32,
+    pub big_blind: u32,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config{stack: 100, small_blind: 1, big_blind: 2}
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Action {
+    Fold,
+    Check,
+    Call,
+    // Raise to a total bet of this many chips for the round.
+    Raise(u32),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum ActionError {
+    HandOver,
+    CannotCheck,
+    NothingToCall,
+    RaiseTooSmall { min: u32 },
+    RaiseTooLarge { max: u32 },
+    // An action index outside the discrete set `env::Env` understands.
+    InvalidAction(usize),
+}
+
+impl fmt::Display for ActionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ActionError::HandOver => write!(f, "the hand is over"),
+            ActionError::CannotCheck => write!(f, "cannot check facing a bet"),
+            ActionError::NothingToCall => write!(f, "there is no bet to call"),
+            ActionError::RaiseTooSmall { min } => write!(f, "raise must be to at least {}", min),
+            ActionError::RaiseTooLarge { max } => write!(f, "raise must be to at most {}", max),
+            ActionError::InvalidAction(a) => write!(f, "unknown action index {}", a),
+        }
+    }
+}
+
+impl std::error::Error for ActionError {}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RaiseBounds {
+    pub min: u32,
+    pub max: u32,
+}
+
+// Everything the player to act may do. An empty set once the hand is over.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct LegalActions {
+    pub fold: bool,
+    pub check: bool,
+    // Chips needed to call, capped at the caller's stack.
+    pub call: Option<u32>,
+    // Totals the bet may be raised to; `max` is all-in.
+    pub raise: Option<RaiseBounds>,
+}
+
+impl LegalActions {
+    pub fn contains(&self, action: Action) -> bool {
+        match action {
+            Action::Fold => self.fold,
+            Action::Check => self.check,
+            Action::Call => self.call.is_some(),
+            Action::Raise(to) => match self.raise {
+                Some(b) => b.min <= to && to <= b.max,
+                None => false,
+            },
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        !self.fold && !self.check && self.call.is_none() && self.raise.is_none()
+    }
+}
+
+// How much of the hidden state a `GameView` exposes.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum InfoLevel {
+    // Only what the seat could see at a real table.
+    #[default]
+    Realistic,
+    // The opponent's cards as well, for debugging and diagnostic training.
+    Revealed,
+}
+
+// One seat's view of the game. Built only by `HeadsUp::view`, which is the
+// single place hidden information is masked.
+#[derive(Clone, Debug)]
+pub struct GameView {
+    player: usize,
+    level: InfoLevel,
+    hand: Hand,
+    opponent_hand: Option<Hand>,
+    stacks: [u32; 2],
+    bets: [u32; 2],
+    to_act: usize,
+    over: bool,
+}
+
+impl GameView {
+    pub fn player(&self) -> usize {
+        self.player
+    }
+
+    pub fn level(&self) -> InfoLevel {
+        self.level
+    }
+
+    pub fn hand(&self) -> &Hand {
+        &self.hand
+    }
+
+    // `None` unless the view was built with `InfoLevel::Revealed`.
+    pub fn opponent_hand(&self) -> Option<&Hand> {
+        self.opponent_hand.as_ref()
+    }
+
+    pub fn stacks(&self) -> [u32; 2] {
+        self.stacks
+    }
+
+    pub fn bets(&self) -> [u32; 2] {
+        self.bets
+    }
+
+    pub fn to_act(&self) -> usize {
+        self.to_act
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.over
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct HeadsUp {
+    config: Config,
+    hands: [Hand; 2],
+    stacks: [u32; 2],
+    bets: [u32; 2],
+    acted: [bool; 2],
+    last_raise: u32,
+    to_act: usize,
+    folded: Option<usize>,
+    over: bool,
+}
+
+impl HeadsUp {
+    pub fn new<R: Rng + ?Sized>(config: Config, rng: &mut R) -> Self {
+        let mut deck = Deck::new();
+        deck.shuffle(rng);
+        let hands = [deck.deal_hand().unwrap(), deck.deal_hand().unwrap()];
+        HeadsUp::with_hands(config, hands).unwrap()
+    }
+
+    pub fn with_hands(config: Config, hands: [Hand; 2]) -> Result<Self, DuplicateCard> {
+        if let Some(card) = hands[0].shares_cards(&hands[1]) {
+            return Err(DuplicateCard(card));
+        }
+
+        let sb = config.small_blind.min(config.stack);
+        let bb = config.big_blind.min(config.stack);
+
+        Ok(HeadsUp {
+            config,
+            hands,
+            stacks: [config.stack - sb, config.stack - bb],
+            bets: [sb, bb],
+            acted: [false, false],
+            last_raise: config.big_blind,
+            to_act: 0,
+            folded: None,
+            over: false,
+        })
+    }
+
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    // Either seat's hole cards. Crate-internal: callers outside the engine go
+    // through `view`, so hidden information cannot leak past it.
+    pub(crate) fn hand(&self, player: usize) -> &Hand {
+        &self.hands[player]
+    }
+
+    pub fn stacks(&self) -> [u32; 2] {
+        self.stacks
+    }
+
+    pub fn bets(&self) -> [u32; 2] {
+        self.bets
+    }
+
+    pub fn pot(&self) -> u32 {
+        self.bets[0] + self.bets[1]
+    }
+
+    pub fn to_act(&self) -> usize {
+        self.to_act
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.over
+    }
+
+    pub fn folded(&self) -> Option<usize> {
+        self.folded
+    }
+
+    pub fn view(&self, player: usize, level: InfoLevel) -> GameView {
+        let opponent_hand = match level {
+            InfoLevel::Realistic => None,
+            InfoLevel::Revealed  => Some(self.hands[1 - player].clone()),
+        };
+
+        GameView {
+            player,
+            level,
+            hand: self.hands[player].clone(),
+            opponent_hand,
+            stacks: self.stacks,
+            bets: self.bets,
+            to_act: self.to_act,
+            over: self.over,
+        }
+    }
+
+    fn max_bet(&self) -> u32 {
+        self.bets[0].max(self.bets[1])
+    }
+
+    pub fn legal_actions(&self) -> LegalActions {
+        if self.over { return LegalActions::default(); }
+
+        let p = self.to_act;
+        let max_bet = self.max_bet();
+        let facing = max_bet > self.bets[p];
+        let all_in = self.bets[p] + self.stacks[p];
+
+        let call = if facing {
+            Some((max_bet - self.bets[p]).min(self.stacks[p]))
+        } else {
+            None
+        };
+
+        // No raising an all-in player, or with too few chips to go over.
+        let raise = if self.stacks[1 - p] == 0 || all_in <= max_bet {
+            None
+        } else {
+            Some(RaiseBounds {
+                min: (max_bet + self.last_raise).min(all_in),
+                max: all_in,
+            })
+        };
+
+        LegalActions{fold: true, check: !facing, call, raise}
+    }
+
+    pub fn validate(&self, action: Action) -> Result<(), ActionError> {
+        if self.over { return Err(ActionError::HandOver); }
+
+        let legal = self.legal_actions();
+
+        match action {
+            Action::Check if !legal.check => Err(ActionError::CannotCheck),
+            Action::Call if legal.call.is_none() => Err(ActionError::NothingToCall),
+            Action::Raise(to) => match legal.raise {
+                None => Err(ActionError::RaiseTooLarge{max: self.max_bet()}),
+                Some(b) if to > b.max => Err(ActionError::RaiseTooLarge{max: b.max}),
+                Some(b) if to < b.min => Err(ActionError::RaiseTooSmall{min: b.min}),
+                Some(_) => Ok(()),
+            },
+            _ => Ok(()),
+        }
+    }
+
+    pub fn apply(&mut self, action: Action) -> Result<(), ActionError> {
+        self.validate(action)?;
+
+        let p = self.to_act;
+        let o = 1 - p;
+
+        match action {
+            Action::Fold => {
+                self.folded = Some(p);
+                self.over = true;
+                return Ok(());
+            },
+            Action::Check => (),
+            Action::Call => {
+                let amount = (self.max_bet() - self.bets[p]).min(self.stacks[p]);
+                self.stacks[p] -= amount;
+                self.bets[p] += amount;
+            },
+            Action::Raise(to) => {
+                self.last_raise = self.last_raise.max(to - self.max_bet());
+                self.stacks[p] -= to - self.bets[p];
+                self.bets[p] = to;
+                self.acted[o] = false;
+            },
+        }
+        self.acted[p] = true;
+
+        let matched = self.bets[0] == self.bets[1]
+            || self.stacks[0] == 0
+            || self.stacks[1] == 0;
+
+        if self.acted[o] && matched {
+            self.over = true;
+        } else {
+            self.to_act = o;
+        }
+        Ok(())
+    }
+
+    // Net chips won by each seat once the hand is over.
+    pub fn payoffs(&self) -> Option<[i64; 2]> {
+        if !self.over { return None; }
+
+        let winner = match self.folded {
+            Some(p) => Some(1 - p),
+            None => match self.hands[0].cmp(&self.hands[1]) {
+                Ordering::Greater => Some(0),
+                Ordering::Less    => Some(1),
+                Ordering::Equal   => None,
+            },
+        };
+
+        // Whatever one seat put in beyond the other's total is returned.
+        let matched = self.bets[0].min(self.bets[1]) as i64;
+
+        Some(match winner {
+            Some(0) => [matched, -matched],
+            Some(_) => [-matched, matched],
+            None    => [0, 0],
+        })
+    }
+}
+
+#[cfg(test)]
+mod engine_tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn game() -> HeadsUp {
+        HeadsUp::with_hands(Config::default(), [
+            Hand::from_str("AH AD AC KS KD").unwrap(),
+            Hand::from_str("2H 3D 5C 7S 9D").unwrap(),
+        ]).unwrap()
+    }
+
+    #[test]
+    fn test_shared_cards() {
+        let hands = [
+            Hand::from_str("AH AD AC KS KD").unwrap(),
+            Hand::from_str("2H 3D 5C 7S AH").unwrap(),
+        ];
+
+        assert!(HeadsUp::with_hands(Config::default(), hands).is_err());
+    }
+
+    #[test]
+    fn test_blinds() {
+        let g = game();
+
+        assert_eq!(g.bets(), [1, 2]);
+        assert_eq!(g.stacks(), [99, 98]);
+        assert_eq!(g.to_act(), 0);
+    }
+
+    #[test]
+    fn test_validate() {
+        let g = game();
+
+        assert_eq!(g.validate(Action::Check), Err(ActionError::CannotCheck));
+        assert_eq!(g.validate(Action::Call), Ok(()));
+        assert_eq!(g.validate(Action::Raise(3)), Err(ActionError::RaiseTooSmall{min: 4}));
+        assert_eq!(g.validate(Action::Raise(4)), Ok(()));
+        assert_eq!(g.validate(Action::Raise(101)), Err(ActionError::RaiseTooLarge{max: 100}));
+    }
+
+    #[test]
+    fn test_legal_actions() {
+        let mut g = game();
+
+        let legal = g.legal_actions();
+        assert!(legal.fold);
+        assert!(!legal.check);
+        assert_eq!(legal.call, Some(1));
+        assert_eq!(legal.raise, Some(RaiseBounds{min: 4, max: 100}));
+        assert!(legal.contains(Action::Raise(50)));
+        assert!(!legal.contains(Action::Raise(3)));
+
+        g.apply(Action::Raise(10)).unwrap();
+        let legal = g.legal_actions();
+        assert_eq!(legal.call, Some(8));
+        assert_eq!(legal.raise, Some(RaiseBounds{min: 18, max: 100}));
+
+        g.apply(Action::Raise(100)).unwrap();
+        let legal = g.legal_actions();
+        assert_eq!(legal.call, Some(90));
+        assert_eq!(legal.raise, None);
+
+        g.apply(Action::Call).unwrap();
+        assert!(g.legal_actions().is_empty());
+    }
+
+    #[test]
+    fn test_big_blind_option() {
+        let mut g = game();
+        g.apply(Action::Call).unwrap();
+
+        assert!(!g.is_over());
+        assert_eq!(g.to_act(), 1);
+        assert_eq!(g.validate(Action::Call), Err(ActionError::NothingToCall));
+
+        g.apply(Action::Check).unwrap();
+        assert!(g.is_over());
+        assert_eq!(g.payoffs(), Some([2, -2]));
+    }
+
+    #[test]
+    fn test_view() {
+        let g = game();
+
+        let v = g.view(1, InfoLevel::Realistic);
+        assert_eq!(v.player(), 1);
+        assert_eq!(v.hand().ranks(), g.hand(1).ranks());
+        assert!(v.opponent_hand().is_none());
+
+        let v = g.view(1, InfoLevel::Revealed);
+        assert_eq!(v.opponent_hand().unwrap().ranks(), g.hand(0).ranks());
+    }
+
+    #[test]
+    fn test_fold() {
+        let mut g = game();
+        g.apply(Action::Raise(6)).unwrap();
+        g.apply(Action::Fold).unwrap();
+
+        assert_eq!(g.folded(), Some(1));
+        assert_eq!(g.payoffs(), Some([2, -2]));
+        assert_eq!(g.apply(Action::Check), Err(ActionError::HandOver));
+    }
+
+    #[test]
+    fn test_reraise_all_in() {
+        let mut g = game();
+        g.apply(Action::Raise(6)).unwrap();
+        g.apply(Action::Raise(100)).unwrap();
+
+        assert_eq!(g.pot(), 106);
+        assert_eq!(g.payoffs(), None);
+
+        g.apply(Action::Call).unwrap();
+        assert!(g.is_over());
+        assert_eq!(g.payoffs(), Some([100, -100]));
+    }
+}
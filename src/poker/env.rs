@@ -0,0 +1,210 @@
+//! A reinforcement-learning environment over the heads-up engine.
+//!
+//! The interface follows the usual reset/step shape. Both seats are driven
+//! through the same `step` call; `Observation::player` says whose turn it is.
+//!
+//! Discrete actions: `FOLD`, `CALL` (check when not facing a bet),
+//! `MIN_RAISE` and `ALL_IN`.
+//!
+//! Observation features (`OBS_LEN` values): the acting player's hand as laid
+//! out by `features::encode_hand`, a one-hot block for the opponent's cards,
+//! then pot, own bet, opponent bet, own stack and opponent stack, each divided
+//! by the starting stack, then the seat.
+//!
+//! Observations are built from `HeadsUp::view`, so the opponent block is all
+//! zeros unless the environment was created with `InfoLevel::Revealed`.
+
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+use super::engine::{Action, ActionError, Config, HeadsUp, InfoLevel};
+use super::features::{encode_hand, one_hot, CARDS, HAND_LEN};
+
+pub const FOLD: usize = 0;
+pub const CALL: usize = 1;
+pub const MIN_RAISE: usize = 2;
+pub const ALL_IN: usize = 3;
+pub const ACTIONS: usize = 4;
+
+pub const OBS_LEN: usize = HAND_LEN + CARDS + 6;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Observation {
+    pub player: usize,
+    pub features: Vec<f32>,
+    pub mask: [bool; ACTIONS],
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Step {
+    pub observation: Observation,
+    // Net chips won by each seat, non-zero only on the final step.
+    pub rewards: [f32; 2],
+    pub done: bool,
+}
+
+pub struct Env {
+    config: Config,
+    level: InfoLevel,
+    rng: ChaCha8Rng,
+    game: HeadsUp,
+}
+
+impl Env {
+    pub fn new(config: Config, seed: u64) -> Self {
+        Env::with_info_level(config, InfoLevel::Realistic, seed)
+    }
+
+    pub fn with_info_level(config: Config, level: InfoLevel, seed: u64) -> Self {
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+        let game = HeadsUp::new(config, &mut rng);
+        Env{config, level, rng, game}
+    }
+
+    pub fn info_level(&self) -> InfoLevel {
+        self.level
+    }
+
+    pub fn reset(&mut self) -> Observation {
+        self.game = HeadsUp::new(self.config, &mut self.rng);
+        self.observation()
+    }
+
+    pub fn step(&mut self, action: usize) -> Result<Step, ActionError> {
+        let action = self.to_action(action).ok_or(ActionError::InvalidAction(action))?;
+        self.game.apply(action)?;
+
+        let rewards = match self.game.payoffs() {
+            Some([a, b]) => [a as f32, b as f32],
+            None => [0.0, 0.0],
+        };
+
+        Ok(Step {
+            observation: self.observation(),
+            rewards,
+            done: self.game.is_over(),
+        })
+    }
+
+    pub fn action_mask(&self) -> [bool; ACTIONS] {
+        let legal = self.game.legal_actions();
+        let raise = legal.raise.is_some();
+
+        [legal.fold, legal.check || legal.call.is_some(), raise, raise]
+    }
+
+    pub fn observation(&self) -> Observation {
+        let view = self.game.view(self.game.to_act(), self.level);
+        let p = view.player();
+        let o = 1 - p;
+        let stack = self.config.stack.max(1) as f32;
+
+        let mut features = encode_hand(view.hand());
+        match view.opponent_hand() {
+            Some(h) => features.extend_from_slice(&one_hot(h.cards())),
+            None => features.extend_from_slice(&[0.0; CARDS]),
+        }
+        let bets = view.bets();
+        let stacks = view.stacks();
+        features.extend_from_slice(&[
+            (bets[0] + bets[1]) as f32 / stack,
+            bets[p] as f32 / stack,
+            bets[o] as f32 / stack,
+            stacks[p] as f32 / stack,
+            stacks[o] as f32 / stack,
+            p as f32,
+        ]);
+
+        Observation{player: p, features, mask: self.action_mask()}
+    }
+
+    fn to_action(&self, action: usize) -> Option<Action> {
+        let legal = self.game.legal_actions();
+        let p = self.game.to_act();
+        let all_in = self.game.bets()[p] + self.game.stacks()[p];
+
+        match action {
+            FOLD => Some(Action::Fold),
+            CALL if legal.call.is_some() => Some(Action::Call),
+            CALL => Some(Action::Check),
+            MIN_RAISE => Some(Action::Raise(legal.raise.map_or(all_in, |b| b.min))),
+            ALL_IN => Some(Action::Raise(all_in)),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod env_tests {
+    use super::*;
+
+    #[test]
+    fn test_reset() {
+        let mut env = Env::new(Config::default(), 3);
+        let obs = env.reset();
+
+        assert_eq!(obs.player, 0);
+        assert_eq!(obs.features.len(), OBS_LEN);
+        assert_eq!(obs.mask, [true, true, true, true]);
+    }
+
+    #[test]
+    fn test_info_level() {
+        let mut env = Env::new(Config::default(), 5);
+        let obs = env.reset();
+        assert!(obs.features[HAND_LEN..HAND_LEN + CARDS].iter().all(|&f| f == 0.0));
+
+        let mut env = Env::with_info_level(Config::default(), InfoLevel::Revealed, 5);
+        let obs = env.reset();
+        assert_eq!(obs.features[HAND_LEN..HAND_LEN + CARDS].iter().sum::<f32>(), 5.0);
+    }
+
+    #[test]
+    fn test_episode() {
+        let mut env = Env::new(Config::default(), 3);
+        env.reset();
+
+        let step = env.step(ALL_IN).unwrap();
+        assert!(!step.done);
+        assert_eq!(step.observation.player, 1);
+        assert_eq!(step.observation.mask, [true, true, false, false]);
+
+        let step = env.step(CALL).unwrap();
+        assert!(step.done);
+        assert_eq!(step.rewards[0], -step.rewards[1]);
+        assert_eq!(step.rewards[0].abs() % 100.0, 0.0);
+        assert_eq!(env.step(CALL), Err(ActionError::HandOver));
+    }
+
+    #[test]
+    fn test_fold_reward() {
+        let mut env = Env::new(Config::default(), 11);
+        env.reset();
+
+        let step = env.step(FOLD).unwrap();
+        assert!(step.done);
+        assert_eq!(step.rewards, [-1.0, 1.0]);
+    }
+
+    #[test]
+    fn test_invalid_action() {
+        let mut env = Env::new(Config::default(), 0);
+        env.reset();
+
+        assert_eq!(env.step(ACTIONS), Err(ActionError::InvalidAction(ACTIONS)));
+        assert_eq!(env.step(usize::MAX), Err(ActionError::InvalidAction(usize::MAX)));
+
+        // A bad index is not an action, so the hand is still live.
+        assert!(env.step(FOLD).unwrap().done);
+    }
+
+    #[test]
+    fn test_step_after_hand_over() {
+        let mut env = Env::new(Config::default(), 0);
+        env.reset();
+        env.step(FOLD).unwrap();
+
+        assert_eq!(env.step(CALL), Err(ActionError::HandOver));
+        assert_eq!(env.step(ACTIONS), Err(ActionError::InvalidAction(ACTIONS)));
+    }
+}
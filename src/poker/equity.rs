@@ -0,0 +1,1145 @@
+//! Showdown equity of Hold'em hole cards on a partial board.
+//!
+//! Every entry point takes dead cards as well as the board: cards known to
+//! be out of the deck, such as an exposed card, a folded hand or a rabbit
+//! hunt, which no runout and no range combo can hold.
+//!
+//! Equity is estimated by sampling runouts of the missing board cards. Near
+//! the river only a handful of distinct runouts remain, and sampling them
+//! over and over wastes time and adds noise; the simulator can count those
+//! repeats and switch to enumerating every runout instead.
+
+use std::cmp::Ordering;
+use std::collections::HashSet;
+
+use rand::Rng;
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::seq::index;
+
+use super::{Board, Card, DuplicateCard};
+use super::batch;
+use super::deck::Deck;
+use super::features::card_index;
+use super::incremental::IncrementalEval;
+use super::range::{Combo, Range};
+
+// Repeats above this share of the samples are worth telling the user about.
+const DUPLICATE_WARNING: f64 = 0.01;
+
+/// Equity of known hole cards on a partial board. The heads up `equity`
+/// runs on this, and it takes any number of players.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Simulator {
+    mode: EquityMode,
+    track_duplicates: bool,
+    auto_enumerate: bool,
+    // Dead cards, bit `batch::index`.
+    dead: u64,
+}
+
+/// What the simulator did to reach its estimate. `duplicates` is only
+/// counted when tracking is switched on.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SimStats {
+    pub runouts: usize,
+    pub possible: u64,
+    pub duplicates: Option<usize>,
+    pub enumerated: bool,
+}
+
+/// Each player's share of the pot, ties split evenly, with how often
+/// each won alone and how often it tied, all from 0 to 1. Shares sum to
+/// one.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Equity {
+    pub shares: Vec<f64>,
+    pub wins: Vec<f64>,
+    pub ties: Vec<f64>,
+    // Of each share, in percent; zero when enumerated.
+    pub standard_errors: Vec<f64>,
+    pub stats: SimStats,
+}
+
+impl Simulator {
+    /// Samples `samples` runouts.
+    pub fn new(samples: usize) -> Self {
+        Simulator::with_mode(EquityMode::MonteCarlo{trials: samples})
+    }
+
+    pub fn with_mode(mode: EquityMode) -> Self {
+        Simulator{mode, track_duplicates: false, auto_enumerate: false, dead: 0}
+    }
+
+    /// Cards out of the deck besides the hole cards and board.
+    pub fn dead(mut self, cards: &[Card]) -> Self {
+        self.dead = cards.iter().filter_map(batch::index).fold(0, |m, i| m | 1 << i);
+        self
+    }
+
+    pub fn track_duplicates(mut self, track: bool) -> Self {
+        self.track_duplicates = track;
+        self
+    }
+
+    // Enumerate instead of sampling whenever there are no more distinct
+    // runouts than samples asked for, at most.
+    pub fn auto_enumerate(mut self, enumerate: bool) -> Self {
+        self.auto_enumerate = enumerate;
+        self
+    }
+
+    pub fn run<R: Rng + ?Sized>(
+        &self,
+        holes: &[[Card; 2]],
+        board: &Board,
+        rng: &mut R,
+    ) -> Result<Equity, DuplicateCard> {
+        assert!(!holes.is_empty(), "at least one player");
+
+        let dead = (0..52).filter(|i| self.dead >> i & 1 == 1).map(batch::card);
+        let known: Vec<Card> = holes.iter().flatten().chain(board.iter()).cloned().chain(dead).collect();
+        for (i, card) in known.iter().enumerate() {
+            if known[i + 1..].contains(card) { return Err(DuplicateCard(*card)); }
+        }
+
+        let deck = Deck::without(known.iter().copied());
+        let remaining = deck.cards();
+        let missing = 5 - board.len();
+        let possible = choose(remaining.len(), missing);
+        let evals: Vec<IncrementalEval> = holes.iter().map(|h| IncrementalEval::new(h).with_all(board)).collect();
+        let players = holes.len();
+
+        let sample = match self.mode.sampling(possible <= AUTO_RUNOUTS) {
+            Some(sampling) if self.auto_enumerate && possible <= sampling.trials as u64 => None,
+            sample => sample,
+        };
+
+        let mut tally = Tally::new(players);
+        let mut stats = SimStats{runouts: 0, possible, duplicates: None, enumerated: sample.is_none()};
+        match sample {
+            None => {
+                for_each_combination(remaining, missing, &mut |runout| tally.add(&evals, runout, 1.0));
+                stats.runouts = possible as usize;
+            }
+            Some(sampling) => {
+                let mut seen = HashSet::new();
+                let (mut duplicates, mut run) = (0, 0);
+                let mut runout = Vec::with_capacity(missing);
+
+                stats.runouts = sampling.run(|n| {
+                    for _ in 0..n {
+                        runout.clear();
+                        runout.extend(index::sample(rng, remaining.len(), missing).into_iter().map(|i| remaining[i]));
+
+                        if self.track_duplicates {
+                            let mut key: Vec<usize> = runout.iter().map(card_index).collect();
+                            key.sort_unstable();
+                            if !seen.insert(key) { duplicates += 1; }
+                        }
+                        tally.add(&evals, &runout, 1.0);
+                    }
+                    run += n;
+                    Some((0..players).map(|p| tally.standard_error(p, run)).fold(0.0, f64::max))
+                }).expect("runouts always deal");
+                if self.track_duplicates { stats.duplicates = Some(duplicates); }
+            }
+        }
+
+        let standard_errors = (0..players)
+            .map(|p| if stats.enumerated { 0.0 } else { tally.standard_error(p, stats.runouts) })
+            .collect();
+        let total = tally.weight;
+        let share = |v: Vec<f64>| v.into_iter().map(|x| x / total).collect();
+        Ok(Equity{
+            shares: share(tally.shares),
+            wins: share(tally.wins),
+            ties: share(tally.ties),
+            standard_errors,
+            stats,
+        })
+    }
+}
+
+/// How often one hand wins, ties and loses, in percent.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Outcomes {
+    pub win: f64,
+    pub tie: f64,
+    pub loss: f64,
+}
+
+impl Outcomes {
+    /// The share of the pot, in percent, with ties split.
+    pub fn equity(&self) -> f64 {
+        self.win + self.tie / 2.0
+    }
+}
+
+/// Two hands' outcomes over `trials` runouts. Each one's wins are the
+/// other's losses.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HeadsUp {
+    pub a: Outcomes,
+    pub b: Outcomes,
+    pub trials: usize,
+    // Every runout counted once, so the figures are exact.
+    pub exhaustive: bool,
+    // Of either hand's equity, in percent; zero when exhaustive.
+    pub standard_error: f64,
+}
+
+impl HeadsUp {
+    /// The 95% confidence interval of the first hand's equity.
+    pub fn confidence_interval(&self) -> (f64, f64) {
+        interval(self.a.equity(), self.standard_error)
+    }
+}
+
+// `Auto` enumerates up to this many runouts, every flop and later board
+// heads up, and samples this many trials past it.
+const AUTO_RUNOUTS: u64 = 50_000;
+const AUTO_TRIALS: usize = 100_000;
+
+// `Precision` checks the interval after each batch of this many trials.
+const PRECISION_BATCH: usize = 1000;
+
+// Standard errors either side of the estimate in a 95% interval.
+const Z95: f64 = 1.959964;
+
+/// How `equity` goes over the runouts.
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+pub enum EquityMode {
+    // Every runout once, exactly; 1,712,304 of them before the flop.
+    Exhaustive,
+    MonteCarlo { trials: usize },
+    // Samples until the 95% interval is within `margin` percent either
+    // side of the estimate, such as 0.1, or `max_trials` run out.
+    Precision { margin: f64, max_trials: usize },
+    // Exhaustive when there are few runouts, otherwise 100,000 trials.
+    #[default]
+    Auto,
+}
+
+impl EquityMode {
+    // How to sample, or `None` to enumerate; `enumerate` is what `Auto`
+    // should do.
+    fn sampling(self, enumerate: bool) -> Option<Sampling> {
+        match self {
+            EquityMode::Exhaustive => None,
+            EquityMode::MonteCarlo { trials } => Some(Sampling{trials, margin: None}),
+            EquityMode::Precision { margin, max_trials } => {
+                assert!(margin > 0.0, "a positive margin");
+                Some(Sampling{trials: max_trials, margin: Some(margin)})
+            }
+            EquityMode::Auto if enumerate => None,
+            EquityMode::Auto => Some(Sampling{trials: AUTO_TRIALS, margin: None}),
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Sampling {
+    trials: usize,
+    margin: Option<f64>,
+}
+
+impl Sampling {
+    // Calls `batch` with a number of trials to run until they are all run
+    // or, given a margin, the standard error it returns after a batch is
+    // small enough. Returns the trials run, or `None` if a batch failed.
+    fn run<F: FnMut(usize) -> Option<f64>>(self, mut batch: F) -> Option<usize> {
+        assert!(self.trials > 0, "at least one trial");
+        let mut done = 0;
+        while done < self.trials {
+            let n = match self.margin {
+                Some(_) => PRECISION_BATCH.min(self.trials - done),
+                None => self.trials - done,
+            };
+            let error = batch(n)?;
+            done += n;
+            if self.margin.is_some_and(|m| Z95 * error <= m) { break; }
+        }
+        Some(done)
+    }
+}
+
+/// The standard error, in percent, of the mean of `n` trials' pot shares
+/// from 0 to 1, given their sum and the sum of their squares. Infinite
+/// with fewer than two trials.
+pub fn standard_error(sum: f64, squares: f64, n: usize) -> f64 {
+    if n < 2 { return f64::INFINITY; }
+    let mean = sum / n as f64;
+    let variance = (squares - n as f64 * mean * mean) / (n - 1) as f64;
+    100.0 * (variance.max(0.0) / n as f64).sqrt()
+}
+
+// The 95% interval around an equity in percent, kept within 0 to 100.
+fn interval(equity: f64, standard_error: f64) -> (f64, f64) {
+    ((equity - Z95 * standard_error).max(0.0), (equity + Z95 * standard_error).min(100.0))
+}
+
+/// Counts how often each of two hands wins, ties and loses over the
+/// runouts of `board` without the `dead` cards, every one of them or a
+/// random sample as `mode` says. The generator is only used for sampling.
+/// This is `Simulator` with two players, in percent.
+pub fn equity<R: Rng + ?Sized>(
+    hole_a: &[Card; 2],
+    hole_b: &[Card; 2],
+    board: &Board,
+    dead: &[Card],
+    mode: EquityMode,
+    rng: &mut R,
+) -> Result<HeadsUp, DuplicateCard> {
+    let e = Simulator::with_mode(mode).dead(dead).run(&[*hole_a, *hole_b], board, rng)?;
+    let (win, tie, loss) = (100.0 * e.wins[0], 100.0 * e.ties[0], 100.0 * e.wins[1]);
+    Ok(HeadsUp{
+        a: Outcomes{win, tie, loss},
+        b: Outcomes{win: loss, tie, loss: win},
+        trials: e.stats.runouts,
+        exhaustive: e.stats.enumerated,
+        standard_error: e.standard_errors[0],
+    })
+}
+
+/// One combo's equity against the other range, in percent.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ComboEquity {
+    pub combo: Combo,
+    pub equity: f64,
+    // Runouts it was counted over, against every opponent combo.
+    pub runouts: usize,
+}
+
+/// Two ranges' equity against each other, in percent, with each combo's.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RangeEquity {
+    pub a: f64,
+    pub b: f64,
+    pub runouts: usize,
+    pub exhaustive: bool,
+    // Of either range's equity; zero when exhaustive.
+    pub standard_error: f64,
+    // Combos blocked by the board, or which met no opponent, are left out.
+    pub combos_a: Vec<ComboEquity>,
+    pub combos_b: Vec<ComboEquity>,
+}
+
+impl RangeEquity {
+    /// The 95% confidence interval of the first range's equity.
+    pub fn confidence_interval(&self) -> (f64, f64) {
+        interval(self.a, self.standard_error)
+    }
+}
+
+// `Auto` goes over every matchup and runout up to this many in all.
+const AUTO_MATCHUP_RUNOUTS: u64 = 2_000_000;
+
+/// The equity of one range against another on `board`. Each pair of
+/// combos that can be dealt together counts in proportion to the product
+/// of their weights, and pairs sharing a card are never dealt, so each
+/// range is narrowed by what the other holds as well as by the board.
+///
+/// Exhaustively, every pair meets on every runout. Monte Carlo deals a
+/// weighted pair and a runout for each trial; `Auto` enumerates while
+/// the pairs times the runouts stay small, from the turn for most ranges.
+/// Combos holding a `dead` card are left out too. `None` if no two combos
+/// can be dealt together.
+pub fn range_equity<R: Rng + ?Sized>(
+    range_a: &Range,
+    range_b: &Range,
+    board: &Board,
+    dead: &[Card],
+    mode: EquityMode,
+    rng: &mut R,
+) -> Option<RangeEquity> {
+    let out: Vec<Card> = board.iter().chain(dead).cloned().collect();
+    let a = range_a.without_blocked(&out);
+    let b = range_b.without_blocked(&out);
+    let (a, b) = (a.combos(), b.combos());
+
+    let pairs: Vec<(usize, usize)> = (0..a.len())
+        .flat_map(|i| (0..b.len()).map(move |j| (i, j)))
+        .filter(|&(i, j)| a[i].weight * b[j].weight > 0.0 && !a[i].overlaps(&b[j].cards))
+        .collect();
+    if pairs.is_empty() { return None; }
+
+    let missing = 5 - board.len();
+    let per_pair = choose(Deck::without(out.iter().copied()).len() - 4, missing);
+    let sample = mode.sampling(per_pair * pairs.len() as u64 <= AUTO_MATCHUP_RUNOUTS);
+
+    // Per combo: weighted share won, weight counted and runouts.
+    let mut tally_a = vec![(0.0, 0.0, 0); a.len()];
+    let mut tally_b = vec![(0.0, 0.0, 0); b.len()];
+    let (mut won, mut weight) = (0.0, 0.0);
+    let mut count = |i: usize, j: usize, share: f64, w: f64, runouts: usize| {
+        tally_a[i].0 += w * share;
+        tally_a[i].1 += w;
+        tally_a[i].2 += runouts;
+        tally_b[j].0 += w * (1.0 - share);
+        tally_b[j].1 += w;
+        tally_b[j].2 += runouts;
+        won += w * share;
+        weight += w;
+    };
+
+    // Sampled shares and their squares, for the standard error.
+    let (mut sum, mut squares) = (0.0, 0.0);
+    let runouts = match sample {
+        None => {
+            for &(i, j) in &pairs {
+                let r = equity(&a[i].cards, &b[j].cards, board, dead, EquityMode::Exhaustive, rng).ok()?;
+                count(i, j, r.a.equity() / 100.0, a[i].weight * b[j].weight, r.trials);
+            }
+            per_pair as usize * pairs.len()
+        }
+        Some(sampling) => {
+            let pick = WeightedIndex::new(pairs.iter().map(|&(i, j)| a[i].weight * b[j].weight)).ok()?;
+            let deck = Deck::without(out.iter().copied());
+            let remaining = deck.cards();
+            let (from_a, from_b) = (IncrementalEval::new(board), IncrementalEval::new(board));
+
+            let mut run = 0;
+            sampling.run(|n| {
+                for _ in 0..n {
+                    let (i, j) = pairs[pick.sample(rng)];
+                    let (mut ea, mut eb) = (from_a.with_all(&a[i].cards), from_b.with_all(&b[j].cards));
+                    let runout = index::sample(rng, remaining.len(), missing + 4).into_iter()
+                        .map(|k| remaining[k])
+                        .filter(|c| !a[i].cards.contains(c) && !b[j].cards.contains(c))
+                        .take(missing);
+                    for card in runout {
+                        ea.add(card);
+                        eb.add(card);
+                    }
+                    let share = match ea.value().cmp(&eb.value()) {
+                        Ordering::Greater => 1.0,
+                        Ordering::Equal => 0.5,
+                        Ordering::Less => 0.0,
+                    };
+                    count(i, j, share, 1.0, 1);
+                    sum += share;
+                    squares += share * share;
+                }
+                run += n;
+                Some(standard_error(sum, squares, run))
+            })?
+        }
+    };
+
+    let breakdown = |combos: &[Combo], tally: &[(f64, f64, usize)]| -> Vec<ComboEquity> {
+        combos.iter().zip(tally)
+            .filter(|(_, t)| t.1 > 0.0)
+            .map(|(&combo, t)| ComboEquity{combo, equity: 100.0 * t.0 / t.1, runouts: t.2})
+            .collect()
+    };
+    let equity_a = 100.0 * won / weight;
+    Some(RangeEquity{
+        a: equity_a,
+        b: 100.0 - equity_a,
+        runouts,
+        exhaustive: sample.is_none(),
+        standard_error: if sample.is_none() { 0.0 } else { standard_error(sum, squares, runouts) },
+        combos_a: breakdown(a, &tally_a),
+        combos_b: breakdown(b, &tally_b),
+    })
+}
+
+/// Each player's results against all the others, in percent: `shares` the
+/// pot won with ties split, `wins` how often the hand took it alone and
+/// `ties` how often it split.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Multiway {
+    pub shares: Vec<f64>,
+    pub wins: Vec<f64>,
+    pub ties: Vec<f64>,
+    pub runouts: usize,
+    pub exhaustive: bool,
+    // Of each player's share; zero when exhaustive.
+    pub standard_errors: Vec<f64>,
+}
+
+impl Multiway {
+    /// The 95% confidence interval of `player`'s share.
+    pub fn confidence_interval(&self, player: usize) -> (f64, f64) {
+        interval(self.shares[player], self.standard_errors[player])
+    }
+}
+
+// Attempts at dealing every player a combo without a shared card before
+// giving up on a trial.
+const DEAL_ATTEMPTS: usize = 1000;
+
+/// Equity among two to nine ranges, or single hands written as one combo
+/// ranges, on `board`. A pot split by several winners goes to each of them
+/// equally. Combos are dealt in proportion to the product of their
+/// weights, never two sharing a card nor holding a `dead` one, as in
+/// `range_equity`.
+///
+/// `None` if no deal gives every player a combo; when sampling, if one
+/// cannot be found in a thousand attempts.
+///
+/// # Panics
+///
+/// With fewer than two or more than nine ranges.
+pub fn multiway_equity<R: Rng + ?Sized>(
+    ranges: &[Range],
+    board: &Board,
+    dead: &[Card],
+    mode: EquityMode,
+    rng: &mut R,
+) -> Option<Multiway> {
+    assert!((2..=9).contains(&ranges.len()), "two to nine players");
+    let out: Vec<Card> = board.iter().chain(dead).cloned().collect();
+    let ranges: Vec<Range> = ranges.iter().map(|r| r.without_blocked(&out)).collect();
+    let combos: Vec<&[Combo]> = ranges.iter().map(Range::combos).collect();
+    let players = combos.len();
+
+    let missing = 5 - board.len();
+    let per_deal = choose(Deck::without(out.iter().copied()).len() - 2 * players, missing);
+    let few = matches!(mode, EquityMode::Auto) && {
+        let limit = (AUTO_MATCHUP_RUNOUTS / per_deal.max(1)) as usize;
+        let mut deals = 0;
+        for_each_deal(&combos, &mut vec![], &mut |_| deals += 1, limit + 1);
+        deals <= limit
+    };
+    let sample = mode.sampling(few);
+
+    let mut tally = Tally::new(players);
+    let start = IncrementalEval::new(board);
+    let runouts = match sample {
+        None => {
+            let mut deals = 0;
+            for_each_deal(&combos, &mut vec![], &mut |deal: &[&Combo]| {
+                deals += 1;
+                let w = deal.iter().map(|c| c.weight).product();
+                let evals: Vec<IncrementalEval> = deal.iter().map(|c| start.with_all(&c.cards)).collect();
+                let deck = Deck::without(deal.iter().flat_map(|c| c.cards).chain(out.iter().copied()));
+                for_each_combination(deck.cards(), missing, &mut |runout| {
+                    tally.add(&evals, runout, w);
+                });
+            }, usize::MAX);
+            if deals == 0 { return None; }
+            deals * per_deal as usize
+        }
+        Some(sampling) => {
+            let picks: Vec<WeightedIndex<f64>> = combos.iter()
+                .map(|c| WeightedIndex::new(c.iter().map(|c| c.weight)).ok())
+                .collect::<Option<_>>()?;
+            let deck = Deck::without(out.iter().copied());
+            let remaining = deck.cards();
+
+            let mut deal: Vec<&Combo> = Vec::with_capacity(players);
+            let mut run = 0;
+            sampling.run(|n| {
+                for _ in 0..n {
+                    let dealt = (0..DEAL_ATTEMPTS).any(|_| {
+                        deal.clear();
+                        for (p, pick) in picks.iter().enumerate() {
+                            let combo = &combos[p][pick.sample(rng)];
+                            if deal.iter().any(|d| d.overlaps(&combo.cards)) { return false; }
+                            deal.push(combo);
+                        }
+                        true
+                    });
+                    if !dealt { return None; }
+
+                    let evals: Vec<IncrementalEval> = deal.iter().map(|c| start.with_all(&c.cards)).collect();
+                    let runout: Vec<Card> = index::sample(rng, remaining.len(), missing + 2 * players).into_iter()
+                        .map(|k| remaining[k])
+                        .filter(|c| !deal.iter().any(|d| d.cards.contains(c)))
+                        .take(missing)
+                        .collect();
+                    tally.add(&evals, &runout, 1.0);
+                }
+                run += n;
+                Some((0..players).map(|p| tally.standard_error(p, run)).fold(0.0, f64::max))
+            })?
+        }
+    };
+
+    let standard_errors = (0..players)
+        .map(|p| if sample.is_none() { 0.0 } else { tally.standard_error(p, runouts) })
+        .collect();
+    let total = tally.weight;
+    let percent = |v: Vec<f64>| v.into_iter().map(|x| 100.0 * x / total).collect();
+    Some(Multiway{
+        shares: percent(tally.shares),
+        wins: percent(tally.wins),
+        ties: percent(tally.ties),
+        runouts,
+        exhaustive: sample.is_none(),
+        standard_errors,
+    })
+}
+
+// Weighted pot shares, their squares, outright wins and ties per player.
+struct Tally {
+    shares: Vec<f64>,
+    squares: Vec<f64>,
+    wins: Vec<f64>,
+    ties: Vec<f64>,
+    weight: f64,
+}
+
+impl Tally {
+    fn new(players: usize) -> Self {
+        let zeros = vec![0.0; players];
+        Tally{shares: zeros.clone(), squares: zeros.clone(), wins: zeros.clone(), ties: zeros, weight: 0.0}
+    }
+
+    // Of a player's share over `n` unweighted trials.
+    fn standard_error(&self, player: usize, n: usize) -> f64 {
+        standard_error(self.shares[player], self.squares[player], n)
+    }
+
+    fn add(&mut self, evals: &[IncrementalEval], runout: &[Card], w: f64) {
+        let values: Vec<_> = evals.iter().map(|e| e.with_all(runout).value()).collect();
+        let best = values.iter().max().unwrap();
+        let winners: Vec<usize> = (0..values.len()).filter(|&p| values[p] == *best).collect();
+
+        for &p in &winners {
+            let share = 1.0 / winners.len() as f64;
+            self.shares[p] += w * share;
+            self.squares[p] += w * share * share;
+            if winners.len() == 1 { self.wins[p] += w; } else { self.ties[p] += w; }
+        }
+        self.weight += w;
+    }
+}
+
+// Calls `f` with each way of giving every player one of their combos with
+// no card dealt twice, stopping after `limit` of them.
+fn for_each_deal<'a, F: FnMut(&[&'a Combo])>(
+    combos: &[&'a [Combo]],
+    deal: &mut Vec<&'a Combo>,
+    f: &mut F,
+    limit: usize,
+) -> usize {
+    let p = deal.len();
+    if p == combos.len() {
+        f(deal);
+        return 1;
+    }
+
+    let mut found = 0;
+    for combo in combos[p].iter().filter(|c| c.weight > 0.0) {
+        if found >= limit { break; }
+        if deal.iter().any(|d| d.overlaps(&combo.cards)) { continue; }
+        deal.push(combo);
+        found += for_each_deal(combos, deal, f, limit - found);
+        deal.pop();
+    }
+    found
+}
+
+/// How the first hand's equity, in percent, is spread over the boards of
+/// one street, each board as likely as it is to be dealt.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StreetProfile {
+    // Sorted.
+    equities: Vec<f64>,
+}
+
+impl StreetProfile {
+    fn new(mut equities: Vec<f64>) -> Self {
+        equities.sort_by(f64::total_cmp);
+        StreetProfile{equities}
+    }
+
+    /// The first hand's equity on each board, lowest first.
+    pub fn equities(&self) -> &[f64] {
+        &self.equities
+    }
+
+    pub fn boards(&self) -> usize {
+        self.equities.len()
+    }
+
+    pub fn mean(&self) -> f64 {
+        self.equities.iter().sum::<f64>() / self.boards() as f64
+    }
+
+    /// How often, in percent, the first hand's equity on this street is
+    /// above `equity`, such as 50 for a favourite.
+    pub fn above(&self, equity: f64) -> f64 {
+        let below = self.equities.partition_point(|&e| e <= equity);
+        100.0 * (self.boards() - below) as f64 / self.boards() as f64
+    }
+
+    /// The share of boards, in percent, in each of `buckets` equal bands of
+    /// equity from 0 to 100; 100 itself goes in the last.
+    pub fn histogram(&self, buckets: usize) -> Vec<f64> {
+        assert!(buckets > 0, "at least one bucket");
+        let mut counts = vec![0.0; buckets];
+        for &e in &self.equities {
+            counts[((e / 100.0 * buckets as f64) as usize).min(buckets - 1)] += 1.0;
+        }
+        counts.into_iter().map(|c| 100.0 * c / self.boards() as f64).collect()
+    }
+}
+
+/// The first hand's equity before the flop, and how it is spread over
+/// the flops and the turns that follow them.
+#[derive(Clone, Debug, PartialEq)]
+pub struct EquityProfile {
+    pub preflop: f64,
+    // Of `preflop`; zero when exhaustive.
+    pub standard_error: f64,
+    pub exhaustive: bool,
+    pub flop: StreetProfile,
+    pub turn: StreetProfile,
+}
+
+// `Auto` profiles this many random flops; all 17,296 take seconds.
+const AUTO_PROFILE_FLOPS: usize = 2000;
+
+/// Equity street by street for two hands before the flop, with `dead`
+/// cards out. Each flop is dealt with every turn after it and every river
+/// after that, so its equity and those of its turns are exact; `mode` says
+/// which flops. Exhaustive goes over all of them, Monte Carlo and
+/// `Precision` count flops as trials, and `Auto` samples two thousand.
+/// For the second hand's view, swap the hands.
+pub fn equity_profile<R: Rng + ?Sized>(
+    hole_a: &[Card; 2],
+    hole_b: &[Card; 2],
+    dead: &[Card],
+    mode: EquityMode,
+    rng: &mut R,
+) -> Result<EquityProfile, DuplicateCard> {
+    let known: Vec<Card> = hole_a.iter().chain(hole_b.iter()).chain(dead.iter()).cloned().collect();
+    for (i, card) in known.iter().enumerate() {
+        if known[i + 1..].contains(card) { return Err(DuplicateCard(*card)); }
+    }
+
+    let deck = Deck::without(known.iter().copied());
+    let remaining = deck.cards();
+    let (a, b) = (IncrementalEval::new(hole_a), IncrementalEval::new(hole_b));
+
+    let (mut flops, mut turns) = (vec![], vec![]);
+    let mut deal = |flop: &[Card]| {
+        let e = flop_equity(a.with_all(flop), b.with_all(flop), flop, remaining, &mut turns);
+        flops.push(e);
+        e
+    };
+
+    let mode = match mode {
+        EquityMode::Auto => EquityMode::MonteCarlo{trials: AUTO_PROFILE_FLOPS},
+        mode => mode,
+    };
+    let sample = mode.sampling(false);
+    match sample {
+        None => for_each_combination(remaining, 3, &mut |flop| { deal(flop); }),
+        Some(sampling) => {
+            let (mut sum, mut squares, mut run) = (0.0, 0.0, 0);
+            let mut flop = Vec::with_capacity(3);
+            sampling.run(|n| {
+                for _ in 0..n {
+                    flop.clear();
+                    flop.extend(index::sample(rng, remaining.len(), 3).into_iter().map(|i| remaining[i]));
+                    let share = deal(&flop) / 100.0;
+                    sum += share;
+                    squares += share * share;
+                }
+                run += n;
+                Some(standard_error(sum, squares, run))
+            }).expect("flops always deal");
+        }
+    }
+
+    let flop = StreetProfile::new(flops);
+    let standard_error = match sample {
+        None => 0.0,
+        Some(_) => {
+            let shares = flop.equities().iter().map(|e| e / 100.0);
+            standard_error(shares.clone().sum(), shares.map(|s| s * s).sum(), flop.boards())
+        }
+    };
+    Ok(EquityProfile{
+        preflop: flop.mean(),
+        standard_error,
+        exhaustive: sample.is_none(),
+        flop,
+        turn: StreetProfile::new(turns),
+    })
+}
+
+// The first hand's equity on `flop`, over every turn and river left in
+// `remaining`, pushing its equity on each turn onto `turns`.
+fn flop_equity(a: IncrementalEval, b: IncrementalEval, flop: &[Card], remaining: &[Card], turns: &mut Vec<f64>) -> f64 {
+    let rest: Vec<Card> = remaining.iter().filter(|c| !flop.contains(c)).copied().collect();
+    let mut total = 0.0;
+    for (t, &turn) in rest.iter().enumerate() {
+        let (a, b) = (a.with(turn), b.with(turn));
+        let mut won = 0.0;
+        for (r, &river) in rest.iter().enumerate() {
+            if r == t { continue; }
+            won += match a.with(river).value().cmp(&b.with(river).value()) {
+                Ordering::Greater => 1.0,
+                Ordering::Equal => 0.5,
+                Ordering::Less => 0.0,
+            };
+        }
+        let e = 100.0 * won / (rest.len() - 1) as f64;
+        turns.push(e);
+        total += e;
+    }
+    total / rest.len() as f64
+}
+
+impl SimStats {
+    // A message for the user when many samples repeated an earlier runout.
+    pub fn warning(&self) -> Option<String> {
+        let duplicates = self.duplicates?;
+        if self.runouts == 0 || (duplicates as f64) < DUPLICATE_WARNING * self.runouts as f64 {
+            return None;
+        }
+
+        Some(format!(
+            "{} of {} sampled runouts were repeats; only {} distinct runouts exist, \
+             enumerating them would be exact",
+            duplicates, self.runouts, self.possible
+        ))
+    }
+}
+
+fn choose(n: usize, k: usize) -> u64 {
+    if k > n { return 0; }
+    (0..k).fold(1u64, |acc, i| acc * (n - i) as u64 / (i + 1) as u64)
+}
+
+fn for_each_combination<F: FnMut(&[Card])>(cards: &[Card], k: usize, f: &mut F) {
+    let mut picked = Vec::with_capacity(k);
+    combinations(cards, k, &mut picked, f);
+}
+
+fn combinations<F: FnMut(&[Card])>(cards: &[Card], k: usize, picked: &mut Vec<Card>, f: &mut F) {
+    if picked.len() == k {
+        f(picked);
+        return;
+    }
+    let needed = k - picked.len();
+    for i in 0..cards.len() {
+        if cards.len() - i < needed { break; }
+        picked.push(cards[i]);
+        combinations(&cards[i + 1..], k, picked, f);
+        picked.pop();
+    }
+}
+
+#[cfg(test)]
+mod equity_tests {
+    use super::*;
+    use super::super::Rank;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    fn cards(s: &str) -> Vec<Card> {
+        s.split_whitespace().map(|c| Card::from_code(c).unwrap()).collect()
+    }
+
+    fn hole(s: &str) -> [Card; 2] {
+        let c = cards(s);
+        [c[0], c[1]]
+    }
+
+    #[test]
+    fn test_river_is_exact() {
+        let board: Board = "2C 7D 9H JS KD".parse().unwrap();
+        let holes = [hole("AS AD"), hole("KS QS")];
+        let e = Simulator::new(10).auto_enumerate(true)
+            .run(&holes, &board, &mut ChaCha8Rng::seed_from_u64(0)).unwrap();
+
+        assert!(e.stats.enumerated);
+        assert_eq!(e.stats.possible, 1);
+        assert_eq!(e.shares, vec![1.0, 0.0]);
+    }
+
+    #[test]
+    fn test_turn_enumeration() {
+        let board: Board = "2C 7D 9H JS".parse().unwrap();
+        let holes = [hole("AS AD"), hole("KS KD")];
+        let e = Simulator::new(1000).auto_enumerate(true)
+            .run(&holes, &board, &mut ChaCha8Rng::seed_from_u64(0)).unwrap();
+
+        // Kings need one of the two kings left in 44 cards.
+        assert!(e.stats.enumerated);
+        assert_eq!(e.stats.runouts, 44);
+        assert!((e.shares[1] - 2.0 / 44.0).abs() < 1e-9);
+        assert!((e.shares.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_duplicates_warn() {
+        let board: Board = "2C 7D 9H JS".parse().unwrap();
+        let holes = [hole("AS AD"), hole("KS KD")];
+        let e = Simulator::new(500).track_duplicates(true)
+            .run(&holes, &board, &mut ChaCha8Rng::seed_from_u64(3)).unwrap();
+
+        assert!(!e.stats.enumerated);
+        assert!(e.stats.duplicates.unwrap() >= 500 - 44);
+        assert!(e.stats.warning().unwrap().contains("only 44 distinct"));
+
+        let e = Simulator::new(200).track_duplicates(true)
+            .run(&holes, &Board::new(), &mut ChaCha8Rng::seed_from_u64(3)).unwrap();
+        assert!(e.stats.warning().is_none());
+    }
+
+    #[test]
+    fn test_simulator_modes() {
+        let board: Board = "2C 7D 9H".parse().unwrap();
+        let (aces, kings) = (hole("AS AD"), hole("KS KD"));
+        let mut rng = ChaCha8Rng::seed_from_u64(5);
+
+        // `equity` is the simulator's heads up figures in percent.
+        let e = Simulator::with_mode(EquityMode::Exhaustive).run(&[aces, kings], &board, &mut rng).unwrap();
+        let r = equity(&aces, &kings, &board, &[], EquityMode::Exhaustive, &mut rng).unwrap();
+        assert_eq!((e.stats.runouts, e.stats.enumerated), (r.trials, r.exhaustive));
+        assert_eq!((100.0 * e.wins[1], 100.0 * e.ties[0]), (r.b.win, r.a.tie));
+        assert_eq!(e.standard_errors, vec![0.0, 0.0]);
+
+        // Three players sampled to a precision.
+        let holes = [aces, kings, hole("QS QD")];
+        let mode = EquityMode::Precision{margin: 2.0, max_trials: 100_000};
+        let e = Simulator::with_mode(mode).run(&holes, &Board::new(), &mut rng).unwrap();
+        assert!(!e.stats.enumerated && e.stats.runouts < 100_000);
+        assert!(e.standard_errors.iter().all(|&se| se > 0.0 && Z95 * se <= 2.0), "{:?}", e.standard_errors);
+        for p in 0..3 {
+            assert!((e.shares[p] - e.wins[p] - e.ties[p] / 2.0).abs() < 0.01);
+        }
+    }
+
+    #[test]
+    fn test_heads_up() {
+        let mut rng = ChaCha8Rng::seed_from_u64(26);
+        let mode = EquityMode::MonteCarlo{trials: 20000};
+        let r = equity(&hole("AS AD"), &hole("KS KD"), &Board::new(), &[], mode, &mut rng).unwrap();
+
+        // Aces hold about 82% against kings, tying rarely.
+        assert!((r.a.equity() - 82.0).abs() < 1.5, "{:?}", r);
+        assert!(r.a.tie < 1.0);
+        assert!(!r.exhaustive);
+        assert_eq!(r.a.win, r.b.loss);
+        assert!((r.a.win + r.a.tie + r.a.loss - 100.0).abs() < 1e-9);
+        assert!((r.a.equity() + r.b.equity() - 100.0).abs() < 1e-9);
+
+        // On this river the board plays for both.
+        let board: Board = "AC KC QC JC TC".parse().unwrap();
+        let r = equity(&hole("2S 3D"), &hole("4S 5D"), &board, &[], EquityMode::Auto, &mut rng).unwrap();
+        assert_eq!((r.a.tie, r.b.tie, r.trials), (100.0, 100.0, 1));
+
+        let r = equity(&hole("AS AD"), &hole("AS KD"), &Board::new(), &[], mode, &mut rng);
+        assert_eq!(r, Err(DuplicateCard(Card::from_code("AS").unwrap())));
+    }
+
+    #[test]
+    fn test_heads_up_exhaustive() {
+        let mut rng = ChaCha8Rng::seed_from_u64(27);
+        let board: Board = "2C 7D 9H".parse().unwrap();
+        let r = equity(&hole("AS AD"), &hole("KS KD"), &board, &[], EquityMode::Auto, &mut rng).unwrap();
+
+        // Kings need a king and no ace: 903 - 820 of the 990 turns and
+        // rivers, those without an ace less those without either.
+        assert!(r.exhaustive);
+        assert_eq!(r.trials, 990);
+        assert!((r.b.win - 100.0 * 83.0 / 990.0).abs() < 1e-9);
+        assert_eq!(r, equity(&hole("AS AD"), &hole("KS KD"), &board, &[], EquityMode::Exhaustive, &mut rng).unwrap());
+
+        // Sampling comes close to the exact figure.
+        let sampled = equity(&hole("AS AD"), &hole("KS KD"), &board, &[], EquityMode::MonteCarlo{trials: 5000}, &mut rng).unwrap();
+        assert!((sampled.a.equity() - r.a.equity()).abs() < 1.5);
+    }
+
+    #[test]
+    fn test_confidence_interval() {
+        let mut rng = ChaCha8Rng::seed_from_u64(28);
+        let (aces, kings) = (hole("AS AD"), hole("KS KD"));
+        let mode = EquityMode::MonteCarlo{trials: 20000};
+        let r = equity(&aces, &kings, &Board::new(), &[], mode, &mut rng).unwrap();
+
+        // Close to sqrt(p(1 - p) / n) with so few ties.
+        let p = r.a.equity() / 100.0;
+        let expected = 100.0 * (p * (1.0 - p) / 20000.0).sqrt();
+        assert!((r.standard_error - expected).abs() < 0.02 * expected, "{:?}", r);
+        let (low, high) = r.confidence_interval();
+        assert!(low < 82.4 && 82.4 < high, "{} {}", low, high);
+        assert!((high - low - 2.0 * Z95 * r.standard_error).abs() < 1e-9);
+
+        let board: Board = "2C 7D 9H".parse().unwrap();
+        let exact = equity(&aces, &kings, &board, &[], EquityMode::Exhaustive, &mut rng).unwrap();
+        assert_eq!(exact.standard_error, 0.0);
+        assert_eq!(exact.confidence_interval(), (exact.a.equity(), exact.a.equity()));
+
+        // Keeps going until the interval is within half a point.
+        let mode = EquityMode::Precision{margin: 0.5, max_trials: 1_000_000};
+        let r = equity(&aces, &kings, &Board::new(), &[], mode, &mut rng).unwrap();
+        assert!(Z95 * r.standard_error <= 0.5, "{:?}", r);
+        assert!(r.trials % PRECISION_BATCH == 0 && (10_000..50_000).contains(&r.trials), "{}", r.trials);
+
+        // Or until the trials run out.
+        let mode = EquityMode::Precision{margin: 0.01, max_trials: 2500};
+        let r = equity(&aces, &kings, &Board::new(), &[], mode, &mut rng).unwrap();
+        assert_eq!(r.trials, 2500);
+
+        let mode = EquityMode::Precision{margin: 1.0, max_trials: 1_000_000};
+        let (a, b): (Range, Range) = ("QQ+".parse().unwrap(), "AKs, JJ".parse().unwrap());
+        let r = range_equity(&a, &b, &Board::new(), &[], mode, &mut rng).unwrap();
+        assert!(Z95 * r.standard_error <= 1.0 && r.runouts < 1_000_000, "{:?}", r.runouts);
+        let ranges = [a, b, "TT".parse().unwrap()];
+        let m = multiway_equity(&ranges, &Board::new(), &[], mode, &mut rng).unwrap();
+        assert!(m.standard_errors.iter().all(|&e| e > 0.0 && Z95 * e <= 1.0), "{:?}", m.standard_errors);
+        let (low, high) = m.confidence_interval(2);
+        assert!(low < m.shares[2] && m.shares[2] < high);
+    }
+
+    #[test]
+    fn test_equity_profile() {
+        let mut rng = ChaCha8Rng::seed_from_u64(29);
+        let (aces, kings) = (hole("AS AD"), hole("KS KD"));
+        let mode = EquityMode::MonteCarlo{trials: 60};
+        let p = equity_profile(&aces, &kings, &[], mode, &mut rng).unwrap();
+
+        assert_eq!((p.flop.boards(), p.turn.boards(), p.exhaustive), (60, 60 * 45, false));
+        assert_eq!(p.preflop, p.flop.mean());
+        assert!((p.turn.mean() - p.preflop).abs() < 1e-9);
+        assert!((p.preflop - 82.0).abs() < 4.0 * p.standard_error + 1.0, "{} {}", p.preflop, p.standard_error);
+        assert!(p.turn.above(50.0) > 70.0);
+        assert_eq!((p.turn.above(-1.0), p.turn.above(100.0)), (100.0, 0.0));
+        assert!((p.flop.histogram(10).iter().sum::<f64>() - 100.0).abs() < 1e-9);
+        assert!(p.flop.equities().windows(2).all(|w| w[0] <= w[1]));
+
+        // A flop's figure is its exact equity, turns and rivers enumerated.
+        let board: Board = "2C 7D 9H".parse().unwrap();
+        let remaining = Deck::without(aces.iter().chain(&kings).copied());
+        let (a, b) = (IncrementalEval::new(&aces).with_all(&board), IncrementalEval::new(&kings).with_all(&board));
+        let mut turns = vec![];
+        let e = flop_equity(a, b, &board, remaining.cards(), &mut turns);
+        let exact = equity(&aces, &kings, &board, &[], EquityMode::Exhaustive, &mut rng).unwrap();
+        assert!((e - exact.a.equity()).abs() < 1e-9);
+        assert_eq!(turns.len(), 45);
+        // Kings are drawing dead on the two aces.
+        assert_eq!(turns.iter().filter(|&&t| t == 100.0).count(), 2);
+
+        let mode = EquityMode::Precision{margin: 5.0, max_trials: 10_000};
+        let p = equity_profile(&aces, &kings, &[], mode, &mut rng).unwrap();
+        assert!(Z95 * p.standard_error <= 5.0 && p.flop.boards() < 10_000);
+
+        let r = equity_profile(&aces, &kings, &cards("KS"), mode, &mut rng);
+        assert_eq!(r, Err(DuplicateCard(Card::from_code("KS").unwrap())));
+    }
+
+    #[test]
+    fn test_range_vs_range() {
+        let mut rng = ChaCha8Rng::seed_from_u64(29);
+        let board: Board = "2C 7D 9H JS".parse().unwrap();
+        let aces: Range = "AA".parse().unwrap();
+        let kings: Range = "KK".parse().unwrap();
+
+        // One combo each is the heads-up figure.
+        let one: Range = "ASAD".parse().unwrap();
+        let other: Range = "KSKD".parse().unwrap();
+        let r = range_equity(&one, &other, &board, &[], EquityMode::Auto, &mut rng).unwrap();
+        let h = equity(&hole("AS AD"), &hole("KS KD"), &board, &[], EquityMode::Exhaustive, &mut rng).unwrap();
+        assert!(r.exhaustive);
+        assert!((r.a - h.a.equity()).abs() < 1e-9);
+
+        // 36 matchups of 44 rivers each, and every combo met every other.
+        let r = range_equity(&aces, &kings, &board, &[], EquityMode::Auto, &mut rng).unwrap();
+        assert_eq!((r.runouts, r.combos_a.len(), r.combos_b.len()), (36 * 44, 6, 6));
+        assert!((r.a + r.b - 100.0).abs() < 1e-9);
+        assert!(r.combos_a.iter().all(|c| (c.equity - r.a).abs() < 1e-9 && c.runouts == 6 * 44));
+
+        let sampled = range_equity(&aces, &kings, &board, &[], EquityMode::MonteCarlo{trials: 4000}, &mut rng).unwrap();
+        assert!(!sampled.exhaustive);
+        assert!((sampled.a - r.a).abs() < 1.5);
+    }
+
+    #[test]
+    fn test_card_removal() {
+        let mut rng = ChaCha8Rng::seed_from_u64(30);
+        let board: Board = "AH 7D 2C TS 3S".parse().unwrap();
+
+        // With an ace on the board, AK only meets the aces it does not block.
+        let hero: Range = "ASKS".parse().unwrap();
+        let villain: Range = "AA, KQs:0.5".parse().unwrap();
+        let r = range_equity(&hero, &villain, &board, &[], EquityMode::Auto, &mut rng).unwrap();
+        assert_eq!(r.combos_b.iter().filter(|c| c.combo.cards[0].rank == Rank::Ace).count(), 1);
+        assert_eq!(r.combos_b.len(), 1 + 3);
+        // AK loses to the one set of aces, weight 1, and beats the three
+        // KQs, weight 1.5.
+        assert!((r.a - 60.0).abs() < 1e-9);
+
+        let blocked: Range = "AHKH".parse().unwrap();
+        assert_eq!(range_equity(&blocked, &villain, &board, &[], EquityMode::Auto, &mut rng), None);
+    }
+
+    #[test]
+    fn test_multiway() {
+        let mut rng = ChaCha8Rng::seed_from_u64(30);
+        let range = |s: &str| s.parse::<Range>().unwrap();
+
+        // Heads up it agrees with the two hand figure.
+        let board: Board = "2C 7D 9H".parse().unwrap();
+        let two = [range("ASAD"), range("KSKD")];
+        let m = multiway_equity(&two, &board, &[], EquityMode::Auto, &mut rng).unwrap();
+        let h = equity(&hole("AS AD"), &hole("KS KD"), &board, &[], EquityMode::Exhaustive, &mut rng).unwrap();
+        assert!(m.exhaustive);
+        assert!((m.shares[0] - h.a.equity()).abs() < 1e-9);
+
+        // A river where the board's straight plays for all three.
+        let board: Board = "6C 7D 8H 9S TC".parse().unwrap();
+        let three = [range("2S3S"), range("2D4D"), range("ACAD")];
+        let m = multiway_equity(&three, &board, &[], EquityMode::Auto, &mut rng).unwrap();
+        assert!(m.shares.iter().all(|s| (s - 100.0 / 3.0).abs() < 1e-9));
+        assert_eq!((m.ties.clone(), m.wins.clone()), (vec![100.0; 3], vec![0.0; 3]));
+
+        let board: Board = "6C 7D 8H 9S 2C".parse().unwrap();
+        let three = [range("TS5S"), range("TD5D"), range("ACAD")];
+        let m = multiway_equity(&three, &board, &[], EquityMode::Auto, &mut rng).unwrap();
+        assert_eq!((m.shares.clone(), m.ties.clone()), (vec![50.0, 50.0, 0.0], vec![100.0, 100.0, 0.0]));
+
+        // Preflop three ways, sampled: shares add up to the whole pot.
+        let three = [range("AA"), range("KK"), range("QQ")];
+        let m = multiway_equity(&three, &Board::new(), &[], EquityMode::MonteCarlo{trials: 3000}, &mut rng).unwrap();
+        assert!(!m.exhaustive);
+        assert!((m.shares.iter().sum::<f64>() - 100.0).abs() < 1e-9);
+        assert!(m.shares[0] > m.shares[1] && m.shares[1] > m.shares[2]);
+
+        let blocked = [range("ASAD"), range("ASKD")];
+        assert_eq!(multiway_equity(&blocked, &Board::new(), &[], EquityMode::MonteCarlo{trials: 10}, &mut rng), None);
+        assert_eq!(multiway_equity(&blocked, &Board::new(), &[], EquityMode::Exhaustive, &mut rng), None);
+    }
+
+    #[test]
+    fn test_dead_cards() {
+        let mut rng = ChaCha8Rng::seed_from_u64(32);
+        let board: Board = "2C 7D 9H JS".parse().unwrap();
+        let dead = cards("KC KH");
+
+        // With the last two kings folded, kings are drawing dead.
+        let r = equity(&hole("AS AD"), &hole("KS KD"), &board, &dead, EquityMode::Auto, &mut rng).unwrap();
+        assert_eq!((r.trials, r.b.win, r.b.tie), (42, 0.0, 0.0));
+        let e = Simulator::new(100).auto_enumerate(true).dead(&dead)
+            .run(&[hole("AS AD"), hole("KS KD")], &board, &mut rng).unwrap();
+        assert_eq!((e.stats.runouts, e.shares[1]), (42, 0.0));
+
+        let r = equity(&hole("AS AD"), &hole("KS KD"), &board, &cards("AS"), EquityMode::Auto, &mut rng);
+        assert_eq!(r, Err(DuplicateCard(Card::from_code("AS").unwrap())));
+
+        // Dead cards block combos of a range and leave the runouts.
+        let aces: Range = "AA".parse().unwrap();
+        let kings: Range = "KK".parse().unwrap();
+        let r = range_equity(&aces, &kings, &board, &cards("AS KC"), EquityMode::Auto, &mut rng).unwrap();
+        assert_eq!((r.combos_a.len(), r.combos_b.len(), r.runouts), (3, 3, 9 * 42));
+
+        let m = multiway_equity(&[aces, kings], &board, &dead, EquityMode::Auto, &mut rng).unwrap();
+        assert_eq!((m.runouts, m.shares[1]), (6 * 42, 0.0));
+    }
+
+    #[test]
+    fn test_rejects_duplicates() {
+        let holes = [hole("AS AD"), hole("AS KD")];
+        let e = Simulator::new(1).run(&holes, &Board::new(), &mut ChaCha8Rng::seed_from_u64(0));
+
+        assert_eq!(e, Err(DuplicateCard(Card::from_code("AS").unwrap())));
+    }
+}
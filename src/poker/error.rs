@@ -0,0 +1,464 @@
+//! One error type over every failure the poker module can report.
+//!
+//! Each leaf error has a numeric code that will not change between releases;
+//! new failures get new codes. The thousands digit is the failure class and
+//! the hundreds digit the source type within it:
+//!
+//! | codes | class      | source type              |
+//! |-------|------------|--------------------------|
+//! | 10xx  | parsing    | `HandParseError`         |
+//! | 11xx  | parsing    | `DealParseError`         |
+//! | 12xx  | parsing    | `RangeParseError`        |
+//! | 13xx  | parsing    | `StartingHandParseError` |
+//! | 14xx  | parsing    | `ParseIndexError`        |
+//! | 20xx  | evaluation | `DuplicateCard`          |
+//! | 21xx  | evaluation | `OutsError`              |
+//! | 3xxx  | engine     | `ActionError`            |
+//! | 4xxx  | dataset    | `DatasetError`           |
+//! | 50xx  | IO         | `std::io::Error`         |
+//! | 51xx  | IO         | `FormatError`            |
+//! | 60xx  | import     | `ImportError`            |
+//! | 61xx  | import     | `StateError`             |
+//! | 70xx  | protocol   | `ProtocolError`          |
+//! | 71xx  | protocol   | `JsonError`              |
+//! | 8xxx  | settings   | `ConfigError`            |
+//! | 90xx  | dealing    | `NotEnoughCards`         |
+//! | 91xx  | dealing    | `VerifyError`            |
+//! | 92xx  | dealing    | `DeckDecodeError`        |
+//! | 93xx  | dealing    | `ShoeDecodeError`        |
+//!
+//! A `PipelineError` becomes `Error::Line`, which keeps the line and takes
+//! the code of the error on it.
+
+use std::fmt;
+use std::io;
+
+use super::{DuplicateCard, HandParseError};
+use super::config::ConfigError;
+use super::dataset::DatasetError;
+use super::deal::DealParseError;
+use super::deck::{DeckDecodeError, NotEnoughCards};
+use super::engine::ActionError;
+use super::format::FormatError;
+use super::history::ImportError;
+use super::json::JsonError;
+use super::mental_poker::VerifyError;
+use super::outs::OutsError;
+use super::permutation::ParseIndexError;
+use super::pipeline::PipelineError;
+use super::range::RangeParseError;
+use super::serve::ProtocolError;
+use super::shoe::ShoeDecodeError;
+use super::starting::StartingHandParseError;
+use super::stats::StateError;
+
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    Parse(HandParseError),
+    Evaluation(DuplicateCard),
+    Engine(ActionError),
+    Dataset(DatasetError),
+    Io(io::Error),
+    Import(ImportError),
+    Protocol(ProtocolError),
+    Config(ConfigError),
+    Deal(NotEnoughCards),
+    DealParse(DealParseError),
+    RangeParse(RangeParseError),
+    StartingHandParse(StartingHandParseError),
+    IndexParse(ParseIndexError),
+    Outs(OutsError),
+    Format(FormatError),
+    State(StateError),
+    Json(JsonError),
+    Verify(VerifyError),
+    DeckDecode(DeckDecodeError),
+    ShoeDecode(ShoeDecodeError),
+    // An error on one line of bulk input; lines count from 1.
+    Line { line: usize, error: Box<Error> },
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl Error {
+    pub fn code(&self) -> u32 {
+        match self {
+            Error::Parse(e) => match e {
+                HandParseError::TooFewCards { .. } => 1001,
+                HandParseError::TooManyCards => 1002,
+                HandParseError::BadCard { .. } => 1003,
+                HandParseError::DuplicateCard(_) => 1004,
+                HandParseError::WrongCount { .. } => 1005,
+            },
+            Error::Evaluation(_) => 2001,
+            Error::Engine(e) => match e {
+                ActionError::HandOver => 3001,
+                ActionError::CannotCheck => 3002,
+                ActionError::NothingToCall => 3003,
+                ActionError::RaiseTooSmall { .. } => 3004,
+                ActionError::RaiseTooLarge { .. } => 3005,
+                ActionError::InvalidAction(_) => 3006,
+            },
+            Error::Dataset(e) => match e {
+                DatasetError::UnknownSchema(_) => 4001,
+                // 4002 was a schema that needed a solver; retired.
+                DatasetError::UnsupportedFormat(_) => 4003,
+                DatasetError::Io(_) => 4004,
+                DatasetError::Format(_) => 4005,
+                DatasetError::Malformed { .. } => 4006,
+                DatasetError::Truncated { .. } => 4007,
+            },
+            Error::Io(_) => 5001,
+            Error::Import(e) => match e {
+                ImportError::Io(_) => 6001,
+                ImportError::Format(_) => 6002,
+                ImportError::MissingColumn(_) => 6003,
+                ImportError::BadCards { .. } => 6004,
+                ImportError::Malformed { .. } => 6005,
+                ImportError::Truncated { .. } => 6006,
+            },
+            Error::Protocol(e) => match e {
+                ProtocolError::BadJson(_) => 7001,
+                ProtocolError::UnknownMethod(_) => 7002,
+                ProtocolError::MissingParam(_) => 7003,
+            },
+            Error::Config(e) => match e {
+                ConfigError::Io(_) => 8001,
+                ConfigError::Syntax { .. } => 8002,
+                ConfigError::UnknownKey { .. } => 8003,
+                ConfigError::BadValue { .. } => 8004,
+            },
+            Error::Deal(_) => 9001,
+            Error::DealParse(e) => match e {
+                DealParseError::Malformed(_) => 1101,
+                DealParseError::DuplicateCard(_) => 1102,
+            },
+            Error::RangeParse(_) => 1201,
+            Error::StartingHandParse(_) => 1301,
+            Error::IndexParse(_) => 1401,
+            Error::Outs(e) => match e {
+                OutsError::DuplicateCard(_) => 2101,
+                OutsError::Preflop => 2102,
+            },
+            Error::Format(e) => match e {
+                FormatError::MissingHeader => 5101,
+                FormatError::Malformed(_) => 5102,
+                FormatError::WrongKind { .. } => 5103,
+                FormatError::Unsupported { .. } => 5104,
+            },
+            Error::State(e) => match e {
+                StateError::Io(_) => 6101,
+                StateError::Json(_) => 6102,
+                StateError::BadState => 6103,
+            },
+            Error::Json(_) => 7101,
+            Error::Verify(e) => match e {
+                VerifyError::BadKey { .. } => 9101,
+                VerifyError::NotAShuffle { .. } => 9102,
+                VerifyError::WrongStages => 9103,
+            },
+            Error::DeckDecode(e) => match e {
+                DeckDecodeError::BadChar { .. } => 9201,
+                DeckDecodeError::BadCard { .. } => 9202,
+                DeckDecodeError::DuplicateCard(_) => 9203,
+                DeckDecodeError::NotAnArray => 9204,
+                DeckDecodeError::BadCopies => 9205,
+            },
+            Error::ShoeDecode(e) => match e {
+                ShoeDecodeError::BadState => 9301,
+                ShoeDecodeError::Cards(_) => 9302,
+            },
+            Error::Line { error, .. } => error.code(),
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "E{}: ", self.code())?;
+        self.message(f)
+    }
+}
+
+impl Error {
+    // The text after the code.
+    fn message(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Parse(e) => write!(f, "{}", e),
+            Error::Evaluation(e) => write!(f, "{}", e),
+            Error::Engine(e) => write!(f, "{}", e),
+            Error::Dataset(e) => write!(f, "{}", e),
+            Error::Io(e) => write!(f, "{}", e),
+            Error::Import(e) => write!(f, "{}", e),
+            Error::Protocol(e) => write!(f, "{}", e),
+            Error::Config(e) => write!(f, "{}", e),
+            Error::Deal(e) => write!(f, "{}", e),
+            Error::DealParse(e) => write!(f, "{}", e),
+            Error::RangeParse(e) => write!(f, "{}", e),
+            Error::StartingHandParse(e) => write!(f, "{}", e),
+            Error::IndexParse(e) => write!(f, "{}", e),
+            Error::Outs(e) => write!(f, "{}", e),
+            Error::Format(e) => write!(f, "{}", e),
+            Error::State(e) => write!(f, "{}", e),
+            Error::Json(e) => write!(f, "{}", e),
+            Error::Verify(e) => write!(f, "{}", e),
+            Error::DeckDecode(e) => write!(f, "{}", e),
+            Error::ShoeDecode(e) => write!(f, "{}", e),
+            Error::Line { line, error } => {
+                write!(f, "line {}: ", line)?;
+                error.message(f)
+            },
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Parse(e) => Some(e),
+            Error::Evaluation(e) => Some(e),
+            Error::Engine(e) => Some(e),
+            Error::Dataset(e) => Some(e),
+            Error::Io(e) => Some(e),
+            Error::Import(e) => Some(e),
+            Error::Protocol(e) => Some(e),
+            Error::Config(e) => Some(e),
+            Error::Deal(e) => Some(e),
+            Error::DealParse(e) => Some(e),
+            Error::RangeParse(e) => Some(e),
+            Error::StartingHandParse(e) => Some(e),
+            Error::IndexParse(e) => Some(e),
+            Error::Outs(e) => Some(e),
+            Error::Format(e) => Some(e),
+            Error::State(e) => Some(e),
+            Error::Json(e) => Some(e),
+            Error::Verify(e) => Some(e),
+            Error::DeckDecode(e) => Some(e),
+            Error::ShoeDecode(e) => Some(e),
+            Error::Line { error, .. } => Some(error.as_ref()),
+        }
+    }
+}
+
+impl From<HandParseError> for Error {
+    fn from(e: HandParseError) -> Self {
+        Error::Parse(e)
+    }
+}
+
+impl From<DuplicateCard> for Error {
+    fn from(e: DuplicateCard) -> Self {
+        Error::Evaluation(e)
+    }
+}
+
+impl From<ActionError> for Error {
+    fn from(e: ActionError) -> Self {
+        Error::Engine(e)
+    }
+}
+
+impl From<DatasetError> for Error {
+    fn from(e: DatasetError) -> Self {
+        Error::Dataset(e)
+    }
+}
+
+impl From<ImportError> for Error {
+    fn from(e: ImportError) -> Self {
+        Error::Import(e)
+    }
+}
+
+impl From<ProtocolError> for Error {
+    fn from(e: ProtocolError) -> Self {
+        Error::Protocol(e)
+    }
+}
+
+impl From<ConfigError> for Error {
+    fn from(e: ConfigError) -> Self {
+        Error::Config(e)
+    }
+}
+
+impl From<NotEnoughCards> for Error {
+    fn from(e: NotEnoughCards) -> Self {
+        Error::Deal(e)
+    }
+}
+
+impl From<DealParseError> for Error {
+    fn from(e: DealParseError) -> Self {
+        Error::DealParse(e)
+    }
+}
+
+impl From<RangeParseError> for Error {
+    fn from(e: RangeParseError) -> Self {
+        Error::RangeParse(e)
+    }
+}
+
+impl From<StartingHandParseError> for Error {
+    fn from(e: StartingHandParseError) -> Self {
+        Error::StartingHandParse(e)
+    }
+}
+
+impl From<ParseIndexError> for Error {
+    fn from(e: ParseIndexError) -> Self {
+        Error::IndexParse(e)
+    }
+}
+
+impl From<OutsError> for Error {
+    fn from(e: OutsError) -> Self {
+        Error::Outs(e)
+    }
+}
+
+impl From<FormatError> for Error {
+    fn from(e: FormatError) -> Self {
+        Error::Format(e)
+    }
+}
+
+impl From<StateError> for Error {
+    fn from(e: StateError) -> Self {
+        Error::State(e)
+    }
+}
+
+impl From<JsonError> for Error {
+    fn from(e: JsonError) -> Self {
+        Error::Json(e)
+    }
+}
+
+impl From<VerifyError> for Error {
+    fn from(e: VerifyError) -> Self {
+        Error::Verify(e)
+    }
+}
+
+impl From<DeckDecodeError> for Error {
+    fn from(e: DeckDecodeError) -> Self {
+        Error::DeckDecode(e)
+    }
+}
+
+impl From<ShoeDecodeError> for Error {
+    fn from(e: ShoeDecodeError) -> Self {
+        Error::ShoeDecode(e)
+    }
+}
+
+impl<E: Into<Error>> From<PipelineError<E>> for Error {
+    fn from(e: PipelineError<E>) -> Self {
+        match e {
+            PipelineError::Io(e) => Error::Io(e),
+            PipelineError::Line { line, error } => Error::Line{line, error: Box::new(error.into())},
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+#[cfg(test)]
+mod error_tests {
+    use super::*;
+    use super::super::{Card, Deal, Hand};
+    use super::super::json::Json;
+    use super::super::range::Range;
+    use super::super::starting::StartingHand;
+    use std::error::Error as _;
+    use std::str::FromStr;
+
+    fn parse(s: &str) -> Result<Hand> {
+        Ok(Hand::from_str(s)?)
+    }
+
+    #[test]
+    fn test_codes() {
+        assert_eq!(parse("AS KS").unwrap_err().code(), 1001);
+        assert_eq!(Error::from(ActionError::InvalidAction(9)).code(), 3006);
+        assert_eq!(Error::from(ActionError::HandOver).code(), 3001);
+        assert_eq!(Error::from(io::Error::other("disk")).code(), 5001);
+        assert_eq!(Error::from(ConfigError::Syntax{line: 1}).code(), 8002);
+        assert_eq!(Error::from(NotEnoughCards{needed: 9, left: 4}).code(), 9001);
+    }
+
+    #[test]
+    fn test_later_codes() {
+        let card = Card::from_code("AS").unwrap();
+
+        let cases: Vec<(Error, u32)> = vec![
+            ("holes=AS".parse::<Deal>().unwrap_err().into(), 1101),
+            (DealParseError::DuplicateCard(card).into(), 1102),
+            ("AA:2".parse::<Range>().unwrap_err().into(), 1201),
+            ("AKx".parse::<StartingHand>().unwrap_err().into(), 1301),
+            (ParseIndexError.into(), 1401),
+            (OutsError::DuplicateCard(card).into(), 2101),
+            (OutsError::Preflop.into(), 2102),
+            (FormatError::MissingHeader.into(), 5101),
+            (FormatError::Unsupported{kind: "x".to_string(), version: 2, latest: 1}.into(), 5104),
+            (StateError::BadState.into(), 6103),
+            (Json::parse("{").unwrap_err().into(), 7101),
+            (VerifyError::WrongStages.into(), 9103),
+            (DeckDecodeError::BadCopies.into(), 9205),
+            (ShoeDecodeError::BadState.into(), 9301),
+            (ShoeDecodeError::Cards(DeckDecodeError::NotAnArray).into(), 9302),
+        ];
+        for (e, code) in cases {
+            assert_eq!(e.code(), code, "{}", e);
+            assert!(e.to_string().starts_with(&format!("E{}: ", code)));
+            assert!(e.source().is_some());
+        }
+    }
+
+    #[test]
+    fn test_pipeline() {
+        let line: PipelineError<HandParseError> = PipelineError::Line{line: 3, error: HandParseError::TooManyCards};
+        let e = Error::from(line);
+        assert_eq!(e.code(), 1002);
+        assert_eq!(e.to_string(), format!("E1002: line 3: {}", HandParseError::TooManyCards));
+        assert_eq!(e.source().unwrap().to_string(), Error::from(HandParseError::TooManyCards).to_string());
+
+        let io: PipelineError<HandParseError> = PipelineError::Io(io::Error::other("disk"));
+        assert_eq!(Error::from(io).code(), 5001);
+    }
+
+    fn deal(s: &str) -> Result<Deal> {
+        Ok(s.parse()?)
+    }
+
+    #[test]
+    fn test_question_mark() {
+        assert_eq!(deal("holes=ASAS burns=2C3C4C board=7S8D9HJC2D").unwrap_err().code(), 1102);
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(
+            parse("AS AS KS QS JS").unwrap_err().to_string(),
+            "E1004: duplicate card AS"
+        );
+    }
+
+    #[test]
+    fn test_source_chain() {
+        let io = io::Error::other("disk full");
+        let e = Error::from(DatasetError::Io(io));
+
+        let dataset = e.source().unwrap();
+        assert_eq!(dataset.to_string(), "disk full");
+        assert_eq!(dataset.source().unwrap().to_string(), "disk full");
+        assert!(dataset.source().unwrap().source().is_none());
+    }
+}
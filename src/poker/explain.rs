@@ -0,0 +1,142 @@
+use std::cmp::Ordering;
+use std::fmt;
+
+use super::{Category, Hand, Rank};
+use super::value::HandValue;
+
+/// Why one hand beat, lost to or tied with another, for showing to people.
+/// Displays as e.g. "Two Pairs, Nines and Eights beats One Pair, Kings";
+/// when the hands only differ on a kicker it is named, as in
+/// "One Pair, Kings beats One Pair, Kings (kicker Queen plays)".
+#[derive(Clone, Debug, PartialEq)]
+pub struct Explanation {
+    pub ordering: Ordering,
+    pub hand: String,
+    pub other: String,
+    pub kicker: Option<Rank>,
+}
+
+impl Hand {
+    pub fn explain_vs(&self, other: &Hand) -> Explanation {
+        let (a, b) = (self.value(), other.value());
+
+        Explanation{
+            ordering: a.cmp(&b),
+            hand: describe(&a),
+            other: describe(&b),
+            kicker: deciding_kicker(&a, &b),
+        }
+    }
+}
+
+impl fmt::Display for Explanation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let verb = match self.ordering {
+            Ordering::Greater => "beats",
+            Ordering::Less => "loses to",
+            Ordering::Equal => "ties with",
+        };
+        write!(f, "{} {} {}", self.hand, verb, self.other)?;
+        if let Some(rank) = self.kicker {
+            write!(f, " (kicker {} plays)", name(rank))?;
+        }
+        Ok(())
+    }
+}
+
+fn deciding_kicker(a: &HandValue, b: &HandValue) -> Option<Rank> {
+    if a.category() != b.category() || a.primary_ranks() != b.primary_ranks() { return None; }
+
+    a.kickers().iter().zip(b.kickers()).find(|(x, y)| x != y).map(|(x, y)| *x.max(y))
+}
+
+pub(crate) fn describe(value: &HandValue) -> String {
+    let ranks = value.tiebreaks();
+    match value.category() {
+        Category::HighCard => format!("High Card, {}", name(ranks[0])),
+        Category::OnePair => format!("One Pair, {}", plural(ranks[0])),
+        Category::TwoPairs => format!("Two Pairs, {} and {}", plural(ranks[0]), plural(ranks[1])),
+        Category::ThreeOfAKind => format!("Three of a Kind, {}", plural(ranks[0])),
+        Category::Straight => format!("Straight, {} high", name(ranks[0])),
+        Category::Flush => format!("Flush, {} high", name(ranks[0])),
+        Category::FullHouse => format!("Full House, {} over {}", plural(ranks[0]), plural(ranks[1])),
+        Category::FourOfAKind => format!("Four of a Kind, {}", plural(ranks[0])),
+        Category::StraightFlush => format!("Straight Flush, {} high", name(ranks[0])),
+        Category::RoyalFlush => "Royal Flush".to_string(),
+        Category::FiveOfAKind => format!("Five of a Kind, {}", plural(ranks[0])),
+    }
+}
+
+pub(crate) fn name(rank: Rank) -> &'static str {
+    match rank {
+        Rank::One => "One",
+        Rank::Two => "Two",
+        Rank::Three => "Three",
+        Rank::Four => "Four",
+        Rank::Five => "Five",
+        Rank::Six => "Six",
+        Rank::Seven => "Seven",
+        Rank::Eight => "Eight",
+        Rank::Nine => "Nine",
+        Rank::Ten => "Ten",
+        Rank::Jack => "Jack",
+        Rank::Queen => "Queen",
+        Rank::King => "King",
+        Rank::Ace => "Ace",
+    }
+}
+
+fn plural(rank: Rank) -> String {
+    match rank {
+        Rank::Six => "Sixes".to_string(),
+        _ => format!("{}s", name(rank)),
+    }
+}
+
+#[cfg(test)]
+mod explain_tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn explain(a: &str, b: &str) -> String {
+        Hand::from_str(a).unwrap().explain_vs(&Hand::from_str(b).unwrap()).to_string()
+    }
+
+    #[test]
+    fn test_categories() {
+        assert_eq!(
+            explain("8C 8S 2C 9H 9S", "KH KD QC 7S 4D"),
+            "Two Pairs, Nines and Eights beats One Pair, Kings"
+        );
+        assert_eq!(
+            explain("6H 6D 2C 2D 6S", "3D 4D 5D 6C 7D"),
+            "Full House, Sixes over Twos beats Straight, Seven high"
+        );
+        assert_eq!(
+            explain("TH JH QH KH AH", "TC JC QC KC 9C").as_str(),
+            "Royal Flush beats Straight Flush, King high"
+        );
+    }
+
+    #[test]
+    fn test_kicker() {
+        assert_eq!(
+            explain("KH KD QC 7S 4D", "KS KC JD 7D 5C"),
+            "One Pair, Kings beats One Pair, Kings (kicker Queen plays)"
+        );
+        assert_eq!(
+            explain("AH 9D 7C 5S 3D", "AS 9C 7D 5C 4H"),
+            "High Card, Ace loses to High Card, Ace (kicker Four plays)"
+        );
+    }
+
+    #[test]
+    fn test_tie() {
+        let e = Hand::from_str("2H 3H 5C 8D KS").unwrap()
+            .explain_vs(&Hand::from_str("2C 3C 5H 8S KD").unwrap());
+
+        assert_eq!(e.ordering, Ordering::Equal);
+        assert_eq!(e.kicker, None);
+        assert_eq!(e.to_string(), "High Card, King ties with High Card, King");
+    }
+}
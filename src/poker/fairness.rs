@@ -0,0 +1,434 @@
+//! Evidence that shuffles and deals are fair: many decks are shuffled and
+//! the results compared with what a uniform shuffle would give.
+//!
+//! Two things are counted. Which card lands in each position of the deck,
+//! where every card should be equally likely, and the category of the five
+//! card hand dealt off the top, whose odds are known exactly. Each is
+//! summarised by a chi-squared statistic and its p-value; positions also get
+//! the Shannon entropy of their card counts, at most log2(52) bits.
+//!
+//! A fair source gives p-values spread evenly over 0 to 1, so out of 52
+//! positions two or three under 0.05 are expected. Consistently small ones
+//! are the warning sign.
+
+use std::fmt;
+
+use rand::rngs::{OsRng, StdRng};
+use rand::{RngCore, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+use super::Category;
+use super::deck::Deck;
+use super::physical::rising_sequences;
+
+// Five card hands of each category out of 2,598,960, weakest first.
+const CATEGORY_HANDS: [(Category, u64); 10] = [
+    (Category::HighCard, 1_302_540),
+    (Category::OnePair, 1_098_240),
+    (Category::TwoPairs, 123_552),
+    (Category::ThreeOfAKind, 54_912),
+    (Category::Straight, 10_200),
+    (Category::Flush, 5_108),
+    (Category::FullHouse, 3_744),
+    (Category::FourOfAKind, 624),
+    (Category::StraightFlush, 36),
+    (Category::RoyalFlush, 4),
+];
+const HANDS: u64 = 2_598_960;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Source {
+    ChaCha8(u64),
+    // rand's StdRng, seeded.
+    Std(u64),
+    // The operating system's generator; cannot be replayed.
+    Os,
+}
+
+impl Source {
+    pub fn parse(name: &str, seed: u64) -> Option<Source> {
+        match name {
+            "chacha8" => Some(Source::ChaCha8(seed)),
+            "std" => Some(Source::Std(seed)),
+            "os" => Some(Source::Os),
+            _ => None,
+        }
+    }
+
+    fn rng(&self) -> Box<dyn RngCore> {
+        match *self {
+            Source::ChaCha8(seed) => Box::new(ChaCha8Rng::seed_from_u64(seed)),
+            Source::Std(seed) => Box::new(StdRng::seed_from_u64(seed)),
+            Source::Os => Box::new(OsRng),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ChiSquared {
+    pub statistic: f64,
+    pub df: usize,
+    pub p: f64,
+}
+
+impl ChiSquared {
+    // Observed against expected counts, with `df` one less than the cells.
+    pub fn test(observed: &[u64], expected: &[f64]) -> Self {
+        let statistic = observed.iter().zip(expected)
+            .map(|(&o, &e)| (o as f64 - e).powi(2) / e)
+            .sum();
+        let df = observed.len() - 1;
+
+        ChiSquared{statistic, df, p: upper_gamma(df as f64 / 2.0, statistic / 2.0)}
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Position {
+    pub chi: ChiSquared,
+    pub entropy: f64,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct FairnessReport {
+    pub deals: usize,
+    // Position 0 is the top of the deck, the first card dealt.
+    pub positions: Vec<Position>,
+    // Categories too rare for the deal count are pooled with the next
+    // weaker one, so the cells can be fewer than ten.
+    pub categories: ChiSquared,
+    pub category_counts: Vec<(Category, u64)>,
+}
+
+impl FairnessReport {
+    // Positions whose p-value falls below `alpha`.
+    pub fn failing(&self, alpha: f64) -> Vec<usize> {
+        (0..self.positions.len()).filter(|&i| self.positions[i].chi.p < alpha).collect()
+    }
+}
+
+pub fn run(source: Source, deals: usize) -> FairnessReport {
+    assert!(deals > 0, "at least one deal");
+    let mut rng = source.rng();
+
+    // Cards are counted by where they sit in a new deck.
+    let fresh = Deck::new();
+    let index = |card| fresh.cards().iter().position(|c| *c == card).unwrap();
+    let mut counts = vec![[0u64; 52]; 52];
+    let mut categories = [0u64; 10];
+
+    for _ in 0..deals {
+        let mut deck = Deck::new();
+        deck.shuffle(&mut rng);
+
+        for (position, card) in deck.cards().iter().rev().enumerate() {
+            counts[position][index(*card)] += 1;
+        }
+        let category = deck.deal_hand().unwrap().value().category();
+        categories[CATEGORY_HANDS.iter().position(|(c, _)| *c == category).unwrap()] += 1;
+    }
+
+    let expected = [deals as f64 / 52.0; 52];
+    let positions = counts.iter()
+        .map(|c| Position{chi: ChiSquared::test(c, &expected), entropy: entropy(c)})
+        .collect();
+
+    // Pool from the strongest down until each cell expects at least five.
+    let mut observed: Vec<u64> = vec![];
+    let mut wanted: Vec<f64> = vec![];
+    let (mut o, mut e) = (0, 0.0);
+    for (i, (_, hands)) in CATEGORY_HANDS.iter().enumerate().rev() {
+        o += categories[i];
+        e += deals as f64 * *hands as f64 / HANDS as f64;
+        if e >= 5.0 || i == 0 {
+            observed.push(o);
+            wanted.push(e);
+            o = 0;
+            e = 0.0;
+        }
+    }
+    let categories_chi = if observed.len() > 1 {
+        ChiSquared::test(&observed, &wanted)
+    } else {
+        ChiSquared{statistic: 0.0, df: 0, p: 1.0}
+    };
+
+    FairnessReport{
+        deals,
+        positions,
+        categories: categories_chi,
+        category_counts: CATEGORY_HANDS.iter().zip(categories.iter()).map(|((c, _), &n)| (*c, n)).collect(),
+    }
+}
+
+/// What `audit_shuffle` found over many shuffles of a new deck.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ShuffleReport {
+    pub trials: usize,
+    // Position 0 is the top of the deck, as in `FairnessReport`.
+    pub positions: Vec<Position>,
+    // The largest gap, over every card and position, between how often the
+    // card landed there and 1/52.
+    pub max_bias: f64,
+    // `rising[k]` shuffles left k rising sequences of the new deck's order.
+    pub rising: Vec<u64>,
+    pub rising_mean: f64,
+    // Rising sequence counts against those of a uniform shuffle.
+    pub rising_chi: ChiSquared,
+}
+
+impl ShuffleReport {
+    pub fn failing(&self, alpha: f64) -> Vec<usize> {
+        (0..self.positions.len()).filter(|&i| self.positions[i].chi.p < alpha).collect()
+    }
+}
+
+/// Runs `shuffle` on `trials` new decks, with a generator from `source`,
+/// and compares the orders it leaves with a uniform shuffle's: where each
+/// card ends up, and how many rising sequences are left. A uniform shuffle
+/// leaves 26.5 on average, and a few riffles far fewer, so the rising
+/// sequences catch shuffles whose positions look fair, such as a cut.
+///
+/// ```ignore
+/// let report = audit_shuffle(|deck, rng| deck.apply(&casino, rng), Source::ChaCha8(1), 10_000);
+/// ```
+pub fn audit_shuffle<F>(mut shuffle: F, source: Source, trials: usize) -> ShuffleReport
+where
+    F: FnMut(&mut Deck, &mut dyn RngCore),
+{
+    assert!(trials > 0, "at least one trial");
+    let mut rng = source.rng();
+
+    let fresh = Deck::new();
+    let index = |card| fresh.cards().iter().position(|c| *c == card).expect("a card of the deck");
+    let mut counts = vec![[0u64; 52]; 52];
+    let mut rising = vec![0u64; 53];
+
+    for _ in 0..trials {
+        let mut deck = Deck::new();
+        shuffle(&mut deck, &mut *rng);
+        assert_eq!(deck.len(), 52, "the shuffle must keep every card");
+
+        for (position, card) in deck.cards().iter().rev().enumerate() {
+            counts[position][index(*card)] += 1;
+        }
+        rising[rising_sequences(fresh.cards(), deck.cards())] += 1;
+    }
+
+    let expected = [trials as f64 / 52.0; 52];
+    let positions = counts.iter()
+        .map(|c| Position{chi: ChiSquared::test(c, &expected), entropy: entropy(c)})
+        .collect();
+    let max_bias = counts.iter().flatten()
+        .map(|&n| (n as f64 / trials as f64 - 1.0 / 52.0).abs())
+        .fold(0.0, f64::max);
+    let rising_mean = rising.iter().enumerate().map(|(k, &n)| k as f64 * n as f64).sum::<f64>() / trials as f64;
+
+    // Pool from the fewest sequences up until each cell expects at least
+    // five, the last cell taking whatever is left.
+    let uniform = eulerian(52);
+    let mut observed: Vec<u64> = vec![];
+    let mut wanted: Vec<f64> = vec![];
+    let (mut o, mut e) = (0, 0.0);
+    for k in 1..=52 {
+        o += rising[k];
+        e += trials as f64 * uniform[k];
+        if e >= 5.0 {
+            observed.push(o);
+            wanted.push(e);
+            o = 0;
+            e = 0.0;
+        }
+    }
+    match (observed.last_mut(), wanted.last_mut()) {
+        (Some(last_o), Some(last_e)) => {
+            *last_o += o;
+            *last_e += e;
+        }
+        _ => {
+            observed.push(o);
+            wanted.push(e);
+        }
+    }
+    let rising_chi = if observed.len() > 1 {
+        ChiSquared::test(&observed, &wanted)
+    } else {
+        ChiSquared{statistic: 0.0, df: 0, p: 1.0}
+    };
+
+    ShuffleReport{trials, positions, max_bias, rising, rising_mean, rising_chi}
+}
+
+// The chance that a uniform order of `n` cards has k rising sequences, at
+// index k: Eulerian numbers over n!, by their recurrence.
+fn eulerian(n: usize) -> Vec<f64> {
+    let mut p = vec![0.0; n + 1];
+    p[1] = 1.0;
+    for m in 2..=n {
+        for k in (1..=m).rev() {
+            p[k] = (k as f64 * p[k] + (m + 1 - k) as f64 * p[k - 1]) / m as f64;
+        }
+    }
+    p
+}
+
+fn entropy(counts: &[u64]) -> f64 {
+    let total: u64 = counts.iter().sum();
+    counts.iter()
+        .filter(|&&n| n > 0)
+        .map(|&n| {
+            let p = n as f64 / total as f64;
+            -p * p.log2()
+        })
+        .sum()
+}
+
+impl fmt::Display for FairnessReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{} deals", self.deals)?;
+        writeln!(f, "{:>8} {:>10} {:>8} {:>8}", "position", "chi2", "p", "entropy")?;
+        for (i, p) in self.positions.iter().enumerate() {
+            writeln!(f, "{:>8} {:>10.2} {:>8.4} {:>8.4}", i, p.chi.statistic, p.chi.p, p.entropy)?;
+        }
+        writeln!(f, "{} of 52 positions with p < 0.05 (about 2.6 expected)", self.failing(0.05).len())?;
+
+        writeln!(f, "{:<16} {:>10} {:>12}", "category", "dealt", "expected")?;
+        for (&(category, n), (_, hands)) in self.category_counts.iter().zip(CATEGORY_HANDS.iter()) {
+            let expected = self.deals as f64 * *hands as f64 / HANDS as f64;
+            writeln!(f, "{:<16} {:>10} {:>12.2}", format!("{:?}", category), n, expected)?;
+        }
+        writeln!(
+            f,
+            "categories chi2 {:.2} on {} df, p {:.4}",
+            self.categories.statistic, self.categories.df, self.categories.p
+        )
+    }
+}
+
+impl fmt::Display for ShuffleReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{} shuffles", self.trials)?;
+        writeln!(f, "{} of 52 positions with p < 0.05 (about 2.6 expected)", self.failing(0.05).len())?;
+        writeln!(f, "largest positional bias {:.4} (card at a position against 1/52)", self.max_bias)?;
+        writeln!(f, "rising sequences: mean {:.2} (26.50 expected)", self.rising_mean)?;
+        write!(
+            f,
+            "rising sequences chi2 {:.2} on {} df, p {:.4}",
+            self.rising_chi.statistic, self.rising_chi.df, self.rising_chi.p
+        )
+    }
+}
+
+// Q(a, x), the upper regularized incomplete gamma function, by its series
+// below a + 1 and its continued fraction above.
+fn upper_gamma(a: f64, x: f64) -> f64 {
+    if x <= 0.0 { return 1.0; }
+    if a <= 0.0 { return 0.0; }
+
+    let front = (a * x.ln() - x - ln_gamma(a)).exp();
+    if x < a + 1.0 {
+        let (mut term, mut sum, mut n) = (1.0 / a, 1.0 / a, a);
+        while term.abs() > sum.abs() * 1e-15 {
+            n += 1.0;
+            term *= x / n;
+            sum += term;
+        }
+        1.0 - sum * front
+    } else {
+        // Lentz's method.
+        let tiny = 1e-300;
+        let mut b = x + 1.0 - a;
+        let mut c = 1.0 / tiny;
+        let mut d = 1.0 / b;
+        let mut h = d;
+        for i in 1..1000 {
+            let an = -(i as f64) * (i as f64 - a);
+            b += 2.0;
+            d = an * d + b;
+            if d.abs() < tiny { d = tiny; }
+            c = b + an / c;
+            if c.abs() < tiny { c = tiny; }
+            d = 1.0 / d;
+            let delta = d * c;
+            h *= delta;
+            if (delta - 1.0).abs() < 1e-15 { break; }
+        }
+        front * h
+    }
+}
+
+// Lanczos approximation, good to about 15 digits for positive arguments.
+fn ln_gamma(x: f64) -> f64 {
+    const G: [f64; 6] = [
+        76.18009172947146,
+        -86.50532032941677,
+        24.01409824083091,
+        -1.231739572450155,
+        0.1208650973866179e-2,
+        -0.5395239384953e-5,
+    ];
+    let tmp = x + 5.5 - (x + 0.5) * (x + 5.5).ln();
+    let mut ser = 1.000000000190015;
+    for (i, g) in G.iter().enumerate() {
+        ser += g / (x + 1.0 + i as f64);
+    }
+    -tmp + (2.5066282746310005 * ser / x).ln()
+}
+
+#[cfg(test)]
+mod fairness_tests {
+    use super::*;
+    use super::super::physical::Shuffle;
+
+    #[test]
+    fn test_chi_squared() {
+        // Reference values: the 0.95 quantiles of chi-squared on 1 and 10 df.
+        assert!((upper_gamma(0.5, 3.841459 / 2.0) - 0.05).abs() < 1e-6);
+        assert!((upper_gamma(5.0, 18.307038 / 2.0) - 0.05).abs() < 1e-6);
+        assert!((ChiSquared::test(&[50, 50], &[50.0, 50.0]).p - 1.0).abs() < 1e-12);
+        assert!(ChiSquared::test(&[90, 10], &[50.0, 50.0]).p < 1e-10);
+    }
+
+    #[test]
+    fn test_fair_source() {
+        let report = run(Source::ChaCha8(1), 5200);
+
+        assert_eq!(report.positions.len(), 52);
+        assert!(report.failing(0.001).is_empty());
+        assert!(report.positions.iter().all(|p| p.entropy > 5.6 && p.entropy <= 52f64.log2()));
+        assert!(report.categories.p > 0.001);
+        assert_eq!(report.category_counts.iter().map(|(_, n)| n).sum::<u64>(), 5200);
+        assert!(report.to_string().contains("categories chi2"));
+    }
+
+    #[test]
+    fn test_audit_shuffle() {
+        let p: f64 = eulerian(52).iter().sum();
+        assert!((p - 1.0).abs() < 1e-12);
+        assert_eq!(eulerian(3), vec![0.0, 1.0 / 6.0, 4.0 / 6.0, 1.0 / 6.0]);
+
+        let fair = audit_shuffle(|deck, rng| deck.shuffle(rng), Source::ChaCha8(2), 2000);
+        assert!(fair.failing(0.001).is_empty());
+        assert!((fair.rising_mean - 26.5).abs() < 0.2);
+        assert!(fair.rising_chi.p > 0.001);
+        assert!(fair.to_string().contains("rising sequences chi2"));
+
+        // One riffle leaves two rising sequences at most and the top card
+        // near the top.
+        let riffle = audit_shuffle(|deck, rng| deck.apply(&[Shuffle::Riffle], rng), Source::ChaCha8(2), 2000);
+        assert!(riffle.rising_mean <= 2.0);
+        assert!(riffle.rising_chi.p < 1e-100);
+        assert!(riffle.failing(0.001).len() > 40);
+        assert!(riffle.max_bias > fair.max_bias);
+    }
+
+    #[test]
+    fn test_unshuffled_fails() {
+        // A deck that is never shuffled puts the same card in every position.
+        let counts: Vec<u64> = (0..52).map(|i| if i == 0 { 520 } else { 0 }).collect();
+        let chi = ChiSquared::test(&counts, &[10.0; 52]);
+
+        assert!(chi.p < 1e-100);
+        assert_eq!(entropy(&counts), 0.0);
+    }
+}
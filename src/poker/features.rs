@@ -0,0 +1,234 @@
+//! Numeric feature encodings for cards, hands and spots.
+//!
+//! Every encoder writes into a flat `Vec<f32>` with a fixed layout so that
+//! models trained against one version of the crate keep working against the
+//! next. Offsets are exported as constants; the layouts are:
+//!
+//! Hand (`encode_hand`, `HAND_LEN` values)
+//!
+//! | offset              | len   | content                                  |
+//! |---------------------|-------|------------------------------------------|
+//! | `HAND_ONE_HOT`      | 56    | one-hot cards, index `rank * 4 + suit`   |
+//! | `HAND_RANKS`        | 14    | rank histogram, `Rank::One..=Rank::Ace`  |
+//! | `HAND_SUITS`        | 4     | suit histogram, hearts/diamonds/clubs/spades |
+//! | `HAND_CATEGORY`     | 1     | `Category` index, 0 (high card) to 10    |
+//! | `HAND_CLASS`        | 1     | `HandValue::class_index`, 0 if none      |
+//!
+//! Spot (`encode_spot`, `SPOT_LEN` values)
+//!
+//! | offset              | len   | content                                  |
+//! |---------------------|-------|------------------------------------------|
+//! | `SPOT_HOLE`         | 56    | one-hot hole cards                       |
+//! | `SPOT_BOARD`        | 56    | one-hot board cards                      |
+//! | `SPOT_RANKS`        | 14    | rank histogram of hole and board         |
+//! | `SPOT_SUITS`        | 4     | suit histogram of hole and board         |
+//! | `SPOT_TEXTURE`      | 6     | board texture flags, see `texture`       |
+//! | `SPOT_SPR`          | 1     | stack-to-pot ratio                       |
+//! | `SPOT_POSITION`     | 1     | seat relative to the button, 0 = button  |
+//! | `SPOT_PLAYERS`      | 1     | players in the hand                      |
+
+use super::{Card, Hand, Rank};
+
+pub const RANKS: usize = 14;
+pub const SUITS: usize = 4;
+pub const CARDS: usize = RANKS * SUITS;
+pub const TEXTURE: usize = 6;
+
+pub const HAND_ONE_HOT: usize = 0;
+pub const HAND_RANKS: usize = HAND_ONE_HOT + CARDS;
+pub const HAND_SUITS: usize = HAND_RANKS + RANKS;
+pub const HAND_CATEGORY: usize = HAND_SUITS + SUITS;
+pub const HAND_CLASS: usize = HAND_CATEGORY + 1;
+pub const HAND_LEN: usize = HAND_CLASS + 1;
+
+pub const SPOT_HOLE: usize = 0;
+pub const SPOT_BOARD: usize = SPOT_HOLE + CARDS;
+pub const SPOT_RANKS: usize = SPOT_BOARD + CARDS;
+pub const SPOT_SUITS: usize = SPOT_RANKS + RANKS;
+pub const SPOT_TEXTURE: usize = SPOT_SUITS + SUITS;
+pub const SPOT_SPR: usize = SPOT_TEXTURE + TEXTURE;
+pub const SPOT_POSITION: usize = SPOT_SPR + 1;
+pub const SPOT_PLAYERS: usize = SPOT_POSITION + 1;
+pub const SPOT_LEN: usize = SPOT_PLAYERS + 1;
+
+/// A decision point: hole cards, community cards and the table situation.
+pub struct Spot<'a> {
+    pub hole: &'a [Card],
+    pub board: &'a [Card],
+    pub spr: f32,
+    pub position: u8,
+    pub players: u8,
+}
+
+pub fn card_index(card: &Card) -> usize {
+    card.rank.index() * SUITS + card.suit.index()
+}
+
+pub fn one_hot(cards: &[Card]) -> [f32; CARDS] {
+    let mut out = [0.0; CARDS];
+    for card in cards {
+        out[card_index(card)] = 1.0;
+    }
+    out
+}
+
+pub fn rank_histogram(cards: &[Card]) -> [f32; RANKS] {
+    let mut out = [0.0; RANKS];
+    for card in cards {
+        out[card.rank.index()] += 1.0;
+    }
+    out
+}
+
+pub fn suit_histogram(cards: &[Card]) -> [f32; SUITS] {
+    let mut out = [0.0; SUITS];
+    for card in cards {
+        out[card.suit.index()] += 1.0;
+    }
+    out
+}
+
+/// Board texture flags, each 0.0 or 1.0, in order:
+/// paired, trips or better, monotone, two-tone, rainbow, straight possible
+/// (three ranks within a five-rank window).
+pub fn texture(board: &[Card]) -> [f32; TEXTURE] {
+    let ranks = rank_histogram(board);
+    let suits = suit_histogram(board);
+
+    let max_rank = ranks.iter().cloned().fold(0.0, f32::max);
+    let max_suit = suits.iter().cloned().fold(0.0, f32::max);
+    let n = board.len() as f32;
+
+    // Aces play low as well, so A-2-3 is as connected as 2-3-4.
+    let connected_ranks = {
+        let mut r = ranks;
+        r[Rank::One.index()] += r[Rank::Ace.index()];
+        r
+    };
+    let connected = (0..RANKS).any(|low| {
+        let window = &connected_ranks[low..(low + 5).min(RANKS)];
+        window.iter().filter(|&&c| c > 0.0).count() >= 3
+    });
+
+    let flag = |b: bool| if b { 1.0 } else { 0.0 };
+
+    [
+        flag(max_rank >= 2.0),
+        flag(max_rank >= 3.0),
+        flag(n >= 3.0 && max_suit == n),
+        flag(max_suit == 2.0),
+        flag(n >= 3.0 && max_suit == 1.0),
+        flag(connected),
+    ]
+}
+
+pub fn encode_hand(hand: &Hand) -> Vec<f32> {
+    let cards = hand.cards();
+
+    let mut out = Vec::with_capacity(HAND_LEN);
+    out.extend_from_slice(&one_hot(cards));
+    out.extend_from_slice(&rank_histogram(cards));
+    out.extend_from_slice(&suit_histogram(cards));
+    let value = hand.value();
+    out.push(value.category() as usize as f32);
+    out.push(value.class_index().map_or(0.0, f32::from));
+    out
+}
+
+pub fn encode_spot(spot: &Spot) -> Vec<f32> {
+    let all: Vec<Card> = spot.hole.iter().chain(spot.board).cloned().collect();
+
+    let mut out = Vec::with_capacity(SPOT_LEN);
+    out.extend_from_slice(&one_hot(spot.hole));
+    out.extend_from_slice(&one_hot(spot.board));
+    out.extend_from_slice(&rank_histogram(&all));
+    out.extend_from_slice(&suit_histogram(&all));
+    out.extend_from_slice(&texture(spot.board));
+    out.push(spot.spr);
+    out.push(spot.position as f32);
+    out.push(spot.players as f32);
+    out
+}
+
+#[cfg(test)]
+mod features_tests {
+    use super::*;
+    use super::super::Suit;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_card_index() {
+        assert_eq!(card_index(&Card{rank: Rank::One, suit: Suit::Hearts}), 0);
+        assert_eq!(card_index(&Card{rank: Rank::Ace, suit: Suit::Spades}), CARDS - 1);
+    }
+
+    #[test]
+    fn test_encode_hand() {
+        let hand = Hand::from_str("8C 8S KC 9H 9S").unwrap();
+        let v = encode_hand(&hand);
+
+        assert_eq!(v.len(), HAND_LEN);
+        assert_eq!(v[HAND_ONE_HOT..HAND_RANKS].iter().sum::<f32>(), 5.0);
+        assert_eq!(v[HAND_RANKS + Rank::Eight as usize], 2.0);
+        assert_eq!(v[HAND_SUITS + 2], 2.0);
+        assert_eq!(v[HAND_CATEGORY], 2.0);
+        assert_eq!(v[HAND_CLASS], hand.value().class_index().unwrap() as f32);
+
+        let royal = Hand::from_str("AS KS QS JS TS").unwrap();
+        assert_eq!(encode_hand(&royal)[HAND_CLASS], 1.0);
+    }
+
+    #[test]
+    fn test_texture() {
+        let board = [
+            Card{rank: Rank::Nine,  suit: Suit::Hearts},
+            Card{rank: Rank::Ten,   suit: Suit::Hearts},
+            Card{rank: Rank::Nine,  suit: Suit::Hearts},
+        ];
+        assert_eq!(texture(&board), [1.0, 0.0, 1.0, 0.0, 0.0, 0.0]);
+
+        let board = [
+            Card{rank: Rank::Nine,  suit: Suit::Hearts},
+            Card{rank: Rank::Ten,   suit: Suit::Clubs},
+            Card{rank: Rank::Queen, suit: Suit::Spades},
+        ];
+        assert_eq!(texture(&board), [0.0, 0.0, 0.0, 0.0, 1.0, 1.0]);
+
+        // The wheel: the Ace plays low.
+        let board = [
+            Card{rank: Rank::Ace,   suit: Suit::Hearts},
+            Card{rank: Rank::Two,   suit: Suit::Clubs},
+            Card{rank: Rank::Three, suit: Suit::Spades},
+        ];
+        assert_eq!(texture(&board), [0.0, 0.0, 0.0, 0.0, 1.0, 1.0]);
+
+        let board = [
+            Card{rank: Rank::Ace,   suit: Suit::Hearts},
+            Card{rank: Rank::Six,   suit: Suit::Clubs},
+            Card{rank: Rank::Three, suit: Suit::Spades},
+        ];
+        assert_eq!(texture(&board), [0.0, 0.0, 0.0, 0.0, 1.0, 0.0]);
+    }
+
+    #[test]
+    fn test_encode_spot() {
+        let hole = [
+            Card{rank: Rank::Ace,  suit: Suit::Spades},
+            Card{rank: Rank::King, suit: Suit::Spades},
+        ];
+        let board = [
+            Card{rank: Rank::Two,  suit: Suit::Spades},
+            Card{rank: Rank::Ace,  suit: Suit::Clubs},
+            Card{rank: Rank::Nine, suit: Suit::Spades},
+        ];
+        let v = encode_spot(&Spot{hole: &hole, board: &board, spr: 3.5, position: 1, players: 2});
+
+        assert_eq!(v.len(), SPOT_LEN);
+        assert_eq!(v[SPOT_HOLE + card_index(&hole[0])], 1.0);
+        assert_eq!(v[SPOT_BOARD + card_index(&hole[0])], 0.0);
+        assert_eq!(v[SPOT_RANKS + Rank::Ace as usize], 2.0);
+        assert_eq!(v[SPOT_SUITS + 3], 4.0);
+        assert_eq!(v[SPOT_SPR], 3.5);
+        assert_eq!(v[SPOT_PLAYERS], 2.0);
+    }
+}
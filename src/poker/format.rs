@@ -0,0 +1,139 @@
+//! Version headers for files the crate writes.
+//!
+//! Every persisted artifact starts with a single header line
+//!
+//! ```text
+//! # misc-poker <kind> v<version> [key=value ...]
+//! ```
+//!
+//! Readers check the kind, migrate any older version they still understand
+//! and reject anything newer than they know, rather than guessing at it.
+
+use std::fmt;
+use std::io::{self, Write};
+
+pub const MAGIC: &str = "misc-poker";
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Header {
+    pub kind: String,
+    pub version: u32,
+    pub fields: Vec<(String, String)>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum FormatError {
+    MissingHeader,
+    Malformed(String),
+    WrongKind { expected: String, found: String },
+    // Written by a newer version of the crate.
+    Unsupported { kind: String, version: u32, latest: u32 },
+}
+
+impl fmt::Display for FormatError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FormatError::MissingHeader => write!(f, "missing {} header", MAGIC),
+            FormatError::Malformed(line) => write!(f, "malformed header '{}'", line),
+            FormatError::WrongKind { expected, found } => {
+                write!(f, "expected a {} file, found {}", expected, found)
+            },
+            FormatError::Unsupported { kind, version, latest } => write!(
+                f,
+                "{} format v{} is newer than the latest supported v{}",
+                kind, version, latest
+            ),
+        }
+    }
+}
+
+impl std::error::Error for FormatError {}
+
+impl Header {
+    pub fn new(kind: &str, version: u32) -> Self {
+        Header{kind: kind.to_string(), version, fields: vec![]}
+    }
+
+    pub fn field<V: fmt::Display>(mut self, key: &str, value: V) -> Self {
+        self.fields.push((key.to_string(), value.to_string()));
+        self
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.fields.iter().find(|(k, _)| k == key).map(|(_, v)| v.as_str())
+    }
+
+    pub fn write<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        write!(w, "# {} {} v{}", MAGIC, self.kind, self.version)?;
+        for (k, v) in &self.fields {
+            write!(w, " {}={}", k, v)?;
+        }
+        writeln!(w)
+    }
+
+    pub fn parse(line: &str) -> Result<Header, FormatError> {
+        let malformed = || FormatError::Malformed(line.to_string());
+
+        let mut words = line.trim_end().split(' ');
+        if words.next() != Some("#") || words.next() != Some(MAGIC) {
+            return Err(FormatError::MissingHeader);
+        }
+        let kind = words.next().ok_or_else(malformed)?;
+        let version = words.next()
+            .and_then(|v| v.strip_prefix('v'))
+            .and_then(|v| v.parse().ok())
+            .ok_or_else(malformed)?;
+
+        let mut header = Header::new(kind, version);
+        for word in words {
+            let (k, v) = word.split_once('=').ok_or_else(malformed)?;
+            header = header.field(k, v);
+        }
+        Ok(header)
+    }
+
+    // Ok if this is a `kind` file no newer than `latest`.
+    pub fn check(&self, kind: &str, latest: u32) -> Result<(), FormatError> {
+        if self.kind != kind {
+            return Err(FormatError::WrongKind{expected: kind.to_string(), found: self.kind.clone()});
+        }
+        if self.version > latest {
+            return Err(FormatError::Unsupported{kind: self.kind.clone(), version: self.version, latest});
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod format_tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let header = Header::new("dataset", 3).field("seed", 42).field("schema", "showdown");
+        let mut out = Vec::new();
+        header.write(&mut out).unwrap();
+        let line = String::from_utf8(out).unwrap();
+
+        assert_eq!(line, "# misc-poker dataset v3 seed=42 schema=showdown\n");
+        assert_eq!(Header::parse(&line), Ok(header));
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(Header::parse("row,c0,c1"), Err(FormatError::MissingHeader));
+        assert!(matches!(Header::parse("# misc-poker dataset x1"), Err(FormatError::Malformed(_))));
+        assert!(matches!(Header::parse("# misc-poker dataset v1 seed"), Err(FormatError::Malformed(_))));
+    }
+
+    #[test]
+    fn test_check() {
+        let header = Header::new("dataset", 2);
+
+        assert_eq!(header.check("dataset", 2), Ok(()));
+        assert_eq!(header.check("dataset", 3), Ok(()));
+        assert!(matches!(header.check("dataset", 1), Err(FormatError::Unsupported{latest: 1, ..})));
+        assert!(matches!(header.check("table", 2), Err(FormatError::WrongKind{..})));
+    }
+}
@@ -0,0 +1,113 @@
+//! Pot geometry: bet sizes over the remaining streets, when every bet is
+//! called.
+//!
+//! A bet of `f` times the pot that is called grows a pot `P` to
+//! `P * (1 + 2f)`, so `n` such bets turn it into `P * (1 + 2f)^n`. Getting
+//! effective stacks `S` in exactly by the last of them takes
+//!
+//! ```text
+//! f = ((1 + 2S / P)^(1/n) - 1) / 2
+//! ```
+//!
+//! and leaving a stack-to-pot ratio `T` behind takes the same with
+//! `(1 + 2S / P) / (1 + 2T)` in place of `1 + 2S / P`.
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Step {
+    pub bet: f64,
+    // Pot and effective stack once the bet is called.
+    pub pot: f64,
+    pub stack: f64,
+}
+
+impl Step {
+    pub fn spr(&self) -> f64 {
+        spr(self.stack, self.pot)
+    }
+
+    pub fn all_in(&self) -> bool {
+        self.stack <= 0.0
+    }
+}
+
+pub fn spr(stack: f64, pot: f64) -> f64 {
+    stack / pot
+}
+
+// Called bets of `fraction` of the pot on each of `streets` streets, all in
+// once a bet would be more than the stack left.
+pub fn fixed(pot: f64, stack: f64, fraction: f64, streets: usize) -> Vec<Step> {
+    let (mut pot, mut stack) = (pot, stack);
+    let mut steps = vec![];
+
+    for _ in 0..streets {
+        let bet = (fraction * pot).min(stack);
+        pot += 2.0 * bet;
+        stack -= bet;
+        steps.push(Step{bet, pot, stack});
+    }
+    steps
+}
+
+// The one pot fraction that gets the stacks in over `streets` bets.
+pub fn geometric(pot: f64, stack: f64, streets: usize) -> f64 {
+    fraction_for_growth(1.0 + 2.0 * stack / pot, streets)
+}
+
+// The pot fraction that, bet on each of `streets` streets, leaves `target`
+// as the stack-to-pot ratio. None when that is more than there is now.
+pub fn fraction_for_spr(pot: f64, stack: f64, streets: usize, target: f64) -> Option<f64> {
+    let growth = (1.0 + 2.0 * stack / pot) / (1.0 + 2.0 * target);
+    if growth < 1.0 { return None; }
+    Some(fraction_for_growth(growth, streets))
+}
+
+fn fraction_for_growth(growth: f64, streets: usize) -> f64 {
+    (growth.powf(1.0 / streets as f64) - 1.0) / 2.0
+}
+
+#[cfg(test)]
+mod geometry_tests {
+    use super::*;
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9
+    }
+
+    #[test]
+    fn test_geometric() {
+        // 100 behind in a pot of 10 grows it 21 times over three streets.
+        let f = geometric(10.0, 100.0, 3);
+        assert!(close((1.0 + 2.0 * f).powi(3), 21.0));
+
+        let steps = fixed(10.0, 100.0, f, 3);
+        assert!(close(steps[2].stack, 0.0));
+        assert!(close(steps[2].pot, 210.0));
+        assert!(close(geometric(10.0, 5.0, 1), 0.5));
+    }
+
+    #[test]
+    fn test_fixed() {
+        let steps = fixed(10.0, 100.0, 0.66, 3);
+
+        assert!(close(steps[0].bet, 6.6));
+        assert!(close(steps[0].pot, 23.2));
+        assert!(close(steps[1].stack, 100.0 - 6.6 - 0.66 * 23.2));
+        assert!(!steps[2].all_in());
+
+        let shove = fixed(10.0, 20.0, 1.0, 3);
+        assert!(close(shove[1].bet, 10.0));
+        assert!(shove[1].all_in());
+        assert_eq!(shove[2].bet, 0.0);
+    }
+
+    #[test]
+    fn test_fraction_for_spr() {
+        let f = fraction_for_spr(10.0, 100.0, 1, 1.0).unwrap();
+        let steps = fixed(10.0, 100.0, f, 1);
+
+        assert!(close(steps[0].spr(), 1.0));
+        assert!(close(fraction_for_spr(10.0, 100.0, 2, 0.0).unwrap(), geometric(10.0, 100.0, 2)));
+        assert_eq!(fraction_for_spr(10.0, 100.0, 1, 20.0), None);
+    }
+}
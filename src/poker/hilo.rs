@@ -0,0 +1,218 @@
+//! Eight or better high-low split games, such as Omaha-8 and Stud-8. The
+//! pot is split between the best high hand and the best low hand, where a
+//! low is five different ranks of eight or under with aces low. Straights
+//! and flushes do not count against a low, so A-2-3-4-5 is the best low
+//! there is, and for the high it is a five high straight as well. Without
+//! a qualifying low the high hand takes the whole pot.
+//!
+//! The high and the low are picked separately and may use different cards.
+//! In Stud-8 they come from any five of the player's seven; in Omaha-8
+//! each uses exactly two hole cards and three from the board.
+
+use std::cmp::Ordering;
+use std::fmt;
+
+use super::{Board, Card, Hand, Rank};
+use super::deal::{best_of, omaha_hands};
+use super::value::HandValue;
+
+/// A qualifying low: five different ranks, eight or under. Better lows
+/// compare greater, as with `HandValue`, so the best of several is the
+/// `max`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct LowValue {
+    // Aces as 1, highest first.
+    ranks: [u8; 5],
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct HiLo {
+    pub high: HandValue,
+    pub low: Option<LowValue>,
+}
+
+/// Which seats share each half of the pot. `low` is empty when nobody
+/// qualified, and `high` then scoops.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Split {
+    pub high: Vec<usize>,
+    pub low: Vec<usize>,
+}
+
+impl LowValue {
+    /// The best low among `cards`, of any number, if five of them make one.
+    pub fn best(cards: &[Card]) -> Option<LowValue> {
+        let mut seen = [false; 9];
+        for card in cards {
+            if let Some(r) = low_rank(card.rank) { seen[r as usize] = true; }
+        }
+
+        let mut ranks = [0; 5];
+        let mut n = 0;
+        for r in (1..=8).filter(|&r| seen[r as usize]).take(5) {
+            ranks[4 - n] = r;
+            n += 1;
+        }
+        if n == 5 { Some(LowValue{ranks}) } else { None }
+    }
+
+    /// The ranks, aces as 1, highest first.
+    pub fn ranks(&self) -> [u8; 5] {
+        self.ranks
+    }
+}
+
+impl Ord for LowValue {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.ranks.cmp(&self.ranks)
+    }
+}
+
+impl PartialOrd for LowValue {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+// As low hands are called: "8-6-4-2-A".
+impl fmt::Display for LowValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let names: Vec<String> = self.ranks.iter()
+            .map(|&r| if r == 1 { "A".to_string() } else { r.to_string() })
+            .collect();
+        write!(f, "{}", names.join("-"))
+    }
+}
+
+fn low_rank(rank: Rank) -> Option<u8> {
+    match rank {
+        Rank::One | Rank::Ace => Some(1),
+        // `Rank::One` is 0, so each rank sits one below its number.
+        r if r <= Rank::Eight => Some(r as u8 + 1),
+        _ => None,
+    }
+}
+
+/// The high and eight or better low of a hand made from any five of
+/// `cards` and `board` together: a Stud-8 hand with no board, or a
+/// Hold'em hand with one.
+///
+/// # Panics
+///
+/// If there are fewer than five cards in all.
+pub fn evaluate_hilo(cards: &[Card], board: &Board) -> HiLo {
+    let all: Vec<Card> = cards.iter().chain(board.iter()).cloned().collect();
+    HiLo{high: best_of(&all).value(), low: LowValue::best(&all)}
+}
+
+/// The high and low of an Omaha-8 hand, each made from exactly two of the
+/// `hole` cards and three of the `board`.
+///
+/// # Panics
+///
+/// If there are fewer than two hole cards or three board cards.
+pub fn evaluate_hilo_omaha(hole: &[Card], board: &Board) -> HiLo {
+    let mut high: Option<HandValue> = None;
+    let mut low: Option<LowValue> = None;
+    for five in omaha_hands(hole, board) {
+        high = high.max(Some(Hand::new(five).value()));
+        low = low.max(LowValue::best(&five));
+    }
+    HiLo{high: high.unwrap(), low}
+}
+
+/// Splits a showdown between `hands`, one per seat.
+pub fn split_pot(hands: &[HiLo]) -> Split {
+    let best_high = hands.iter().map(|h| h.high).max();
+    let best_low = hands.iter().filter_map(|h| h.low).max();
+
+    Split{
+        high: (0..hands.len()).filter(|&i| Some(hands[i].high) == best_high).collect(),
+        low: (0..hands.len()).filter(|&i| best_low.is_some() && hands[i].low == best_low).collect(),
+    }
+}
+
+#[cfg(test)]
+mod hilo_tests {
+    use super::*;
+    use super::super::Category;
+
+    fn cards(s: &str) -> Vec<Card> {
+        s.split_whitespace().map(|c| Card::from_code(c).unwrap()).collect()
+    }
+
+    fn low(s: &str) -> Option<LowValue> {
+        LowValue::best(&cards(s))
+    }
+
+    #[test]
+    fn test_low() {
+        assert_eq!(low("AH 2D 3C 4S 5H").unwrap().to_string(), "5-4-3-2-A");
+        assert_eq!(low("KH 8D 6C 4S 2H AD 8S").unwrap().to_string(), "8-6-4-2-A");
+        assert_eq!(low("AH 2D 3C 4S 9H"), None);
+        assert_eq!(low("AH AD 2C 3S 4H 4D"), None);
+
+        assert!(low("AH 2D 3C 4S 5H") > low("AH 2D 3C 4S 6H"));
+        assert!(low("7H 5D 4C 3S 2H") > low("8H 4D 3C 2S AH"));
+        assert!(low("8H 6D 4C 2S AH") > low("8H 6D 5C 2S AH"));
+    }
+
+    #[test]
+    fn test_stud() {
+        // A wheel plays for both halves.
+        let hand = evaluate_hilo(&cards("AH 2D 3C 4S 5H KD KS"), &Board::new());
+        assert_eq!(hand.high.category(), Category::Straight);
+        assert_eq!(hand.high.tiebreaks(), &[Rank::Five]);
+        assert_eq!(hand.low.unwrap().to_string(), "5-4-3-2-A");
+
+        // So it scoops against trips.
+        let trips = evaluate_hilo(&cards("KH KC KD 9S 7H 6D 2C"), &Board::new());
+        assert_eq!(split_pot(&[hand, trips]), Split{high: vec![0], low: vec![0]});
+
+        let board: Board = "2C 7D 8H".parse().unwrap();
+        let hand = evaluate_hilo(&cards("KH QH JC TS"), &board);
+        assert_eq!(hand.low, None);
+    }
+
+    #[test]
+    fn test_omaha() {
+        // Two low cards in the hand and three on the board.
+        let board: Board = "2C 5D 8H KS KD".parse().unwrap();
+        let hand = evaluate_hilo_omaha(&cards("AH 3D KC QC"), &board);
+        assert_eq!(hand.low.unwrap().to_string(), "8-5-3-2-A");
+        assert_eq!(hand.high.category(), Category::ThreeOfAKind);
+
+        // Four low cards in the hand make no low with only two low on board,
+        // though any five of the nine would.
+        let board: Board = "2C 5D KH KS QD".parse().unwrap();
+        assert_eq!(evaluate_hilo_omaha(&cards("AH 3D 4C 6C"), &board).low, None);
+        assert!(evaluate_hilo(&cards("AH 3D 4C 6C"), &board).low.is_some());
+
+        // The wheel plays for both halves here too, and beats a set.
+        let board: Board = "3C 4S 5H KD KS".parse().unwrap();
+        let wheel = evaluate_hilo_omaha(&cards("AH 2D QS JS"), &board);
+        assert_eq!(wheel.high, HandValue::new(Category::Straight, &[Rank::Five]));
+        assert_eq!(wheel.low.unwrap().to_string(), "5-4-3-2-A");
+        let set = evaluate_hilo_omaha(&cards("KC QC 9D 9H"), &board);
+        assert_eq!(split_pot(&[wheel, set]), Split{high: vec![0], low: vec![0]});
+
+        // And four hearts in the hand are no flush with one on the board.
+        let board: Board = "2H 9C TD JS 3C".parse().unwrap();
+        assert_ne!(evaluate_hilo_omaha(&cards("AH KH QH 7H"), &board).high.category(), Category::Flush);
+    }
+
+    #[test]
+    fn test_split_pot() {
+        let board: Board = "2C 5D 8H KS QD".parse().unwrap();
+        let hands: Vec<HiLo> = ["AH 3D KC 9C", "AS 4D KH 9H", "QH QS JC TC"].iter()
+            .map(|h| evaluate_hilo_omaha(&cards(h), &board))
+            .collect();
+        assert_eq!(split_pot(&hands), Split{high: vec![2], low: vec![0]});
+
+        let board: Board = "TC JD QH 9S 2D".parse().unwrap();
+        let hands: Vec<HiLo> = ["AH 3D KC 9C", "AS 3C KH 9H"].iter()
+            .map(|h| evaluate_hilo_omaha(&cards(h), &board))
+            .collect();
+        assert_eq!(split_pot(&hands), Split{high: vec![0, 1], low: vec![]});
+    }
+}
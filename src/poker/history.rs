@@ -0,0 +1,628 @@
+//! Hands played elsewhere, imported from the CSV or TSV exports of hand
+//! tracking software and kept in the crate's own history files.
+//!
+//! Exports have one row per action, and the rows of a hand are consecutive
+//! and share its id. Hole cards and the board may be given on any row of the
+//! hand. Which column holds what differs between trackers and is set with
+//! `Columns`:
+//!
+//! ```
+//! use misc::poker::history::{Columns, Field};
+//!
+//! let export = "Hand #\tName\tStreet\tAction\tAmount\tCards\n\
+//!               17\tbob\tpreflop\tposts\t$1\tAh Kd\n\
+//!               17\tjo\tpreflop\tfolds\t\t\n";
+//! let hands = Columns::tsv()
+//!     .column(Field::Hand, "Hand #")
+//!     .column(Field::Player, "Name")
+//!     .column(Field::Hole, "Cards")
+//!     .import(export.as_bytes())
+//!     .unwrap();
+//! assert_eq!(hands[0].seats.len(), 2);
+//! ```
+
+use std::fmt;
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+use std::str::FromStr;
+
+use super::{Board, Cards, HandParseError};
+use super::atomic;
+use super::board::Street;
+use super::format::{FormatError, Header};
+use super::parse::ParseOptions;
+
+const KIND: &str = "history";
+// v1 files have no table, stack, pot or rake columns.
+const VERSION: u32 = 2;
+
+// Amounts are money as the export gives them, not chips. A raise is to the
+// player's total for the street; every other amount is what the action
+// adds. `Collect` is a share of the pot won and `Refund` an uncalled bet
+// given back.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Play {
+    Post(f64),
+    Fold,
+    Check,
+    Call(f64),
+    Bet(f64),
+    Raise(f64),
+    Collect(f64),
+    Refund(f64),
+}
+
+impl Play {
+    fn name(&self) -> &'static str {
+        match self {
+            Play::Post(_) => "post",
+            Play::Fold => "fold",
+            Play::Check => "check",
+            Play::Call(_) => "call",
+            Play::Bet(_) => "bet",
+            Play::Raise(_) => "raise",
+            Play::Collect(_) => "collect",
+            Play::Refund(_) => "refund",
+        }
+    }
+
+    pub fn amount(&self) -> Option<f64> {
+        match *self {
+            Play::Post(a) | Play::Call(a) | Play::Bet(a) | Play::Raise(a)
+            | Play::Collect(a) | Play::Refund(a) => Some(a),
+            Play::Fold | Play::Check => None,
+        }
+    }
+
+    // Trackers write actions as verbs, "raises" as often as "raise".
+    fn parse(action: &str, amount: Option<f64>) -> Option<Play> {
+        let action = action.trim().to_lowercase();
+        let word = action.strip_suffix('s').unwrap_or(&action);
+
+        match (word, amount) {
+            ("fold", _) => Some(Play::Fold),
+            ("check", _) => Some(Play::Check),
+            ("post", Some(a)) => Some(Play::Post(a)),
+            ("call", Some(a)) => Some(Play::Call(a)),
+            ("bet", Some(a)) => Some(Play::Bet(a)),
+            ("raise", Some(a)) => Some(Play::Raise(a)),
+            ("collect" | "collected" | "win" | "won", Some(a)) => Some(Play::Collect(a)),
+            ("refund" | "refunded" | "return" | "returned", Some(a)) => Some(Play::Refund(a)),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Action {
+    pub player: String,
+    pub street: Street,
+    pub play: Play,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Seat {
+    pub player: String,
+    // Only known for the players whose cards were shown or recorded.
+    pub hole: Option<Cards>,
+    // Chips at the start of the hand.
+    pub stack: Option<f64>,
+}
+
+/// One played hand. Seats are in the order the players first act.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HandHistory {
+    pub id: String,
+    pub table: Option<String>,
+    pub seats: Vec<Seat>,
+    pub board: Board,
+    pub actions: Vec<Action>,
+    // The final pot and the rake taken from it, as recorded.
+    pub pot: Option<f64>,
+    pub rake: Option<f64>,
+}
+
+impl HandHistory {
+    fn new(id: &str) -> Self {
+        HandHistory {
+            id: id.to_string(),
+            table: None,
+            seats: vec![],
+            board: Board::new(),
+            actions: vec![],
+            pot: None,
+            rake: None,
+        }
+    }
+
+    fn seat(&mut self, player: &str) -> &mut Seat {
+        match self.seats.iter().position(|s| s.player == player) {
+            Some(i) => &mut self.seats[i],
+            None => {
+                self.seats.push(Seat{player: player.to_string(), hole: None, stack: None});
+                self.seats.last_mut().unwrap()
+            },
+        }
+    }
+}
+
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum ImportError {
+    Io(io::Error),
+    Format(FormatError),
+    MissingColumn(String),
+    BadCards { line: usize, error: HandParseError },
+    Malformed { line: usize },
+    // Fewer hands than the header counts, from a write cut short.
+    Truncated { expected: usize, found: usize },
+}
+
+impl fmt::Display for ImportError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ImportError::Io(e) => write!(f, "{}", e),
+            ImportError::Format(e) => write!(f, "{}", e),
+            ImportError::MissingColumn(name) => write!(f, "missing column '{}'", name),
+            ImportError::BadCards { line, error } => write!(f, "line {}: {}", line, error),
+            ImportError::Malformed { line } => write!(f, "malformed history row on line {}", line),
+            ImportError::Truncated { expected, found } => {
+                write!(f, "truncated history: {} of {} hands", found, expected)
+            },
+        }
+    }
+}
+
+impl std::error::Error for ImportError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ImportError::Io(e) => Some(e),
+            ImportError::Format(e) => Some(e),
+            ImportError::BadCards { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for ImportError {
+    fn from(e: io::Error) -> Self {
+        ImportError::Io(e)
+    }
+}
+
+impl From<FormatError> for ImportError {
+    fn from(e: FormatError) -> Self {
+        ImportError::Format(e)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Field {
+    Hand,
+    Player,
+    Street,
+    Action,
+    Amount,
+    Hole,
+    Board,
+    Table,
+    Stack,
+    Pot,
+    Rake,
+}
+
+const FIELDS: [Field; 11] = [
+    Field::Hand,
+    Field::Player,
+    Field::Street,
+    Field::Action,
+    Field::Amount,
+    Field::Hole,
+    Field::Board,
+    Field::Table,
+    Field::Stack,
+    Field::Pot,
+    Field::Rake,
+];
+
+impl Field {
+    // The column name in the crate's own files, and the default mapping.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Field::Hand => "hand",
+            Field::Player => "player",
+            Field::Street => "street",
+            Field::Action => "action",
+            Field::Amount => "amount",
+            Field::Hole => "hole",
+            Field::Board => "board",
+            Field::Table => "table",
+            Field::Stack => "stack",
+            Field::Pot => "pot",
+            Field::Rake => "rake",
+        }
+    }
+
+    fn required(&self) -> bool {
+        matches!(self, Field::Hand | Field::Player | Field::Street | Field::Action)
+    }
+}
+
+impl FromStr for Field {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        FIELDS.iter()
+            .find(|f| f.name() == s)
+            .copied()
+            .ok_or_else(|| format!("unknown field '{}'", s))
+    }
+}
+
+/// Where an export keeps each field, by header name, matched without regard
+/// to case. Hand, player, street and action columns are required; the rest
+/// are read when present.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Columns {
+    pub delimiter: char,
+    pub cards: ParseOptions,
+    names: Vec<(Field, String)>,
+}
+
+impl Default for Columns {
+    fn default() -> Self {
+        Columns {
+            delimiter: ',',
+            cards: ParseOptions::default().lenient(true),
+            names: FIELDS.iter().map(|f| (*f, f.name().to_string())).collect(),
+        }
+    }
+}
+
+impl Columns {
+    pub fn tsv() -> Self {
+        Columns::default().delimiter('\t')
+    }
+
+    pub fn delimiter(mut self, delimiter: char) -> Self {
+        self.delimiter = delimiter;
+        self
+    }
+
+    pub fn cards(mut self, cards: ParseOptions) -> Self {
+        self.cards = cards;
+        self
+    }
+
+    pub fn column(mut self, field: Field, name: &str) -> Self {
+        for (f, n) in self.names.iter_mut() {
+            if *f == field { *n = name.to_string(); }
+        }
+        self
+    }
+
+    pub fn import<R: BufRead>(&self, r: R) -> Result<Vec<HandHistory>, ImportError> {
+        self.import_lines(r.lines().enumerate().map(|(i, l)| (i + 1, l)))
+    }
+
+    fn import_lines<I>(&self, mut lines: I) -> Result<Vec<HandHistory>, ImportError>
+    where
+        I: Iterator<Item = (usize, io::Result<String>)>,
+    {
+        let mut reader = match lines.next() {
+            Some((_, line)) => RowReader::new(self.clone(), &line?)?,
+            None => return Ok(vec![]),
+        };
+
+        let mut hands = vec![];
+        for (n, line) in lines {
+            hands.extend(reader.push(n, &line?)?);
+        }
+        hands.extend(reader.finish());
+        Ok(hands)
+    }
+}
+
+/// Builds hands a row at a time, for files that are still being written.
+/// A hand is only known to be complete once a row of the next one is read,
+/// so the last hand is held back until then or until `finish`.
+#[derive(Clone, Debug)]
+pub struct RowReader {
+    columns: Columns,
+    index: Vec<(Field, Option<usize>)>,
+    hand: Option<HandHistory>,
+}
+
+impl RowReader {
+    // `titles` is the line of column names.
+    pub fn new(columns: Columns, titles: &str) -> Result<Self, ImportError> {
+        let titles = split(titles, columns.delimiter);
+
+        let mut index = vec![];
+        for (field, name) in &columns.names {
+            let i = titles.iter().position(|t| t.trim().eq_ignore_ascii_case(name));
+            if i.is_none() && field.required() {
+                return Err(ImportError::MissingColumn(name.clone()));
+            }
+            index.push((*field, i));
+        }
+
+        Ok(RowReader{columns, index, hand: None})
+    }
+
+    // Adds row `n` of the file, returning the hand it completes, if any.
+    pub fn push(&mut self, n: usize, line: &str) -> Result<Option<HandHistory>, ImportError> {
+        if line.trim().is_empty() { return Ok(None); }
+
+        let fields = split(line, self.columns.delimiter);
+        let index = &self.index;
+        let get = |field: Field| -> &str {
+            index.iter()
+                .find(|(f, _)| *f == field)
+                .and_then(|(_, i)| *i)
+                .and_then(|i| fields.get(i))
+                .map(|s| s.trim())
+                .unwrap_or("")
+        };
+        let malformed = ImportError::Malformed{line: n};
+
+        let (id, player) = (get(Field::Hand), get(Field::Player));
+        if id.is_empty() || player.is_empty() { return Err(malformed); }
+        let street: Street = get(Field::Street).parse().map_err(|_| malformed)?;
+        let money = |field: Field| match get(field).trim_start_matches(&['$', '€', '£'][..]) {
+            "" => Ok(None),
+            a => a.parse().map(Some).map_err(|_| ImportError::Malformed{line: n}),
+        };
+        let play = Play::parse(get(Field::Action), money(Field::Amount)?)
+            .ok_or(ImportError::Malformed{line: n})?;
+        let (stack, pot, rake) = (money(Field::Stack)?, money(Field::Pot)?, money(Field::Rake)?);
+        let table = get(Field::Table);
+
+        let bad = |error| ImportError::BadCards{line: n, error};
+        let hole = match get(Field::Hole) {
+            "" => None,
+            s => Some(Cards::parse_with(s, &self.columns.cards, 1..=7).map_err(bad)?),
+        };
+        let board = match get(Field::Board) {
+            "" => None,
+            s => Some(Board::parse_with(s, &self.columns.cards).map_err(bad)?),
+        };
+
+        let done = match &self.hand {
+            Some(hand) if hand.id != id => self.hand.take(),
+            _ => None,
+        };
+        let hand = self.hand.get_or_insert_with(|| HandHistory::new(id));
+
+        if let Some(hole) = hole {
+            let seat = hand.seat(player);
+            if seat.hole.as_ref().map(|h| *h != hole).unwrap_or(false) {
+                return Err(ImportError::Malformed{line: n});
+            }
+            seat.hole = Some(hole);
+        }
+        // The board may be repeated as it grows from street to street.
+        if let Some(board) = board {
+            if board.len() > hand.board.len() { hand.board = board; }
+        }
+
+        if let Some(stack) = stack { hand.seat(player).stack = Some(stack); }
+        if !table.is_empty() { hand.table = Some(table.to_string()); }
+        hand.pot = pot.or(hand.pot);
+        hand.rake = rake.or(hand.rake);
+
+        hand.seat(player);
+        hand.actions.push(Action{player: player.to_string(), street, play});
+        Ok(done)
+    }
+
+    pub fn finish(self) -> Option<HandHistory> {
+        self.hand
+    }
+}
+
+// Splits a row on the delimiter. Fields in double quotes may hold the
+// delimiter, with "" for a quote inside them.
+fn split(line: &str, delimiter: char) -> Vec<String> {
+    let mut fields = vec![String::new()];
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                fields.last_mut().unwrap().push('"');
+            },
+            '"' => quoted = !quoted,
+            c if c == delimiter && !quoted => fields.push(String::new()),
+            c => fields.last_mut().unwrap().push(c),
+        }
+    }
+    fields
+}
+
+fn quote(s: &str) -> String {
+    if s.contains(&[',', '"'][..]) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+// The crate's history files are an export in the default column layout
+// under a format header. Hole cards and stacks go on each player's first
+// action, and the board, table, pot and rake on the first row of the hand.
+pub fn write_csv<W: Write>(hands: &[HandHistory], w: &mut W) -> io::Result<()> {
+    Header::new(KIND, VERSION).field("hands", hands.len()).write(w)?;
+
+    let titles: Vec<&str> = FIELDS.iter().map(|f| f.name()).collect();
+    writeln!(w, "{}", titles.join(","))?;
+
+    for hand in hands {
+        let mut shown: Vec<&str> = vec![];
+        for (i, action) in hand.actions.iter().enumerate() {
+            let (mut hole, mut stack) = (String::new(), String::new());
+            if !shown.contains(&action.player.as_str()) {
+                if let Some(seat) = hand.seats.iter().find(|s| s.player == action.player) {
+                    hole = seat.hole.as_ref().map(|h| h.to_string()).unwrap_or_default();
+                    stack = text(seat.stack);
+                }
+            }
+            shown.push(&action.player);
+
+            let (mut board, mut table, mut pot, mut rake) = (vec![], "", String::new(), String::new());
+            if i == 0 {
+                board = hand.board.iter().map(|c| c.to_string()).collect();
+                table = hand.table.as_deref().unwrap_or("");
+                pot = text(hand.pot);
+                rake = text(hand.rake);
+            }
+
+            writeln!(
+                w,
+                "{},{},{},{},{},{},{},{},{},{},{}",
+                quote(&hand.id),
+                quote(&action.player),
+                action.street,
+                action.play.name(),
+                text(action.play.amount()),
+                hole,
+                board.join(" "),
+                quote(table),
+                stack,
+                pot,
+                rake,
+            )?;
+        }
+    }
+    Ok(())
+}
+
+fn text(amount: Option<f64>) -> String {
+    amount.map(|a| a.to_string()).unwrap_or_default()
+}
+
+pub fn read_csv<R: BufRead>(r: R) -> Result<Vec<HandHistory>, ImportError> {
+    let mut lines = r.lines().enumerate().map(|(i, l)| (i + 1, l));
+    let (_, first) = lines.next().ok_or(FormatError::MissingHeader)?;
+    let first = first?;
+    check_header(&first)?;
+
+    let hands = native().import_lines(lines)?;
+    // Histories may be appended to after the header is written, so only
+    // fewer hands than it counts is a sign of a cut short write.
+    if let Some(expected) = Header::parse(&first)?.get("hands").and_then(|h| h.parse().ok()) {
+        if hands.len() < expected { return Err(ImportError::Truncated{expected, found: hands.len()}); }
+    }
+    Ok(hands)
+}
+
+pub(crate) fn check_header(line: &str) -> Result<(), FormatError> {
+    Header::parse(line)?.check(KIND, VERSION)
+}
+
+// The columns of the crate's own files, which only hold cards it wrote.
+pub(crate) fn native() -> Columns {
+    Columns::default().cards(ParseOptions::default())
+}
+
+pub fn write_to<P: AsRef<Path>>(hands: &[HandHistory], path: P) -> io::Result<()> {
+    atomic::write(path, |w| write_csv(hands, w))
+}
+
+#[cfg(test)]
+mod history_tests {
+    use super::*;
+
+    const EXPORT: &str = "\
+Hand,Player,Street,Action,Amount,Hole Cards,Board
+\"1,001\",alice,Preflop,posts,$0.50,As Kd,
+\"1,001\",bob,Preflop,posts,$1,,
+\"1,001\",alice,Preflop,raises,3,,
+\"1,001\",bob,Preflop,calls,2,Qh Qc,
+\"1,001\",bob,Flop,checks,,,2c 7d 10h
+\"1,001\",alice,Flop,bets,4,,2c 7d Th
+\"1,001\",bob,Flop,folds,,,
+
+1002,bob,Preflop,posts,0.5,,
+1002,alice,Preflop,posts,1,,
+1002,bob,Preflop,folds,,,
+";
+
+    fn columns() -> Columns {
+        Columns::default().column(Field::Hole, "hole cards")
+    }
+
+    #[test]
+    fn test_import() {
+        let hands = columns().import(EXPORT.as_bytes()).unwrap();
+
+        assert_eq!(hands.len(), 2);
+        assert_eq!(hands[0].id, "1,001");
+        assert_eq!(hands[0].seats[0].player, "alice");
+        assert_eq!(hands[0].seats[0].hole, Some(Cards::hole("AS KD").unwrap()));
+        assert_eq!(hands[0].seats[1].hole, Some(Cards::hole("QH QC").unwrap()));
+        assert_eq!(hands[0].board, "2C 7D TH".parse().unwrap());
+        assert_eq!(hands[0].actions.len(), 7);
+        assert_eq!(hands[0].actions[2].play, Play::Raise(3.0));
+        assert_eq!(hands[0].actions[5].street, Street::Flop);
+        assert_eq!(hands[1].seats[0].player, "bob");
+        assert_eq!(hands[1].seats[0].hole, None);
+        assert_eq!(hands[1].actions[2].play, Play::Fold);
+    }
+
+    #[test]
+    fn test_errors() {
+        // Optional columns that are not found are left out.
+        let hands = Columns::default().import(EXPORT.as_bytes()).unwrap();
+        assert!(hands[0].seats.iter().all(|s| s.hole.is_none()));
+
+        assert!(matches!(
+            Columns::default().column(Field::Street, "round").import(EXPORT.as_bytes()),
+            Err(ImportError::MissingColumn(name)) if name == "round"
+        ));
+
+        let bad_cards = "hand,player,street,action,hole\n1,a,preflop,fold,As Zz\n";
+        assert!(matches!(
+            Columns::default().import(bad_cards.as_bytes()),
+            Err(ImportError::BadCards{line: 2, error: HandParseError::BadCard{index: 1, ..}})
+        ));
+
+        let no_amount = "hand,player,street,action\n1,a,preflop,fold\n1,b,preflop,calls\n";
+        assert!(matches!(
+            Columns::default().import(no_amount.as_bytes()),
+            Err(ImportError::Malformed{line: 3})
+        ));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let hands = columns().import(EXPORT.as_bytes()).unwrap();
+        let mut out = Vec::new();
+        write_csv(&hands, &mut out).unwrap();
+        let text = String::from_utf8(out).unwrap();
+
+        assert!(text.starts_with("# misc-poker history v2 hands=2\n"));
+        assert!(text.contains("\"1,001\",alice,preflop,post,0.5,AS KD,2C 7D TH,,,,\n"));
+        assert_eq!(read_csv(text.as_bytes()).unwrap(), hands);
+
+        let second = text.find("\n1002,").unwrap();
+        assert!(matches!(
+            read_csv(&text.as_bytes()[..second + 1]),
+            Err(ImportError::Truncated{expected: 2, found: 1})
+        ));
+
+        let newer = "# misc-poker history v3 hands=0\n";
+        assert!(matches!(
+            read_csv(newer.as_bytes()),
+            Err(ImportError::Format(FormatError::Unsupported{version: 3, ..}))
+        ));
+    }
+
+    #[test]
+    fn test_split() {
+        assert_eq!(split("a,\"b,c\",\"d\"\"e\"", ','), vec!["a", "b,c", "d\"e"]);
+        assert_eq!(split("a\t\tb", '\t'), vec!["a", "", "b"]);
+    }
+}
@@ -0,0 +1,198 @@
+//! Evaluation that follows a hand street by street. The hole cards are
+//! taken in once and each board card as it comes, and what the value is
+//! read off is kept up to date as it goes: a rank mask per suit and the
+//! ranks held once, twice and so on. Valuing the hand is then a few bit
+//! operations on those, not every five card pick of up to seven cards.
+//!
+//! The state is `Copy`, so loops over turns and rivers branch from the
+//! street before:
+//!
+//! ```ignore
+//! let flop = IncrementalEval::new(&hole).with_all(&board);
+//! for turn in deck {
+//!     let turned = flop.with(turn);
+//!     // ...
+//! }
+//! ```
+
+use super::{Card, Category, Rank};
+use super::value::HandValue;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct IncrementalEval {
+    // The ranks held in each suit, bit `Rank as usize`.
+    suits: [u16; 4],
+    held: [u8; 14],
+    // The ranks held exactly `n` times in `counts[n]`.
+    counts: [u16; 8],
+    len: u8,
+}
+
+impl IncrementalEval {
+    /// Starts from the hole cards, or from no cards at all.
+    pub fn new(hole: &[Card]) -> Self {
+        let mut eval = IncrementalEval{suits: [0; 4], held: [0; 14], counts: [0; 8], len: 0};
+        for &card in hole {
+            eval.add(card);
+        }
+        eval
+    }
+
+    /// Takes in one more card.
+    ///
+    /// # Panics
+    ///
+    /// Past seven cards.
+    pub fn add(&mut self, card: Card) {
+        assert!(self.len < 7, "at most seven cards");
+        let (r, bit) = (card.rank.index(), card.rank.bit());
+
+        self.counts[self.held[r] as usize] &= !bit;
+        self.held[r] += 1;
+        self.counts[self.held[r] as usize] |= bit;
+        self.suits[card.suit.index()] |= bit;
+        self.len += 1;
+    }
+
+    /// A copy with `card` added, leaving this one as it is.
+    pub fn with(&self, card: Card) -> Self {
+        let mut next = *self;
+        next.add(card);
+        next
+    }
+
+    pub fn with_all(&self, cards: &[Card]) -> Self {
+        let mut next = *self;
+        for &card in cards {
+            next.add(card);
+        }
+        next
+    }
+
+    pub fn len(&self) -> usize {
+        self.len as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The value of the best five card hand out of the cards so far, the
+    /// same as `best_hand` gives.
+    ///
+    /// # Panics
+    ///
+    /// With fewer than five cards.
+    pub fn value(&self) -> HandValue {
+        assert!(self.len >= 5, "at least five cards");
+
+        let at_least = |n: usize| self.counts[n..].iter().fold(0, |m, c| m | c);
+        let ranks = at_least(1);
+        let flush = self.suits.iter().copied().find(|s| s.count_ones() >= 5);
+
+        if let Some(r) = Rank::highest_in(at_least(5)) {
+            return HandValue::new(Category::FiveOfAKind, &[r]);
+        }
+        if let Some(top) = flush.and_then(straight_top) {
+            let category = if top == Rank::Ace { Category::RoyalFlush } else { Category::StraightFlush };
+            return HandValue::new(category, &[top]);
+        }
+        if let Some(quads) = Rank::highest_in(at_least(4)) {
+            return with_kickers(Category::FourOfAKind, &[quads], ranks, 1);
+        }
+        let trips = Rank::highest_in(at_least(3));
+        if let Some(t) = trips {
+            if let Some(pair) = Rank::highest_in(at_least(2) & !t.bit()) {
+                return HandValue::new(Category::FullHouse, &[t, pair]);
+            }
+        }
+        if let Some(suit) = flush {
+            return with_kickers(Category::Flush, &[], suit, 5);
+        }
+        if let Some(top) = straight_top(ranks) {
+            return HandValue::new(Category::Straight, &[top]);
+        }
+        if let Some(t) = trips {
+            return with_kickers(Category::ThreeOfAKind, &[t], ranks, 2);
+        }
+
+        let mut pairs = Rank::in_mask(at_least(2));
+        match (pairs.next(), pairs.next()) {
+            (Some(high), Some(low)) => with_kickers(Category::TwoPairs, &[high, low], ranks, 1),
+            (Some(pair), None) => with_kickers(Category::OnePair, &[pair], ranks, 3),
+            _ => with_kickers(Category::HighCard, &[], ranks, 5),
+        }
+    }
+}
+
+// The top of the highest five ranks in a row in `mask`, where an ace also
+// plays low as `Rank::One`.
+fn straight_top(mask: u16) -> Option<Rank> {
+    let mask = if mask & Rank::Ace.bit() != 0 { mask | Rank::One.bit() } else { mask };
+    (4..Rank::ALL.len()).rev()
+        .find(|&top| mask >> (top - 4) & 0b11111 == 0b11111)
+        .map(|top| Rank::ALL[top])
+}
+
+// `made`, then the highest `n` ranks of `mask` not among them.
+fn with_kickers(category: Category, made: &[Rank], mask: u16, n: usize) -> HandValue {
+    let rest = mask & !Rank::mask(made.iter().copied());
+    let mut ranks = made.to_vec();
+    ranks.extend(Rank::in_mask(rest).take(n));
+    HandValue::new(category, &ranks)
+}
+
+#[cfg(test)]
+mod incremental_tests {
+    use super::*;
+    use super::super::deal::best_hand;
+    use super::super::deck::Deck;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    fn cards(s: &str) -> Vec<Card> {
+        s.split_whitespace().map(|c| Card::from_code(c).unwrap()).collect()
+    }
+
+    #[test]
+    fn test_streets() {
+        let hole = IncrementalEval::new(&cards("AH KH"));
+        let flop = hole.with_all(&cards("QH 7C 2D"));
+        assert_eq!(flop.value().category(), Category::HighCard);
+
+        let turn = flop.with(cards("JH")[0]);
+        assert_eq!(turn.len(), 6);
+        assert_eq!(flop.len(), 5);
+
+        let river = turn.with(cards("TH")[0]);
+        assert_eq!(river.value(), HandValue::new(Category::RoyalFlush, &[Rank::Ace]));
+        let river = turn.with(cards("TC")[0]);
+        assert_eq!(river.value(), HandValue::new(Category::Straight, &[Rank::Ace]));
+
+        let wheel = IncrementalEval::new(&cards("AD 2H 3C 4S 5D KC KH"));
+        assert_eq!(wheel.value(), HandValue::new(Category::Straight, &[Rank::Five]));
+        let steel = IncrementalEval::new(&cards("AD 2D 3D 4D 5D 6C"));
+        assert_eq!(steel.value(), HandValue::new(Category::StraightFlush, &[Rank::Five]));
+
+        let full = IncrementalEval::new(&cards("9C 9D 9H 5S 5H 5C 2D"));
+        assert_eq!(full.value(), HandValue::new(Category::FullHouse, &[Rank::Nine, Rank::Five]));
+    }
+
+    #[test]
+    fn test_matches_best_hand() {
+        let mut rng = ChaCha8Rng::seed_from_u64(9);
+        for _ in 0..3000 {
+            let mut deck = Deck::new();
+            deck.shuffle(&mut rng);
+            let dealt: Vec<Card> = (0..7).map(|_| deck.deal().unwrap()).collect();
+
+            let mut eval = IncrementalEval::new(&dealt[..2]);
+            for n in 3..=7 {
+                eval.add(dealt[n - 1]);
+                if n >= 5 {
+                    assert_eq!(eval.value(), best_hand(&dealt[..n]).1, "{:?}", &dealt[..n]);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,390 @@
+//! Just enough JSON for the line protocols the binary speaks: a value type,
+//! a parser for one document and compact output.
+//!
+//! Input comes from untrusted clients, so arrays and objects may nest at
+//! most `MAX_DEPTH` deep; anything deeper is an error rather than a stack
+//! overflow.
+
+use std::fmt;
+
+pub const MAX_DEPTH: usize = 128;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    // Keys in document order.
+    Object(Vec<(String, Json)>),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct JsonError {
+    // Byte offset where parsing stopped.
+    pub offset: usize,
+}
+
+impl fmt::Display for JsonError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid JSON at byte {}", self.offset)
+    }
+}
+
+impl std::error::Error for JsonError {}
+
+impl Json {
+    pub fn parse(s: &str) -> Result<Json, JsonError> {
+        let mut p = Parser{s: s.as_bytes(), i: 0, depth: 0};
+        let value = p.value()?;
+        p.space();
+        if p.i != p.s.len() { return Err(p.error()); }
+        Ok(value)
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Json> {
+        match self {
+            Json::Object(fields) => fields.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Json::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Json::Number(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    pub fn object(fields: Vec<(&str, Json)>) -> Json {
+        Json::Object(fields.into_iter().map(|(k, v)| (k.to_string(), v)).collect())
+    }
+}
+
+impl From<&str> for Json {
+    fn from(s: &str) -> Self {
+        Json::String(s.to_string())
+    }
+}
+
+impl From<String> for Json {
+    fn from(s: String) -> Self {
+        Json::String(s)
+    }
+}
+
+impl From<f64> for Json {
+    fn from(n: f64) -> Self {
+        Json::Number(n)
+    }
+}
+
+impl From<bool> for Json {
+    fn from(b: bool) -> Self {
+        Json::Bool(b)
+    }
+}
+
+impl fmt::Display for Json {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Json::Null => write!(f, "null"),
+            Json::Bool(b) => write!(f, "{}", b),
+            Json::Number(n) if n.is_finite() => write!(f, "{}", n),
+            Json::Number(_) => write!(f, "null"),
+            Json::String(s) => write_string(f, s),
+            Json::Array(items) => {
+                write!(f, "[")?;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 { write!(f, ",")?; }
+                    write!(f, "{}", item)?;
+                }
+                write!(f, "]")
+            },
+            Json::Object(fields) => {
+                write!(f, "{{")?;
+                for (i, (k, v)) in fields.iter().enumerate() {
+                    if i > 0 { write!(f, ",")?; }
+                    write_string(f, k)?;
+                    write!(f, ":{}", v)?;
+                }
+                write!(f, "}}")
+            },
+        }
+    }
+}
+
+fn write_string(f: &mut fmt::Formatter, s: &str) -> fmt::Result {
+    write!(f, "\"")?;
+    for c in s.chars() {
+        match c {
+            '"' => write!(f, "\\\"")?,
+            '\\' => write!(f, "\\\\")?,
+            '\n' => write!(f, "\\n")?,
+            '\r' => write!(f, "\\r")?,
+            '\t' => write!(f, "\\t")?,
+            c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+            c => write!(f, "{}", c)?,
+        }
+    }
+    write!(f, "\"")
+}
+
+struct Parser<'a> {
+    s: &'a [u8],
+    i: usize,
+    // Arrays and objects open around the current position.
+    depth: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn error(&self) -> JsonError {
+        JsonError{offset: self.i}
+    }
+
+    fn space(&mut self) {
+        while self.i < self.s.len() && b" \t\r\n".contains(&self.s[self.i]) { self.i += 1; }
+    }
+
+    fn eat(&mut self, b: u8) -> Result<(), JsonError> {
+        self.space();
+        if self.s.get(self.i) != Some(&b) { return Err(self.error()); }
+        self.i += 1;
+        Ok(())
+    }
+
+    fn literal(&mut self, word: &str, value: Json) -> Result<Json, JsonError> {
+        if !self.s[self.i..].starts_with(word.as_bytes()) { return Err(self.error()); }
+        self.i += word.len();
+        Ok(value)
+    }
+
+    fn value(&mut self) -> Result<Json, JsonError> {
+        self.space();
+        match self.s.get(self.i) {
+            Some(b'n') => self.literal("null", Json::Null),
+            Some(b't') => self.literal("true", Json::Bool(true)),
+            Some(b'f') => self.literal("false", Json::Bool(false)),
+            Some(b'"') => Ok(Json::String(self.string()?)),
+            Some(b'[') | Some(b'{') => {
+                if self.depth == MAX_DEPTH { return Err(self.error()); }
+                self.depth += 1;
+                let value = if self.s[self.i] == b'[' { self.array() } else { self.object() };
+                self.depth -= 1;
+                value
+            },
+            Some(b'-') | Some(b'0'..=b'9') => self.number(),
+            _ => Err(self.error()),
+        }
+    }
+
+    fn array(&mut self) -> Result<Json, JsonError> {
+        self.i += 1;
+        let mut items = vec![];
+        self.space();
+        if self.s.get(self.i) == Some(&b']') {
+            self.i += 1;
+            return Ok(Json::Array(items));
+        }
+        loop {
+            items.push(self.value()?);
+            self.space();
+            match self.s.get(self.i) {
+                Some(b',') => self.i += 1,
+                Some(b']') => { self.i += 1; return Ok(Json::Array(items)); },
+                _ => return Err(self.error()),
+            }
+        }
+    }
+
+    fn object(&mut self) -> Result<Json, JsonError> {
+        self.i += 1;
+        let mut fields = vec![];
+        self.space();
+        if self.s.get(self.i) == Some(&b'}') {
+            self.i += 1;
+            return Ok(Json::Object(fields));
+        }
+        loop {
+            self.space();
+            let key = self.string()?;
+            self.eat(b':')?;
+            fields.push((key, self.value()?));
+            self.space();
+            match self.s.get(self.i) {
+                Some(b',') => self.i += 1,
+                Some(b'}') => { self.i += 1; return Ok(Json::Object(fields)); },
+                _ => return Err(self.error()),
+            }
+        }
+    }
+
+    // Skips ASCII digits and says how many there were.
+    fn digits(&mut self) -> usize {
+        let start = self.i;
+        while self.s.get(self.i).is_some_and(u8::is_ascii_digit) { self.i += 1; }
+        self.i - start
+    }
+
+    // The JSON grammar exactly: no leading `+`, no leading zeros, and digits
+    // on both sides of the point.
+    fn number(&mut self) -> Result<Json, JsonError> {
+        let start = self.i;
+        if self.s.get(self.i) == Some(&b'-') { self.i += 1; }
+        match self.s.get(self.i) {
+            Some(b'0') => self.i += 1,
+            Some(b'1'..=b'9') => { self.digits(); },
+            _ => return Err(self.error()),
+        }
+        if self.s.get(self.i) == Some(&b'.') {
+            self.i += 1;
+            if self.digits() == 0 { return Err(self.error()); }
+        }
+        if matches!(self.s.get(self.i), Some(b'e') | Some(b'E')) {
+            self.i += 1;
+            if matches!(self.s.get(self.i), Some(b'+') | Some(b'-')) { self.i += 1; }
+            if self.digits() == 0 { return Err(self.error()); }
+        }
+
+        std::str::from_utf8(&self.s[start..self.i]).ok()
+            .and_then(|n| n.parse().ok())
+            .map(Json::Number)
+            .ok_or(JsonError{offset: start})
+    }
+
+    fn string(&mut self) -> Result<String, JsonError> {
+        if self.s.get(self.i) != Some(&b'"') { return Err(self.error()); }
+        self.i += 1;
+
+        let mut out: Vec<u8> = vec![];
+        loop {
+            let b = *self.s.get(self.i).ok_or_else(|| self.error())?;
+            self.i += 1;
+            match b {
+                b'"' => break,
+                b'\\' => {
+                    let e = *self.s.get(self.i).ok_or_else(|| self.error())?;
+                    self.i += 1;
+                    let c = match e {
+                        b'"' => '"',
+                        b'\\' => '\\',
+                        b'/' => '/',
+                        b'b' => '\u{8}',
+                        b'f' => '\u{c}',
+                        b'n' => '\n',
+                        b'r' => '\r',
+                        b't' => '\t',
+                        b'u' => self.escape()?,
+                        _ => return Err(self.error()),
+                    };
+                    let mut buf = [0; 4];
+                    out.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+                },
+                b => out.push(b),
+            }
+        }
+        String::from_utf8(out).map_err(|_| self.error())
+    }
+
+    // The rest of a `\u` escape. A high surrogate must be followed by an
+    // escaped low one and the two make one character; a lone half is an error.
+    fn escape(&mut self) -> Result<char, JsonError> {
+        let start = self.i - 2;
+        let high = self.hex4()?;
+        let code = match high {
+            0xD800..=0xDBFF => {
+                if self.s.get(self.i..self.i + 2) != Some(&b"\\u"[..]) { return Err(JsonError{offset: start}); }
+                self.i += 2;
+                let low = self.hex4()?;
+                if !(0xDC00..=0xDFFF).contains(&low) { return Err(JsonError{offset: start}); }
+                0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)
+            },
+            0xDC00..=0xDFFF => return Err(JsonError{offset: start}),
+            code => code,
+        };
+        std::char::from_u32(code).ok_or(JsonError{offset: start})
+    }
+
+    fn hex4(&mut self) -> Result<u32, JsonError> {
+        let hex = self.s.get(self.i..self.i + 4).ok_or_else(|| self.error())?;
+        let code = std::str::from_utf8(hex).ok()
+            .filter(|h| h.bytes().all(|b| b.is_ascii_hexdigit()))
+            .and_then(|h| u32::from_str_radix(h, 16).ok())
+            .ok_or_else(|| self.error())?;
+        self.i += 4;
+        Ok(code)
+    }
+}
+
+#[cfg(test)]
+mod json_tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let text = r#"{"id":1,"params":{"cards":"AS KS","ok":true,"list":[1.5,null,"a\"b"]}}"#;
+        let value = Json::parse(text).unwrap();
+
+        assert_eq!(value.get("id"), Some(&Json::Number(1.0)));
+        assert_eq!(value.get("params").and_then(|p| p.get("cards")).and_then(Json::as_str), Some("AS KS"));
+        assert_eq!(value.to_string(), text);
+    }
+
+    #[test]
+    fn test_whitespace_and_escapes() {
+        let value = Json::parse(" [ \"\\u00e9\\n\" , -2e1 , { } ] ").unwrap();
+
+        assert_eq!(value, Json::Array(vec![
+            Json::String("é\n".to_string()),
+            Json::Number(-20.0),
+            Json::Object(vec![]),
+        ]));
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(Json::parse("{\"a\":}"), Err(JsonError{offset: 5}));
+        assert!(Json::parse("[1,2").is_err());
+        assert!(Json::parse("1 2").is_err());
+        assert!(Json::parse("\"open").is_err());
+    }
+
+    #[test]
+    fn test_depth() {
+        let nested = |n: usize| format!("{}{}", "[".repeat(n), "]".repeat(n));
+
+        assert!(Json::parse(&nested(MAX_DEPTH)).is_ok());
+        assert_eq!(Json::parse(&nested(MAX_DEPTH + 1)), Err(JsonError{offset: MAX_DEPTH}));
+        assert!(Json::parse(&"[".repeat(1_000_000)).is_err());
+        assert!(Json::parse(&"{\"a\":".repeat(1_000_000)).is_err());
+    }
+
+    #[test]
+    fn test_surrogates() {
+        assert_eq!(Json::parse(r#""\uD83C\uDCA1""#), Ok(Json::from("🂡")));
+        assert_eq!(Json::parse(r#""\ud83c\udca1x""#), Ok(Json::from("🂡x")));
+        assert_eq!(Json::parse(r#""\uD83C""#), Err(JsonError{offset: 1}));
+        assert_eq!(Json::parse(r#""\uD83Cx""#), Err(JsonError{offset: 1}));
+        assert_eq!(Json::parse(r#""\uD83C\u0041""#), Err(JsonError{offset: 1}));
+        assert_eq!(Json::parse(r#""a\uDCA1""#), Err(JsonError{offset: 2}));
+        assert!(Json::parse(r#""\u+123""#).is_err());
+    }
+
+    #[test]
+    fn test_numbers() {
+        for good in ["0", "-0", "12", "-1.5", "0.25", "1e3", "1E+3", "2.5e-1"] {
+            assert!(Json::parse(good).is_ok(), "{}", good);
+        }
+        for bad in ["+1", "01", "-01", "00", ".5", "1.", "1e", "1e+", "-", "--1", "1.2.3"] {
+            assert!(Json::parse(bad).is_err(), "{}", bad);
+        }
+    }
+}
@@ -0,0 +1,219 @@
+//! Kuhn poker, solved with CFR.
+//!
+//! Three cards (J, Q, K), one each, antes of one and a single bet of one.
+//! Histories are strings of `p` (pass: check or fold) and `b` (bet or
+//! call); an information set is the player's card followed by the history,
+//! such as "Qpb". The game value for the first player is -1/18.
+
+use std::collections::BTreeMap;
+
+use super::strategy::{StrategyTable, Trace};
+
+const CARDS: [char; 3] = ['J', 'Q', 'K'];
+const ACTIONS: [char; 2] = ['p', 'b'];
+const DEALS: [[usize; 2]; 6] = [[0, 1], [0, 2], [1, 0], [1, 2], [2, 0], [2, 1]];
+
+// Where each player acts, so their information sets can be listed.
+const DECISIONS: [&[&str]; 2] = [&["", "pb"], &["p", "b"]];
+
+#[derive(Clone, Debug, Default)]
+pub struct Kuhn {
+    regrets: BTreeMap<String, [f64; 2]>,
+    strategy_sum: BTreeMap<String, [f64; 2]>,
+    iterations: usize,
+}
+
+impl Kuhn {
+    pub fn new() -> Self {
+        Kuhn::default()
+    }
+
+    pub fn iterations(&self) -> usize {
+        self.iterations
+    }
+
+    /// Runs `iterations` more rounds of CFR. Every `trace_every` iterations
+    /// the exploitability of the average strategy is recorded; zero turns
+    /// tracing off.
+    pub fn train(&mut self, iterations: usize, trace_every: usize) -> Trace {
+        let mut trace = Trace::default();
+
+        for _ in 0..iterations {
+            for deal in DEALS.iter() {
+                self.cfr(deal, "", 1.0, 1.0);
+            }
+            self.iterations += 1;
+
+            if trace_every > 0 && self.iterations % trace_every == 0 {
+                trace.push(self.iterations, exploitability(&self.average()));
+            }
+        }
+        trace
+    }
+
+    pub fn average(&self) -> StrategyTable {
+        let rows = info_sets().into_iter().map(|key| {
+            let probs = match self.strategy_sum.get(&key) {
+                Some(sum) if sum[0] + sum[1] > 0.0 => {
+                    let total = sum[0] + sum[1];
+                    vec![sum[0] / total, sum[1] / total]
+                },
+                _ => vec![0.5, 0.5],
+            };
+            (key, probs)
+        }).collect();
+
+        StrategyTable{
+            game: "kuhn".to_string(),
+            actions: vec!["pass".to_string(), "bet".to_string()],
+            rows,
+        }
+    }
+
+    // Returns the first player's expected payoff from `history` on.
+    fn cfr(&mut self, deal: &[usize; 2], history: &str, reach0: f64, reach1: f64) -> f64 {
+        if let Some(payoff) = payoff(deal, history) {
+            return payoff;
+        }
+
+        let player = history.len() % 2;
+        let key = format!("{}{}", CARDS[deal[player]], history);
+        let strategy = regret_matching(self.regrets.get(&key));
+
+        let mut util = [0.0; 2];
+        for (a, action) in ACTIONS.iter().enumerate() {
+            let next = format!("{}{}", history, action);
+            util[a] = if player == 0 {
+                self.cfr(deal, &next, reach0 * strategy[a], reach1)
+            } else {
+                self.cfr(deal, &next, reach0, reach1 * strategy[a])
+            };
+        }
+        let node = strategy[0] * util[0] + strategy[1] * util[1];
+
+        let (own, other, sign) = if player == 0 { (reach0, reach1, 1.0) } else { (reach1, reach0, -1.0) };
+        let regrets = self.regrets.entry(key.clone()).or_insert([0.0; 2]);
+        let sums = self.strategy_sum.entry(key).or_insert([0.0; 2]);
+        for a in 0..2 {
+            regrets[a] += other * sign * (util[a] - node);
+            sums[a] += own * strategy[a];
+        }
+        node
+    }
+}
+
+fn regret_matching(regrets: Option<&[f64; 2]>) -> [f64; 2] {
+    let r = regrets.cloned().unwrap_or([0.0; 2]);
+    let (a, b) = (r[0].max(0.0), r[1].max(0.0));
+    if a + b > 0.0 { [a / (a + b), b / (a + b)] } else { [0.5, 0.5] }
+}
+
+fn info_sets() -> Vec<String> {
+    let mut keys = vec![];
+    for decisions in DECISIONS.iter() {
+        for card in CARDS.iter() {
+            for history in decisions.iter() {
+                keys.push(format!("{}{}", card, history));
+            }
+        }
+    }
+    keys
+}
+
+// The first player's payoff at a terminal history, or None mid-hand.
+fn payoff(deal: &[usize; 2], history: &str) -> Option<f64> {
+    let showdown = if deal[0] > deal[1] { 1.0 } else { -1.0 };
+    match history {
+        "pp" => Some(showdown),
+        "bb" | "pbb" => Some(2.0 * showdown),
+        "bp" => Some(1.0),
+        "pbp" => Some(-1.0),
+        _ => None,
+    }
+}
+
+fn expected(policy: &dyn Fn(&str) -> [f64; 2]) -> f64 {
+    fn walk(deal: &[usize; 2], history: &str, policy: &dyn Fn(&str) -> [f64; 2]) -> f64 {
+        if let Some(p) = payoff(deal, history) { return p; }
+
+        let player = history.len() % 2;
+        let probs = policy(&format!("{}{}", CARDS[deal[player]], history));
+        ACTIONS.iter().zip(probs.iter())
+            .map(|(a, p)| p * walk(deal, &format!("{}{}", history, a), policy))
+            .sum()
+    }
+
+    DEALS.iter().map(|d| walk(d, "", policy)).sum::<f64>() / DEALS.len() as f64
+}
+
+/// The first player's expected payoff when both follow `table`.
+pub fn value(table: &StrategyTable) -> f64 {
+    expected(&|key| lookup(table, key))
+}
+
+/// Average gain of a best response against each side of `table`. Zero
+/// exactly at equilibrium.
+pub fn exploitability(table: &StrategyTable) -> f64 {
+    let mut total = 0.0;
+
+    for (player, decisions) in DECISIONS.iter().enumerate() {
+        let own: Vec<String> = CARDS.iter()
+            .flat_map(|c| decisions.iter().map(move |h| format!("{}{}", c, h)))
+            .collect();
+
+        // Six information sets each, so every pure strategy can be tried.
+        let best = (0..1u32 << own.len()).map(|mask| {
+            let v = expected(&|key| match own.iter().position(|k| k == key) {
+                Some(i) if mask & (1 << i) != 0 => [0.0, 1.0],
+                Some(_) => [1.0, 0.0],
+                None => lookup(table, key),
+            });
+            if player == 0 { v } else { -v }
+        }).fold(f64::MIN, f64::max);
+
+        total += best;
+    }
+    total / 2.0
+}
+
+fn lookup(table: &StrategyTable, key: &str) -> [f64; 2] {
+    table.get(key).map(|p| [p[0], p[1]]).unwrap_or([0.5, 0.5])
+}
+
+#[cfg(test)]
+mod kuhn_tests {
+    use super::*;
+
+    #[test]
+    fn test_converges() {
+        let mut kuhn = Kuhn::new();
+        let trace = kuhn.train(5000, 1000);
+        let table = kuhn.average();
+
+        assert_eq!(trace.points.len(), 5);
+        assert!(trace.points[4].1 < trace.points[0].1);
+        assert!(exploitability(&table) < 0.01);
+        assert!((value(&table) + 1.0 / 18.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_equilibrium_lines() {
+        let mut kuhn = Kuhn::new();
+        kuhn.train(5000, 0);
+        let table = kuhn.average();
+
+        // The second player calls a bet with the king and folds the jack.
+        assert!(table.get("Kb").unwrap()[1] > 0.99);
+        assert!(table.get("Jb").unwrap()[0] > 0.99);
+        // After a check, the king always bets.
+        assert!(table.get("Kp").unwrap()[1] > 0.99);
+        assert_eq!(table.rows.len(), 12);
+    }
+
+    #[test]
+    fn test_uniform_is_exploitable() {
+        let table = Kuhn::new().average();
+
+        assert!(exploitability(&table) > 0.1);
+    }
+}
@@ -0,0 +1,301 @@
+//! A table driven five card evaluator after Cactus Kev, for enumerating
+//! boards by the million where building a `Hand` for each is too slow.
+//!
+//! Every card is packed into a `u32`:
+//!
+//! ```text
+//! xxxbbbbb bbbbbbbb cdhsrrrr xxpppppp
+//! ```
+//!
+//! with one `b` bit for its rank, one `cdhs` bit for its suit, the rank
+//! number in `r` and the rank's prime in `p`. Five cards of one suit are
+//! looked up by the OR of their rank bits, as are five different ranks in
+//! mixed suits. Every other hand has a pair or better, and is found from the
+//! product of its primes, which is the same for any order of the cards,
+//! through a perfect hash built with the tables.
+//!
+//! A lookup gives the hand's class, one of the 7462 distinct values a hand
+//! from one deck can take, numbered as `HandValue::class_index` numbers
+//! them: 1 for a royal flush down to 7462 for 7-5-4-3-2, so the stronger
+//! hand has the smaller class. Hands no deck can
+//! deal, with the low ace `Rank::One` or the same card twice, are not in the
+//! tables and `evaluate` falls back to `Hand::value` for them.
+
+use std::sync::OnceLock;
+
+use super::{Card, Hand, Rank, Suit};
+use super::value::HandValue;
+
+const PRIMES: [u32; 13] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41];
+
+pub(crate) const EMPTY: u16 = u16::MAX;
+// Slots and buckets of the perfect hash; 4888 products go in the slots.
+pub(crate) const SLOTS: usize = 8192;
+const BUCKETS: usize = 1 << 11;
+pub(crate) const MULTIPLIER: u64 = 0x9E37_79B9_7F4A_7C15;
+
+pub(crate) struct Tables {
+    // Class values, strongest first, so class c is at c - 1.
+    pub(crate) values: Vec<HandValue>,
+    // Indexed by the OR of the rank bits.
+    pub(crate) flushes: Vec<u16>,
+    pub(crate) unique: Vec<u16>,
+    // Per bucket displacement, then the product and class in each slot.
+    pub(crate) displace: Vec<u16>,
+    pub(crate) keys: Vec<u32>,
+    pub(crate) classes: Vec<u16>,
+}
+
+static TABLES: OnceLock<Tables> = OnceLock::new();
+
+/// The packed form of a card, `None` for `Rank::One`.
+pub fn code(card: &Card) -> Option<u32> {
+    let r = (card.rank as u32).checked_sub(Rank::Two as u32)?;
+    let suit = match card.suit {
+        Suit::Clubs    => 0x8000,
+        Suit::Diamonds => 0x4000,
+        Suit::Hearts   => 0x2000,
+        Suit::Spades   => 0x1000,
+    };
+
+    Some(pack(r, suit))
+}
+
+// Rank 0 for a two up to 12 for an ace, and the suit bit, or 0 for a card
+// that cannot be part of a flush.
+pub(crate) fn pack(r: u32, suit: u32) -> u32 {
+    1 << (16 + r) | suit | r << 8 | PRIMES[r as usize]
+}
+
+/// The class of five packed cards, from 1 for a royal flush to 7462 for
+/// the weakest hand, or `None` if no deck could deal them.
+pub fn class(codes: [u32; 5]) -> Option<u16> {
+    Lookup::get().class(codes)
+}
+
+/// A handle on the tables, built on first use. Holding one saves the check
+/// for whether they are built on every call, which counts in tight loops.
+#[derive(Clone, Copy)]
+pub struct Lookup(&'static Tables);
+
+impl Lookup {
+    pub fn get() -> Self {
+        Lookup(TABLES.get_or_init(build))
+    }
+
+    pub(crate) fn tables(&self) -> &'static Tables {
+        self.0
+    }
+
+    #[inline]
+    pub fn class(&self, codes: [u32; 5]) -> Option<u16> {
+        let t = self.0;
+        let [a, b, c, d, e] = codes;
+        let bits = ((a | b | c | d | e) >> 16) as usize & 0x1FFF;
+
+        let class = if a & b & c & d & e & 0xF000 != 0 {
+            t.flushes[bits]
+        } else if t.unique[bits] != EMPTY {
+            t.unique[bits]
+        } else {
+            let product = (a & 0xFF) * (b & 0xFF) * (c & 0xFF) * (d & 0xFF) * (e & 0xFF);
+            let (bucket, h) = hash(product);
+            let slot = (h ^ t.displace[bucket] as usize) & (SLOTS - 1);
+            if t.keys[slot] == product { t.classes[slot] } else { EMPTY }
+        };
+
+        if class == EMPTY { None } else { Some(class) }
+    }
+}
+
+/// The best, which is the smallest, class of any five of `codes`, five or
+/// more packed cards, with every way to pick five tried.
+///
+/// # Panics
+///
+/// If there are fewer than five codes or no five could come from one deck.
+pub fn best_class(codes: &[u32]) -> u16 {
+    let lookup = Lookup::get();
+    let n = codes.len();
+    assert!(n >= 5, "at least five cards");
+
+    let mut best = u16::MAX;
+    let mut picked = [0, 1, 2, 3, 4];
+    loop {
+        let class = lookup.class(picked.map(|i| codes[i])).expect("cards from one deck");
+        best = best.min(class);
+
+        let i = match (0..5).rev().find(|&i| picked[i] < n - 5 + i) {
+            Some(i) => i,
+            None => return best,
+        };
+        picked[i] += 1;
+        for j in i + 1..5 { picked[j] = picked[j - 1] + 1; }
+    }
+}
+
+/// The value of a class, as `Hand::value` gives it.
+///
+/// # Panics
+///
+/// If the class is outside 1..=7462.
+pub fn value(class: u16) -> HandValue {
+    TABLES.get_or_init(build).values[class as usize - 1]
+}
+
+/// The same value `Hand::new(*cards).value()` gives, by table lookup.
+pub fn evaluate(cards: &[Card; 5]) -> HandValue {
+    let codes = [code(&cards[0]), code(&cards[1]), code(&cards[2]), code(&cards[3]), code(&cards[4])];
+
+    match codes {
+        [Some(a), Some(b), Some(c), Some(d), Some(e)] => match class([a, b, c, d, e]) {
+            Some(class) => value(class),
+            None => Hand::new(*cards).value(),
+        },
+        _ => Hand::new(*cards).value(),
+    }
+}
+
+// One multiply gives both the bucket, from the top bits, and the slot
+// before displacement, from the bits below.
+fn hash(product: u32) -> (usize, usize) {
+    let h = (product as u64).wrapping_mul(MULTIPLIER);
+    ((h >> 53) as usize, (h >> 32) as usize)
+}
+
+fn build() -> Tables {
+    let deck = &Rank::DECK;
+    let mut found: Vec<(HandValue, Vec<usize>, bool)> = vec![];
+
+    // Every multiset of five deck ranks, no rank more than four times, in
+    // mixed suits and, for five different ranks, suited.
+    let mut pick = [0usize; 5];
+    loop {
+        if (0..13).all(|i| pick.iter().filter(|&&p| p == i).count() <= 4) {
+            let mixed: Vec<Card> = pick.iter().enumerate()
+                .map(|(i, &r)| Card{rank: deck[r], suit: Suit::ALL[i % 4]})
+                .collect();
+            let hand = |cards: &[Card]| Hand::new([cards[0], cards[1], cards[2], cards[3], cards[4]]).value();
+
+            found.push((hand(&mixed), pick.to_vec(), false));
+            if pick.windows(2).all(|w| w[0] != w[1]) {
+                let suited: Vec<Card> = mixed.iter().map(|c| Card{rank: c.rank, suit: Suit::Hearts}).collect();
+                found.push((hand(&suited), pick.to_vec(), true));
+            }
+        }
+
+        match (0..5).rev().find(|&i| pick[i] < 12) {
+            Some(i) => {
+                pick[i] += 1;
+                for j in i + 1..5 { pick[j] = pick[i]; }
+            },
+            None => break,
+        }
+    }
+
+    let mut values: Vec<HandValue> = found.iter().map(|(v, _, _)| *v).collect();
+    values.sort_by(|a, b| b.cmp(a));
+    values.dedup();
+
+    let mut flushes = vec![EMPTY; 1 << 13];
+    let mut unique = vec![EMPTY; 1 << 13];
+    let mut paired: Vec<(u32, u16)> = vec![];
+    for (v, ranks, suited) in &found {
+        let class = values.binary_search_by(|x| v.cmp(x)).unwrap() as u16 + 1;
+        let bits: usize = ranks.iter().map(|&r| 1 << r).sum();
+
+        if *suited {
+            flushes[bits] = class;
+        } else if ranks.windows(2).all(|w| w[0] != w[1]) {
+            unique[bits] = class;
+        } else {
+            paired.push((ranks.iter().map(|&r| PRIMES[r]).product(), class));
+        }
+    }
+
+    // Hash and displace: the fullest buckets first, each with the first
+    // displacement that puts all its products in free slots.
+    let mut buckets: Vec<Vec<(u32, u16)>> = vec![vec![]; BUCKETS];
+    for &(product, class) in &paired {
+        buckets[hash(product).0].push((product, class));
+    }
+    let mut order: Vec<usize> = (0..BUCKETS).collect();
+    order.sort_by_key(|&b| std::cmp::Reverse(buckets[b].len()));
+
+    let mut displace = vec![0; BUCKETS];
+    let mut keys = vec![0; SLOTS];
+    let mut classes = vec![EMPTY; SLOTS];
+    for b in order {
+        if buckets[b].is_empty() { break; }
+
+        let slots = |d: u16| buckets[b].iter().map(move |&(p, _)| (hash(p).1 ^ d as usize) & (SLOTS - 1));
+        let d = (0..SLOTS as u16).find(|&d| {
+            let mut taken: Vec<usize> = slots(d).collect();
+            taken.sort();
+            taken.dedup();
+            taken.len() == buckets[b].len() && taken.iter().all(|&s| classes[s] == EMPTY)
+        }).expect("products of one bucket share a slot");
+
+        displace[b] = d;
+        for (s, &(product, class)) in slots(d).zip(&buckets[b]) {
+            keys[s] = product;
+            classes[s] = class;
+        }
+    }
+
+    Tables{values, flushes, unique, displace, keys, classes}
+}
+
+#[cfg(test)]
+mod lookup_tests {
+    use super::*;
+    use super::super::deck::Deck;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    fn hand(s: &str) -> Hand {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_classes() {
+        let t = TABLES.get_or_init(build);
+        assert_eq!(t.values.len(), 7462);
+
+        let class = |s: &str| {
+            let h = hand(s);
+            class(h.cards().map(|c| code(&c).unwrap()))
+        };
+        assert_eq!(class("AS KS QS JS TS"), Some(1));
+        assert_eq!(class("7D 5S 4D 3S 2C"), Some(7462));
+        // Under the royal flush and the straight flushes five to king high.
+        assert_eq!(class("AH AD AC AS KS"), Some(11));
+        assert_eq!(class("5D 4D 3D 2D AD"), Some(10));
+        assert_eq!(class("5S 4D 3D 2C AH"), Some(class("6S 5D 4D 3C 2H").unwrap() + 1));
+        for s in ["AS KS QS JS TS", "9C 9D 8S 8H 2C", "7D 5S 4D 3S 2C"] {
+            assert_eq!(class(s), hand(s).value().class_index());
+        }
+    }
+
+    #[test]
+    fn test_matches_hand() {
+        let mut rng = ChaCha8Rng::seed_from_u64(7);
+        for _ in 0..20_000 {
+            let mut deck = Deck::new();
+            deck.shuffle(&mut rng);
+            let h = deck.deal_hand().unwrap();
+            assert_eq!(evaluate(h.cards()), h.value(), "{}", h);
+        }
+    }
+
+    #[test]
+    fn test_outside_the_deck() {
+        let options = super::super::parse::ParseOptions::default().allow_duplicates(true);
+
+        for s in ["AS AS AS AS AS", "AS AS KS QS JS", "1H 2D 3C 4S 5H", "9C 9C 9C 2D 2D"] {
+            let h = options.parse_hand(s).unwrap();
+            assert_eq!(evaluate(h.cards()), h.value(), "{}", s);
+        }
+        assert_eq!(code(&Card{rank: Rank::One, suit: Suit::Hearts}), None);
+    }
+}
@@ -0,0 +1,224 @@
+//! The preflop equity of every starting hand against every other, a 169
+//! by 169 table. Each cell plays all the combos of one hand against all
+//! the combos of the other that share no card with them, with runouts
+//! sampled as in `equity::range_equity`, and holds the first hand's share
+//! of the pot. A hand against itself is an even split.
+//!
+//! Filling the table takes minutes, so like the all in table it is kept on
+//! disk by `load_or_generate`. With the `matchups` feature a table of
+//! 100,000 samples a cell is built into the crate instead, and
+//! `preflop_equity` reads it with no file and no work beyond parsing the
+//! two hands.
+
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::Path;
+
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+use super::Board;
+use super::atomic;
+use super::equity::{range_equity, EquityMode};
+use super::format::Header;
+use super::preflop::PreflopMatrix;
+use super::range::{Combo, Range};
+use super::starting::StartingHand;
+
+const KIND: &str = "matchups";
+const VERSION: u32 = 1;
+
+// Equities are kept to four places, finer than sampling can give, so a
+// table reads back from its file exactly.
+const PLACES: f64 = 10_000.0;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MatchupConfig {
+    pub samples: usize,
+    pub seed: u64,
+}
+
+/// The first hand's equity against the second, from 0 to 1, for every
+/// pair of starting hands.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MatchupTable {
+    pub config: MatchupConfig,
+    // By grid cell of the first hand, then of the second.
+    equity: Vec<f64>,
+}
+
+impl Default for MatchupConfig {
+    fn default() -> Self {
+        MatchupConfig{samples: 10_000, seed: 0}
+    }
+}
+
+// Where a hand's row or column is, by its cell in the 13 by 13 grid.
+fn cell(hand: StartingHand) -> usize {
+    let (row, col) = PreflopMatrix::<()>::cell_of(hand);
+    row * 13 + col
+}
+
+fn round(e: f64) -> f64 {
+    (e * PLACES).round() / PLACES
+}
+
+impl MatchupTable {
+    pub fn generate(config: MatchupConfig) -> Self {
+        let all = StartingHand::all();
+        let ranges: Vec<Range> = all.iter()
+            .map(|h| Range::from_combos(h.combos().into_iter().map(Combo::new).collect()))
+            .collect();
+        let mut equity = vec![0.5; 169 * 169];
+
+        for (i, &a) in all.iter().enumerate() {
+            for (j, &b) in all.iter().enumerate().skip(i + 1) {
+                // Each cell has its own stream, so cells can be regenerated alone.
+                let mut rng = ChaCha8Rng::seed_from_u64(config.seed);
+                rng.set_stream((i * 169 + j) as u64);
+                let mode = EquityMode::MonteCarlo{trials: config.samples};
+                let e = range_equity(&ranges[i], &ranges[j], &Board::new(), &[], mode, &mut rng)
+                    .expect("any two starting hands can be dealt together");
+
+                let e = round(e.a / 100.0);
+                equity[cell(a) * 169 + cell(b)] = e;
+                equity[cell(b) * 169 + cell(a)] = round(1.0 - e);
+            }
+        }
+
+        MatchupTable{config, equity}
+    }
+
+    /// `a`'s share of the pot all in before the flop against `b`.
+    pub fn equity(&self, a: StartingHand, b: StartingHand) -> f64 {
+        self.equity[cell(a) * 169 + cell(b)]
+    }
+
+    pub fn write<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        Header::new(KIND, VERSION)
+            .field("samples", self.config.samples)
+            .field("seed", self.config.seed)
+            .write(w)?;
+
+        let all = StartingHand::all();
+        write!(w, "hand")?;
+        for b in &all { write!(w, ",{}", b)?; }
+        writeln!(w)?;
+
+        for &a in &all {
+            write!(w, "{}", a)?;
+            for &b in &all { write!(w, ",{}", self.equity(a, b))?; }
+            writeln!(w)?;
+        }
+        Ok(())
+    }
+
+    pub fn read<R: BufRead>(r: R) -> io::Result<Self> {
+        let invalid = |e: String| io::Error::new(io::ErrorKind::InvalidData, e);
+
+        let mut lines = r.lines();
+        let first = lines.next().ok_or_else(|| invalid("empty file".to_string()))??;
+        let header = Header::parse(&first).map_err(|e| invalid(e.to_string()))?;
+        header.check(KIND, VERSION).map_err(|e| invalid(e.to_string()))?;
+
+        let field = |key: &str| header.get(key).and_then(|v| v.parse().ok())
+            .ok_or_else(|| invalid(format!("missing {}", key)));
+        let config = MatchupConfig{samples: field("samples")? as usize, seed: field("seed")?};
+
+        let columns: Vec<StartingHand> = match lines.next() {
+            Some(line) => line?.split(',').skip(1).map(|h| h.parse().ok()).collect::<Option<_>>(),
+            None => None,
+        }.ok_or_else(|| invalid("malformed column names".to_string()))?;
+        if columns.len() != 169 { return Err(invalid("malformed column names".to_string())); }
+
+        let mut equity = vec![f64::NAN; 169 * 169];
+        for (n, line) in lines.enumerate() {
+            let line = line?;
+            let bad = || invalid(format!("malformed row on line {}", n + 3));
+
+            let mut fields = line.split(',');
+            let a: StartingHand = fields.next().and_then(|h| h.parse().ok()).ok_or_else(bad)?;
+            let row = fields.map(|f| f.parse().ok()).collect::<Option<Vec<f64>>>().ok_or_else(bad)?;
+            if row.len() != 169 { return Err(bad()); }
+            for (&b, e) in columns.iter().zip(row) {
+                equity[cell(a) * 169 + cell(b)] = e;
+            }
+        }
+        if equity.iter().any(|e| e.is_nan()) {
+            return Err(invalid("missing starting hands".to_string()));
+        }
+
+        Ok(MatchupTable{config, equity})
+    }
+
+    // Reads the table at `path` if it was made with `config`, otherwise
+    // generates it and writes it there.
+    pub fn load_or_generate<P: AsRef<Path>>(path: P, config: MatchupConfig) -> io::Result<Self> {
+        let path = path.as_ref();
+        atomic::remove_stale(path)?;
+
+        if let Ok(file) = File::open(path) {
+            if let Ok(table) = MatchupTable::read(BufReader::new(file)) {
+                if table.config == config { return Ok(table); }
+            }
+        }
+
+        let table = MatchupTable::generate(config);
+        atomic::write(path, |w| table.write(w))?;
+        Ok(table)
+    }
+
+    /// The table built into the crate, read once on first use.
+    #[cfg(feature = "matchups")]
+    pub fn embedded() -> &'static MatchupTable {
+        static TABLE: std::sync::OnceLock<MatchupTable> = std::sync::OnceLock::new();
+        TABLE.get_or_init(|| {
+            MatchupTable::read(include_str!("matchups.csv").as_bytes()).expect("the built in table is valid")
+        })
+    }
+}
+
+/// The equity of one starting hand against another all in before the
+/// flop, from 0 to 1, such as `preflop_equity("AKs", "QQ")`; `None` if
+/// either is not a starting hand.
+#[cfg(feature = "matchups")]
+pub fn preflop_equity(a: &str, b: &str) -> Option<f64> {
+    Some(MatchupTable::embedded().equity(a.parse().ok()?, b.parse().ok()?))
+}
+
+#[cfg(test)]
+mod matchups_tests {
+    use super::*;
+
+    fn hand(s: &str) -> StartingHand {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_generate() {
+        let config = MatchupConfig{samples: 40, seed: 3};
+        let table = MatchupTable::generate(config);
+
+        assert_eq!(table.equity(hand("AA"), hand("AA")), 0.5);
+        assert_eq!(table.equity(hand("AKs"), hand("QQ")) + table.equity(hand("QQ"), hand("AKs")), 1.0);
+        assert!(table.equity(hand("AA"), hand("72o")) > 0.6);
+        assert_eq!(table, MatchupTable::generate(config));
+
+        let mut out = vec![];
+        table.write(&mut out).unwrap();
+        assert!(out.starts_with(b"# misc-poker matchups v1 samples=40 seed=3\nhand,AA,KK,"));
+        assert_eq!(MatchupTable::read(&out[..]).unwrap(), table);
+        assert!(MatchupTable::read(&out[..out.len() / 2]).is_err());
+    }
+
+    #[cfg(feature = "matchups")]
+    #[test]
+    fn test_embedded() {
+        let aks_qq = preflop_equity("AKs", "QQ").unwrap();
+        assert!((aks_qq - 0.46).abs() < 0.01, "{}", aks_qq);
+        assert!((preflop_equity("AA", "KK").unwrap() - 0.82).abs() < 0.01);
+        assert_eq!(preflop_equity("AA", "AA"), Some(0.5));
+        assert_eq!(preflop_equity("AKx", "QQ"), None);
+        assert_eq!(MatchupTable::embedded().config.samples, 100_000);
+    }
+}
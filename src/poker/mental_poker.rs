@@ -0,0 +1,450 @@
+//! Dealing without a trusted dealer, after Shamir, Rivest and Adleman's
+//! mental poker. Cards are numbers modulo a shared safe prime p, and each
+//! player's key is an exponent: encrypting is raising to `e`, decrypting
+//! raising to `d`, with e * d = 1 modulo p - 1. Exponents commute, so
+//! layers of encryption can be taken off in any order.
+//!
+//! A hand goes:
+//!
+//! 1. Every player makes a `Key` for the agreed `Group`.
+//! 2. Starting from `Group::deck`, each player in turn encrypts every card
+//!    and shuffles, with `Key::encrypt_shuffle`, and passes the deck on.
+//!    Nobody knows the final order, nor which card is which.
+//! 3. To give a player a card, every other player takes their layer off
+//!    with `Key::decrypt` and the owner takes the last, reading the card
+//!    with `Group::card`. Cards for the board are opened by everyone.
+//! 4. After the hand, the keys are shown and anyone can check every
+//!    shuffle with `verify`.
+//!
+//! Cards are encoded as squares, so every card and every encryption of one
+//! is a quadratic residue and whether a ciphertext is a residue, which
+//! raising to a power does not change, says nothing about the card. Use
+//! `Group::modp_2048` and a cryptographic generator for real games; smaller
+//! groups are only for trying the protocol out.
+
+use std::cmp::Ordering;
+use std::fmt;
+
+use rand::Rng;
+use rand::seq::SliceRandom;
+
+use super::Card;
+use super::batch;
+
+// The 2048 bit MODP group of RFC 3526, a safe prime.
+const MODP_2048: &str = "\
+    FFFFFFFFFFFFFFFFC90FDAA22168C234C4C6628B80DC1CD129024E088A67CC74\
+    020BBEA63B139B22514A08798E3404DDEF9519B3CD3A431B302B0A6DF25F1437\
+    4FE1356D6D51C245E485B576625E7EC6F44C42E9A637ED6B0BFF5CB6F406B7ED\
+    EE386BFB5A899FA5AE9F24117C4B1FE649286651ECE45B3DC2007CB8A163BF05\
+    98DA48361C55D39A69163FA8FD24CF5F83655D23DCA3AD961C62F356208552BB\
+    9ED529077096966D670C354E4ABC9804F1746C08CA18217C32905E462E36CE3B\
+    E39E772C180E86039B2783A2EC07A28FB5C55DF06F4C52C9DE2BCBF695581718\
+    3995497CEA956AE515D2261898FA051015728E5A8AACAA68FFFFFFFFFFFFFFFF";
+
+/// The prime the players agree on, p = 2q + 1 with q prime.
+#[derive(Clone, Debug)]
+pub struct Group {
+    p: Modulus,
+    q: Modulus,
+}
+
+/// One player's secret exponents.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Key {
+    e: Vec<u64>,
+    d: Vec<u64>,
+}
+
+/// A card under zero or more layers of encryption.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Cipher(Vec<u64>);
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum VerifyError {
+    // Players count from 0 in the order they shuffled.
+    BadKey { player: usize },
+    NotAShuffle { player: usize },
+    WrongStages,
+}
+
+impl fmt::Display for VerifyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            VerifyError::BadKey { player } => write!(f, "player {} showed a key that does not work", player),
+            VerifyError::NotAShuffle { player } => write!(f, "player {} did not encrypt and shuffle the deck passed on", player),
+            VerifyError::WrongStages => write!(f, "expected the starting deck and one deck per key"),
+        }
+    }
+}
+
+impl std::error::Error for VerifyError {}
+
+impl Group {
+    /// The 2048 bit group, for real games.
+    pub fn modp_2048() -> Group {
+        Group::from_hex(MODP_2048).unwrap()
+    }
+
+    /// A group from a safe prime in hex. It must be a safe prime, which is
+    /// not checked; `None` only if it is even or too small for a deck.
+    pub fn from_hex(p: &str) -> Option<Group> {
+        let p = from_hex(p)?;
+        if p[0] & 1 == 0 || (p.len() == 1 && p[0] < 1 << 16) { return None; }
+
+        let mut q = p.clone();
+        shr1(&mut q);
+        Some(Group{p: Modulus::new(p), q: Modulus::new(q)})
+    }
+
+    /// The cards of `Deck::new`, unencrypted, in order.
+    pub fn deck(&self) -> Vec<Cipher> {
+        super::deck::Deck::new().cards().iter().map(|c| self.encode(c)).collect()
+    }
+
+    /// The card a fully decrypted cipher holds.
+    pub fn card(&self, cipher: &Cipher) -> Option<Card> {
+        (0..52).map(batch::card).find(|c| self.encode(c) == *cipher)
+    }
+
+    pub fn cipher_from_hex(&self, s: &str) -> Option<Cipher> {
+        let n = self.fit(from_hex(s)?)?;
+        Some(Cipher(n))
+    }
+
+    // (index + 2)^2, a nonzero square.
+    fn encode(&self, card: &Card) -> Cipher {
+        let i = batch::index(card).expect("a card of the deck") as u64 + 2;
+        let mut n = vec![0; self.p.n.len()];
+        n[0] = i * i;
+        Cipher(n)
+    }
+
+    // `n` padded to the modulus, if below it.
+    fn fit(&self, mut n: Vec<u64>) -> Option<Vec<u64>> {
+        let len = self.p.n.len();
+        if n[len.min(n.len())..].iter().any(|&l| l != 0) { return None; }
+        n.resize(len, 0);
+        if compare(&n, &self.p.n) == Ordering::Less { Some(n) } else { None }
+    }
+}
+
+impl Key {
+    /// A new key. The generator should be a cryptographic one.
+    pub fn generate<R: Rng + ?Sized>(group: &Group, rng: &mut R) -> Key {
+        let q = &group.q.n;
+        let top = 64 - q[q.len() - 1].leading_zeros();
+        let mask = if top == 64 { u64::MAX } else { (1 << top) - 1 };
+
+        // Odd and between 3 and q, so prime to p - 1 = 2q.
+        let e = loop {
+            let mut e: Vec<u64> = (0..q.len()).map(|_| rng.gen()).collect();
+            let last = e.len() - 1;
+            e[last] &= mask;
+            e[0] |= 1;
+            if compare(&e, q) == Ordering::Less && !(e[1..].iter().all(|&l| l == 0) && e[0] < 3) {
+                break e;
+            }
+        };
+        Key{d: inverse(group, &e), e}
+    }
+
+    pub fn encrypt(&self, group: &Group, cipher: &Cipher) -> Cipher {
+        Cipher(group.p.pow(&cipher.0, &self.e))
+    }
+
+    pub fn decrypt(&self, group: &Group, cipher: &Cipher) -> Cipher {
+        Cipher(group.p.pow(&cipher.0, &self.d))
+    }
+
+    /// Every card of `deck` encrypted, in a new random order.
+    pub fn encrypt_shuffle<R: Rng + ?Sized>(&self, group: &Group, deck: &[Cipher], rng: &mut R) -> Vec<Cipher> {
+        let mut out: Vec<Cipher> = deck.iter().map(|c| self.encrypt(group, c)).collect();
+        out.shuffle(rng);
+        out
+    }
+
+    /// The key as "e:d" in hex, to show after the hand.
+    pub fn to_hex(&self) -> String {
+        format!("{}:{}", to_hex(&self.e), to_hex(&self.d))
+    }
+
+    pub fn from_hex(group: &Group, s: &str) -> Option<Key> {
+        let (e, d) = s.split_once(':')?;
+        Some(Key{e: group.fit(from_hex(e)?)?, d: group.fit(from_hex(d)?)?})
+    }
+}
+
+// Keys stay out of logs.
+impl fmt::Debug for Key {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Key {{ .. }}")
+    }
+}
+
+impl fmt::Display for Cipher {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", to_hex(&self.0))
+    }
+}
+
+/// Checks a finished hand. `stages` holds the deck each player was passed
+/// and the one they passed on: `Group::deck` first, then the output of
+/// each player's `encrypt_shuffle`, in the order of `keys`.
+pub fn verify(group: &Group, keys: &[Key], stages: &[Vec<Cipher>]) -> Result<(), VerifyError> {
+    if stages.len() != keys.len() + 1 { return Err(VerifyError::WrongStages); }
+    let probe = &group.deck()[0];
+
+    for (player, key) in keys.iter().enumerate() {
+        let valid = key.e[0] & 1 == 1 && compare(&key.e, &group.q.n) == Ordering::Less
+            && key.decrypt(group, &key.encrypt(group, probe)) == *probe;
+        if !valid { return Err(VerifyError::BadKey{player}); }
+
+        let mut expected: Vec<&[u64]> = vec![];
+        let encrypted: Vec<Cipher> = stages[player].iter().map(|c| key.encrypt(group, c)).collect();
+        expected.extend(encrypted.iter().map(|c| &c.0[..]));
+        let mut found: Vec<&[u64]> = stages[player + 1].iter().map(|c| &c.0[..]).collect();
+        expected.sort_unstable();
+        found.sort_unstable();
+        if expected != found { return Err(VerifyError::NotAShuffle{player}); }
+    }
+    Ok(())
+}
+
+// d = e^-1 mod 2q: e^(q-2) mod q by Fermat, made odd by adding q if need
+// be, so that it is also 1 mod 2.
+fn inverse(group: &Group, e: &[u64]) -> Vec<u64> {
+    let q = &group.q.n;
+    let mut q_2 = q.clone();
+    sub(&mut q_2, &small(2, q.len()));
+
+    let mut d = group.q.pow(e, &q_2);
+    if d[0] & 1 == 0 {
+        add(&mut d, q);
+    }
+    d
+}
+
+// Arithmetic on unsigned numbers as little endian `u64` limbs, all as long
+// as the modulus.
+
+#[derive(Clone, Debug)]
+struct Modulus {
+    n: Vec<u64>,
+    // -n^-1 mod 2^64.
+    inv: u64,
+    // R^2 mod n, R being 2^(64 * limbs).
+    r2: Vec<u64>,
+}
+
+impl Modulus {
+    fn new(n: Vec<u64>) -> Modulus {
+        let mut x: u64 = 1;
+        for _ in 0..6 {
+            x = x.wrapping_mul(2u64.wrapping_sub(n[0].wrapping_mul(x)));
+        }
+
+        // Doubling 1 up to R^2, reducing as it goes.
+        let mut r2 = small(1, n.len());
+        for _ in 0..128 * n.len() {
+            let carry = shl1(&mut r2);
+            if carry || compare(&r2, &n) != Ordering::Less {
+                sub(&mut r2, &n);
+            }
+        }
+        Modulus{inv: x.wrapping_neg(), r2, n}
+    }
+
+    // a * b / R mod n, for a and b below n.
+    fn mul(&self, a: &[u64], b: &[u64]) -> Vec<u64> {
+        let len = self.n.len();
+        let mut t = vec![0u64; len + 2];
+
+        for &bi in b {
+            let mut carry = 0u128;
+            for j in 0..len {
+                let x = t[j] as u128 + a[j] as u128 * bi as u128 + carry;
+                t[j] = x as u64;
+                carry = x >> 64;
+            }
+            let x = t[len] as u128 + carry;
+            t[len] = x as u64;
+            t[len + 1] = (x >> 64) as u64;
+
+            let m = t[0].wrapping_mul(self.inv);
+            let mut carry = (t[0] as u128 + m as u128 * self.n[0] as u128) >> 64;
+            for j in 1..len {
+                let x = t[j] as u128 + m as u128 * self.n[j] as u128 + carry;
+                t[j - 1] = x as u64;
+                carry = x >> 64;
+            }
+            let x = t[len] as u128 + carry;
+            t[len - 1] = x as u64;
+            t[len] = t[len + 1] + (x >> 64) as u64;
+        }
+
+        let over = t[len] != 0;
+        t.truncate(len);
+        if over || compare(&t, &self.n) != Ordering::Less {
+            sub(&mut t, &self.n);
+        }
+        t
+    }
+
+    // base^exp mod n, square and multiply from the top bit.
+    fn pow(&self, base: &[u64], exp: &[u64]) -> Vec<u64> {
+        let one = small(1, self.n.len());
+        let b = self.mul(base, &self.r2);
+        let mut acc = self.mul(&one, &self.r2);
+
+        for &limb in exp.iter().rev() {
+            for bit in (0..64).rev() {
+                acc = self.mul(&acc, &acc);
+                if limb >> bit & 1 == 1 {
+                    acc = self.mul(&acc, &b);
+                }
+            }
+        }
+        self.mul(&acc, &one)
+    }
+}
+
+fn small(x: u64, len: usize) -> Vec<u64> {
+    let mut n = vec![0; len];
+    n[0] = x;
+    n
+}
+
+fn compare(a: &[u64], b: &[u64]) -> Ordering {
+    a.iter().rev().cmp(b.iter().rev())
+}
+
+// a -= b, wrapping.
+fn sub(a: &mut [u64], b: &[u64]) {
+    let mut borrow = false;
+    for (x, &y) in a.iter_mut().zip(b) {
+        let (d, b1) = x.overflowing_sub(y);
+        let (d, b2) = d.overflowing_sub(borrow as u64);
+        *x = d;
+        borrow = b1 || b2;
+    }
+}
+
+// a += b, wrapping.
+fn add(a: &mut [u64], b: &[u64]) {
+    let mut carry = false;
+    for (x, &y) in a.iter_mut().zip(b) {
+        let (s, c1) = x.overflowing_add(y);
+        let (s, c2) = s.overflowing_add(carry as u64);
+        *x = s;
+        carry = c1 || c2;
+    }
+}
+
+// Doubles `a`, returning the bit shifted out.
+fn shl1(a: &mut [u64]) -> bool {
+    let mut carry = 0;
+    for x in a.iter_mut() {
+        let out = *x >> 63;
+        *x = *x << 1 | carry;
+        carry = out;
+    }
+    carry == 1
+}
+
+fn shr1(a: &mut [u64]) {
+    let mut carry = 0;
+    for x in a.iter_mut().rev() {
+        let out = *x & 1;
+        *x = *x >> 1 | carry << 63;
+        carry = out;
+    }
+}
+
+fn from_hex(s: &str) -> Option<Vec<u64>> {
+    let digits: Vec<u64> = s.chars().map(|c| c.to_digit(16).map(u64::from)).collect::<Option<_>>()?;
+    if digits.is_empty() { return None; }
+
+    let mut limbs: Vec<u64> = digits.rchunks(16)
+        .map(|chunk| chunk.iter().fold(0, |n, &d| n << 4 | d))
+        .collect();
+    while limbs.len() > 1 && limbs[limbs.len() - 1] == 0 {
+        limbs.pop();
+    }
+    Some(limbs)
+}
+
+fn to_hex(n: &[u64]) -> String {
+    n.iter().rev().map(|l| format!("{:016x}", l)).collect()
+}
+
+#[cfg(test)]
+mod mental_poker_tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
+
+    // A 128 bit safe prime: quick, and far too small to play for money.
+    const SMALL: &str = "e619d0948c4c2fdc96f6ffecb6f32dfb";
+
+    #[test]
+    fn test_hand() {
+        let group = Group::from_hex(SMALL).unwrap();
+        let mut rng = ChaCha20Rng::seed_from_u64(21);
+        let (alice, bob) = (Key::generate(&group, &mut rng), Key::generate(&group, &mut rng));
+
+        let start = group.deck();
+        let by_alice = alice.encrypt_shuffle(&group, &start, &mut rng);
+        let by_bob = bob.encrypt_shuffle(&group, &by_alice, &mut rng);
+        assert!(by_bob.iter().all(|c| group.card(c).is_none()));
+
+        // Bob opens his layer of the top card for Alice, who reads it.
+        let for_alice = bob.decrypt(&group, &by_bob[0]);
+        let card = group.card(&alice.decrypt(&group, &for_alice)).unwrap();
+        // And the same card opened the other way round.
+        assert_eq!(group.card(&bob.decrypt(&group, &alice.decrypt(&group, &by_bob[0]))), Some(card));
+
+        // Every card comes out once.
+        let mut dealt: Vec<Card> = by_bob.iter()
+            .map(|c| group.card(&alice.decrypt(&group, &bob.decrypt(&group, c))).unwrap())
+            .collect();
+        dealt.sort_by_key(batch::index);
+        assert_eq!(dealt, (0..52).map(batch::card).collect::<Vec<_>>());
+
+        let stages = vec![start, by_alice, by_bob];
+        let keys = [alice.clone(), bob.clone()];
+        assert_eq!(verify(&group, &keys, &stages), Ok(()));
+
+        let mut cheated = stages.clone();
+        cheated[2][5] = cheated[2][6].clone();
+        assert_eq!(verify(&group, &keys, &cheated), Err(VerifyError::NotAShuffle{player: 1}));
+        let shown = Key::from_hex(&group, &alice.to_hex()).unwrap();
+        assert!(shown == alice);
+        assert_eq!(verify(&group, &[bob, shown], &stages), Err(VerifyError::NotAShuffle{player: 0}));
+    }
+
+    #[test]
+    fn test_modp_2048() {
+        let group = Group::modp_2048();
+        assert_eq!(group.p.n.len(), 32);
+
+        let key = Key::generate(&group, &mut ChaCha20Rng::seed_from_u64(3));
+        let card = group.deck()[17].clone();
+        let hidden = key.encrypt(&group, &card);
+        assert_ne!(hidden, card);
+        assert_eq!(group.cipher_from_hex(&hidden.to_string()), Some(hidden.clone()));
+        assert_eq!(group.card(&key.decrypt(&group, &hidden)), Some(batch::card(17)));
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let m = Modulus::new(vec![97]);
+        assert_eq!(m.pow(&[5], &[96]), vec![1]);
+        assert_eq!(m.pow(&[3], &[5]), vec![243 % 97]);
+        assert!(from_hex("1_").is_none());
+        assert_eq!(from_hex("10000000000000000"), Some(vec![0, 1]));
+        assert_eq!(to_hex(&[1, 2]), "00000000000000020000000000000001");
+        assert!(Group::from_hex("10").is_none());
+    }
+}
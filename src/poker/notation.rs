@@ -0,0 +1,170 @@
+use std::borrow::Cow;
+use std::fmt;
+
+use super::{Card, Rank, Suit};
+
+/// The letters used to write each rank and suit. Ranks are listed from
+/// `Rank::One` to `Rank::Ace`, suits as hearts, diamonds, clubs, spades.
+///
+/// Tens are always written with the notation's single letter, 'T' in all of
+/// these, but every parser also reads "10" as a ten: "10H" and "TH" are the
+/// same card. No notation has a '0', so the two never clash.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CardNotation {
+    pub ranks: [char; 14],
+    pub suits: [char; 4],
+}
+
+impl CardNotation {
+    pub const ENGLISH: CardNotation = CardNotation {
+        ranks: ['1', '2', '3', '4', '5', '6', '7', '8', '9', 'T', 'J', 'Q', 'K', 'A'],
+        suits: ['H', 'D', 'C', 'S'],
+    };
+
+    // Bube, Dame, König, Ass.
+    pub const GERMAN: CardNotation = CardNotation {
+        ranks: ['1', '2', '3', '4', '5', '6', '7', '8', '9', 'T', 'B', 'D', 'K', 'A'],
+        suits: ['H', 'D', 'C', 'S'],
+    };
+
+    // Valet, Dame, Roi, As.
+    pub const FRENCH: CardNotation = CardNotation {
+        ranks: ['1', '2', '3', '4', '5', '6', '7', '8', '9', 'T', 'V', 'D', 'R', 'A'],
+        suits: ['H', 'D', 'C', 'S'],
+    };
+
+    // Boer, Vrouw, Heer, Aas.
+    pub const DUTCH: CardNotation = CardNotation {
+        ranks: ['1', '2', '3', '4', '5', '6', '7', '8', '9', 'T', 'B', 'V', 'H', 'A'],
+        suits: ['H', 'D', 'C', 'S'],
+    };
+
+    pub fn rank(&self, c: char) -> Option<Rank> {
+        self.ranks.iter().position(|&r| r == c).map(|i| Rank::ALL[i])
+    }
+
+    pub fn suit(&self, c: char) -> Option<Suit> {
+        self.suits.iter().position(|&s| s == c).map(|i| Suit::ALL[i])
+    }
+
+    pub fn rank_char(&self, rank: Rank) -> char {
+        self.ranks[rank as usize]
+    }
+
+    pub fn suit_char(&self, suit: Suit) -> char {
+        self.suits[suit.index()]
+    }
+
+    pub fn parse(&self, code: &str) -> Option<Card> {
+        let (rank, mut chars) = match code.strip_prefix("10") {
+            Some(rest) => (Rank::Ten, rest.chars()),
+            None => {
+                let mut chars = code.chars();
+                (self.rank(chars.next()?)?, chars)
+            },
+        };
+        let suit = self.suit(chars.next()?)?;
+        if chars.next().is_some() { return None; }
+
+        Some(Card{rank, suit})
+    }
+
+    // `s` with every "10" written as this notation's ten, for parsers that
+    // read ranks one character at a time.
+    pub(crate) fn tens<'a>(&self, s: &'a str) -> Cow<'a, str> {
+        if s.contains("10") {
+            Cow::Owned(s.replace("10", &self.rank_char(Rank::Ten).to_string()))
+        } else {
+            Cow::Borrowed(s)
+        }
+    }
+
+    pub fn format(&self, card: &Card) -> String {
+        self.display(card).to_string()
+    }
+
+    pub fn display<'a>(&'a self, card: &'a Card) -> CardDisplay<'a> {
+        CardDisplay{notation: self, card}
+    }
+}
+
+impl Default for CardNotation {
+    fn default() -> Self {
+        CardNotation::ENGLISH
+    }
+}
+
+pub struct CardDisplay<'a> {
+    notation: &'a CardNotation,
+    card: &'a Card,
+}
+
+impl<'a> fmt::Display for CardDisplay<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}{}",
+            self.notation.rank_char(self.card.rank),
+            self.notation.suit_char(self.card.suit)
+        )
+    }
+}
+
+#[cfg(test)]
+mod notation_tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            CardNotation::GERMAN.parse("BH"),
+            Some(Card{rank: Rank::Jack, suit: Suit::Hearts})
+        );
+        assert_eq!(
+            CardNotation::FRENCH.parse("RS"),
+            Some(Card{rank: Rank::King, suit: Suit::Spades})
+        );
+        assert_eq!(CardNotation::ENGLISH.parse("BH"), None);
+        assert_eq!(CardNotation::ENGLISH.parse("JHX"), None);
+    }
+
+    #[test]
+    fn test_tens() {
+        let ten = Card{rank: Rank::Ten, suit: Suit::Hearts};
+
+        assert_eq!(CardNotation::ENGLISH.parse("10H"), Some(ten));
+        assert_eq!(CardNotation::ENGLISH.parse("TH"), Some(ten));
+        assert_eq!(CardNotation::GERMAN.parse("10H"), Some(ten));
+        assert_eq!(CardNotation::ENGLISH.parse("10"), None);
+        assert_eq!(CardNotation::ENGLISH.parse("10HX"), None);
+        assert_eq!(CardNotation::ENGLISH.format(&ten), "TH");
+        assert_eq!(CardNotation::ENGLISH.tens("A10s, 1010"), "ATs, TT");
+    }
+
+    #[test]
+    fn test_format() {
+        let card = Card{rank: Rank::Queen, suit: Suit::Diamonds};
+
+        assert_eq!(CardNotation::ENGLISH.format(&card), "QD");
+        assert_eq!(CardNotation::GERMAN.format(&card), "DD");
+        assert_eq!(CardNotation::FRENCH.format(&card), "DD");
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let notations = [
+            CardNotation::ENGLISH,
+            CardNotation::GERMAN,
+            CardNotation::FRENCH,
+            CardNotation::DUTCH,
+        ];
+        for notation in notations.iter() {
+            for &rank in Rank::ALL.iter() {
+                for &suit in Suit::ALL.iter() {
+                    let card = Card{rank, suit};
+                    assert_eq!(notation.parse(&notation.format(&card)), Some(card));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,161 @@
+use std::fmt;
+
+use super::{Board, Card, Category, DuplicateCard, Hand};
+use super::board::Street;
+use super::deal::best_of;
+use super::deck::Deck;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Target {
+    // Any hand of a higher category.
+    Category(Category),
+    // The opponent's hole cards, which also use the board.
+    Hand([Card; 2]),
+}
+
+/// The cards that, dealt next, leave the hand beating the target. Ties do
+/// not count. `ahead` says whether the hand already beats it, in which case
+/// the cards are the ones that keep it ahead.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Outs {
+    pub cards: Vec<Card>,
+    pub ahead: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum OutsError {
+    DuplicateCard(Card),
+    // Before the flop no one card makes a hand.
+    Preflop,
+}
+
+impl fmt::Display for OutsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            OutsError::DuplicateCard(card) => write!(f, "duplicate card {}", card),
+            OutsError::Preflop => write!(f, "outs need a flop or later board"),
+        }
+    }
+}
+
+impl std::error::Error for OutsError {}
+
+impl From<DuplicateCard> for OutsError {
+    fn from(e: DuplicateCard) -> Self {
+        OutsError::DuplicateCard(e.0)
+    }
+}
+
+impl Outs {
+    pub fn len(&self) -> usize {
+        self.cards.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cards.is_empty()
+    }
+}
+
+/// The outs on a flop or turn `board`. On the river no card is to come,
+/// so there are none and only `ahead` says anything; before the flop it is
+/// an error.
+pub fn outs(
+    hole: &[Card; 2],
+    board: &Board,
+    dead: &[Card],
+    target: Target,
+) -> Result<Outs, OutsError> {
+    let mut known: Vec<Card> = hole.iter().chain(board.iter()).chain(dead).cloned().collect();
+    if let Target::Hand(other) = target { known.extend_from_slice(&other); }
+    for (i, card) in known.iter().enumerate() {
+        if known[i + 1..].contains(card) { return Err(OutsError::DuplicateCard(*card)); }
+    }
+    if board.street() == Street::Preflop { return Err(OutsError::Preflop); }
+
+    let beats = |board: &[Card]| {
+        let hand = with(hole, board);
+        match target {
+            Target::Category(category) => hand.value().category() > category,
+            Target::Hand(other) => hand > with(&other, board),
+        }
+    };
+
+    if board.street() == Street::River {
+        return Ok(Outs{cards: vec![], ahead: beats(board)});
+    }
+
+    let deck = Deck::without(known.iter().copied());
+    let cards = deck.cards().iter()
+        .filter(|&&card| beats(&[board, &[card][..]].concat()))
+        .cloned()
+        .collect();
+
+    Ok(Outs{cards, ahead: beats(board)})
+}
+
+fn with(hole: &[Card; 2], board: &[Card]) -> Hand {
+    best_of(&[&hole[..], board].concat())
+}
+
+#[cfg(test)]
+mod outs_tests {
+    use super::*;
+
+    fn cards(s: &str) -> Vec<Card> {
+        s.split_whitespace().map(|c| Card::from_code(c).unwrap()).collect()
+    }
+
+    fn hole(s: &str) -> [Card; 2] {
+        let c = cards(s);
+        [c[0], c[1]]
+    }
+
+    fn board(s: &str) -> Board {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_flush_draw() {
+        let o = outs(&hole("AH 5H"), &board("KH 9H 2C 7S"), &[], Target::Category(Category::Straight)).unwrap();
+
+        assert_eq!(o.len(), 9);
+        assert!(!o.ahead);
+        assert!(o.cards.iter().all(|c| c.suit == super::super::Suit::Hearts));
+
+        let dead = cards("3H QH");
+        let o = outs(&hole("AH 5H"), &board("KH 9H 2C 7S"), &dead, Target::Category(Category::Straight)).unwrap();
+        assert_eq!(o.len(), 7);
+    }
+
+    #[test]
+    fn test_against_hand() {
+        // Two overcards against a pair of nines: three aces and three kings.
+        let o = outs(&hole("AS KD"), &board("9H 5C 2D 7S"), &[], Target::Hand(hole("9C 8C"))).unwrap();
+        assert_eq!(o.len(), 6);
+
+        let o = outs(&hole("AS AD"), &board("9H 5C 2D 7S"), &[], Target::Hand(hole("9C 8C"))).unwrap();
+        assert!(o.ahead);
+        // Everything but the two nines, three eights and four sixes.
+        assert_eq!(o.len(), 44 - 2 - 3 - 4);
+    }
+
+    #[test]
+    fn test_duplicates() {
+        assert_eq!(
+            outs(&hole("AS KD"), &board("AS 5C 2D"), &[], Target::Category(Category::OnePair)),
+            Err(OutsError::DuplicateCard(Card::from_code("AS").unwrap()))
+        );
+    }
+
+    #[test]
+    fn test_streets() {
+        let target = Target::Hand(hole("9C 8C"));
+        assert_eq!(outs(&hole("AS KD"), &Board::new(), &[], target), Err(OutsError::Preflop));
+
+        let o = outs(&hole("AS KD"), &board("9H 5C 2D 7S AC"), &[], target).unwrap();
+        assert_eq!(o, Outs{cards: vec![], ahead: true});
+        let o = outs(&hole("AS KD"), &board("9H 5C 2D 7S 3C"), &[], target).unwrap();
+        assert_eq!(o, Outs{cards: vec![], ahead: false});
+    }
+}
@@ -0,0 +1,302 @@
+//! Evaluation and comparison of large slices of hands across threads,
+//! enabled by the `parallel` feature. The slice is cut into one run per
+//! thread, each run goes through the batch evaluator on its own scoped
+//! thread, and the results come back in the order of the input.
+//!
+//! Threads default to what `std::thread::available_parallelism` reports;
+//! the `_with` forms take a count. Slices too small to be worth a thread
+//! each are done on fewer.
+//!
+//! `ParallelEquity` runs the trials of `equity::multiway_equity` the same
+//! way, in blocks handed out to the threads as they come free. After each
+//! block the caller's callback sees the trials done so far and the
+//! estimate, and a `CancelToken` stops the run early with what it has.
+//! Every block has its own stream of the seed, so the result does not
+//! depend on the number of threads.
+//!
+//! This is plain `std::thread` rather than rayon on purpose. The work is
+//! one even split of a slice, or blocks pulled off a counter, which scoped
+//! threads and a channel cover in a few lines; rayon would be the crate's
+//! first dependency outside the `rand` family, with its own thread pool
+//! living for the whole process, for no speed on jobs this regular.
+
+use std::cmp::Ordering;
+use std::num::NonZeroUsize;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering as Atomic};
+use std::sync::mpsc;
+use std::thread;
+
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+use super::{Board, Card, Hand};
+use super::batch::evaluate_batch;
+use super::equity::{multiway_equity, standard_error, EquityMode, Multiway};
+use super::range::Range;
+use super::value::HandValue;
+
+// Fewer hands than this per thread cost more to hand out than to evaluate.
+const MIN_PER_THREAD: usize = 4096;
+
+pub fn threads() -> usize {
+    thread::available_parallelism().map(NonZeroUsize::get).unwrap_or(1)
+}
+
+/// `batch::evaluate_batch` over every available thread.
+pub fn par_evaluate(hands: &[Hand]) -> Vec<HandValue> {
+    par_evaluate_with(hands, threads())
+}
+
+pub fn par_evaluate_with(hands: &[Hand], threads: usize) -> Vec<HandValue> {
+    par_map(hands, threads, evaluate_batch)
+}
+
+/// How the first hand of each pair compares with the second.
+pub fn par_compare_pairs(pairs: &[(Hand, Hand)]) -> Vec<Ordering> {
+    par_compare_pairs_with(pairs, threads())
+}
+
+pub fn par_compare_pairs_with(pairs: &[(Hand, Hand)], threads: usize) -> Vec<Ordering> {
+    par_map(pairs, threads, |run| {
+        let (a, b): (Vec<Hand>, Vec<Hand>) = run.iter().cloned().unzip();
+        evaluate_batch(&a).iter().zip(evaluate_batch(&b)).map(|(a, b)| a.cmp(&b)).collect()
+    })
+}
+
+// Trials per block handed to a thread.
+const BLOCK: usize = 4096;
+
+/// Stops a parallel run from another thread, or from its own progress
+/// callback. Clones share the one flag.
+#[derive(Clone, Debug, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn new() -> Self {
+        CancelToken::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Atomic::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Atomic::Relaxed)
+    }
+}
+
+/// How far a run has got, given to the progress callback after each
+/// block of trials.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Progress {
+    pub trials: usize,
+    pub total: usize,
+    // Each player's share of the pot so far, in percent.
+    pub shares: Vec<f64>,
+}
+
+/// Monte Carlo multiway equity across threads.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ParallelEquity {
+    trials: usize,
+    threads: usize,
+    seed: u64,
+}
+
+impl ParallelEquity {
+    pub fn new(trials: usize) -> Self {
+        ParallelEquity{trials, threads: threads(), seed: 0}
+    }
+
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.threads = threads.max(1);
+        self
+    }
+
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// `multiway_equity` with its trials spread over the threads, calling
+    /// `progress` on this thread as blocks finish. A cancelled run returns
+    /// the blocks finished by then, whose trials `Multiway::runouts`
+    /// counts. `None` if no block finished, or no deal gives every player
+    /// a combo.
+    pub fn run<F: FnMut(&Progress)>(
+        &self,
+        ranges: &[Range],
+        board: &Board,
+        dead: &[Card],
+        cancel: &CancelToken,
+        mut progress: F,
+    ) -> Option<Multiway> {
+        let blocks = self.trials.div_ceil(BLOCK);
+        let next = AtomicUsize::new(0);
+        let failed = AtomicBool::new(false);
+        let (tx, rx) = mpsc::channel();
+
+        let mut done: Vec<Option<Multiway>> = vec![None; blocks];
+        thread::scope(|s| {
+            for _ in 0..self.threads.min(blocks) {
+                let (tx, next, failed) = (tx.clone(), &next, &failed);
+                s.spawn(move || loop {
+                    if cancel.is_cancelled() || failed.load(Atomic::Relaxed) { break; }
+                    let block = next.fetch_add(1, Atomic::Relaxed);
+                    if block >= blocks { break; }
+
+                    let mut rng = ChaCha8Rng::seed_from_u64(self.seed);
+                    rng.set_stream(block as u64);
+                    let trials = BLOCK.min(self.trials - block * BLOCK);
+                    let result = multiway_equity(ranges, board, dead, EquityMode::MonteCarlo{trials}, &mut rng);
+                    if result.is_none() { failed.store(true, Atomic::Relaxed); }
+                    if tx.send((block, result)).is_err() { break; }
+                });
+            }
+            drop(tx);
+
+            let (mut trials, mut won) = (0, vec![0.0; ranges.len()]);
+            for (block, result) in rx {
+                let Some(m) = result else { continue };
+                trials += m.runouts;
+                for (w, share) in won.iter_mut().zip(&m.shares) {
+                    *w += share * m.runouts as f64;
+                }
+                done[block] = Some(m);
+
+                let shares = won.iter().map(|w| w / trials as f64).collect();
+                progress(&Progress{trials, total: self.trials, shares});
+            }
+        });
+        if failed.load(Atomic::Relaxed) { return None; }
+
+        // Summed in block order, so the figures are the same on any number
+        // of threads.
+        let done: Vec<Multiway> = done.into_iter().flatten().collect();
+        let runouts: usize = done.iter().map(|m| m.runouts).sum();
+        if runouts == 0 { return None; }
+        let average = |field: fn(&Multiway) -> &Vec<f64>| -> Vec<f64> {
+            (0..ranges.len())
+                .map(|p| done.iter().map(|m| field(m)[p] * m.runouts as f64).sum::<f64>() / runouts as f64)
+                .collect()
+        };
+        Some(Multiway{
+            shares: average(|m| &m.shares),
+            wins: average(|m| &m.wins),
+            ties: average(|m| &m.ties),
+            runouts,
+            exhaustive: false,
+            standard_errors: (0..ranges.len()).map(|p| pooled_standard_error(&done, p)).collect(),
+        })
+    }
+}
+
+// A player's standard error over every block, from each block's mean and
+// standard error; a block of one trial adds no spread of its own.
+fn pooled_standard_error(blocks: &[Multiway], player: usize) -> f64 {
+    let (mut n, mut sum, mut squares) = (0, 0.0, 0.0);
+    for m in blocks {
+        let (k, mean) = (m.runouts as f64, m.shares[player] / 100.0);
+        let spread = if m.runouts > 1 { (m.standard_errors[player] / 100.0).powi(2) * k * (k - 1.0) } else { 0.0 };
+        n += m.runouts;
+        sum += k * mean;
+        squares += spread + k * mean * mean;
+    }
+    standard_error(sum, squares, n)
+}
+
+// Applies `f` to runs of `items` on up to `threads` threads and joins the
+// results in order.
+fn par_map<T, U, F>(items: &[T], threads: usize, f: F) -> Vec<U>
+where
+    T: Sync,
+    U: Send,
+    F: Fn(&[T]) -> Vec<U> + Sync,
+{
+    let threads = threads.clamp(1, (items.len() / MIN_PER_THREAD).max(1));
+    if threads == 1 { return f(items); }
+
+    let run = items.len().div_ceil(threads);
+    let f = &f;
+    thread::scope(|s| {
+        let handles: Vec<_> = items.chunks(run).map(|chunk| s.spawn(move || f(chunk))).collect();
+        handles.into_iter().flat_map(|h| h.join().unwrap()).collect()
+    })
+}
+
+#[cfg(test)]
+mod par_tests {
+    use super::*;
+    use super::super::deck::Deck;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    fn hands(n: usize, seed: u64) -> Vec<Hand> {
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+        (0..n).map(|_| {
+            let mut deck = Deck::new();
+            deck.shuffle(&mut rng);
+            deck.deal_hand().unwrap()
+        }).collect()
+    }
+
+    #[test]
+    fn test_par_evaluate() {
+        let hands = hands(3 * MIN_PER_THREAD + 17, 1);
+        let expected: Vec<HandValue> = hands.iter().map(Hand::value).collect();
+
+        assert_eq!(par_evaluate_with(&hands, 4), expected);
+        assert_eq!(par_evaluate_with(&hands, 1), expected);
+        assert_eq!(par_evaluate_with(&hands[..10], 8), expected[..10]);
+        assert!(par_evaluate(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_parallel_equity() {
+        let ranges: Vec<Range> = ["AA", "KK", "QQ"].iter().map(|r| r.parse().unwrap()).collect();
+        let board = Board::new();
+        let sim = ParallelEquity::new(3 * BLOCK + 100).seed(4);
+
+        let mut seen = vec![];
+        let m = sim.threads(3).run(&ranges, &board, &[], &CancelToken::new(), |p| seen.push(p.trials)).unwrap();
+        assert_eq!(m.runouts, 3 * BLOCK + 100);
+        assert!((m.shares.iter().sum::<f64>() - 100.0).abs() < 1e-9);
+        assert!(m.shares[0] > m.shares[1] && m.shares[1] > m.shares[2]);
+        assert_eq!(seen.len(), 4);
+        assert_eq!(seen.last(), Some(&m.runouts));
+        // Pooled over the blocks, close to sqrt(p(1 - p) / n) with few ties.
+        for (&share, &error) in m.shares.iter().zip(&m.standard_errors) {
+            let p = share / 100.0;
+            let expected = 100.0 * (p * (1.0 - p) / m.runouts as f64).sqrt();
+            assert!((error - expected).abs() < 0.05 * expected, "{} {}", error, expected);
+        }
+
+        // The same on one thread.
+        assert_eq!(sim.threads(1).run(&ranges, &board, &[], &CancelToken::new(), |_| {}), Some(m));
+    }
+
+    #[test]
+    fn test_cancel() {
+        let ranges: Vec<Range> = ["AA", "KK"].iter().map(|r| r.parse().unwrap()).collect();
+        let cancel = CancelToken::new();
+
+        // Cancelled from the callback after the first block; the thread may
+        // have started another by then.
+        let sim = ParallelEquity::new(50 * BLOCK).threads(1);
+        let m = sim.run(&ranges, &Board::new(), &[], &cancel, |_| cancel.cancel()).unwrap();
+        assert!(m.runouts < 50 * BLOCK && m.runouts % BLOCK == 0);
+
+        // Cancelled before it starts.
+        assert_eq!(sim.run(&ranges, &Board::new(), &[], &cancel, |_| {}), None);
+    }
+
+    #[test]
+    fn test_par_compare_pairs() {
+        let pairs: Vec<(Hand, Hand)> = hands(2 * MIN_PER_THREAD, 2).into_iter().zip(hands(2 * MIN_PER_THREAD, 3)).collect();
+        let expected: Vec<Ordering> = pairs.iter().map(|(a, b)| a.cmp(b)).collect();
+
+        assert_eq!(par_compare_pairs_with(&pairs, 3), expected);
+        assert_eq!(par_compare_pairs(&pairs), expected);
+    }
+}
@@ -0,0 +1,196 @@
+use super::{Card, Hand, HandParseError, Rank};
+use super::notation::CardNotation;
+
+// What a '1' rank letter means. Project Euler style data never uses it, but
+// some sources write the ace as '1'.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum OnePolicy {
+    AsOne,
+    AsAce,
+    Reject,
+}
+
+/// Settings shared by every parser in the crate. `ParseOptions::default()`
+/// is the strict parser behind `Card::from_code` and `Hand::from_str`; the
+/// other settings are opted into with the builder methods:
+///
+/// ```
+/// use misc::poker::notation::CardNotation;
+/// use misc::poker::parse::{OnePolicy, ParseOptions};
+///
+/// let opts = ParseOptions::default()
+///     .notation(CardNotation::GERMAN)
+///     .lenient(true)
+///     .one(OnePolicy::AsAce);
+/// assert!(opts.parse_hand("bh, dd, kc, 1s, ts").is_ok());
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ParseOptions {
+    pub notation: CardNotation,
+    // Accept lower case letters and ',', '|', '-', '[' and ']' between cards.
+    pub lenient: bool,
+    pub one: OnePolicy,
+    pub allow_duplicates: bool,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        ParseOptions {
+            notation: CardNotation::ENGLISH,
+            lenient: false,
+            one: OnePolicy::AsOne,
+            allow_duplicates: false,
+        }
+    }
+}
+
+impl ParseOptions {
+    pub fn strict() -> Self {
+        ParseOptions::default()
+    }
+
+    pub fn german() -> Self {
+        ParseOptions::default().notation(CardNotation::GERMAN)
+    }
+
+    pub fn french() -> Self {
+        ParseOptions::default().notation(CardNotation::FRENCH)
+    }
+
+    pub fn dutch() -> Self {
+        ParseOptions::default().notation(CardNotation::DUTCH)
+    }
+
+    pub fn notation(mut self, notation: CardNotation) -> Self {
+        self.notation = notation;
+        self
+    }
+
+    pub fn lenient(mut self, lenient: bool) -> Self {
+        self.lenient = lenient;
+        self
+    }
+
+    pub fn one(mut self, one: OnePolicy) -> Self {
+        self.one = one;
+        self
+    }
+
+    pub fn allow_duplicates(mut self, allow: bool) -> Self {
+        self.allow_duplicates = allow;
+        self
+    }
+
+    pub fn is_separator(&self, c: char) -> bool {
+        c.is_whitespace() || (self.lenient && ",|-[]".contains(c))
+    }
+
+    pub fn parse_card(&self, code: &str) -> Option<Card> {
+        let mut card = if self.lenient {
+            self.notation.parse(&code.to_uppercase())?
+        } else {
+            self.notation.parse(code)?
+        };
+
+        if card.rank == Rank::One {
+            match self.one {
+                OnePolicy::AsOne => (),
+                OnePolicy::AsAce => card.rank = Rank::Ace,
+                OnePolicy::Reject => return None,
+            }
+        }
+        Some(card)
+    }
+
+    pub fn parse_hand(&self, s: &str) -> Result<Hand, HandParseError> {
+        Hand::parse_with(s, self)
+    }
+}
+
+#[cfg(test)]
+mod parse_tests {
+    use super::*;
+    use super::super::Suit;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_default_is_strict_english() {
+        let opts = ParseOptions::default();
+
+        assert_eq!(opts.parse_card("QD"), Card::from_code("QD"));
+        assert_eq!(opts.parse_card("DD"), None);
+        assert_eq!(
+            opts.parse_hand("JH QD KC AS TS").unwrap(),
+            Hand::from_str("JH QD KC AS TS").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_locales() {
+        let jack = Card{rank: Rank::Jack, suit: Suit::Hearts};
+
+        assert_eq!(ParseOptions::german().parse_card("BH"), Some(jack));
+        assert_eq!(ParseOptions::french().parse_card("VH"), Some(jack));
+        assert_eq!(ParseOptions::dutch().parse_card("BH"), Some(jack));
+        assert_eq!(
+            ParseOptions::dutch().parse_card("HS"),
+            Some(Card{rank: Rank::King, suit: Suit::Spades})
+        );
+    }
+
+    #[test]
+    fn test_lenient() {
+        let strict = ParseOptions::strict();
+        let lenient = ParseOptions::default().lenient(true);
+
+        assert_eq!(strict.parse_card("jh"), None);
+        assert_eq!(lenient.parse_card("jh"), Card::from_code("JH"));
+        assert!(strict.parse_hand("JH,QD,KC,AS,TS").is_err());
+        assert_eq!(
+            lenient.parse_hand("[jh, qd, kc, as, ts]").unwrap().cards(),
+            Hand::from_str("JH QD KC AS TS").unwrap().cards()
+        );
+    }
+
+    #[test]
+    fn test_one_policy() {
+        let one = Card{rank: Rank::One, suit: Suit::Spades};
+        let ace = Card{rank: Rank::Ace, suit: Suit::Spades};
+
+        assert_eq!(ParseOptions::default().parse_card("1S"), Some(one));
+        assert_eq!(ParseOptions::default().one(OnePolicy::AsAce).parse_card("1S"), Some(ace));
+        assert_eq!(ParseOptions::default().one(OnePolicy::Reject).parse_card("1S"), None);
+    }
+
+    #[test]
+    fn test_tens() {
+        let hand = Hand::from_str("10H JH QH KH AH").unwrap();
+
+        assert_eq!(hand.cards(), Hand::from_str("TH JH QH KH AH").unwrap().cards());
+        assert_eq!(hand.to_string(), "AH KH QH JH TH");
+        assert_eq!(ParseOptions::default().lenient(true).parse_card("10c"), Card::from_code("TC"));
+        assert!(ParseOptions::default().lenient(true).parse_hand("10h,js,10d,2c,3c").is_ok());
+        assert!(matches!(Hand::from_str("10H JH QH KH 10"), Err(HandParseError::BadCard{index: 4, ..})));
+        assert_eq!(ParseOptions::default().parse_card("1S").map(|c| c.rank), Some(Rank::One));
+    }
+
+    #[test]
+    fn test_duplicates() {
+        assert!(matches!(
+            Hand::from_str("JH JH KC AS TS"),
+            Err(HandParseError::DuplicateCard(_))
+        ));
+        assert!(ParseOptions::default().allow_duplicates(true).parse_hand("JH JH KC AS TS").is_ok());
+    }
+
+    #[test]
+    fn test_parse_hand() {
+        let a = ParseOptions::german().parse_hand("BH DD KC AS TS").unwrap();
+        let b = ParseOptions::french().parse_hand("VH DD RC AS TS").unwrap();
+        let c = Hand::from_str("JH QD KC AS TS").unwrap();
+
+        assert_eq!(a.cards(), c.cards());
+        assert_eq!(b.cards(), c.cards());
+        assert!(Hand::from_str("BH DD KC AS TS").is_err());
+    }
+}
@@ -0,0 +1,135 @@
+use std::sync::OnceLock;
+
+use super::{Card, Hand, Rank, Suit};
+use super::value::HandValue;
+
+// Every distinct value a five card hand from one deck can take, weakest
+// first, with the number of hands below it.
+struct Table {
+    values: Vec<HandValue>,
+    below: Vec<u64>,
+    total: u64,
+}
+
+static TABLE: OnceLock<Table> = OnceLock::new();
+
+
+impl Hand {
+    /// Percentage of the 2,598,960 five card hands from one deck that this
+    /// hand beats. The table behind it is built on first use, from the rank
+    /// patterns rather than by dealing every hand, and shared afterwards.
+    pub fn percentile(&self) -> f64 {
+        let table = TABLE.get_or_init(build);
+        let i = table.values.partition_point(|v| *v < self.value());
+
+        100.0 * table.below[i] as f64 / table.total as f64
+    }
+}
+
+// Number of suit assignments for a rank pattern, split into those that are
+// flushes and those that are not. Only five different ranks can be suited.
+fn suitings(counts: &[u8]) -> (u64, u64) {
+    let choose = |n: u64, k: u64| (0..k).fold(1, |acc, i| acc * (n - i) / (i + 1));
+    let all: u64 = counts.iter().map(|&c| choose(4, c as u64)).product();
+
+    if counts.len() == 5 { (all - 4, 4) } else { (all, 0) }
+}
+
+fn build() -> Table {
+    let deck: Vec<Rank> = Rank::DECK.to_vec();
+    let mut counted: Vec<(HandValue, u64)> = vec![];
+
+    // Multisets of five deck ranks, no rank more than four times.
+    let mut pick = [0usize; 5];
+    loop {
+        let ranks: Vec<Rank> = pick.iter().map(|&i| deck[i]).collect();
+        if (0..deck.len()).all(|i| pick.iter().filter(|&&p| p == i).count() <= 4) {
+            let mut counts: Vec<u8> = vec![];
+            for (i, r) in ranks.iter().enumerate() {
+                if i == 0 || ranks[i - 1] != *r { counts.push(0); }
+                *counts.last_mut().unwrap() += 1;
+            }
+            let (plain, flush) = suitings(&counts);
+
+            // Suits dealt round robin never make a flush from two or more
+            // suits; all hearts is the flush.
+            let plain_hand = Hand::new(cards(&ranks, |i| Suit::ALL[i % 4]));
+            counted.push((plain_hand.value(), plain));
+            if flush > 0 {
+                counted.push((Hand::new(cards(&ranks, |_| Suit::Hearts)).value(), flush));
+            }
+        }
+
+        // Next non-decreasing index sequence.
+        let mut i = 4;
+        loop {
+            if pick[i] < deck.len() - 1 {
+                pick[i] += 1;
+                for j in i + 1..5 { pick[j] = pick[i]; }
+                break;
+            }
+            if i == 0 {
+                return tabulate(counted);
+            }
+            i -= 1;
+        }
+    }
+}
+
+fn cards(ranks: &[Rank], suit: impl Fn(usize) -> Suit) -> [Card; 5] {
+    let c = |i: usize| Card{rank: ranks[i], suit: suit(i)};
+    [c(0), c(1), c(2), c(3), c(4)]
+}
+
+fn tabulate(mut counted: Vec<(HandValue, u64)>) -> Table {
+    counted.sort_by_key(|&(v, _)| v);
+
+    let mut values: Vec<HandValue> = vec![];
+    let mut counts: Vec<u64> = vec![];
+    for (v, n) in counted {
+        if values.last() == Some(&v) {
+            *counts.last_mut().unwrap() += n;
+        } else {
+            values.push(v);
+            counts.push(n);
+        }
+    }
+
+    let mut below = Vec::with_capacity(values.len() + 1);
+    let mut total = 0;
+    for n in &counts {
+        below.push(total);
+        total += n;
+    }
+    below.push(total);
+
+    Table{values, below, total}
+}
+
+#[cfg(test)]
+mod percentile_tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn percentile(s: &str) -> f64 {
+        Hand::from_str(s).unwrap().percentile()
+    }
+
+    #[test]
+    fn test_table() {
+        let table = TABLE.get_or_init(build);
+
+        assert_eq!(table.total, 2_598_960);
+        assert_eq!(table.values.len(), 7462);
+    }
+
+    #[test]
+    fn test_percentile() {
+        assert_eq!(percentile("7D 5S 4H 3C 2D"), 0.0);
+        assert_eq!(percentile("TH JH QH KH AH"), 100.0 * (2_598_960.0 - 4.0) / 2_598_960.0);
+        // Any pair beats every high card hand, about half of all hands.
+        let pair = percentile("2H 2D 3C 4S 5H");
+        assert!(pair > 50.0 && pair < 51.0);
+        assert!(percentile("8C 8S KC 9H 9S") > pair);
+    }
+}
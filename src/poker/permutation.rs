@@ -0,0 +1,232 @@
+//! Numbering the orders of a deck. Every order of `n` cards gets an index
+//! from 0 to n! - 1, lexicographic in the order `Deck::new` puts them in:
+//! the unshuffled order is 0 and its reverse is the last. A full deck has
+//! 52! orders, about 2^225.6, so an index is a 256 bit `PermutationIndex`,
+//! 32 bytes to store a deal in. Counting up through indices enumerates
+//! every deal of a small deck.
+//!
+//! Indices go through the Lehmer code: for each card, how many cards after
+//! it come before it in the deck's order. That is a number in the factorial
+//! base, and `rank` and `unrank` read and write it by Horner's rule.
+
+use std::fmt;
+use std::str::FromStr;
+
+use super::{Card, Rank};
+use super::deck::Deck;
+
+/// An unsigned 256 bit number, enough for the index of any order of a
+/// deck. Compares as a number.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct PermutationIndex {
+    // Most significant first, so the derived order is numeric.
+    limbs: [u64; 4],
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseIndexError;
+
+impl fmt::Display for ParseIndexError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "expected a decimal number below 2^256")
+    }
+}
+
+impl std::error::Error for ParseIndexError {}
+
+impl PermutationIndex {
+    pub const ZERO: PermutationIndex = PermutationIndex{limbs: [0; 4]};
+
+    /// How many orders `n` cards have, n!, for `n` up to 57.
+    pub fn orders(n: usize) -> PermutationIndex {
+        (2..=n as u64).fold(PermutationIndex::from(1), |f, k| f.mul_add(k, 0).expect("n! fits for n <= 57"))
+    }
+
+    /// Big endian bytes.
+    pub fn to_bytes(&self) -> [u8; 32] {
+        let mut bytes = [0; 32];
+        for (chunk, limb) in bytes.chunks_mut(8).zip(&self.limbs) {
+            chunk.copy_from_slice(&limb.to_be_bytes());
+        }
+        bytes
+    }
+
+    pub fn from_bytes(bytes: [u8; 32]) -> PermutationIndex {
+        let mut limbs = [0; 4];
+        for (limb, chunk) in limbs.iter_mut().zip(bytes.chunks(8)) {
+            let mut word = [0; 8];
+            word.copy_from_slice(chunk);
+            *limb = u64::from_be_bytes(word);
+        }
+        PermutationIndex{limbs}
+    }
+
+    fn is_zero(&self) -> bool {
+        self.limbs == [0; 4]
+    }
+
+    // self * m + a, or `None` on overflow.
+    fn mul_add(self, m: u64, a: u64) -> Option<PermutationIndex> {
+        let mut limbs = [0; 4];
+        let mut carry = a as u128;
+        for i in (0..4).rev() {
+            let x = self.limbs[i] as u128 * m as u128 + carry;
+            limbs[i] = x as u64;
+            carry = x >> 64;
+        }
+        if carry == 0 { Some(PermutationIndex{limbs}) } else { None }
+    }
+
+    // (self / d, self % d).
+    fn div_rem(self, d: u64) -> (PermutationIndex, u64) {
+        let mut limbs = [0; 4];
+        let mut rem = 0u128;
+        for (limb, &word) in limbs.iter_mut().zip(&self.limbs) {
+            let x = rem << 64 | word as u128;
+            *limb = (x / d as u128) as u64;
+            rem = x % d as u128;
+        }
+        (PermutationIndex{limbs}, rem as u64)
+    }
+}
+
+impl From<u128> for PermutationIndex {
+    fn from(n: u128) -> Self {
+        PermutationIndex{limbs: [0, 0, (n >> 64) as u64, n as u64]}
+    }
+}
+
+impl fmt::Display for PermutationIndex {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_zero() { return write!(f, "0"); }
+
+        let mut digits = vec![];
+        let mut n = *self;
+        while !n.is_zero() {
+            let (q, r) = n.div_rem(10);
+            digits.push(b'0' + r as u8);
+            n = q;
+        }
+        digits.reverse();
+        write!(f, "{}", String::from_utf8(digits).unwrap())
+    }
+}
+
+impl FromStr for PermutationIndex {
+    type Err = ParseIndexError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.is_empty() { return Err(ParseIndexError); }
+        s.chars().try_fold(PermutationIndex::ZERO, |n, c| {
+            let digit = c.to_digit(10).ok_or(ParseIndexError)?;
+            n.mul_add(10, digit as u64).ok_or(ParseIndexError)
+        })
+    }
+}
+
+// Where `Deck::new` puts a card, `None` for `Rank::One`.
+fn position(card: &Card) -> Option<usize> {
+    let r = card.rank.index().checked_sub(Rank::Two.index())?;
+    Some(card.suit.index() * 13 + r)
+}
+
+/// The index of `cards`, in the order given, among all orders of the same
+/// cards; `None` if a card repeats or is a `Rank::One`.
+pub fn rank(cards: &[Card]) -> Option<PermutationIndex> {
+    let positions: Vec<usize> = cards.iter().map(position).collect::<Option<_>>()?;
+    let n = positions.len();
+
+    let mut index = PermutationIndex::ZERO;
+    for (i, p) in positions.iter().enumerate() {
+        if positions[i + 1..].contains(p) { return None; }
+        let smaller = positions[i + 1..].iter().filter(|&q| q < p).count();
+        index = index.mul_add((n - i) as u64, smaller as u64)?;
+    }
+    Some(index)
+}
+
+/// The order of `cards` with the given index; the order they are passed
+/// in does not matter. `None` if the index is n! or more.
+pub fn unrank(cards: &[Card], index: PermutationIndex) -> Option<Vec<Card>> {
+    let mut sorted = cards.to_vec();
+    sorted.sort_by_key(position);
+    let n = sorted.len();
+
+    // The Lehmer code, last digit first.
+    let mut code = vec![0; n];
+    let mut rest = index;
+    for i in (0..n).rev() {
+        let (q, r) = rest.div_rem((n - i) as u64);
+        code[i] = r as usize;
+        rest = q;
+    }
+    if !rest.is_zero() { return None; }
+
+    Some(code.into_iter().map(|c| sorted.remove(c)).collect())
+}
+
+impl Deck {
+    /// The index of this order among all orders of the cards left, counted
+    /// from the bottom card.
+    pub fn permutation_index(&self) -> Option<PermutationIndex> {
+        rank(self.cards())
+    }
+
+    /// The full deck in the order with the given index, 0 being the order
+    /// of `Deck::new`; `None` past 52! - 1.
+    pub fn from_permutation_index(index: PermutationIndex) -> Option<Deck> {
+        let mut deck = Deck::new();
+        *deck.cards_mut() = unrank(Deck::new().cards(), index)?;
+        Some(deck)
+    }
+}
+
+#[cfg(test)]
+mod permutation_tests {
+    use super::*;
+
+    fn cards(s: &str) -> Vec<Card> {
+        s.split_whitespace().map(|c| Card::from_code(c).unwrap()).collect()
+    }
+
+    #[test]
+    fn test_small() {
+        // All six orders of three cards, in order.
+        let three = cards("2H 3H 4H");
+        for n in 0..6u128 {
+            let order = unrank(&three, n.into()).unwrap();
+            assert_eq!(rank(&order), Some(n.into()));
+        }
+        assert_eq!(unrank(&three, 1u128.into()).unwrap(), cards("2H 4H 3H"));
+        assert_eq!(unrank(&three, 5u128.into()).unwrap(), cards("4H 3H 2H"));
+        assert_eq!(unrank(&three, 6u128.into()), None);
+        assert_eq!(rank(&cards("2H 2H")), None);
+    }
+
+    #[test]
+    fn test_deck() {
+        let orders = PermutationIndex::orders(52);
+        assert_eq!(orders.to_string(), "80658175170943878571660636856403766975289505440883277824000000000000");
+
+        assert_eq!(Deck::new().permutation_index(), Some(PermutationIndex::ZERO));
+        let deck = Deck::shuffled_with_seed(5);
+        let index = deck.permutation_index().unwrap();
+        assert!(index < orders);
+        assert_eq!(Deck::from_permutation_index(index).unwrap(), deck);
+        assert_eq!(Deck::from_permutation_index(orders), None);
+
+        let reversed: Vec<Card> = Deck::new().cards().iter().rev().cloned().collect();
+        assert_eq!(rank(&reversed).unwrap().mul_add(1, 1), Some(orders));
+    }
+
+    #[test]
+    fn test_encoding() {
+        let index = Deck::shuffled_with_seed(9).permutation_index().unwrap();
+        assert_eq!(index.to_string().parse::<PermutationIndex>(), Ok(index));
+        assert_eq!(PermutationIndex::from_bytes(index.to_bytes()), index);
+        assert_eq!(PermutationIndex::ZERO.to_string(), "0");
+        assert_eq!("340282366920938463463374607431768211456".parse::<PermutationIndex>().unwrap().to_bytes()[15], 1);
+        assert!("12a".parse::<PermutationIndex>().is_err());
+        assert!("".parse::<PermutationIndex>().is_err());
+    }
+}
@@ -0,0 +1,177 @@
+//! Models of shuffling by hand, for studying how much of it a deck needs.
+//! Each is applied to a `Deck` with a generator, and a `Shuffle` list runs
+//! several in turn, such as the casino procedure of riffle, riffle, strip,
+//! riffle and cut.
+//!
+//! - The riffle follows Gilbert, Shannon and Reeds: the deck is cut at a
+//!   binomial point, and cards drop from the two halves with chances in
+//!   proportion to the sizes left, every interleaving equally likely.
+//! - The overhand shuffle follows Pemantle: each gap between cards is a
+//!   break with chance `p`, and the packets land in reverse order.
+//! - The strip cut pulls the deck apart into a given number of packets at
+//!   random points and stacks them in reverse order.
+//!
+//! A riffle leaves at most two rising sequences of the order before, and
+//! `k` riffles at most 2^k, so `rising_sequences` after a few riffles shows
+//! how far from random a deck still is.
+
+use rand::Rng;
+use rand::seq::index;
+
+use super::Card;
+use super::deck::Deck;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Shuffle {
+    Riffle,
+    // A break between each pair of cards with this chance.
+    Overhand { p: f64 },
+    StripCut { packets: usize },
+}
+
+impl Deck {
+    pub fn riffle<R: Rng + ?Sized>(&mut self, rng: &mut R) {
+        let cards = self.cards_mut();
+        let n = cards.len();
+        let cut = (0..n).filter(|_| rng.gen_bool(0.5)).count();
+
+        // The vector holds the top last, so the top `cut` cards are the end.
+        let (bottom, top) = cards.split_at(n - cut);
+        let (mut b, mut t) = (bottom.len(), top.len());
+        let mut merged = Vec::with_capacity(n);
+        while b + t > 0 {
+            if rng.gen_range(0..b + t) < b {
+                b -= 1;
+                merged.push(bottom[b]);
+            } else {
+                t -= 1;
+                merged.push(top[t]);
+            }
+        }
+        merged.reverse();
+        *cards = merged;
+    }
+
+    pub fn overhand<R: Rng + ?Sized>(&mut self, p: f64, rng: &mut R) {
+        let n = self.len();
+        let breaks: Vec<usize> = (1..n).filter(|_| rng.gen_bool(p)).collect();
+        reverse_packets(self.cards_mut(), &breaks);
+    }
+
+    /// # Panics
+    ///
+    /// If there are no packets or more packets than cards.
+    pub fn strip_cut<R: Rng + ?Sized>(&mut self, packets: usize, rng: &mut R) {
+        let n = self.len();
+        assert!(packets >= 1 && packets <= n.max(1), "between one packet and one per card");
+
+        let mut breaks: Vec<usize> = index::sample(rng, n.saturating_sub(1), packets - 1)
+            .into_iter().map(|i| i + 1).collect();
+        breaks.sort_unstable();
+        reverse_packets(self.cards_mut(), &breaks);
+    }
+
+    /// Applies `shuffles` in order.
+    pub fn apply<R: Rng + ?Sized>(&mut self, shuffles: &[Shuffle], rng: &mut R) {
+        for shuffle in shuffles {
+            match *shuffle {
+                Shuffle::Riffle => self.riffle(rng),
+                Shuffle::Overhand { p } => self.overhand(p, rng),
+                Shuffle::StripCut { packets } => self.strip_cut(packets, rng),
+            }
+        }
+    }
+}
+
+// Splits `cards` at `breaks`, ascending positions, and stacks the packets
+// in reverse order, each packet keeping its own.
+fn reverse_packets(cards: &mut Vec<Card>, breaks: &[usize]) {
+    let mut bounds = vec![0];
+    bounds.extend_from_slice(breaks);
+    bounds.push(cards.len());
+
+    let packets: Vec<&[Card]> = bounds.windows(2).rev().map(|w| &cards[w[0]..w[1]]).collect();
+    *cards = packets.concat();
+}
+
+/// How many rising sequences `cards` holds relative to `before`, the order
+/// they were in: the runs of consecutive positions of `before` that appear
+/// in increasing order, interleaved with each other. An unshuffled deck has
+/// one; a random one about half as many as it has cards.
+///
+/// # Panics
+///
+/// If `cards` holds a card that `before` does not.
+pub fn rising_sequences(before: &[Card], cards: &[Card]) -> usize {
+    let mut at = vec![0; before.len()];
+    for (i, card) in cards.iter().enumerate() {
+        let j = before.iter().position(|c| c == card).expect("the same cards");
+        at[j] = i;
+    }
+    1 + at.windows(2).filter(|w| w[1] < w[0]).count()
+}
+
+#[cfg(test)]
+mod physical_tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    fn sorted(deck: &Deck) -> Vec<Card> {
+        let mut cards = deck.cards().to_vec();
+        cards.sort_by_key(|c| (c.suit, c.rank));
+        cards
+    }
+
+    #[test]
+    fn test_riffle() {
+        let mut rng = ChaCha8Rng::seed_from_u64(3);
+        let fresh = Deck::new();
+
+        let mut deck = fresh.clone();
+        deck.riffle(&mut rng);
+        assert_eq!(sorted(&deck), sorted(&fresh));
+        assert!(rising_sequences(fresh.cards(), deck.cards()) <= 2);
+
+        for k in 2..=4 {
+            deck.riffle(&mut rng);
+            assert!(rising_sequences(fresh.cards(), deck.cards()) <= 1 << k);
+        }
+        assert_eq!(rising_sequences(fresh.cards(), fresh.cards()), 1);
+    }
+
+    #[test]
+    fn test_overhand_and_strip() {
+        let mut rng = ChaCha8Rng::seed_from_u64(5);
+        let fresh = Deck::new();
+
+        let mut deck = fresh.clone();
+        deck.overhand(0.0, &mut rng);
+        assert_eq!(deck, fresh);
+        deck.overhand(1.0, &mut rng);
+        assert_eq!(deck.cards(), &fresh.cards().iter().rev().cloned().collect::<Vec<_>>()[..]);
+
+        let mut deck = fresh.clone();
+        deck.strip_cut(4, &mut rng);
+        assert_eq!(sorted(&deck), sorted(&fresh));
+        // Four packets in reverse: three places where the order breaks.
+        assert_eq!(rising_sequences(fresh.cards(), deck.cards()), 4);
+
+        let mut deck = fresh.clone();
+        deck.strip_cut(1, &mut rng);
+        assert_eq!(deck, fresh);
+    }
+
+    #[test]
+    fn test_apply() {
+        let casino = [Shuffle::Riffle, Shuffle::Riffle, Shuffle::StripCut{packets: 5}, Shuffle::Riffle, Shuffle::Overhand{p: 0.2}];
+
+        let mut a = Deck::new();
+        let mut b = Deck::new();
+        a.apply(&casino, &mut ChaCha8Rng::seed_from_u64(8));
+        b.apply(&casino, &mut ChaCha8Rng::seed_from_u64(8));
+        assert_eq!(a, b);
+        assert_eq!(sorted(&a), sorted(&Deck::new()));
+        assert_ne!(a, Deck::new());
+    }
+}
@@ -0,0 +1,291 @@
+//! Bulk processing of line based input in stages, each on its own threads:
+//!
+//! ```text
+//! reader -> parsers -> evaluators -> aggregator
+//! ```
+//!
+//! The reader runs on one thread, parsers and evaluators on as many as
+//! configured, and the aggregator on the calling thread. Stages hand lines
+//! on in batches over bounded channels, so a slow stage holds back the ones
+//! before it instead of letting input pile up in memory, and reading
+//! overlaps with everything after it.
+//!
+//! Results reach the aggregator in no particular order, each with the line
+//! it came from. The first line to fail stops the reader; what was already
+//! read is still finished, and that line's error is returned.
+
+use std::fmt;
+use std::io::{self, BufRead};
+use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{sync_channel, Receiver, SyncSender};
+use std::sync::Mutex;
+use std::thread;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Pipeline {
+    pub parsers: usize,
+    pub evaluators: usize,
+    // Batches each channel holds before its sender waits.
+    pub capacity: usize,
+    // Lines per batch.
+    pub batch: usize,
+}
+
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum PipelineError<E> {
+    Io(io::Error),
+    // Parsing or evaluating a line failed; lines count from 1.
+    Line { line: usize, error: E },
+}
+
+impl<E: fmt::Display> fmt::Display for PipelineError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PipelineError::Io(e) => write!(f, "{}", e),
+            PipelineError::Line { line, error } => write!(f, "line {}: {}", line, error),
+        }
+    }
+}
+
+impl<E: std::error::Error + 'static> std::error::Error for PipelineError<E> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            PipelineError::Io(e) => Some(e),
+            PipelineError::Line { error, .. } => Some(error),
+        }
+    }
+}
+
+impl<E> From<io::Error> for PipelineError<E> {
+    fn from(e: io::Error) -> Self {
+        PipelineError::Io(e)
+    }
+}
+
+type Batch<T> = Vec<(usize, T)>;
+
+impl Default for Pipeline {
+    // The cores split between parsing and evaluating.
+    fn default() -> Self {
+        let cores = thread::available_parallelism().map(NonZeroUsize::get).unwrap_or(1);
+        Pipeline{parsers: (cores / 2).max(1), evaluators: (cores - cores / 2).max(1), capacity: 16, batch: 256}
+    }
+}
+
+impl Pipeline {
+    pub fn parsers(self, parsers: usize) -> Self {
+        Pipeline{parsers: parsers.max(1), ..self}
+    }
+
+    pub fn evaluators(self, evaluators: usize) -> Self {
+        Pipeline{evaluators: evaluators.max(1), ..self}
+    }
+
+    pub fn capacity(self, capacity: usize) -> Self {
+        Pipeline{capacity: capacity.max(1), ..self}
+    }
+
+    pub fn batch(self, batch: usize) -> Self {
+        Pipeline{batch: batch.max(1), ..self}
+    }
+
+    /// Runs every line of `input`, without its line ending, through
+    /// `parse` and then `evaluate`, and folds the results into `init`
+    /// with `aggregate`, which also gets each result's line.
+    pub fn run<R, T, U, E, A, P, V, F>(
+        &self,
+        input: R,
+        parse: P,
+        evaluate: V,
+        init: A,
+        mut aggregate: F,
+    ) -> Result<A, PipelineError<E>>
+    where
+        R: BufRead + Send,
+        T: Send,
+        U: Send,
+        E: Send,
+        P: Fn(&str) -> Result<T, E> + Sync,
+        V: Fn(T) -> Result<U, E> + Sync,
+        F: FnMut(A, usize, U) -> A,
+    {
+        let stop = AtomicBool::new(false);
+        let failed: Mutex<Option<(usize, E)>> = Mutex::new(None);
+
+        let (lines_tx, lines_rx) = sync_channel::<Batch<String>>(self.capacity);
+        let (parsed_tx, parsed_rx) = sync_channel::<Batch<T>>(self.capacity);
+        let (done_tx, done_rx) = sync_channel::<Batch<U>>(self.capacity);
+        let (lines_rx, parsed_rx) = (Mutex::new(lines_rx), Mutex::new(parsed_rx));
+
+        let (acc, read) = thread::scope(|s| {
+            let reader = s.spawn(|| read(input, self.batch, &stop, lines_tx));
+
+            for _ in 0..self.parsers {
+                let tx = parsed_tx.clone();
+                s.spawn(|| work(&lines_rx, tx, |line: String| parse(&line), &stop, &failed));
+            }
+            for _ in 0..self.evaluators {
+                let tx = done_tx.clone();
+                s.spawn(|| work(&parsed_rx, tx, &evaluate, &stop, &failed));
+            }
+            // The channels close once the workers holding the clones finish.
+            drop((parsed_tx, done_tx));
+
+            let mut acc = init;
+            for batch in done_rx {
+                for (line, result) in batch {
+                    acc = aggregate(acc, line, result);
+                }
+            }
+            (acc, reader.join().unwrap())
+        });
+
+        if let Some((line, error)) = failed.into_inner().unwrap() {
+            return Err(PipelineError::Line{line, error});
+        }
+        read?;
+        Ok(acc)
+    }
+}
+
+// Sends the lines of `input` on in batches until the end, an error or a
+// stop.
+fn read<R: BufRead>(mut input: R, size: usize, stop: &AtomicBool, tx: SyncSender<Batch<String>>) -> io::Result<()> {
+    let mut n = 0;
+    loop {
+        let mut batch = Vec::with_capacity(size);
+        while batch.len() < size {
+            let mut line = String::new();
+            if input.read_line(&mut line)? == 0 { break; }
+            n += 1;
+            let len = line.trim_end_matches(&['\n', '\r'][..]).len();
+            line.truncate(len);
+            batch.push((n, line));
+        }
+
+        let last = batch.len() < size;
+        if !batch.is_empty() && tx.send(batch).is_err() { return Ok(()); }
+        if last || stop.load(Ordering::Relaxed) { return Ok(()); }
+    }
+}
+
+// Applies `f` to each batch taken from the shared receiver. A batch stops
+// at its first failure, which is kept if no earlier line has failed.
+fn work<T, U, E, F>(
+    rx: &Mutex<Receiver<Batch<T>>>,
+    tx: SyncSender<Batch<U>>,
+    f: F,
+    stop: &AtomicBool,
+    failed: &Mutex<Option<(usize, E)>>,
+) where
+    F: Fn(T) -> Result<U, E>,
+{
+    loop {
+        // The lock is let go before the batch is worked on.
+        let batch = match rx.lock().unwrap().recv() {
+            Ok(batch) => batch,
+            Err(_) => return,
+        };
+
+        let mut out = Vec::with_capacity(batch.len());
+        for (line, item) in batch {
+            match f(item) {
+                Ok(u) => out.push((line, u)),
+                Err(error) => {
+                    stop.store(true, Ordering::Relaxed);
+                    let mut failed = failed.lock().unwrap();
+                    if failed.as_ref().map_or(true, |(l, _)| line < *l) {
+                        *failed = Some((line, error));
+                    }
+                    break;
+                },
+            }
+        }
+        if tx.send(out).is_err() { return; }
+    }
+}
+
+#[cfg(test)]
+mod pipeline_tests {
+    use super::*;
+    use super::super::Hand;
+    use std::cmp;
+
+    // One line per deal: two hands of five cards.
+    const DEALS: &str = "\
+        8C TS KC 9H 4S 7D 2S 5D 3S AC\n\
+        5C AD 5D AC 9C 7C 5H 8D TD KS\n\
+        3H 7H 6S KC JS QH TD JC 2D 8S\n\
+        TH 8H 5C QS TC 9H 4D JC KS JS\n\
+        7C 5H KC QH JD AS KH 4C AD 4S\n";
+
+    fn deal(line: &str) -> Option<(Hand, Hand)> {
+        let a = line.get(..14)?.parse().ok()?;
+        let b = line.get(15..)?.parse().ok()?;
+        Some((a, b))
+    }
+
+    fn count(pipeline: Pipeline, text: &str) -> Result<(u32, u32), PipelineError<String>> {
+        pipeline.run(
+            text.as_bytes(),
+            |line| deal(line).ok_or_else(|| format!("bad deal '{}'", line)),
+            |(a, b)| Ok(a.cmp(&b)),
+            (0, 0),
+            |(one, two), _, ordering| match ordering {
+                cmp::Ordering::Greater => (one + 1, two),
+                _ => (one, two + 1),
+            },
+        )
+    }
+
+    #[test]
+    fn test_run() {
+        let text = DEALS.repeat(200);
+        let one = DEALS.lines().filter(|l| deal(l).is_some_and(|(a, b)| a > b)).count() as u32;
+
+        assert_eq!(count(Pipeline::default(), DEALS).unwrap(), (one, 5 - one));
+        assert_eq!(count(Pipeline::default().batch(3).capacity(1), &text).unwrap(), (200 * one, 200 * (5 - one)));
+        assert_eq!(count(Pipeline::default().parsers(1).evaluators(1).batch(1), &text).unwrap(), (200 * one, 200 * (5 - one)));
+        assert_eq!(count(Pipeline::default(), "").unwrap(), (0, 0));
+
+        // Every line reaches the aggregator once.
+        let lines = Pipeline::default().batch(7).run(
+            text.as_bytes(),
+            |line| Ok::<_, ()>(line.len()),
+            Ok,
+            vec![],
+            |mut seen, line, _| { seen.push(line); seen },
+        ).unwrap();
+        let mut sorted = lines.clone();
+        sorted.sort_unstable();
+        assert_eq!(sorted, (1..=1000).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_errors() {
+        let mut text = DEALS.repeat(100);
+        text.insert_str(DEALS.len() * 40, "XX\n");
+        text.push_str("YY\n");
+
+        for pipeline in [Pipeline::default(), Pipeline::default().batch(1).parsers(4)] {
+            match count(pipeline, &text) {
+                Err(PipelineError::Line{line, error}) => {
+                    assert_eq!(line, 201);
+                    assert_eq!(error, "bad deal 'XX'");
+                },
+                other => panic!("expected a failed line, got {:?}", other),
+            }
+        }
+
+        let failed: Result<(), PipelineError<String>> = Pipeline::default().run(
+            "1\n2\n".as_bytes(),
+            |_| Ok(()),
+            |()| Err::<(), _>("no".to_string()),
+            (),
+            |_, _, _| (),
+        );
+        assert_eq!(failed.unwrap_err().to_string(), "line 1: no");
+    }
+}
@@ -0,0 +1,249 @@
+//! A value for each of the 169 starting hands, laid out the way hand charts
+//! draw them: a 13 by 13 grid with Aces first, pairs on the diagonal,
+//! suited hands above it and offsuit hands below. Row `i` and column `j`
+//! name the ranks; the cell is suited when the row's rank is the higher.
+//!
+//! Two groupings come built in, for bots and trainers that want sensible
+//! defaults without data files of their own: Sklansky and Malmuth's eight
+//! groups, and ten tiers by equity against one random hand.
+
+use std::fmt;
+use std::ops::{Index, IndexMut};
+
+use super::Rank;
+use super::notation::CardNotation;
+use super::range::{Combo, Range};
+use super::starting::StartingHand;
+
+// Sklansky and Malmuth's groups, strongest first. Hands in none of them
+// are group 9.
+const SKLANSKY: [&str; 8] = [
+    "AA KK QQ JJ AKs",
+    "TT AQs AJs KQs AKo",
+    "99 JTs QJs KJs ATs AQo",
+    "T9s KQo 88 QTs 98s J9s AJo KTs",
+    "77 87s Q9s T8s KJo QJo JTo 76s 97s A9s A8s A7s A6s A5s A4s A3s A2s 65s",
+    "66 ATo 55 86s KTo QTo 54s K9s J8s 75s",
+    "44 J9o 64s T9o 53s 33 98o 43s 22 K8s K7s K6s K5s K4s K3s K2s T7s Q8s",
+    "87o A9o Q9o 76o 42s 32s 96s 85s J8o J7s 65o 54o 74s K9o T8o",
+];
+
+// Every starting hand by its equity against one random hand, best first,
+// from two million samples each. Neighbours within a few tenths of a
+// percent may swap places, which does not move them out of their tier.
+const VS_RANDOM: &str = "\
+    AA KK QQ JJ TT 99 88 AKs 77 AQs AJs AKo ATs AQo AJo 66 KQs A9s ATo KJs \
+    A8s KTs KQo A7s A9o KJo QJs 55 K9s A6s KTo A8o QTs A5s A7o K8s QJo A4s \
+    K9o A6o Q9s K7s JTs QTo A3s 44 A5o K6s A2s K8o Q8s K5s A4o J9s Q9o K7o \
+    JTo A3o K4s Q7s K6o J8s T9s K3s A2o Q8o Q6s 33 J9o K5o K2s Q5s J7s T8s \
+    K4o Q7o Q4s T9o J8o K3o Q6o Q3s 98s J6s T7s K2o 22 Q2s Q5o J5s J7o T8o \
+    97s Q4o T6s J4s 98o Q3o J3s 87s J6o T7o 96s Q2o J2s T5s J5o 97o T4s 86s \
+    T6o J4o 95s T3s 76s J3o 87o T2s 96o 85s J2o T5o 94s 75s T4o 86o 65s 93s \
+    95o 84s T3o 76o 92s 74s T2o 64s 85o 83s 94o 75o 82s 54s 65o 73s 93o 63s \
+    84o 92o 74o 53s 64o 72s 62s 83o 43s 82o 54o 73o 52s 63o 42s 53o 32s 72o \
+    62o 43o 52o 42o 32o";
+
+/// A value per starting hand, `T` of any type: a group number, a raise
+/// frequency, an equity.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PreflopMatrix<T> {
+    // Row-major, 13 by 13.
+    cells: Vec<T>,
+}
+
+// Deck ranks, Ace down to Two, as the grid orders them.
+fn rank_at(i: usize) -> Rank {
+    Rank::ALL[13 - i]
+}
+
+fn row_of(rank: Rank) -> usize {
+    13 - rank as usize
+}
+
+impl<T> PreflopMatrix<T> {
+    pub fn from_fn<F: FnMut(StartingHand) -> T>(mut f: F) -> Self {
+        let cells = (0..169).map(|k| f(PreflopMatrix::<T>::hand_at(k / 13, k % 13))).collect();
+        PreflopMatrix{cells}
+    }
+
+    /// The hand in row `row` and column `col`.
+    pub fn hand_at(row: usize, col: usize) -> StartingHand {
+        assert!(row < 13 && col < 13, "row and column below 13");
+        StartingHand::new(rank_at(row), rank_at(col), row < col).unwrap()
+    }
+
+    /// The row and column of `hand`.
+    pub fn cell_of(hand: StartingHand) -> (usize, usize) {
+        let (high, low) = (row_of(hand.high()), row_of(hand.low()));
+        if hand.is_suited() { (high, low) } else { (low, high) }
+    }
+
+    pub fn get(&self, hand: StartingHand) -> &T {
+        let (row, col) = PreflopMatrix::<T>::cell_of(hand);
+        &self.cells[row * 13 + col]
+    }
+
+    pub fn set(&mut self, hand: StartingHand, value: T) {
+        let (row, col) = PreflopMatrix::<T>::cell_of(hand);
+        self.cells[row * 13 + col] = value;
+    }
+
+    /// Every hand and its value, row by row.
+    pub fn iter(&self) -> impl Iterator<Item = (StartingHand, &T)> {
+        self.cells.iter().enumerate().map(|(k, v)| (PreflopMatrix::<T>::hand_at(k / 13, k % 13), v))
+    }
+
+    pub fn map<U, F: FnMut(&T) -> U>(&self, f: F) -> PreflopMatrix<U> {
+        PreflopMatrix{cells: self.cells.iter().map(f).collect()}
+    }
+}
+
+impl<T> Index<StartingHand> for PreflopMatrix<T> {
+    type Output = T;
+
+    fn index(&self, hand: StartingHand) -> &T {
+        self.get(hand)
+    }
+}
+
+impl<T> IndexMut<StartingHand> for PreflopMatrix<T> {
+    fn index_mut(&mut self, hand: StartingHand) -> &mut T {
+        let (row, col) = PreflopMatrix::<T>::cell_of(hand);
+        &mut self.cells[row * 13 + col]
+    }
+}
+
+impl PreflopMatrix<u8> {
+    /// Sklansky and Malmuth's groups, 1 for the strongest hands to 8, and 9
+    /// for the hands in no group.
+    pub fn sklansky() -> Self {
+        grouped(&SKLANSKY, 9)
+    }
+
+    /// Ten tiers by equity against a random hand, 1 the best. The first
+    /// nine hold 17 hands each and the last 16.
+    pub fn vs_random() -> Self {
+        let order: Vec<&str> = VS_RANDOM.split_whitespace().collect();
+        let tiers: Vec<String> = order.chunks(17).map(|c| c.join(" ")).collect();
+        let tiers: Vec<&str> = tiers.iter().map(String::as_str).collect();
+        grouped(&tiers, 10)
+    }
+
+    /// The hands in group `group`.
+    pub fn hands_in(&self, group: u8) -> Vec<StartingHand> {
+        self.iter().filter(|(_, &g)| g == group).map(|(h, _)| h).collect()
+    }
+
+    /// The hands in `group` or a stronger one.
+    pub fn hands_up_to(&self, group: u8) -> Vec<StartingHand> {
+        self.iter().filter(|(_, &g)| g <= group).map(|(h, _)| h).collect()
+    }
+
+    /// Every combo of the hands in `group` or a stronger one.
+    pub fn range_up_to(&self, group: u8) -> Range {
+        Range::from_combos(self.hands_up_to(group).iter().flat_map(|h| h.combos()).map(Combo::new).collect())
+    }
+}
+
+// A matrix of `rest` with the hands of each list numbered from 1.
+fn grouped(groups: &[&str], rest: u8) -> PreflopMatrix<u8> {
+    let mut matrix = PreflopMatrix::from_fn(|_| rest);
+    for (i, hands) in groups.iter().enumerate() {
+        for hand in hands.split_whitespace() {
+            matrix.set(hand.parse().unwrap(), i as u8 + 1);
+        }
+    }
+    matrix
+}
+
+/// All 169 starting hands by their equity against one random hand, the
+/// best first, the order "top 15%" ranges are taken in.
+pub fn by_equity() -> Vec<StartingHand> {
+    VS_RANDOM.split_whitespace().map(|h| h.parse().unwrap()).collect()
+}
+
+/// A hand's Sklansky and Malmuth group, 1 to 8, or 9 for none.
+pub fn sklansky_group(hand: StartingHand) -> u8 {
+    PreflopMatrix::sklansky()[hand]
+}
+
+/// A hand's tier by equity against a random hand, 1 to 10.
+pub fn vs_random_tier(hand: StartingHand) -> u8 {
+    PreflopMatrix::vs_random()[hand]
+}
+
+// The grid with rank letters along the top and down the side.
+impl<T: fmt::Display> fmt::Display for PreflopMatrix<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let n = CardNotation::ENGLISH;
+        let cells: Vec<String> = self.cells.iter().map(|c| c.to_string()).collect();
+        let width = cells.iter().map(|c| c.len()).max().unwrap_or(1);
+
+        write!(f, " ")?;
+        for col in 0..13 {
+            write!(f, " {:>w$}", n.rank_char(rank_at(col)), w = width)?;
+        }
+        for row in 0..13 {
+            write!(f, "\n{}", n.rank_char(rank_at(row)))?;
+            for col in 0..13 {
+                write!(f, " {:>w$}", cells[row * 13 + col], w = width)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod preflop_tests {
+    use super::*;
+
+    fn hand(s: &str) -> StartingHand {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_layout() {
+        assert_eq!(PreflopMatrix::<u8>::hand_at(0, 0), hand("AA"));
+        assert_eq!(PreflopMatrix::<u8>::hand_at(0, 1), hand("AKs"));
+        assert_eq!(PreflopMatrix::<u8>::hand_at(1, 0), hand("AKo"));
+        assert_eq!(PreflopMatrix::<u8>::hand_at(12, 11), hand("32o"));
+
+        for h in StartingHand::all() {
+            let (row, col) = PreflopMatrix::<u8>::cell_of(h);
+            assert_eq!(PreflopMatrix::<u8>::hand_at(row, col), h);
+        }
+
+        let mut m = PreflopMatrix::from_fn(|h| h.combos().len());
+        assert_eq!(m.iter().map(|(_, &n)| n).sum::<usize>(), 1326);
+        m[hand("72o")] = 0;
+        assert_eq!(m.get(hand("72o")), &0);
+        assert!(PreflopMatrix::sklansky().to_string().starts_with("  A K Q J T 9 8 7 6 5 4 3 2\nA 1 1 2 2 3 5 5"));
+    }
+
+    #[test]
+    fn test_sklansky() {
+        let m = PreflopMatrix::sklansky();
+
+        assert_eq!(m.hands_in(1).len(), 5);
+        assert_eq!(m.hands_up_to(8).len(), 5 + 5 + 6 + 8 + 18 + 10 + 18 + 15);
+        assert_eq!(sklansky_group(hand("AKs")), 1);
+        assert_eq!(sklansky_group(hand("A5s")), 5);
+        assert_eq!(sklansky_group(hand("72o")), 9);
+        assert_eq!(m.range_up_to(1).len(), 6 * 4 + 4);
+    }
+
+    #[test]
+    fn test_vs_random() {
+        let order: Vec<&str> = VS_RANDOM.split_whitespace().collect();
+        let mut sorted = order.clone();
+        sorted.sort();
+        sorted.dedup();
+        assert_eq!(sorted.len(), 169);
+
+        let m = PreflopMatrix::vs_random();
+        assert_eq!(vs_random_tier(hand("AA")), 1);
+        assert_eq!(vs_random_tier(hand("32o")), 10);
+        assert_eq!(m.hands_in(10).len(), 16);
+        assert!(m.hands_in(1).contains(&hand("AKo")));
+    }
+}
@@ -0,0 +1,31 @@
+//! The most used poker types, for glob import:
+//!
+//! ```
+//! use misc::poker::prelude::*;
+//!
+//! let hand: Hand = "8C 8S KC 9H 9S".parse().unwrap();
+//! assert_eq!(hand.value().category(), Category::TwoPairs);
+//! ```
+
+pub use super::{Card, Category, Hand, HandParseError, Rank, Suit};
+pub use super::{Board, Cards, Deal, Error, HandValue};
+pub use super::deck::Deck;
+pub use super::equity::{Equity, Simulator};
+pub use super::notation::CardNotation;
+pub use super::parse::ParseOptions;
+
+#[cfg(test)]
+mod prelude_tests {
+    use super::*;
+
+    #[test]
+    fn test_prelude() {
+        let board = Cards::board("2C 7D 9H").unwrap();
+        let deck = Deck::new();
+        let card = Card{rank: Rank::Ace, suit: Suit::Spades};
+
+        assert_eq!(board.len(), 3);
+        assert!(deck.cards().contains(&card));
+        assert_eq!(ParseOptions::default().parse_card("AS"), Some(card));
+    }
+}
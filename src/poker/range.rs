@@ -0,0 +1,339 @@
+use std::fmt;
+use std::str::FromStr;
+
+use super::{Card, Rank};
+use super::notation::CardNotation;
+use super::parse::{OnePolicy, ParseOptions};
+use super::preflop;
+use super::starting::StartingHand;
+
+// Two cards out of 52.
+const COMBOS: usize = 1326;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Combo {
+    pub cards: [Card; 2],
+    pub weight: f64,
+}
+
+impl Combo {
+    pub fn new(cards: [Card; 2]) -> Self {
+        Combo{cards, weight: 1.0}
+    }
+
+    pub fn overlaps(&self, cards: &[Card]) -> bool {
+        self.cards.iter().any(|c| cards.contains(c))
+    }
+
+    fn same_cards(&self, other: &Combo) -> bool {
+        self.cards == other.cards || self.cards == [other.cards[1], other.cards[0]]
+    }
+}
+
+/// A weighted set of hole card combos. Written as a comma separated list of
+/// starting hands ("AKs", or "AK" for suited and offsuit), which stand for
+/// all their combos, or single combos ("AHKH"), each optionally weighted:
+/// "QQ,AKs:0.5,AHQH". Weights run from 0 to 1. Runs of hands are written the usual ways:
+///
+/// - "22+" for every pair from Twos up, "ATs+" for ATs up to AKs;
+/// - "A5s-A2s" and "99-66" for the hands between two, ends included;
+/// - "top 15%" for the strongest hands against a random hand, in the order
+///   of `preflop::by_equity`, until they make 15% of the 1326 combos.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Range {
+    combos: Vec<Combo>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct RangeParseError(pub String);
+
+impl fmt::Display for RangeParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "bad range entry '{}'", self.0)
+    }
+}
+
+impl std::error::Error for RangeParseError {}
+
+impl Range {
+    pub fn new() -> Self {
+        Range::default()
+    }
+
+    pub fn from_combos(combos: Vec<Combo>) -> Self {
+        let mut range = Range::new();
+        for combo in combos {
+            range.add(combo);
+        }
+        range
+    }
+
+    // A combo already in the range has its weight replaced.
+    pub fn add(&mut self, combo: Combo) {
+        match self.combos.iter_mut().find(|c| c.same_cards(&combo)) {
+            Some(c) => c.weight = combo.weight,
+            None => self.combos.push(combo),
+        }
+    }
+
+    pub fn combos(&self) -> &[Combo] {
+        &self.combos
+    }
+
+    pub fn len(&self) -> usize {
+        self.combos.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.combos.is_empty()
+    }
+
+    pub fn weight(&self) -> f64 {
+        self.combos.iter().map(|c| c.weight).sum()
+    }
+
+    /// The strongest starting hands, by equity against a random hand,
+    /// taking each whole while the combos before it fall short of
+    /// `percent` of all 1326.
+    pub fn top(percent: f64) -> Range {
+        let target = percent / 100.0 * COMBOS as f64;
+        let mut combos = vec![];
+        for hand in preflop::by_equity() {
+            if combos.len() as f64 >= target { break; }
+            combos.extend(hand.combos().into_iter().map(Combo::new));
+        }
+        Range{combos}
+    }
+
+    // The combos still possible once `dead` cards are known to be out.
+    pub fn without_blocked(&self, dead: &[Card]) -> Range {
+        Range{combos: self.combos.iter().filter(|c| !c.overlaps(dead)).cloned().collect()}
+    }
+
+    // Removes each combo of `other`, however it is weighted there.
+    pub fn subtract(&self, other: &Range) -> Range {
+        Range{
+            combos: self.combos.iter()
+                .filter(|c| !other.combos.iter().any(|o| o.same_cards(c)))
+                .cloned()
+                .collect(),
+        }
+    }
+}
+
+impl FromStr for Range {
+    type Err = RangeParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Range::parse_with(s, &ParseOptions::default())
+    }
+}
+
+impl Range {
+    /// Reads a range with the rank and suit letters, case and '1' policy
+    /// of `options`, so "BB+,AD:0.5" is Jacks up and half of AQ in German.
+    /// The 's', 'o', '+', '-' and "top" of the grammar stay as they are.
+    pub fn parse_with(s: &str, options: &ParseOptions) -> Result<Self, RangeParseError> {
+        let mut range = Range::new();
+
+        for entry in s.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            let err = || RangeParseError(entry.to_string());
+            let (hand, weight) = match entry.split_once(':') {
+                Some((hand, w)) => (hand, w.parse().map_err(|_| err())?),
+                None => (entry, 1.0),
+            };
+            if !(0.0..=1.0).contains(&weight) { return Err(err()); }
+
+            if let Some(percent) = hand.strip_prefix("top") {
+                let percent: f64 = percent.trim().strip_suffix('%').and_then(|p| p.trim().parse().ok()).ok_or_else(err)?;
+                if !(0.0..=100.0).contains(&percent) { return Err(err()); }
+                for combo in Range::top(percent).combos {
+                    range.add(Combo{weight, ..combo});
+                }
+                continue;
+            }
+
+            let hand = options.notation.tens(hand);
+            let cards = if hand.chars().count() == 4 && !hand.ends_with('+') {
+                let split = hand.char_indices().nth(2).map_or(0, |(i, _)| i);
+                let a = options.parse_card(&hand[..split]).ok_or_else(err)?;
+                let b = options.parse_card(&hand[split..]).ok_or_else(err)?;
+                if a == b { return Err(err()); }
+                vec![[a, b]]
+            } else {
+                english(&hand, options).and_then(|e| hands(&e)).ok_or_else(err)?.iter().flat_map(StartingHand::combos).collect()
+            };
+
+            for cards in cards {
+                range.add(Combo{cards, weight});
+            }
+        }
+        Ok(range)
+    }
+}
+
+// `entry` with its rank letters in `options` written as English ones, for
+// `hands`, which reads starting hands the way `StartingHand` prints them.
+// None if it has a letter that is neither a rank there nor 's', 'o', '+'
+// or '-'.
+fn english(entry: &str, options: &ParseOptions) -> Option<String> {
+    entry.chars()
+        .map(|c| {
+            let letter = if options.lenient { c.to_ascii_uppercase() } else { c };
+            match options.notation.rank(letter) {
+                Some(Rank::One) if options.one == OnePolicy::AsAce => Some(CardNotation::ENGLISH.rank_char(Rank::Ace)),
+                Some(rank) => Some(CardNotation::ENGLISH.rank_char(rank)),
+                None if "so+-".contains(c) => Some(c),
+                None => None,
+            }
+        })
+        .collect()
+}
+
+// The starting hands an entry stands for: "AKs", "AK" for both, "22+",
+// "ATs+", "A5s-A2s" or "99-66".
+fn hands(entry: &str) -> Option<Vec<StartingHand>> {
+    if let Some(base) = entry.strip_suffix('+') {
+        return Some(spellings(base)?.into_iter().flat_map(plus).collect());
+    }
+    if let Some((from, to)) = entry.split_once('-') {
+        let (from, to) = (spellings(from)?, spellings(to)?);
+        if from.len() != to.len() { return None; }
+        return from.into_iter().zip(to).map(|(a, b)| span(a, b)).collect::<Option<Vec<_>>>()
+            .map(|spans| spans.concat());
+    }
+    spellings(entry)
+}
+
+// "AKs" as itself, and "AK" as both AKs and AKo.
+fn spellings(s: &str) -> Option<Vec<StartingHand>> {
+    if let Ok(hand) = s.parse() { return Some(vec![hand]); }
+    let suited = format!("{}s", s).parse().ok()?;
+    let offsuit = format!("{}o", s).parse().ok()?;
+    Some(vec![suited, offsuit])
+}
+
+// Pairs from `hand` up to Aces, or the kicker raised up to one below the
+// top card.
+fn plus(hand: StartingHand) -> Vec<StartingHand> {
+    let top = if hand.is_pair() { Rank::Ace } else { hand.high() };
+    Rank::DECK.iter()
+        .filter(|&&r| r >= hand.low() && (r < top || hand.is_pair()))
+        .filter_map(|&r| {
+            let high = if hand.is_pair() { r } else { hand.high() };
+            StartingHand::new(high, r, hand.is_suited())
+        })
+        .collect()
+}
+
+// The hands from `a` to `b`, either way round: pairs between two pairs, or
+// the kickers between two hands with the same top card and suitedness.
+fn span(a: StartingHand, b: StartingHand) -> Option<Vec<StartingHand>> {
+    if a.is_pair() != b.is_pair() || a.is_suited() != b.is_suited() { return None; }
+    if !a.is_pair() && a.high() != b.high() { return None; }
+
+    let (low, high) = (a.low().min(b.low()), a.low().max(b.low()));
+    let mut hands: Vec<StartingHand> = Rank::DECK.iter()
+        .filter(|&&r| r >= low && r <= high)
+        .filter_map(|&r| {
+            let top = if a.is_pair() { r } else { a.high() };
+            StartingHand::new(top, r, a.is_suited())
+        })
+        .collect();
+    // In the order written.
+    if a.low() > b.low() { hands.reverse(); }
+    Some(hands)
+}
+
+#[cfg(test)]
+mod range_tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let range: Range = "QQ, AKs:0.5, AHQH".parse().unwrap();
+
+        assert_eq!(range.len(), 6 + 4 + 1);
+        assert_eq!(range.weight(), 6.0 + 2.0 + 1.0);
+        assert_eq!("AK".parse::<Range>().unwrap().len(), 16);
+        assert!("AKx".parse::<Range>().is_err());
+        assert!("AHAH".parse::<Range>().is_err());
+        assert!("AA:x".parse::<Range>().is_err());
+        assert_eq!("AA:0, KK:1".parse::<Range>().unwrap().weight(), 6.0);
+        assert_eq!("A10s, 10H9H".parse::<Range>().unwrap(), "ATs, TH9H".parse::<Range>().unwrap());
+    }
+
+    #[test]
+    fn test_parse_with() {
+        let parse = |s: &str, options: &ParseOptions| Range::parse_with(s, options).unwrap();
+        let english = parse("JJ+, AQs:0.5, AHQH, AQ", &ParseOptions::default());
+
+        assert_eq!(parse("BB+, ADs:0.5, AHDH, AD", &ParseOptions::german()), english);
+        assert_eq!(parse("VV+, ADs:0.5, AHDH, AD", &ParseOptions::french()), english);
+        assert_eq!(parse("bb+, ads:0.5, ahdh, ad", &ParseOptions::german().lenient(true)), english);
+        assert_eq!(parse("1K, 1H1D", &ParseOptions::default().one(OnePolicy::AsAce)), parse("AK, AHAD", &ParseOptions::default()));
+        assert!(Range::parse_with("QQ", &ParseOptions::german()).is_err());
+        assert!(Range::parse_with("1H1D", &ParseOptions::default().one(OnePolicy::Reject)).is_err());
+        assert_eq!(parse("QQ, AKs:0.5, AHQH", &ParseOptions::default()), "QQ, AKs:0.5, AHQH".parse().unwrap());
+    }
+
+    #[test]
+    fn test_parse_weights() {
+        for bad in ["AA:-1", "AA:NaN", "AA:nan", "AA:inf", "AA:-inf", "AA:5", "AA:1.01", "top 10%:2"] {
+            assert_eq!(bad.parse::<Range>(), Err(RangeParseError(bad.to_string())), "{}", bad);
+        }
+        assert!("AA:-0.0".parse::<Range>().is_ok());
+    }
+
+    #[test]
+    fn test_parse_runs() {
+        let parse = |s: &str| s.parse::<Range>().unwrap();
+
+        assert_eq!(parse("22+").len(), 13 * 6);
+        assert_eq!(parse("TT+"), parse("TT,JJ,QQ,KK,AA"));
+        assert_eq!(parse("AQo+"), parse("AQo,AKo"));
+        assert_eq!(parse("KTs+, QJs"), parse("KTs,KJs,KQs,QJs"));
+        assert_eq!(parse("AT+").len(), 4 * 16);
+        assert_eq!(parse("A5s-A2s"), parse("A5s,A4s,A3s,A2s"));
+        assert_eq!(parse("A2s-A5s"), parse("A2s,A3s,A4s,A5s"));
+        assert_eq!(parse("66-99"), parse("66+").subtract(&parse("TT+")));
+        assert_eq!(parse("A10s+"), parse("ATs+"));
+        assert_eq!(parse("AKo:0.5").weight(), 6.0);
+        assert!("A5s-K2s".parse::<Range>().is_err());
+        assert!("A5s-A2o".parse::<Range>().is_err());
+        assert!("AA-KQs".parse::<Range>().is_err());
+    }
+
+    #[test]
+    fn test_top() {
+        assert_eq!(Range::top(0.0).len(), 0);
+        assert_eq!(Range::top(100.0).len(), 1326);
+
+        // 15% is 199 combos, reached a hand or so past the target.
+        let top = "top 15%".parse::<Range>().unwrap();
+        assert!(top.len() >= 199 && top.len() < 199 + 12, "{}", top.len());
+        assert_eq!(top, Range::top(15.0));
+        assert!(top.combos().iter().all(|c| StartingHand::from_cards(&c.cards).unwrap() != "72o".parse().unwrap()));
+        assert_eq!("top 10%:0.5".parse::<Range>().unwrap().weight(), Range::top(10.0).weight() / 2.0);
+        assert!("top 120%".parse::<Range>().is_err());
+        assert!("top ten%".parse::<Range>().is_err());
+    }
+
+    #[test]
+    fn test_add_replaces() {
+        let mut range: Range = "AHKH".parse().unwrap();
+        range.add(Combo{cards: [Card::from_code("KH").unwrap(), Card::from_code("AH").unwrap()], weight: 0.25});
+
+        assert_eq!(range.len(), 1);
+        assert_eq!(range.weight(), 0.25);
+    }
+
+    #[test]
+    fn test_blocked_and_subtract() {
+        let range: Range = "AA,KK".parse().unwrap();
+        let dead = [Card::from_code("AS").unwrap()];
+
+        assert_eq!(range.without_blocked(&dead).len(), 3 + 6);
+        assert_eq!(range.subtract(&"KK,QQ".parse().unwrap()).len(), 6);
+    }
+}
@@ -0,0 +1,262 @@
+//! What hole cards make of a board, in the words players use: top pair
+//! with a good kicker, an underpair, a set, a nut flush draw. The absolute
+//! category says how strong five cards are; this says how the hole cards
+//! got there, which is what most decisions turn on.
+
+use std::fmt;
+
+use super::{Board, Card, Cards, Category, Rank, Suit};
+use super::board::Street;
+use super::deal::best_of;
+use super::draws::Draw;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Kicker {
+    // The best kicker left: an Ace, or a King with Aces on top.
+    Top,
+    // Ten or better.
+    Good,
+    Weak,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Pair {
+    // A pocket pair above every board card.
+    Over,
+    // A hole card pairs the highest board rank.
+    Top(Kicker),
+    // A hole card pairs the second highest board rank.
+    Second,
+    // A hole card pairs a board rank between the second and the lowest,
+    // which takes a turn or river.
+    Middle,
+    // A hole card pairs the lowest board rank.
+    Bottom,
+    // A pocket pair below the top board card and above the lowest.
+    Pocket,
+    // A pocket pair below every board card.
+    Under,
+}
+
+/// The made hand, counting only what the hole cards take part in. A pair
+/// on the board with nothing in the hole is `Nothing`; trips or better
+/// there is `Board`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Made {
+    Nothing,
+    Pair(Pair),
+    // Each hole card pairs a different board card.
+    TwoPair,
+    // A pocket pair with one on the board.
+    Set,
+    // One hole card with a pair on the board.
+    Trips,
+    Straight,
+    Flush { nut: bool },
+    FullHouse,
+    Quads,
+    StraightFlush,
+    // The best five cards are all on the board.
+    Board,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct RelativeStrength {
+    pub made: Made,
+    // As `Cards::draws` finds them; none on the river.
+    pub draws: Vec<Draw>,
+    // The flush draw is to the best flush left in its suit.
+    pub nut_flush_draw: bool,
+    // Hole cards above every board card, counted when nothing is made.
+    pub overcards: usize,
+}
+
+pub fn classify_relative(hole: &[Card; 2], board: &Board) -> RelativeStrength {
+    assert!(board.street() != Street::Preflop, "a flop, turn or river board");
+
+    // Board cards first, so that of equally good hands the one using the
+    // fewest hole cards is picked.
+    let all: Vec<Card> = board.iter().chain(hole.iter()).cloned().collect();
+    let best = best_of(&all).breakdown();
+    let used = hole.iter().filter(|c| best.made.contains(c)).count();
+    let pocket = hole[0].rank == hole[1].rank;
+
+    let made = match best.category {
+        _ if used == 0 && best.category >= Category::ThreeOfAKind => Made::Board,
+        _ if used == 0 => Made::Nothing,
+        Category::HighCard => Made::Nothing,
+        Category::StraightFlush | Category::RoyalFlush | Category::FiveOfAKind => Made::StraightFlush,
+        Category::FourOfAKind => Made::Quads,
+        Category::FullHouse => Made::FullHouse,
+        Category::Flush => {
+            let suit = best.made[0].suit;
+            Made::Flush{nut: holds_nut(hole, board, suit)}
+        },
+        Category::Straight => Made::Straight,
+        Category::ThreeOfAKind if pocket => Made::Set,
+        Category::ThreeOfAKind => Made::Trips,
+        Category::TwoPairs if used == 2 && !pocket => Made::TwoPair,
+        _ => Made::Pair(pair(hole, board)),
+    };
+
+    let draws = Cards::try_new(hole.to_vec()).unwrap().draws(board);
+    let nut_flush_draw = draws.iter().any(|d| match d {
+        Draw::Flush(suit) => holds_nut(hole, board, *suit),
+        _ => false,
+    });
+    let top = board.iter().map(|c| c.rank).max().unwrap();
+    let overcards = if made == Made::Nothing { hole.iter().filter(|c| c.rank > top).count() } else { 0 };
+
+    RelativeStrength{made, draws, nut_flush_draw, overcards}
+}
+
+// Whether a hole card is the highest card of `suit` not on the board.
+fn holds_nut(hole: &[Card; 2], board: &Board, suit: Suit) -> bool {
+    let nut = Rank::DECK.iter().rev()
+        .map(|&rank| Card{rank, suit})
+        .find(|c| !board.contains(c));
+
+    nut.is_some_and(|nut| hole.contains(&nut))
+}
+
+// The pair the hole cards make, given that they make exactly one.
+fn pair(hole: &[Card; 2], board: &Board) -> Pair {
+    let mut ranks: Vec<Rank> = board.iter().map(|c| c.rank).collect();
+    ranks.sort_by(|a, b| b.cmp(a));
+    ranks.dedup();
+
+    if hole[0].rank == hole[1].rank {
+        let r = hole[0].rank;
+        return if r > ranks[0] {
+            Pair::Over
+        } else if r < ranks[ranks.len() - 1] {
+            Pair::Under
+        } else {
+            Pair::Pocket
+        };
+    }
+
+    let (i, paired) = (0..2).find(|&i| ranks.contains(&hole[i].rank)).map(|i| (i, hole[i].rank)).unwrap();
+    match ranks.iter().position(|&r| r == paired).unwrap() {
+        0 => {
+            let kicker = hole[1 - i].rank;
+            let best = if paired == Rank::Ace { Rank::King } else { Rank::Ace };
+            Pair::Top(if kicker == best {
+                Kicker::Top
+            } else if kicker >= Rank::Ten {
+                Kicker::Good
+            } else {
+                Kicker::Weak
+            })
+        },
+        1 => Pair::Second,
+        p if p == ranks.len() - 1 => Pair::Bottom,
+        _ => Pair::Middle,
+    }
+}
+
+impl fmt::Display for Made {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Made::Nothing => "nothing",
+            Made::Pair(Pair::Over) => "overpair",
+            Made::Pair(Pair::Top(Kicker::Top)) => "top pair, top kicker",
+            Made::Pair(Pair::Top(Kicker::Good)) => "top pair, good kicker",
+            Made::Pair(Pair::Top(Kicker::Weak)) => "top pair, weak kicker",
+            Made::Pair(Pair::Second) => "second pair",
+            Made::Pair(Pair::Middle) => "middle pair",
+            Made::Pair(Pair::Bottom) => "bottom pair",
+            Made::Pair(Pair::Pocket) => "pocket pair below the top card",
+            Made::Pair(Pair::Under) => "underpair",
+            Made::TwoPair => "two pair",
+            Made::Set => "set",
+            Made::Trips => "trips",
+            Made::Straight => "straight",
+            Made::Flush { nut: true } => "nut flush",
+            Made::Flush { nut: false } => "flush",
+            Made::FullHouse => "full house",
+            Made::Quads => "quads",
+            Made::StraightFlush => "straight flush",
+            Made::Board => "playing the board",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+// "second pair, nut flush draw, gutshot", "nothing, two overcards".
+impl fmt::Display for RelativeStrength {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.made)?;
+        for draw in &self.draws {
+            match draw {
+                Draw::Flush(_) if self.nut_flush_draw => write!(f, ", nut flush draw")?,
+                Draw::Flush(_) => write!(f, ", flush draw")?,
+                d => write!(f, ", {}", d)?,
+            }
+        }
+        match self.overcards {
+            0 => Ok(()),
+            1 => write!(f, ", one overcard"),
+            n => write!(f, ", {} overcards", if n == 2 { "two" } else { "more" }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod relative_tests {
+    use super::*;
+
+    fn classify(hole: &str, board: &str) -> RelativeStrength {
+        let h = Cards::hole(hole).unwrap();
+        classify_relative(&[h[0], h[1]], &board.parse().unwrap())
+    }
+
+    fn made(hole: &str, board: &str) -> Made {
+        classify(hole, board).made
+    }
+
+    #[test]
+    fn test_pairs() {
+        assert_eq!(made("AS KD", "KH 9C 2D"), Made::Pair(Pair::Top(Kicker::Top)));
+        assert_eq!(made("KS QD", "KH 9C 2D"), Made::Pair(Pair::Top(Kicker::Good)));
+        assert_eq!(made("KS 5D", "KH 9C 2D"), Made::Pair(Pair::Top(Kicker::Weak)));
+        assert_eq!(made("AS KD", "AH 9C 2D"), Made::Pair(Pair::Top(Kicker::Top)));
+        assert_eq!(made("9S 5D", "KH 9C 2D"), Made::Pair(Pair::Second));
+        assert_eq!(made("2S 5D", "KH 9C 2D"), Made::Pair(Pair::Bottom));
+        assert_eq!(made("6S 5D", "KH 9C 6D 2S"), Made::Pair(Pair::Middle));
+        assert_eq!(made("AS AD", "KH 9C 2D"), Made::Pair(Pair::Over));
+        assert_eq!(made("TS TD", "KH 9C 2D"), Made::Pair(Pair::Pocket));
+        assert_eq!(made("3S 3D", "KH 9C 4D"), Made::Pair(Pair::Under));
+
+        // The board's pair is not the hole cards' doing.
+        assert_eq!(made("9S 5D", "KH KC 9D"), Made::Pair(Pair::Second));
+        assert_eq!(made("AS QD", "KH KC 9D"), Made::Nothing);
+    }
+
+    #[test]
+    fn test_better() {
+        assert_eq!(made("KS 9D", "KH 9C 2D"), Made::TwoPair);
+        assert_eq!(made("9S 9D", "KH 9C 2D"), Made::Set);
+        assert_eq!(made("9S 5D", "KH 9C 9D"), Made::Trips);
+        assert_eq!(made("AH 3H", "KH 9H 2H"), Made::Flush{nut: true});
+        assert_eq!(made("QH 3H", "KH 9H 2H"), Made::Flush{nut: false});
+        assert_eq!(made("QS JD", "KH TC 9D"), Made::Straight);
+        assert_eq!(made("2S 3D", "AH KC QD JS TH"), Made::Board);
+        assert_eq!(made("9S 9D", "KH 9C KD"), Made::FullHouse);
+    }
+
+    #[test]
+    fn test_draws() {
+        let s = classify("AH 5H", "KH 9H 2C");
+        assert_eq!(s.made, Made::Nothing);
+        assert!(s.nut_flush_draw);
+        assert_eq!(s.overcards, 1);
+        assert_eq!(s.to_string(), "nothing, nut flush draw, one overcard");
+
+        let s = classify("9H 5H", "KH 9C 2H 4S");
+        assert_eq!(s.to_string(), "second pair, flush draw");
+        assert!(!s.nut_flush_draw);
+
+        assert_eq!(classify("AS QD", "8H 7C 2D").to_string(), "nothing, two overcards");
+    }
+}
@@ -0,0 +1,255 @@
+//! Equilibrium of the one-bet river game.
+//!
+//! On a complete board the bettor either checks, and the hands go to
+//! showdown, or bets a fixed amount; facing a bet the caller calls or folds.
+//! Both ranges are lists of weighted hole card combos. The equilibrium is
+//! found with CFR+ and is exact up to the reported exploitability, which
+//! shrinks with the number of iterations.
+
+use std::cmp::Ordering;
+
+use super::{Card, DuplicateCard};
+use super::deal::best_holdem;
+pub use super::range::Combo;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct RiverSpot {
+    pub board: [Card; 5],
+    pub pot: f64,
+    pub bet: f64,
+    pub bettor: Vec<Combo>,
+    pub caller: Vec<Combo>,
+}
+
+/// Equilibrium frequencies, one per combo in the order the ranges were
+/// given. `value` is the bettor's expected share of the pot in chips, and
+/// `exploitability` how much a best response could gain on average, also in
+/// chips.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RiverSolution {
+    pub bet: Vec<f64>,
+    pub call: Vec<f64>,
+    pub value: f64,
+    pub exploitability: f64,
+}
+
+// Bettor's payoffs and the weight of every pair of combos that can be dealt
+// together, indexed `[i][j]` by bettor then caller combo.
+struct Matchups {
+    weight: Vec<Vec<f64>>,
+    showdown: Vec<Vec<f64>>,
+    called: Vec<Vec<f64>>,
+}
+
+impl RiverSpot {
+    pub fn solve(&self, iterations: usize) -> Result<RiverSolution, DuplicateCard> {
+        let m = self.matchups()?;
+        let (n, k) = (self.bettor.len(), self.caller.len());
+
+        let mut bet_regret = vec![[0.0; 2]; n];
+        let mut call_regret = vec![[0.0; 2]; k];
+        let mut bet_sum = vec![0.0; n];
+        let mut call_sum = vec![0.0; k];
+
+        for t in 1..=iterations {
+            let call = current(&call_regret);
+            for (i, (check_ev, bet_ev)) in self.bettor_evs(&m, &call).into_iter().enumerate() {
+                let s = current_one(&bet_regret[i]);
+                let node = (1.0 - s) * check_ev + s * bet_ev;
+                bet_regret[i][0] = f64::max(bet_regret[i][0] + check_ev - node, 0.0);
+                bet_regret[i][1] = f64::max(bet_regret[i][1] + bet_ev - node, 0.0);
+            }
+
+            let bet = current(&bet_regret);
+            for i in 0..n {
+                bet_sum[i] += t as f64 * bet[i];
+            }
+            for (j, (fold_ev, call_ev)) in self.caller_evs(&m, &bet).into_iter().enumerate() {
+                let c = current_one(&call_regret[j]);
+                let node = (1.0 - c) * fold_ev + c * call_ev;
+                call_regret[j][0] = f64::max(call_regret[j][0] + fold_ev - node, 0.0);
+                call_regret[j][1] = f64::max(call_regret[j][1] + call_ev - node, 0.0);
+            }
+            let call = current(&call_regret);
+            for j in 0..k {
+                call_sum[j] += t as f64 * call[j];
+            }
+        }
+
+        let norm = (iterations * (iterations + 1) / 2).max(1) as f64;
+        let bet: Vec<f64> = bet_sum.iter().map(|s| s / norm).collect();
+        let call: Vec<f64> = call_sum.iter().map(|s| s / norm).collect();
+
+        Ok(self.evaluate(&m, bet, call))
+    }
+
+    fn matchups(&self) -> Result<Matchups, DuplicateCard> {
+        for combo in self.bettor.iter().chain(&self.caller) {
+            if let Some(c) = combo.cards.iter().find(|c| self.board.contains(c)) {
+                return Err(DuplicateCard(*c));
+            }
+            if combo.cards[0] == combo.cards[1] {
+                return Err(DuplicateCard(combo.cards[0]));
+            }
+        }
+
+        let (pot, bet) = (self.pot, self.bet);
+        let mut m = Matchups{weight: vec![], showdown: vec![], called: vec![]};
+
+        for a in &self.bettor {
+            let hand = best_holdem(&a.cards, &self.board);
+            let (mut weight, mut showdown, mut called) = (vec![], vec![], vec![]);
+
+            for b in &self.caller {
+                if a.overlaps(&b.cards) {
+                    weight.push(0.0);
+                    showdown.push(0.0);
+                    called.push(0.0);
+                    continue;
+                }
+                let (show, call) = match hand.cmp(&best_holdem(&b.cards, &self.board)) {
+                    Ordering::Greater => (pot, pot + bet),
+                    Ordering::Equal => (pot / 2.0, pot / 2.0),
+                    Ordering::Less => (0.0, -bet),
+                };
+                weight.push(a.weight * b.weight);
+                showdown.push(show);
+                called.push(call);
+            }
+            m.weight.push(weight);
+            m.showdown.push(showdown);
+            m.called.push(called);
+        }
+        Ok(m)
+    }
+
+    // Counterfactual values of checking and betting for each bettor combo.
+    fn bettor_evs(&self, m: &Matchups, call: &[f64]) -> Vec<(f64, f64)> {
+        (0..self.bettor.len()).map(|i| {
+            let mut check = 0.0;
+            let mut bet = 0.0;
+            for (j, c) in call.iter().enumerate() {
+                let w = m.weight[i][j];
+                check += w * m.showdown[i][j];
+                bet += w * ((1.0 - c) * self.pot + c * m.called[i][j]);
+            }
+            (check, bet)
+        }).collect()
+    }
+
+    // Counterfactual values of folding and calling for each caller combo,
+    // counted as the caller's share of the pot.
+    fn caller_evs(&self, m: &Matchups, bet: &[f64]) -> Vec<(f64, f64)> {
+        (0..self.caller.len()).map(|j| {
+            let call = bet.iter().enumerate()
+                .map(|(i, b)| m.weight[i][j] * b * (self.pot - m.called[i][j]))
+                .sum();
+            (0.0, call)
+        }).collect()
+    }
+
+    fn evaluate(&self, m: &Matchups, bet: Vec<f64>, call: Vec<f64>) -> RiverSolution {
+        let total: f64 = m.weight.iter().flatten().sum();
+        let total = if total > 0.0 { total } else { 1.0 };
+
+        let bettor = self.bettor_evs(m, &call);
+        let value: f64 = bettor.iter().zip(&bet)
+            .map(|((check, b), s)| (1.0 - s) * check + s * b)
+            .sum::<f64>() / total;
+        let bettor_best: f64 = bettor.iter().map(|(check, b)| check.max(*b)).sum::<f64>() / total;
+
+        // After a check the caller always gets its showdown share, whatever
+        // it would have done facing a bet.
+        let checked: f64 = (0..self.bettor.len())
+            .map(|i| (1.0 - bet[i]) * (0..self.caller.len())
+                .map(|j| m.weight[i][j] * (self.pot - m.showdown[i][j]))
+                .sum::<f64>())
+            .sum();
+        let caller_best: f64 = (checked + self.caller_evs(m, &bet).iter()
+            .map(|(fold, call)| fold.max(*call))
+            .sum::<f64>()) / total;
+
+        RiverSolution{
+            bet,
+            call,
+            value,
+            exploitability: (bettor_best + caller_best - self.pot) / 2.0,
+        }
+    }
+}
+
+fn current_one(regret: &[f64; 2]) -> f64 {
+    let sum = regret[0] + regret[1];
+    if sum > 0.0 { regret[1] / sum } else { 0.5 }
+}
+
+// Probability of the second action, betting or calling, by regret matching.
+fn current(regrets: &[[f64; 2]]) -> Vec<f64> {
+    regrets.iter().map(current_one).collect()
+}
+
+#[cfg(test)]
+mod river_tests {
+    use super::*;
+
+    fn combo(a: &str, b: &str) -> Combo {
+        Combo::new([Card::from_code(a).unwrap(), Card::from_code(b).unwrap()])
+    }
+
+    fn board() -> [Card; 5] {
+        let c = |s| Card::from_code(s).unwrap();
+        [c("2C"), c("3D"), c("8H"), c("9S"), c("KD")]
+    }
+
+    #[test]
+    fn test_polarized() {
+        // Bettor holds the nuts or air, the caller a single bluff catcher.
+        let spot = RiverSpot{
+            board: board(),
+            pot: 1.0,
+            bet: 1.0,
+            bettor: vec![combo("KH", "KS"), combo("4H", "5H")],
+            caller: vec![combo("AH", "KC")],
+        };
+        let s = spot.solve(5000).unwrap();
+
+        assert!(s.bet[0] > 0.95);
+        assert!((s.bet[1] - 0.5).abs() < 0.05);
+        assert!((s.call[0] - 0.5).abs() < 0.05);
+        assert!((s.value - 0.75).abs() < 0.02);
+        assert!(s.exploitability < 0.01);
+    }
+
+    #[test]
+    fn test_nuts_always_bet() {
+        let spot = RiverSpot{
+            board: board(),
+            pot: 2.0,
+            bet: 1.0,
+            bettor: vec![combo("KH", "KS")],
+            caller: vec![combo("AH", "KC"), combo("4H", "5H")],
+        };
+        let s = spot.solve(2000).unwrap();
+
+        assert!(s.bet[0] > 0.95);
+        assert!(s.call.iter().all(|&c| c < 0.05));
+        assert!((s.value - 2.0).abs() < 0.05);
+    }
+
+    #[test]
+    fn test_blockers_and_duplicates() {
+        let mut spot = RiverSpot{
+            board: board(),
+            pot: 1.0,
+            bet: 1.0,
+            bettor: vec![combo("AH", "AS")],
+            caller: vec![combo("AH", "KC"), combo("QH", "QS")],
+        };
+        let s = spot.solve(100).unwrap();
+        assert_eq!(s.bet.len(), 1);
+        assert_eq!(s.call.len(), 2);
+
+        spot.caller.push(combo("KD", "KS"));
+        assert_eq!(spot.solve(1), Err(DuplicateCard(Card::from_code("KD").unwrap())));
+    }
+}
@@ -0,0 +1,120 @@
+//! Rulesets that change how hands rank. The scoring code is shared: a
+//! ruleset only changes which rank patterns are straights and the order of
+//! the categories, and `Hand::value_with` takes it as a parameter.
+//!
+//! In short-deck (6+) Hold'em the Twos through Fives are taken out, leaving
+//! 36 cards. The ace still plays high or low, so A-6-7-8-9 is the lowest
+//! straight, and since flushes are harder to make with fewer cards of each
+//! suit, a flush beats a full house.
+
+use std::cmp::Ordering;
+
+use super::{Card, Category, Hand, Rank};
+use super::deck::Deck;
+use super::value::HandValue;
+
+// A-6-7-8-9 as a rank mask.
+pub(crate) const SHORT_WHEEL: u16 = 1 << Rank::Ace as u16 | 0b1111 << Rank::Six as u16;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Default)]
+pub enum Rules {
+    #[default]
+    Standard,
+    // Six through Ace; flush over full house, A-6-7-8-9 a straight.
+    ShortDeck,
+}
+
+impl Rules {
+    /// The ranks in the deck, low to high.
+    pub fn ranks(self) -> &'static [Rank] {
+        match self {
+            Rules::Standard => &Rank::DECK,
+            Rules::ShortDeck => &Rank::DECK[4..],
+        }
+    }
+
+    /// A full deck for the rules, in order.
+    pub fn deck(self) -> Deck {
+        Deck::with_ranks(self.ranks())
+    }
+
+    /// Where `category` stands among the others, higher is stronger.
+    pub fn strength(self, category: Category) -> u8 {
+        match (self, category) {
+            (Rules::ShortDeck, Category::Flush) => Category::FullHouse as u8,
+            (Rules::ShortDeck, Category::FullHouse) => Category::Flush as u8,
+            (_, category) => category as u8,
+        }
+    }
+
+    /// Orders two values made under these rules.
+    pub fn compare(self, a: &HandValue, b: &HandValue) -> Ordering {
+        self.strength(a.category()).cmp(&self.strength(b.category()))
+            .then_with(|| a.tiebreaks().cmp(b.tiebreaks()))
+    }
+
+    /// The best five card hand out of `cards` under these rules, with its
+    /// value.
+    ///
+    /// # Panics
+    ///
+    /// If there are fewer than five cards.
+    pub fn best_hand(self, cards: &[Card]) -> (Hand, HandValue) {
+        let mut best: Option<(Hand, HandValue)> = None;
+        for hand in super::deal::fives(cards) {
+            let value = hand.value_with(self);
+            if best.as_ref().map_or(true, |(_, b)| self.compare(&value, b) == Ordering::Greater) {
+                best = Some((hand, value));
+            }
+        }
+        best.unwrap()
+    }
+}
+
+#[cfg(test)]
+mod rules_tests {
+    use super::*;
+
+    fn value(s: &str, rules: Rules) -> HandValue {
+        s.parse::<Hand>().unwrap().value_with(rules)
+    }
+
+    #[test]
+    fn test_short_deck() {
+        let short = Rules::ShortDeck;
+
+        let wheel = value("AH 6D 7C 8S 9H", short);
+        assert_eq!(wheel.category(), Category::Straight);
+        assert_eq!(wheel.tiebreaks(), &[Rank::Nine]);
+        assert_eq!(value("AH 6D 7C 8S 9H", Rules::Standard).category(), Category::HighCard);
+        assert_eq!(short.compare(&wheel, &value("6H 7D 8C 9S TH", short)), Ordering::Less);
+        assert_eq!(short.compare(&wheel, &value("KH KD KC 9S 8H", short)), Ordering::Greater);
+        assert_eq!(value("AH 6H 7H 8H 9H", short).category(), Category::StraightFlush);
+        assert_eq!(value("AH KH QH JH TH", short).category(), Category::RoyalFlush);
+
+        let flush = value("AH JH 9H 7H 6H", short);
+        let full = value("KH KD KC QS QH", short);
+        assert_eq!(short.compare(&flush, &full), Ordering::Greater);
+        assert_eq!(Rules::Standard.compare(&flush, &full), Ordering::Less);
+        assert_eq!(Rules::Standard.compare(&flush, &full), flush.cmp(&full));
+        assert_eq!(short.compare(&full, &value("9H 9D 9C 9S 6H", short)), Ordering::Less);
+    }
+
+    #[test]
+    fn test_deck_and_best_hand() {
+        let deck = Rules::ShortDeck.deck();
+        assert_eq!(deck.len(), 36);
+        assert!(deck.cards().iter().all(|c| c.rank >= Rank::Six));
+        assert_eq!(Rules::Standard.deck(), Deck::new());
+
+        let cards: Vec<Card> = "AH 6D 7C 8S 9H KS KC"
+            .split_whitespace().map(|c| Card::from_code(c).unwrap()).collect();
+        assert_eq!(Rules::ShortDeck.best_hand(&cards).1.category(), Category::Straight);
+        assert_eq!(Rules::Standard.best_hand(&cards).1.category(), Category::OnePair);
+
+        let cards: Vec<Card> = "AH JH 9H 7H 6H JD JC 9D"
+            .split_whitespace().map(|c| Card::from_code(c).unwrap()).collect();
+        assert_eq!(Rules::ShortDeck.best_hand(&cards).1.category(), Category::Flush);
+        assert_eq!(Rules::Standard.best_hand(&cards).1.category(), Category::FullHouse);
+    }
+}
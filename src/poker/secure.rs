@@ -0,0 +1,223 @@
+//! Shuffling for games where the deal has to be beyond question. Each
+//! shuffle takes a fresh 256 bit seed from the operating system's
+//! generator (`getrandom`: `/dev/urandom` or `getrandom(2)` on Linux,
+//! `BCryptGenRandom` on Windows), expands it with ChaCha20 and shuffles with
+//! rand's Fisher-Yates. Nothing is seeded from the clock or a thread local
+//! generator.
+//!
+//! Every shuffle returns a `ShuffleAudit` saying where its entropy came
+//! from and holding the seed. Kept private until the hand is over, the seed
+//! lets anyone replay the shuffle afterwards and check the deal with
+//! `ShuffleAudit::verify`. Published early, it gives the deal away, so
+//! neither `Display` nor `Debug` show it: they show the commitment, the
+//! SHA-256 of the seed, which can be published before the deal and checked
+//! against the seed once `ShuffleAudit::reveal_seed` gives it out.
+
+use std::fmt;
+use std::time::SystemTime;
+
+use rand::rngs::OsRng;
+use rand::{RngCore, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+
+use super::deck::Deck;
+
+/// A record of one secure shuffle.
+#[derive(Clone, PartialEq)]
+pub struct ShuffleAudit {
+    // Where the seed came from.
+    pub source: &'static str,
+    // How the seed became an order.
+    pub algorithm: &'static str,
+    seed: [u8; 32],
+    pub at: SystemTime,
+    // Cards in the deck when it was shuffled.
+    pub cards: usize,
+}
+
+impl Deck {
+    /// Shuffles with a seed from the operating system. Fails only if the
+    /// operating system cannot supply entropy, leaving the deck as it was.
+    pub fn shuffle_secure(&mut self) -> Result<ShuffleAudit, rand::Error> {
+        let mut seed = [0; 32];
+        OsRng.try_fill_bytes(&mut seed)?;
+        self.shuffle(&mut ChaCha20Rng::from_seed(seed));
+
+        Ok(ShuffleAudit{
+            source: "operating system (getrandom)",
+            algorithm: "ChaCha20, rand 0.8 Fisher-Yates",
+            seed,
+            at: SystemTime::now(),
+            cards: self.len(),
+        })
+    }
+}
+
+impl ShuffleAudit {
+    /// Whether shuffling `before` with this record's seed gives `after`.
+    pub fn verify(&self, before: &Deck, after: &Deck) -> bool {
+        let mut replay = before.clone();
+        replay.shuffle(&mut ChaCha20Rng::from_seed(self.seed));
+        before.len() == self.cards && replay == *after
+    }
+
+    /// The SHA-256 of the seed, safe to publish before the hand is played.
+    pub fn commitment(&self) -> [u8; 32] {
+        commitment(&self.seed)
+    }
+
+    pub fn commitment_hex(&self) -> String {
+        hex(&self.commitment())
+    }
+
+    /// The seed itself, for publishing once the hand is over. Anyone who
+    /// saw the commitment checks it with `secure::commitment`.
+    pub fn reveal_seed(&self) -> [u8; 32] {
+        self.seed
+    }
+
+    pub fn reveal_seed_hex(&self) -> String {
+        hex(&self.seed)
+    }
+}
+
+/// The commitment to a seed: its SHA-256.
+pub fn commitment(seed: &[u8; 32]) -> [u8; 32] {
+    sha256(seed)
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+// One line for a log: "shuffled 52 cards at 1760000000 with commitment 9c..
+// from operating system (getrandom), ChaCha20, rand 0.8 Fisher-Yates".
+impl fmt::Display for ShuffleAudit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let secs = self.at.duration_since(SystemTime::UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+        write!(f, "shuffled {} cards at {} with commitment {} from {}, {}",
+            self.cards, secs, self.commitment_hex(), self.source, self.algorithm)
+    }
+}
+
+// The commitment in place of the seed, as in `Display`.
+impl fmt::Debug for ShuffleAudit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ShuffleAudit")
+            .field("source", &self.source)
+            .field("algorithm", &self.algorithm)
+            .field("commitment", &self.commitment_hex())
+            .field("at", &self.at)
+            .field("cards", &self.cards)
+            .finish()
+    }
+}
+
+const K: [u32; 64] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+// SHA-256 (FIPS 180-4), kept here rather than taken as a dependency since
+// commitments are all it is needed for.
+fn sha256(data: &[u8]) -> [u8; 32] {
+    let mut h: [u32; 8] = [
+        0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+    ];
+
+    let mut message = data.to_vec();
+    message.push(0x80);
+    while message.len() % 64 != 56 { message.push(0); }
+    message.extend_from_slice(&(data.len() as u64 * 8).to_be_bytes());
+
+    for block in message.chunks_exact(64) {
+        let mut w = [0u32; 64];
+        for (i, word) in block.chunks_exact(4).enumerate() {
+            w[i] = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+        }
+        for i in 16..64 {
+            let s0 = w[i - 15].rotate_right(7) ^ w[i - 15].rotate_right(18) ^ (w[i - 15] >> 3);
+            let s1 = w[i - 2].rotate_right(17) ^ w[i - 2].rotate_right(19) ^ (w[i - 2] >> 10);
+            w[i] = w[i - 16].wrapping_add(s0).wrapping_add(w[i - 7]).wrapping_add(s1);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut hh] = h;
+        for i in 0..64 {
+            let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+            let ch = (e & f) ^ (!e & g);
+            let t1 = hh.wrapping_add(s1).wrapping_add(ch).wrapping_add(K[i]).wrapping_add(w[i]);
+            let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+            let maj = (a & b) ^ (a & c) ^ (b & c);
+            let t2 = s0.wrapping_add(maj);
+
+            hh = g;
+            g = f;
+            f = e;
+            e = d.wrapping_add(t1);
+            d = c;
+            c = b;
+            b = a;
+            a = t1.wrapping_add(t2);
+        }
+        for (x, y) in h.iter_mut().zip([a, b, c, d, e, f, g, hh]) {
+            *x = x.wrapping_add(y);
+        }
+    }
+
+    let mut out = [0; 32];
+    for (chunk, word) in out.chunks_exact_mut(4).zip(h) {
+        chunk.copy_from_slice(&word.to_be_bytes());
+    }
+    out
+}
+
+#[cfg(test)]
+mod secure_tests {
+    use super::*;
+
+    #[test]
+    fn test_shuffle_secure() {
+        let before = Deck::new();
+        let mut deck = before.clone();
+        let audit = deck.shuffle_secure().unwrap();
+
+        assert!(audit.verify(&before, &deck));
+        assert!(!audit.verify(&before, &Deck::new()));
+        assert_eq!(audit.cards, 52);
+        assert_eq!(audit.reveal_seed_hex().len(), 64);
+        assert!(audit.to_string().starts_with("shuffled 52 cards at "));
+        assert_eq!(commitment(&audit.reveal_seed()), audit.commitment());
+
+        // A second shuffle draws a new seed.
+        let mut again = before.clone();
+        assert_ne!(again.shuffle_secure().unwrap().reveal_seed(), audit.reveal_seed());
+    }
+
+    #[test]
+    fn test_logs_hide_seed() {
+        let audit = Deck::new().shuffle_secure().unwrap();
+        let seed = audit.reveal_seed_hex();
+
+        for log in [audit.to_string(), format!("{:?}", audit), format!("{:#?}", audit)] {
+            assert!(!log.contains(&seed), "{}", log);
+            assert!(log.contains(&audit.commitment_hex()), "{}", log);
+        }
+    }
+
+    #[test]
+    fn test_sha256() {
+        assert_eq!(hex(&sha256(b"")), "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855");
+        assert_eq!(hex(&sha256(b"abc")), "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad");
+        assert_eq!(
+            hex(&sha256(b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq")),
+            "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1"
+        );
+        assert_eq!(hex(&commitment(&[0; 32])), "66687aadf862bd776c8fc18b8e9f8e20089714856ee233b3902a591d0d5f2925");
+    }
+}
@@ -0,0 +1,120 @@
+//! Expected value of betting with a hand that may be called: the opponent
+//! folds some of the time, and otherwise the hand is played to showdown.
+//!
+//! With fold probability `f`, showdown equity `e` when called, a pot of `P`
+//! and a bet of `B` that the caller matches,
+//!
+//! ```text
+//! EV = f * P + (1 - f) * (e * (P + 2B) - B)
+//! ```
+//!
+//! measured against giving up the pot, so checking behind with no equity is
+//! worth zero.
+
+use rand::Rng;
+
+use super::{Board, Card, DuplicateCard};
+use super::equity::Simulator;
+use super::range::Range;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FoldEquity {
+    pub fold: f64,
+    pub equity: f64,
+    pub ev: f64,
+}
+
+pub fn bet_ev(pot: f64, bet: f64, fold: f64, equity: f64) -> f64 {
+    fold * pot + (1.0 - fold) * (equity * (pot + 2.0 * bet) - bet)
+}
+
+// The fold probability at which a bet with this equity breaks even.
+pub fn break_even_fold(pot: f64, bet: f64, equity: f64) -> f64 {
+    let called = equity * (pot + 2.0 * bet) - bet;
+    if called >= 0.0 { return 0.0; }
+    -called / (pot - called)
+}
+
+/// A bet on a given board, before anyone knows whether it is called.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Bet {
+    pub hole: [Card; 2],
+    pub board: Board,
+    pub pot: f64,
+    pub bet: f64,
+}
+
+impl Bet {
+    pub fn ev(&self, fold: f64, equity: f64) -> f64 {
+        bet_ev(self.pot, self.bet, fold, equity)
+    }
+
+    /// Works out the fold probability and equity from the opponent's
+    /// `range` and the part of it, `continuing`, that calls. Combos blocked
+    /// by the hole cards or board are left out of both. Equity against each
+    /// calling combo is enumerated when there are at most `samples` runouts
+    /// and sampled otherwise.
+    pub fn against_range<R: Rng + ?Sized>(
+        &self,
+        range: &Range,
+        continuing: &Range,
+        samples: usize,
+        rng: &mut R,
+    ) -> Result<FoldEquity, DuplicateCard> {
+        let dead: Vec<Card> = self.hole.iter().chain(self.board.iter()).cloned().collect();
+        let range = range.without_blocked(&dead);
+        let calls = range.subtract(&range.subtract(continuing));
+
+        let total = range.weight();
+        let fold = if total > 0.0 { 1.0 - calls.weight() / total } else { 1.0 };
+
+        let simulator = Simulator::new(samples).auto_enumerate(true);
+        let mut equity = 0.0;
+        for combo in calls.combos() {
+            let e = simulator.run(&[self.hole, combo.cards], &self.board, rng)?;
+            equity += combo.weight * e.shares[0];
+        }
+        if calls.weight() > 0.0 { equity /= calls.weight(); }
+
+        Ok(FoldEquity{fold, equity, ev: self.ev(fold, equity)})
+    }
+}
+
+#[cfg(test)]
+mod semibluff_tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    #[test]
+    fn test_bet_ev() {
+        assert_eq!(bet_ev(10.0, 10.0, 1.0, 0.0), 10.0);
+        assert_eq!(bet_ev(10.0, 10.0, 0.0, 0.0), -10.0);
+        assert_eq!(bet_ev(10.0, 10.0, 0.5, 0.5), 5.0 + 0.5 * (15.0 - 10.0));
+
+        let f = break_even_fold(10.0, 10.0, 0.2);
+        assert!(bet_ev(10.0, 10.0, f, 0.2).abs() < 1e-9);
+        assert_eq!(break_even_fold(10.0, 10.0, 0.6), 0.0);
+    }
+
+    #[test]
+    fn test_against_range() {
+        // A nut flush draw on the turn, called only by sets.
+        let bet = Bet{
+            hole: [Card::from_code("AH").unwrap(), Card::from_code("5H").unwrap()],
+            board: "KH 9H 2C 7S".parse().unwrap(),
+            pot: 10.0,
+            bet: 10.0,
+        };
+        let range: Range = "KK,99,AK,KQ".parse().unwrap();
+        let sets: Range = "KK,99".parse().unwrap();
+
+        let r = bet.against_range(&range, &sets, 1000, &mut ChaCha8Rng::seed_from_u64(1)).unwrap();
+
+        // 3 + 3 sets out of 3 + 3 + 9 + 12 live combos.
+        assert!((r.fold - 21.0 / 27.0).abs() < 1e-9);
+        // Nine hearts but the board can pair: at most 9 of 44 rivers win.
+        assert!(r.equity > 0.1 && r.equity < 9.0 / 44.0 + 1e-9);
+        assert!((r.ev - bet.ev(r.fold, r.equity)).abs() < 1e-9);
+    }
+}
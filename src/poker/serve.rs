@@ -0,0 +1,206 @@
+//! The evaluator as a subprocess: newline-delimited JSON requests in,
+//! one JSON response per line out, in the same order.
+//!
+//! ```text
+//! > {"id":1,"method":"evaluate","params":{"cards":"AS AD 7S 8D 9H JC 2D"}}
+//! < {"id":1,"result":{"category":"OnePair","best":"AS AD JC 9H 8D",...}}
+//! > {"id":2,"method":"compare","params":{"a":"8C 8S KC 9H 9S","b":"KH KD 2C 3S 4D"}}
+//! < {"id":2,"result":{"order":1,"explanation":"Two Pairs, Nines and Eights beats ..."}}
+//! ```
+//!
+//! `evaluate` takes five to seven cards and describes the best five of
+//! them; `compare` takes two five card hands. Failures answer with
+//! `{"id":..,"error":{"code":..,"message":..}}`, the code being the one
+//! `Error::code` gives, and the loop carries on with the next line.
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::io::{self, BufRead, Write};
+
+use super::{Cards, Error, Hand};
+use super::deal::best_of;
+use super::json::{Json, JsonError};
+use super::notation::CardNotation;
+use super::parse::ParseOptions;
+
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum ProtocolError {
+    BadJson(JsonError),
+    UnknownMethod(String),
+    MissingParam(String),
+}
+
+impl fmt::Display for ProtocolError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ProtocolError::BadJson(e) => write!(f, "{}", e),
+            ProtocolError::UnknownMethod(m) => write!(f, "unknown method '{}'", m),
+            ProtocolError::MissingParam(p) => write!(f, "missing string param '{}'", p),
+        }
+    }
+}
+
+impl std::error::Error for ProtocolError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ProtocolError::BadJson(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+// Answers every line of `input` until it ends.
+pub fn serve<R: BufRead, W: Write>(input: R, output: &mut W) -> io::Result<()> {
+    serve_with(input, output, &ParseOptions::default().lenient(true))
+}
+
+/// `serve`, reading cards with `options`, as a config file may set them.
+pub fn serve_with<R: BufRead, W: Write>(input: R, output: &mut W, options: &ParseOptions) -> io::Result<()> {
+    for line in input.lines() {
+        let line = line?;
+        if line.trim().is_empty() { continue; }
+
+        writeln!(output, "{}", respond_with(&line, options))?;
+        output.flush()?;
+    }
+    Ok(())
+}
+
+pub fn respond(line: &str) -> Json {
+    respond_with(line, &ParseOptions::default().lenient(true))
+}
+
+pub fn respond_with(line: &str, options: &ParseOptions) -> Json {
+    let request = match Json::parse(line) {
+        Ok(request) => request,
+        Err(e) => return error(Json::Null, &ProtocolError::BadJson(e).into()),
+    };
+    let id = request.get("id").cloned().unwrap_or(Json::Null);
+
+    match handle(&request, options) {
+        Ok(result) => Json::object(vec![("id", id), ("result", result)]),
+        Err(e) => error(id, &e),
+    }
+}
+
+fn error(id: Json, e: &Error) -> Json {
+    Json::object(vec![
+        ("id", id),
+        ("error", Json::object(vec![
+            ("code", Json::Number(e.code() as f64)),
+            ("message", e.to_string().into()),
+        ])),
+    ])
+}
+
+fn handle(request: &Json, options: &ParseOptions) -> Result<Json, Error> {
+    let method = request.get("method").and_then(Json::as_str).unwrap_or("");
+    let param = |name: &str| -> Result<&str, Error> {
+        request.get("params")
+            .and_then(|p| p.get(name))
+            .and_then(Json::as_str)
+            .ok_or_else(|| ProtocolError::MissingParam(name.to_string()).into())
+    };
+
+    match method {
+        "evaluate" => {
+            let cards = Cards::parse_with(param("cards")?, options, 5..=7)?;
+            Ok(describe(&best_of(&cards)))
+        },
+        "compare" => {
+            let a = options.parse_hand(param("a")?)?;
+            let b = options.parse_hand(param("b")?)?;
+            a.compare_checked(&b)?;
+
+            let order = match a.cmp(&b) {
+                Ordering::Greater => 1.0,
+                Ordering::Equal => 0.0,
+                Ordering::Less => -1.0,
+            };
+            Ok(Json::object(vec![
+                ("order", order.into()),
+                ("explanation", a.explain_vs(&b).to_string().into()),
+            ]))
+        },
+        _ => Err(ProtocolError::UnknownMethod(method.to_string()).into()),
+    }
+}
+
+fn describe(hand: &Hand) -> Json {
+    let value = hand.value();
+    let ranks = |ranks: &[_]| {
+        Json::Array(ranks.iter().map(|&r| CardNotation::ENGLISH.rank_char(r).to_string().into()).collect())
+    };
+
+    Json::object(vec![
+        ("category", format!("{:?}", value.category()).into()),
+        ("best", format!("{:#}", hand).into()),
+        ("primary", ranks(value.primary_ranks())),
+        ("kickers", ranks(value.kickers())),
+        ("percentile", hand.percentile().into()),
+    ])
+}
+
+#[cfg(test)]
+mod serve_tests {
+    use super::*;
+
+    fn result(line: &str) -> Json {
+        respond(line).get("result").cloned().unwrap()
+    }
+
+    #[test]
+    fn test_evaluate() {
+        let r = result(r#"{"id":1,"method":"evaluate","params":{"cards":"as ad 7s 8d 9h jc 2d"}}"#);
+
+        assert_eq!(r.get("category").and_then(Json::as_str), Some("OnePair"));
+        assert_eq!(r.get("best").and_then(Json::as_str), Some("AS AD JC 9H 8D"));
+        assert_eq!(r.get("primary").unwrap().to_string(), r#"["A"]"#);
+        assert_eq!(r.get("kickers").unwrap().to_string(), r#"["J","9","8"]"#);
+    }
+
+    #[test]
+    fn test_compare() {
+        let r = result(r#"{"id":2,"method":"compare","params":{"a":"8C 8S KC 9H 9S","b":"KH KD 2C 3S 4D"}}"#);
+
+        assert_eq!(r.get("order"), Some(&Json::Number(1.0)));
+        assert!(r.get("explanation").and_then(Json::as_str).unwrap().contains("beats"));
+    }
+
+    #[test]
+    fn test_errors() {
+        let code = |line: &str| respond(line).get("error").and_then(|e| e.get("code")).and_then(Json::as_f64);
+
+        assert_eq!(code("{"), Some(7001.0));
+        assert_eq!(code(&"[".repeat(1_000_000)), Some(7001.0));
+        assert_eq!(code(r#"{"id":3,"method":"fold"}"#), Some(7002.0));
+        assert_eq!(code(r#"{"id":3,"method":"evaluate"}"#), Some(7003.0));
+        assert_eq!(code(r#"{"id":3,"method":"evaluate","params":{"cards":"AS"}}"#), Some(1005.0));
+        assert_eq!(
+            code(r#"{"id":3,"method":"compare","params":{"a":"AS KS QS JS TS","b":"AS 2C 3C 4C 5D"}}"#),
+            Some(2001.0)
+        );
+        assert_eq!(respond(r#"{"id":"x","method":"fold"}"#).get("id"), Some(&Json::from("x")));
+    }
+
+    #[test]
+    fn test_options() {
+        let line = r#"{"id":1,"method":"evaluate","params":{"cards":"bh dh kh ah th"}}"#;
+        let r = respond_with(line, &ParseOptions::german().lenient(true));
+
+        assert_eq!(r.get("result").and_then(|r| r.get("category")).and_then(Json::as_str), Some("RoyalFlush"));
+        assert!(respond(line).get("error").is_some());
+    }
+
+    #[test]
+    fn test_serve() {
+        let input = "{\"id\":1,\"method\":\"evaluate\",\"params\":{\"cards\":\"AS KS QS JS TS\"}}\n\n{\n";
+        let mut out = vec![];
+        serve(input.as_bytes(), &mut out).unwrap();
+        let text = String::from_utf8(out).unwrap();
+
+        assert_eq!(text.lines().count(), 2);
+        assert!(text.starts_with("{\"id\":1,\"result\":{\"category\":\"RoyalFlush\""));
+    }
+}
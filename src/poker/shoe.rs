@@ -0,0 +1,299 @@
+//! A dealing shoe: several decks shuffled together, with a cut card. The
+//! cut card goes in at the shoe's penetration, the share of the cards to
+//! be dealt before the next shuffle, and once the dealer reaches it
+//! `needs_reshuffle` says so. The hand in play is finished from the cards
+//! behind it; they are still dealt, but the shoe is shuffled after.
+//!
+//! Counting simulations depend on penetration: a count only pays as it
+//! gets deep into the shoe, and a casino's cut card keeps it from getting
+//! there.
+//!
+//! A shoe part way through is saved and restored exactly, the cards left
+//! with the decks, penetration and cut card, by `encode` or `to_json` as
+//! a `Deck` is.
+
+use std::fmt;
+
+use rand::Rng;
+use rand::seq::SliceRandom;
+
+use super::Card;
+use super::deck::{self, Deck, DeckDecodeError};
+use super::json::Json;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Shoe {
+    // Dealt from the end, like `Deck`.
+    cards: Vec<Card>,
+    decks: usize,
+    penetration: f64,
+    // How many cards are dealt before the cut card comes out.
+    cut: usize,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum ShoeDecodeError {
+    // The decks, penetration or cut card are missing, or do not fit
+    // together.
+    BadState,
+    // The cards left, with positions from the bottom card.
+    Cards(DeckDecodeError),
+}
+
+impl fmt::Display for ShoeDecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ShoeDecodeError::BadState => write!(f, "missing or inconsistent decks, penetration or cut card"),
+            ShoeDecodeError::Cards(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for ShoeDecodeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ShoeDecodeError::Cards(e) => Some(e),
+            ShoeDecodeError::BadState => None,
+        }
+    }
+}
+
+impl From<DeckDecodeError> for ShoeDecodeError {
+    fn from(e: DeckDecodeError) -> Self {
+        ShoeDecodeError::Cards(e)
+    }
+}
+
+impl Shoe {
+    /// `decks` full decks, unshuffled, with the cut card at `penetration`,
+    /// such as 0.75 to deal three quarters of the shoe.
+    ///
+    /// # Panics
+    ///
+    /// With no decks, or a penetration outside 0 to 1.
+    pub fn new(decks: usize, penetration: f64) -> Self {
+        assert!(decks > 0, "at least one deck");
+        let mut cards = Vec::with_capacity(52 * decks);
+        for _ in 0..decks {
+            cards.extend_from_slice(Deck::new().cards());
+        }
+
+        let mut shoe = Shoe{cards, decks, penetration: 0.0, cut: 0};
+        shoe.set_penetration(penetration);
+        shoe
+    }
+
+    pub fn decks(&self) -> usize {
+        self.decks
+    }
+
+    pub fn penetration(&self) -> f64 {
+        self.penetration
+    }
+
+    /// Moves the cut card to `penetration` of the full shoe, rounded down
+    /// to a whole card.
+    ///
+    /// # Panics
+    ///
+    /// With a penetration outside 0 to 1.
+    pub fn set_penetration(&mut self, penetration: f64) {
+        assert!((0.0..=1.0).contains(&penetration), "penetration between 0 and 1");
+        self.penetration = penetration;
+        self.cut = (penetration * self.capacity() as f64) as usize;
+    }
+
+    /// Puts the cut card behind the first `cards` cards dealt, as a player
+    /// cutting the shoe would, and sets the penetration to match.
+    pub fn place_cut_card(&mut self, cards: usize) {
+        self.cut = cards.min(self.capacity());
+        self.penetration = self.cut as f64 / self.capacity() as f64;
+    }
+
+    /// How many cards are dealt before the cut card comes out.
+    pub fn cut_card(&self) -> usize {
+        self.cut
+    }
+
+    /// Gathers every card back and shuffles them. The cut card stays where
+    /// it was put.
+    pub fn shuffle<R: Rng + ?Sized>(&mut self, rng: &mut R) {
+        self.cards.clear();
+        for _ in 0..self.decks {
+            self.cards.extend_from_slice(Deck::new().cards());
+        }
+        self.cards.shuffle(rng);
+    }
+
+    pub fn deal(&mut self) -> Option<Card> {
+        self.cards.pop()
+    }
+
+    pub fn capacity(&self) -> usize {
+        52 * self.decks
+    }
+
+    pub fn dealt(&self) -> usize {
+        self.capacity() - self.cards.len()
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.cards.len()
+    }
+
+    /// Whether the cut card has come out, so the shoe should be shuffled
+    /// once the hand in play is over.
+    pub fn needs_reshuffle(&self) -> bool {
+        self.dealt() >= self.cut
+    }
+
+    /// The decks, penetration, cut card and the exact order of the cards
+    /// left, as "6:0.75:234:" and then a base64 character per card from
+    /// the bottom up, as `Deck::encode` writes them. Shoes holding
+    /// `Rank::One` cannot be encoded.
+    pub fn encode(&self) -> Option<String> {
+        let cards = deck::encode_cards(&self.cards)?;
+        Some(format!("{}:{}:{}:{}", self.decks, self.penetration, self.cut, cards))
+    }
+
+    /// The shoe `encode` gave.
+    pub fn decode(s: &str) -> Result<Shoe, ShoeDecodeError> {
+        let mut parts = s.splitn(4, ':');
+        let mut field = || parts.next().ok_or(ShoeDecodeError::BadState);
+        let decks = field()?.parse().map_err(|_| ShoeDecodeError::BadState)?;
+        let penetration = field()?.parse().map_err(|_| ShoeDecodeError::BadState)?;
+        let cut = field()?.parse().map_err(|_| ShoeDecodeError::BadState)?;
+        let cards = field()?;
+
+        let decks = check_decks(decks)?;
+        let cards = deck::decode_cards(cards, decks)?;
+        Shoe::restore(cards, decks, penetration, cut)
+    }
+
+    /// The state `encode` saves as a JSON object, with the cards left as
+    /// an array of card codes, bottom first.
+    pub fn to_json(&self) -> Json {
+        Json::object(vec![
+            ("decks", Json::Number(self.decks as f64)),
+            ("penetration", Json::Number(self.penetration)),
+            ("cut", Json::Number(self.cut as f64)),
+            ("cards", Json::Array(self.cards.iter().map(|c| c.to_string().into()).collect())),
+        ])
+    }
+
+    pub fn from_json(json: &Json) -> Result<Shoe, ShoeDecodeError> {
+        let count = |key: &str| json.get(key).and_then(Json::as_f64)
+            .filter(|n| *n >= 0.0 && n.fract() == 0.0)
+            .map(|n| n as usize)
+            .ok_or(ShoeDecodeError::BadState);
+        let (decks, cut) = (check_decks(count("decks")?)?, count("cut")?);
+        let penetration = json.get("penetration").and_then(Json::as_f64).ok_or(ShoeDecodeError::BadState)?;
+        let codes = match json.get("cards") {
+            Some(Json::Array(codes)) => codes,
+            _ => return Err(DeckDecodeError::NotAnArray.into()),
+        };
+
+        let cards = deck::cards_from_json(codes, decks)?;
+        Shoe::restore(cards, decks, penetration, cut)
+    }
+
+    // A decoded shoe, checked for state `new` and dealing could have left.
+    fn restore(cards: Vec<Card>, decks: usize, penetration: f64, cut: usize) -> Result<Shoe, ShoeDecodeError> {
+        let shoe = Shoe{cards, decks, penetration, cut};
+        let fits = (0.0..=1.0).contains(&penetration)
+            && cut <= shoe.capacity()
+            && shoe.cards.len() <= shoe.capacity();
+        if fits { Ok(shoe) } else { Err(ShoeDecodeError::BadState) }
+    }
+}
+
+// A saved deck count, which must be at least one and small enough that
+// `capacity` cannot overflow.
+fn check_decks(decks: usize) -> Result<usize, ShoeDecodeError> {
+    match decks.checked_mul(52) {
+        Some(cards) if cards > 0 => Ok(decks),
+        _ => Err(ShoeDecodeError::BadState),
+    }
+}
+
+#[cfg(test)]
+mod shoe_tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    #[test]
+    fn test_cut_card() {
+        let mut rng = ChaCha8Rng::seed_from_u64(23);
+        let mut shoe = Shoe::new(6, 0.75);
+        shoe.shuffle(&mut rng);
+        assert_eq!((shoe.capacity(), shoe.cut_card()), (312, 234));
+
+        for _ in 0..233 {
+            shoe.deal().unwrap();
+        }
+        assert!(!shoe.needs_reshuffle());
+        shoe.deal().unwrap();
+        assert!(shoe.needs_reshuffle());
+        // The hand can still be finished.
+        assert!(shoe.deal().is_some());
+        assert_eq!((shoe.dealt(), shoe.remaining()), (235, 77));
+
+        shoe.shuffle(&mut rng);
+        assert_eq!(shoe.remaining(), 312);
+        assert!(!shoe.needs_reshuffle());
+    }
+
+    #[test]
+    fn test_placement() {
+        let mut shoe = Shoe::new(2, 0.5);
+        assert_eq!(shoe.cut_card(), 52);
+        shoe.place_cut_card(78);
+        assert_eq!(shoe.penetration(), 0.75);
+        shoe.set_penetration(1.0);
+        assert_eq!(shoe.cut_card(), 104);
+
+        // Every card of both decks, twice.
+        let mut cards: Vec<Card> = std::iter::from_fn(|| shoe.deal()).collect();
+        cards.sort_by_key(|c| (c.suit, c.rank));
+        cards.dedup();
+        assert_eq!(cards.len(), 52);
+    }
+
+    #[test]
+    fn test_encode() {
+        let mut rng = ChaCha8Rng::seed_from_u64(24);
+        let mut shoe = Shoe::new(6, 0.75);
+        shoe.shuffle(&mut rng);
+        for _ in 0..100 {
+            shoe.deal();
+        }
+
+        let saved = shoe.encode().unwrap();
+        assert!(saved.starts_with("6:0.75:234:"));
+        assert_eq!(saved.len(), "6:0.75:234:".len() + 212);
+        assert_eq!(Shoe::decode(&saved).unwrap(), shoe);
+        assert_eq!(Shoe::from_json(&Json::parse(&shoe.to_json().to_string()).unwrap()).unwrap(), shoe);
+
+        // A cut card placed by hand keeps its penetration.
+        shoe.place_cut_card(200);
+        let restored = Shoe::decode(&shoe.encode().unwrap()).unwrap();
+        assert_eq!((restored.cut_card(), restored.penetration()), (200, shoe.penetration()));
+        assert_eq!(restored.needs_reshuffle(), shoe.needs_reshuffle());
+
+        assert_eq!(Shoe::decode("6:0.75:234"), Err(ShoeDecodeError::BadState));
+        assert_eq!(Shoe::decode("0:0.75:0:"), Err(ShoeDecodeError::BadState));
+        assert_eq!(Shoe::decode("400000000000000000:0.5:0:"), Err(ShoeDecodeError::BadState));
+        assert_eq!(Shoe::decode(&format!("{}:0.5:0:", usize::MAX)), Err(ShoeDecodeError::BadState));
+        assert_eq!(Shoe::decode("1:1.5:0:"), Err(ShoeDecodeError::BadState));
+        assert_eq!(Shoe::decode("1:0.5:53:"), Err(ShoeDecodeError::BadState));
+        // A card twice in one deck.
+        let twice = DeckDecodeError::DuplicateCard(super::super::batch::card(0));
+        assert_eq!(Shoe::decode("1:0.5:26:AA"), Err(ShoeDecodeError::Cards(twice)));
+        assert_eq!(Shoe::decode("2:0.5:52:AA").unwrap().remaining(), 2);
+        assert_eq!(Shoe::from_json(&Json::parse(r#"{"decks": 1}"#).unwrap()), Err(ShoeDecodeError::BadState));
+        let huge = r#"{"decks": 1e300, "penetration": 0.5, "cut": 0, "cards": []}"#;
+        assert_eq!(Shoe::from_json(&Json::parse(huge).unwrap()), Err(ShoeDecodeError::BadState));
+    }
+}
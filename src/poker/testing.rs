@@ -0,0 +1,147 @@
+//! `proptest` strategies for the card types, enabled by the `testing`
+//! feature. Every multi-card strategy draws without replacement from a
+//! standard deck, so generated hands and boards never hold duplicates.
+//! `Card`, `Hand`, `Cards` and `Board` implement `Arbitrary`.
+
+use std::ops::RangeInclusive;
+
+use proptest::prelude::*;
+use proptest::sample::{select, subsequence};
+
+use super::{Board, Card, Cards, Hand};
+use super::deck::Deck;
+
+pub fn card() -> impl Strategy<Value = Card> {
+    select(Deck::new().cards().to_vec())
+}
+
+pub fn distinct_cards(n: usize) -> impl Strategy<Value = Vec<Card>> {
+    subsequence(Deck::new().cards().to_vec(), n).prop_shuffle()
+}
+
+pub fn hand() -> impl Strategy<Value = Hand> {
+    distinct_cards(5).prop_map(|c| Hand::new([c[0], c[1], c[2], c[3], c[4]]))
+}
+
+// Any number of cards within `sizes`, at most 52.
+pub fn cards(sizes: RangeInclusive<usize>) -> impl Strategy<Value = Cards> {
+    subsequence(Deck::new().cards().to_vec(), sizes)
+        .prop_shuffle()
+        .prop_map(|c| Cards::try_new(c).unwrap())
+}
+
+// A flop, turn or river board, or an empty preflop board.
+pub fn board() -> impl Strategy<Value = Board> {
+    prop_oneof![Just(0), Just(3), Just(4), Just(5)]
+        .prop_flat_map(distinct_cards)
+        .prop_map(|c| Board::try_new(c).unwrap())
+}
+
+impl Arbitrary for Card {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Card>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        card().boxed()
+    }
+}
+
+impl Arbitrary for Hand {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Hand>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        hand().boxed()
+    }
+}
+
+// Up to seven cards, as many as a stud hand.
+impl Arbitrary for Cards {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Cards>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        cards(0..=7).boxed()
+    }
+}
+
+impl Arbitrary for Board {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Board>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        board().boxed()
+    }
+}
+
+#[cfg(test)]
+mod testing_tests {
+    use super::*;
+    use super::super::starting::StartingHand;
+    use std::cmp::Ordering;
+
+    proptest! {
+        #[test]
+        fn test_distinct_cards(cards in distinct_cards(7)) {
+            for (i, a) in cards.iter().enumerate() {
+                prop_assert!(!cards[i + 1..].contains(a));
+            }
+        }
+
+        #[test]
+        fn test_board(board in any::<Board>()) {
+            prop_assert!([0, 3, 4, 5].contains(&board.len()));
+            prop_assert_eq!(Board::try_new(board.to_vec()), Ok(board));
+        }
+
+        #[test]
+        fn test_cards(cards in cards(2..=4), seven in any::<Cards>()) {
+            prop_assert!((2..=4).contains(&cards.len()));
+            prop_assert!(seven.len() <= 7);
+            prop_assert_eq!(Cards::try_new(seven.to_vec()), Ok(seven));
+        }
+
+        #[test]
+        fn test_cmp_antisymmetric(a in any::<Hand>(), b in any::<Hand>()) {
+            prop_assert_eq!(a.cmp(&b), b.cmp(&a).reverse());
+        }
+
+        #[test]
+        fn test_cmp_reflexive(a in any::<Hand>()) {
+            prop_assert_eq!(a.cmp(&a), Ordering::Equal);
+        }
+
+        // Output always writes 'T'; input takes 'T' or "10" and gives the
+        // same cards back either way.
+        #[test]
+        fn test_card_round_trip(card in any::<Card>()) {
+            let text = card.to_string();
+            prop_assert!(!text.contains("10"));
+            prop_assert_eq!(Card::from_code(&text), Some(card));
+            prop_assert_eq!(Card::from_code(&text.replace('T', "10")), Some(card));
+        }
+
+        #[test]
+        fn test_hand_round_trip(hand in any::<Hand>()) {
+            let text = hand.to_string();
+            let back: Hand = text.parse().unwrap();
+            prop_assert_eq!(back.to_string(), text.clone());
+            prop_assert_eq!(text.replace('T', "10").parse::<Hand>().unwrap().to_string(), text);
+        }
+
+        #[test]
+        fn test_board_round_trip(board in board()) {
+            let text = board.to_string();
+            prop_assert_eq!(text.parse::<Board>(), Ok(board.clone()));
+            prop_assert_eq!(text.replace('T', "10").parse::<Board>(), Ok(board));
+        }
+
+        #[test]
+        fn test_starting_hand_round_trip(cards in distinct_cards(2)) {
+            let hand = StartingHand::from_cards(&[cards[0], cards[1]]).unwrap();
+            let text = hand.to_string();
+            prop_assert_eq!(text.parse::<StartingHand>(), Ok(hand));
+            prop_assert_eq!(text.replace('T', "10").parse::<StartingHand>(), Ok(hand));
+        }
+    }
+}
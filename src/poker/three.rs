@@ -0,0 +1,145 @@
+//! Three card hands, for casino Three Card Poker and the front hand of Open
+//! Face Chinese. With three cards a straight is harder to make than a
+//! flush, so the casino order is straight flush, three of a kind, straight,
+//! flush, pair and high card. A-2-3 is the lowest straight and A-K-Q the
+//! highest.
+//!
+//! In Open Face Chinese the front hand counts only pairs and trips, which
+//! `ThreeCardValue::front` scores.
+
+use std::fmt;
+
+use super::{Card, Rank};
+
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug, Hash)]
+pub enum ThreeCardCategory {
+    HighCard,
+    Pair,
+    Flush,
+    Straight,
+    ThreeOfAKind,
+    StraightFlush,
+}
+
+/// The strength of a three card hand: the category, then the ranks that
+/// break ties, most significant first. Better hands compare greater.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ThreeCardValue {
+    category: ThreeCardCategory,
+    // For a pair the pair then the kicker, for a straight its top card
+    // three times, otherwise high to low.
+    ranks: [Rank; 3],
+}
+
+impl ThreeCardValue {
+    /// The value in the casino ordering.
+    pub fn new(cards: &[Card; 3]) -> Self {
+        ThreeCardValue::score(cards, true)
+    }
+
+    /// The value of an Open Face Chinese front hand, where straights and
+    /// flushes are only high cards.
+    pub fn front(cards: &[Card; 3]) -> Self {
+        ThreeCardValue::score(cards, false)
+    }
+
+    pub fn category(&self) -> ThreeCardCategory {
+        self.category
+    }
+
+    pub fn ranks(&self) -> [Rank; 3] {
+        self.ranks
+    }
+
+    fn score(cards: &[Card; 3], sequences: bool) -> Self {
+        let mut ranks = cards.map(|c| high(c.rank));
+        ranks.sort_unstable_by(|a, b| b.cmp(a));
+        let [a, b, c] = ranks;
+
+        let flush = sequences && cards.iter().all(|card| card.suit == cards[0].suit);
+        let straight = if !sequences || a == b || b == c {
+            None
+        } else if a == Rank::Ace && b == Rank::Three && c == Rank::Two {
+            Some(Rank::Three)
+        } else if a as u8 == b as u8 + 1 && b as u8 == c as u8 + 1 {
+            Some(a)
+        } else {
+            None
+        };
+
+        let (category, ranks) = match straight {
+            _ if a == c => (ThreeCardCategory::ThreeOfAKind, ranks),
+            Some(top) if flush => (ThreeCardCategory::StraightFlush, [top; 3]),
+            Some(top) => (ThreeCardCategory::Straight, [top; 3]),
+            None if a == b => (ThreeCardCategory::Pair, ranks),
+            None if b == c => (ThreeCardCategory::Pair, [b, c, a]),
+            None if flush => (ThreeCardCategory::Flush, ranks),
+            None => (ThreeCardCategory::HighCard, ranks),
+        };
+        ThreeCardValue{category, ranks}
+    }
+}
+
+impl fmt::Display for ThreeCardCategory {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            ThreeCardCategory::HighCard => "High Card",
+            ThreeCardCategory::Pair => "Pair",
+            ThreeCardCategory::Flush => "Flush",
+            ThreeCardCategory::Straight => "Straight",
+            ThreeCardCategory::ThreeOfAKind => "Three of a Kind",
+            ThreeCardCategory::StraightFlush => "Straight Flush",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+// Aces play high but for A-2-3.
+fn high(rank: Rank) -> Rank {
+    if rank == Rank::One { Rank::Ace } else { rank }
+}
+
+#[cfg(test)]
+mod three_tests {
+    use super::*;
+
+    fn cards(s: &str) -> [Card; 3] {
+        let cards: Vec<Card> = s.split_whitespace().map(|c| Card::from_code(c).unwrap()).collect();
+        [cards[0], cards[1], cards[2]]
+    }
+
+    fn value(s: &str) -> ThreeCardValue {
+        ThreeCardValue::new(&cards(s))
+    }
+
+    #[test]
+    fn test_categories() {
+        assert_eq!(value("AH 2H 3H").category(), ThreeCardCategory::StraightFlush);
+        assert_eq!(value("7C 7D 7S").category(), ThreeCardCategory::ThreeOfAKind);
+        assert_eq!(value("QH KD AS").category(), ThreeCardCategory::Straight);
+        assert_eq!(value("2H 9H JH").category(), ThreeCardCategory::Flush);
+        assert_eq!(value("5C 5D KS").category(), ThreeCardCategory::Pair);
+        assert_eq!(value("KH AD 2S").category(), ThreeCardCategory::HighCard);
+        assert_eq!(value("QH KH AH").ranks(), [Rank::Ace; 3]);
+        assert_eq!(value("AD 3C 2S").ranks(), [Rank::Three; 3]);
+    }
+
+    #[test]
+    fn test_order() {
+        let order = ["KH AD 2S", "5C 5D KS", "5C 5D AS", "2H 9H JH", "AD 2C 3S", "2D 3C 4S", "QH KD AS", "2C 2D 2S", "AH 2H 3H", "QH KH AH"];
+        for pair in order.windows(2) {
+            assert!(value(pair[0]) < value(pair[1]), "{} < {}", pair[0], pair[1]);
+        }
+        assert!(value("9C 9D 2S") > value("8C 8D AS"));
+        assert_eq!(value("9C 9D 2S"), value("9H 2C 9S"));
+    }
+
+    #[test]
+    fn test_front() {
+        let front = |s: &str| ThreeCardValue::front(&cards(s));
+        assert_eq!(front("AH 2H 3H").category(), ThreeCardCategory::HighCard);
+        assert_eq!(front("QH KD AS").category(), ThreeCardCategory::HighCard);
+        assert_eq!(front("5C 5D KS").category(), ThreeCardCategory::Pair);
+        assert!(front("2C 2D 2S") > front("AC AD KS"));
+    }
+}
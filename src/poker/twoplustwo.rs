@@ -0,0 +1,374 @@
+//! A precomputed state table for seven card hands, after the one built on
+//! the Two Plus Two forums, enabled by the `twoplustwo` feature. Once the
+//! table is in memory a hand of five to seven cards is evaluated with one
+//! array lookup per card and no other work.
+//!
+//! Each state is a set of cards seen so far, with the suits that can no
+//! longer make a flush forgotten so that equivalent sets share a state.
+//! States have 53 entries: the value of the cards so far in entry 0, once
+//! there are five or more, and the offset of the next state for each of
+//! the 52 cards. From the last but one card the entries give values
+//! directly. Values are `lookup` classes, 1 for a royal flush, 0 marking a
+//! card that cannot follow.
+//!
+//! The seven card table holds about 32 million entries, some 130MB, and
+//! takes a while to build, so it is built once with `build_table` and then
+//! loaded with `TwoPlusTwo::load`.
+
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{self, BufReader, Read, Write};
+use std::path::Path;
+
+use super::{Card, Rank};
+use super::atomic;
+use super::format::Header;
+use super::lookup;
+use super::value::HandValue;
+
+const KIND: &str = "hand-ranks";
+// 2: values are classes numbered from the strongest, not the weakest.
+const VERSION: u32 = 2;
+
+// The largest value an entry can hold, the weakest `lookup` class.
+const MAX_VALUE: u32 = 7462;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct TwoPlusTwo {
+    // The most cards a hand can have, 5 to 7.
+    cards: usize,
+    entries: Vec<u32>,
+}
+
+/// Builds the seven card table and writes it to `path`.
+pub fn build_table<P: AsRef<Path>>(path: P) -> io::Result<TwoPlusTwo> {
+    let table = TwoPlusTwo::build(7);
+    atomic::write(path, |w| table.write(w))?;
+    Ok(table)
+}
+
+// A card as it is kept in a state: the rank from 1 in the high nibble and
+// the suit from 1 in the low, suit 0 once it cannot make a flush.
+fn byte(card: u8) -> u8 {
+    let (r, s) = ((card - 1) / 4, (card - 1) % 4 + 1);
+    (r + 1) << 4 | s
+}
+
+// Table index of a card, 1 to 52; None for `Rank::One`.
+fn index(card: &Card) -> Option<usize> {
+    let r = (card.rank as usize).checked_sub(Rank::Two as usize)?;
+    Some(r * 4 + card.suit.index() + 1)
+}
+
+fn unpack(id: u64) -> Vec<u8> {
+    (0..8).map(|i| (id >> (8 * i)) as u8).take_while(|&b| b != 0).collect()
+}
+
+// The state reached from `id` with one more card, or None if the card is
+// already there or would be the fifth of its rank.
+fn next_id(id: u64, card: u8, max: usize) -> Option<u64> {
+    let mut cards = unpack(id);
+    let new = byte(card);
+    if cards.contains(&new) { return None; }
+    if cards.iter().filter(|&&c| c >> 4 == new >> 4).count() == 4 { return None; }
+    cards.push(new);
+
+    let left = max - cards.len();
+    for suit in 1..=4 {
+        let n = cards.iter().filter(|&&c| c & 0xF == suit).count();
+        if n > 0 && n + left < 5 {
+            for c in cards.iter_mut().filter(|c| **c & 0xF == suit) {
+                *c &= 0xF0;
+            }
+        }
+    }
+
+    cards.sort_unstable_by(|a, b| b.cmp(a));
+    Some(cards.iter().rev().fold(0, |id, &c| id << 8 | c as u64))
+}
+
+// Best class of five or more cards, never 0.
+fn value_of(id: u64) -> u32 {
+    let codes: Vec<u32> = unpack(id).iter()
+        .map(|&c| {
+            let suit = match c & 0xF { 0 => 0, s => 0x1000 << (s - 1) };
+            lookup::pack((c >> 4) as u32 - 1, suit)
+        })
+        .collect();
+
+    lookup::best_class(&codes) as u32
+}
+
+impl TwoPlusTwo {
+    /// A table for hands of up to `cards` cards, five to seven. Smaller
+    /// tables are far quicker to build, and only evaluate that many cards.
+    pub fn build(cards: usize) -> Self {
+        assert!((5..=7).contains(&cards), "five to seven cards");
+
+        // Every state with fewer cards than the most, level by level.
+        let mut ids = vec![0u64];
+        let mut level = vec![0u64];
+        for _ in 1..cards {
+            let mut next = HashSet::new();
+            for &id in &level {
+                next.extend((1..=52).filter_map(|c| next_id(id, c, cards)));
+            }
+            level = next.into_iter().collect();
+            ids.extend(&level);
+        }
+        ids.sort_unstable();
+
+        let mut entries = vec![0u32; ids.len() * 53];
+        for (k, &id) in ids.iter().enumerate() {
+            let n = unpack(id).len();
+            let state = &mut entries[k * 53..(k + 1) * 53];
+
+            if n >= 5 { state[0] = value_of(id); }
+            for c in 1..=52u8 {
+                state[c as usize] = match next_id(id, c, cards) {
+                    None => 0,
+                    Some(next) if n + 1 == cards => value_of(next),
+                    Some(next) => ids.binary_search(&next).unwrap() as u32 * 53,
+                };
+            }
+        }
+
+        TwoPlusTwo{cards, entries}
+    }
+
+    pub fn cards(&self) -> usize {
+        self.cards
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// The value of the best five of `cards`, as `best_hand` gives it.
+    ///
+    /// # Panics
+    ///
+    /// If there are fewer than five cards or more than the table was built
+    /// for, or a card is `Rank::One` or repeated. States forget suits, so
+    /// repeats are checked before the table is walked.
+    pub fn evaluate(&self, cards: &[Card]) -> HandValue {
+        assert!((5..=self.cards).contains(&cards.len()), "five to {} cards", self.cards);
+
+        let mut seen = 0u64;
+        let mut p = 0;
+        for card in cards {
+            let i = index(card).expect("a card from the deck");
+            assert!(seen & 1 << i == 0, "{} repeated", card);
+            seen |= 1 << i;
+            p = self.entries[p as usize + i];
+            assert!(p != 0, "no state after {}", card);
+        }
+        if cards.len() < self.cards {
+            p = self.entries[p as usize];
+        }
+        lookup::value(p as u16)
+    }
+
+    pub fn write<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        Header::new(KIND, VERSION)
+            .field("cards", self.cards)
+            .field("entries", self.entries.len())
+            .write(w)?;
+
+        for e in &self.entries {
+            w.write_all(&e.to_le_bytes())?;
+        }
+        Ok(())
+    }
+
+    pub fn read<R: Read>(r: R) -> io::Result<Self> {
+        let invalid = |e: String| io::Error::new(io::ErrorKind::InvalidData, e);
+        let mut r = BufReader::new(r);
+
+        let mut first = vec![];
+        io::BufRead::read_until(&mut r, b'\n', &mut first)?;
+        let header = Header::parse(&String::from_utf8_lossy(&first)).map_err(|e| invalid(e.to_string()))?;
+        header.check(KIND, VERSION).map_err(|e| invalid(e.to_string()))?;
+
+        let field = |key: &str| header.get(key).and_then(|v| v.parse::<usize>().ok())
+            .ok_or_else(|| invalid(format!("missing {}", key)));
+        let (cards, len) = (field("cards")?, field("entries")?);
+        if !(5..=7).contains(&cards) || len % 53 != 0 {
+            return Err(invalid(format!("bad table of {} entries for {} cards", len, cards)));
+        }
+
+        let mut bytes = vec![];
+        r.read_to_end(&mut bytes)?;
+        if bytes.len() != len * 4 {
+            return Err(invalid(format!("expected {} entries, found {} bytes", len, bytes.len())));
+        }
+        let entries: Vec<u32> = bytes.chunks_exact(4).map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]])).collect();
+        check(cards, &entries).map_err(invalid)?;
+
+        Ok(TwoPlusTwo{cards, entries})
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        TwoPlusTwo::read(File::open(path)?)
+    }
+}
+
+// Walks every state reachable from the first, level by level, so that a
+// corrupt or mismatched file fails to load instead of `evaluate` indexing
+// out of the table or reading a value that is not a class. Before the last
+// level the card entries must be offsets of states one card further on;
+// on it they must be values. States of five cards or more need a value.
+fn check(cards: usize, entries: &[u32]) -> Result<(), String> {
+    let states = entries.len() / 53;
+    if states == 0 { return Err("empty table".to_string()); }
+
+    let mut level_of = vec![u8::MAX; states];
+    level_of[0] = 0;
+    let mut level = vec![0];
+    for n in 0..cards {
+        let mut next = vec![];
+        for &k in &level {
+            let state = &entries[k * 53..(k + 1) * 53];
+            if n >= 5 && !(1..=MAX_VALUE).contains(&state[0]) {
+                return Err(format!("state {} has value {}", k, state[0]));
+            }
+
+            for &e in state[1..].iter().filter(|&&e| e != 0) {
+                if n + 1 == cards {
+                    if e > MAX_VALUE { return Err(format!("state {} has value {}", k, e)); }
+                    continue;
+                }
+
+                let j = e as usize / 53;
+                if e as usize % 53 != 0 || j >= states {
+                    return Err(format!("state {} has offset {}", k, e));
+                }
+                match level_of[j] {
+                    u8::MAX => { level_of[j] = n as u8 + 1; next.push(j); },
+                    l if l as usize == n + 1 => {},
+                    l => return Err(format!("state {} reached with both {} and {} cards", j, l, n + 1)),
+                }
+            }
+        }
+        level = next;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod twoplustwo_tests {
+    use super::*;
+    use super::super::{best_hand, Hand};
+    use super::super::deck::Deck;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    fn cards(s: &str) -> Vec<Card> {
+        s.split_whitespace().map(|c| Card::from_code(c).unwrap()).collect()
+    }
+
+    #[test]
+    fn test_five() {
+        let table = TwoPlusTwo::build(5);
+        let mut rng = ChaCha8Rng::seed_from_u64(3);
+
+        for _ in 0..5_000 {
+            let mut deck = Deck::new();
+            deck.shuffle(&mut rng);
+            let hand = deck.deal_hand().unwrap();
+            assert_eq!(table.evaluate(hand.cards()), hand.value(), "{}", hand);
+        }
+        let royal: Hand = "TS JS QS KS AS".parse().unwrap();
+        assert_eq!(table.evaluate(royal.cards()), royal.value());
+    }
+
+    #[test]
+    fn test_six() {
+        let table = TwoPlusTwo::build(6);
+        let mut rng = ChaCha8Rng::seed_from_u64(4);
+
+        for n in [5, 6] {
+            for _ in 0..2_000 {
+                let mut deck = Deck::new();
+                deck.shuffle(&mut rng);
+                let dealt: Vec<Card> = (0..n).map(|_| deck.deal().unwrap()).collect();
+                assert_eq!(table.evaluate(&dealt), best_hand(&dealt).1);
+            }
+        }
+        // A flush only shows up with the sixth card.
+        let hand = cards("2H 7H 9C KH QH 4H");
+        assert_eq!(table.evaluate(&hand), best_hand(&hand).1);
+    }
+
+    #[test]
+    fn test_seven() {
+        let table = TwoPlusTwo::build(7);
+        let mut rng = ChaCha8Rng::seed_from_u64(5);
+
+        for n in [5, 6, 7] {
+            for _ in 0..2_000 {
+                let mut deck = Deck::new();
+                deck.shuffle(&mut rng);
+                let dealt: Vec<Card> = (0..n).map(|_| deck.deal().unwrap()).collect();
+                assert_eq!(table.evaluate(&dealt), best_hand(&dealt).1);
+            }
+        }
+        let hand = cards("2H 7H 9C KH QH 4D 3H");
+        assert_eq!(table.evaluate(&hand), best_hand(&hand).1);
+
+        let mut out = vec![];
+        table.write(&mut out).unwrap();
+        assert_eq!(TwoPlusTwo::read(&out[..]).unwrap(), table);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_repeated() {
+        TwoPlusTwo::build(5).evaluate(&cards("AS AS KD QD JD"));
+    }
+
+    // Once a suit can no longer make a flush the state forgets it, so the
+    // second 2H would be taken for another Two without the up-front check.
+    #[test]
+    #[should_panic(expected = "2H repeated")]
+    fn test_repeated_after_suit_forgotten() {
+        TwoPlusTwo::build(5).evaluate(&cards("2H 3S 2H 4D 5C"));
+    }
+
+    #[test]
+    fn test_read_corrupt() {
+        let table = TwoPlusTwo::build(5);
+        let mut out = vec![];
+        table.write(&mut out).unwrap();
+        let body = out.iter().position(|&b| b == b'\n').unwrap() + 1;
+
+        let corrupt = |entry: usize, value: u32| {
+            let mut bytes = out.clone();
+            bytes[body + entry * 4..body + entry * 4 + 4].copy_from_slice(&value.to_le_bytes());
+            TwoPlusTwo::read(&bytes[..])
+        };
+        let len = table.len() as u32;
+
+        // An offset past the end, one between states, and a value too large.
+        assert!(corrupt(1, len).is_err());
+        assert!(corrupt(1, 54).is_err());
+        let last = table.entries.iter().rposition(|&e| e != 0).unwrap();
+        assert!(corrupt(last, MAX_VALUE + 1).is_err());
+        assert!(corrupt(last, MAX_VALUE).is_ok());
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let table = TwoPlusTwo::build(5);
+        let mut out = vec![];
+        table.write(&mut out).unwrap();
+
+        assert!(out.starts_with(b"# misc-poker hand-ranks v2 cards=5 entries="));
+        assert_eq!(TwoPlusTwo::read(&out[..]).unwrap(), table);
+        assert!(TwoPlusTwo::read(&out[..out.len() - 1]).is_err());
+    }
+}
@@ -0,0 +1,246 @@
+use super::{Board, Category, Rank};
+use super::explain;
+use super::lookup::Lookup;
+
+/// How many distinct values a five card hand from one deck can take.
+pub const CLASSES: u16 = 7462;
+
+/// The full strength of a five card hand: its category, then the ranks that
+/// break ties within the category, most significant first. For a two pair
+/// hand that is the high pair, the low pair and the kicker; for a straight
+/// just its top card. Values are totally ordered, and two hands are equally
+/// strong exactly when their values are equal.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct HandValue {
+    category: Category,
+    ranks: [Rank; 5],
+    len: u8,
+}
+
+impl HandValue {
+    pub fn new(category: Category, tiebreaks: &[Rank]) -> Self {
+        assert!(tiebreaks.len() <= 5, "at most five tiebreak ranks");
+
+        // Unused slots are padded with the lowest rank. Values of the same
+        // category always have the same number of tiebreaks, so the padding
+        // never decides a comparison.
+        let mut ranks = [Rank::One; 5];
+        ranks[..tiebreaks.len()].copy_from_slice(tiebreaks);
+
+        HandValue{category, ranks, len: tiebreaks.len() as u8}
+    }
+
+    pub fn category(&self) -> Category {
+        self.category
+    }
+
+    pub fn tiebreaks(&self) -> &[Rank] {
+        &self.ranks[..self.len as usize]
+    }
+
+    // How many tiebreaks belong to the made hand rather than the kickers.
+    fn made_len(&self) -> usize {
+        match self.category {
+            Category::Straight
+            | Category::Flush
+            | Category::StraightFlush
+            | Category::RoyalFlush => self.len as usize,
+            Category::HighCard
+            | Category::OnePair
+            | Category::ThreeOfAKind
+            | Category::FourOfAKind
+            | Category::FiveOfAKind => 1,
+            Category::TwoPairs | Category::FullHouse => 2,
+        }
+    }
+
+    // The ranks that make the category: the pair, both pairs high first,
+    // the trips then the pair of a full house. Straights give their top
+    // card and flushes every rank.
+    pub fn primary_ranks(&self) -> &[Rank] {
+        &self.tiebreaks()[..self.made_len()]
+    }
+
+    // The ranks left over, high to low; none for the five card categories.
+    pub fn kickers(&self) -> &[Rank] {
+        &self.tiebreaks()[self.made_len()..]
+    }
+
+    pub fn is_at_least(&self, category: Category) -> bool {
+        self.category >= category
+    }
+
+    // One pair, of the highest rank on the board, where the board does not
+    // hold the pair itself.
+    pub fn is_top_pair(&self, board: &Board) -> bool {
+        let top = match board.iter().map(|c| c.rank).max() {
+            Some(top) => top,
+            None => return false,
+        };
+        self.category == Category::OnePair
+            && self.ranks[0] == top
+            && board.iter().filter(|c| c.rank == top).count() == 1
+    }
+
+    // One pair above every card on the board, which only a pocket pair can
+    // make. Preflop there is nothing to be over.
+    pub fn is_overpair(&self, board: &Board) -> bool {
+        self.category == Category::OnePair
+            && !board.is_empty()
+            && board.iter().all(|c| c.rank < self.ranks[0])
+    }
+
+    /// The hand's place among the 7462 distinct five card hands in the
+    /// numbering other evaluators and datasets share, after Cactus Kev:
+    /// 1 for a royal flush down to 7462 for 7-5-4-3-2 in mixed suits.
+    /// None for hands one deck cannot deal, such as five of a kind.
+    pub fn class_index(&self) -> Option<u16> {
+        standard().binary_search_by(|v| self.cmp(v)).ok().map(|i| i as u16 + 1)
+    }
+
+    /// The value with the given `class_index`. None outside 1..=7462.
+    pub fn from_class_index(index: u16) -> Option<HandValue> {
+        standard().get((index as usize).checked_sub(1)?).copied()
+    }
+}
+
+// The classes in the standard order, strongest first, which is the
+// order `lookup` numbers them in.
+fn standard() -> &'static [HandValue] {
+    &Lookup::get().tables().values
+}
+
+/// Names a `class_index` in full, ranks past the made hand included, as in
+/// "Two Pairs, Nines and Eights with King". None outside 1..=7462.
+pub fn class_description(index: u16) -> Option<String> {
+    let value = HandValue::from_class_index(index)?;
+    let rest = match value.category {
+        Category::Flush | Category::HighCard => &value.tiebreaks()[1..],
+        _ => value.kickers(),
+    };
+
+    let mut text = explain::describe(&value);
+    if !rest.is_empty() {
+        let names: Vec<&str> = rest.iter().map(|&r| explain::name(r)).collect();
+        text.push_str(&format!(" with {}", names.join(" ")));
+    }
+    Some(text)
+}
+
+#[cfg(test)]
+mod value_tests {
+    use super::*;
+    use super::super::Hand;
+    use std::str::FromStr;
+
+    fn value(s: &str) -> HandValue {
+        Hand::from_str(s).unwrap().value()
+    }
+
+    #[test]
+    fn test_tiebreaks() {
+        assert_eq!(
+            value("8C 8S KC 9H 9S").tiebreaks(),
+            &[Rank::Nine, Rank::Eight, Rank::King]
+        );
+        assert_eq!(
+            value("2H 2D 4C 4D 4S").tiebreaks(),
+            &[Rank::Four, Rank::Two]
+        );
+        assert_eq!(value("3D 4D 5D 6D 7D").tiebreaks(), &[Rank::Seven]);
+        assert_eq!(
+            value("7D 2S 5D 3S AC").tiebreaks(),
+            &[Rank::Ace, Rank::Seven, Rank::Five, Rank::Three, Rank::Two]
+        );
+    }
+
+    #[test]
+    fn test_primary_and_kickers() {
+        let pair = value("KH KD AC TS 4D");
+        assert_eq!(pair.primary_ranks(), &[Rank::King]);
+        assert_eq!(pair.kickers(), &[Rank::Ace, Rank::Ten, Rank::Four]);
+
+        let two_pair = value("8C 8S KC 9H 9S");
+        assert_eq!(two_pair.primary_ranks(), &[Rank::Nine, Rank::Eight]);
+        assert_eq!(two_pair.kickers(), &[Rank::King]);
+
+        let full_house = value("2H 2D 4C 4D 4S");
+        assert_eq!(full_house.primary_ranks(), &[Rank::Four, Rank::Two]);
+        assert!(full_house.kickers().is_empty());
+
+        assert_eq!(value("3D 6D 7D TD QD").primary_ranks().len(), 5);
+        assert!(value("3D 4D 5D 6D 7D").kickers().is_empty());
+        assert_eq!(value("7D 2S 5D 3S AC").primary_ranks(), &[Rank::Ace]);
+    }
+
+    #[test]
+    fn test_is_at_least() {
+        assert!(value("2H 2D 4C 4D 4S").is_at_least(Category::Flush));
+        assert!(value("2H 2D 4C 4D 4S").is_at_least(Category::FullHouse));
+        assert!(!value("2H 2D 4C 4D 4S").is_at_least(Category::FourOfAKind));
+    }
+
+    #[test]
+    fn test_board_patterns() {
+        let board: Board = "KD 7S 2C".parse().unwrap();
+
+        assert!(value("KH QC KD 7S 2C").is_top_pair(&board));
+        assert!(!value("7H QC KD 7S 2C").is_top_pair(&board));
+        assert!(!value("KH KC KD 7S 2C").is_top_pair(&board));
+        assert!(value("AH AC KD 7S 2C").is_overpair(&board));
+        assert!(!value("QH QC KD 7S 2C").is_overpair(&board));
+        assert!(!value("AH AC KD 7S 2C").is_overpair(&Board::new()));
+
+        // The kings are on the board, so nobody has top pair.
+        let paired: Board = "KD KS 2C".parse().unwrap();
+        assert!(!value("AH QC KD KS 2C").is_top_pair(&paired));
+    }
+
+    #[test]
+    fn test_ordering() {
+        // The kicker only plays once both pairs are equal.
+        assert!(value("AH AD 3C 3S 4D") > value("AS AC 2C 2S KD"));
+        assert!(value("AH AD 3C 3S 4D") < value("AS AC 3H 3D 5D"));
+        assert!(value("5H 5C 6S 7S KD") < value("2C 3S 8S 8D TD"));
+        assert!(value("KH KD 9C 8S 2D") > value("KS KC 9H 7D 6C"));
+        assert_eq!(value("2H 3H 5C 8D KS"), value("2C 3C 5H 8S KD"));
+        assert_eq!(value("2H 3H 5C 8D KS").category(), Category::HighCard);
+    }
+
+    #[test]
+    fn test_class_index() {
+        let index = |s: &str| value(s).class_index().unwrap();
+
+        assert_eq!(index("AS KS QS JS TS"), 1);
+        assert_eq!(index("6S 5S 4S 3S 2S"), 9);
+        assert_eq!(index("AS 5S 4S 3S 2S"), 10);
+        assert_eq!(index("AH AD AC AS KS"), 11);
+        assert_eq!(index("AS AD AH KS KD"), 167);
+        assert_eq!(index("AS KS QS JS 9S"), 323);
+        assert_eq!(index("AS KD QS JS TS"), 1600);
+        assert_eq!(index("AS 5D 4S 3S 2S"), 1609);
+        assert_eq!(index("AH AD AC KS QS"), 1610);
+        assert_eq!(index("AH AD KC KS QS"), 2468);
+        assert_eq!(index("AH AD KC QS JS"), 3326);
+        assert_eq!(index("AH KD QC JS 9S"), 6186);
+        assert_eq!(index("7H 5D 4C 3S 2S"), CLASSES);
+
+        let five = super::super::parse::ParseOptions::default().allow_duplicates(true).parse_hand("9C 9C 9C 9C 9C").unwrap();
+        assert_eq!(five.value().class_index(), None);
+
+        for i in 1..=CLASSES {
+            assert_eq!(HandValue::from_class_index(i).unwrap().class_index(), Some(i));
+        }
+        assert_eq!(HandValue::from_class_index(0), None);
+        assert_eq!(HandValue::from_class_index(CLASSES + 1), None);
+    }
+
+    #[test]
+    fn test_class_description() {
+        assert_eq!(class_description(1).unwrap(), "Royal Flush");
+        assert_eq!(class_description(10).unwrap(), "Straight Flush, Five high");
+        assert_eq!(class_description(2468).unwrap(), "Two Pairs, Aces and Kings with Queen");
+        assert_eq!(class_description(CLASSES).unwrap(), "High Card, Seven with Five Four Three Two");
+        assert_eq!(class_description(0), None);
+    }
+}
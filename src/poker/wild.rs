@@ -0,0 +1,164 @@
+//! Hands with wild cards: jokers, or cards named wild such as every Two in
+//! deuces wild. A wild card stands for whatever card makes the best hand,
+//! even one already in the hand, so five of a kind is possible. Jokers are
+//! counted apart from the cards since a `Card` cannot be one.
+//!
+//! Only the ranks of the stand-ins need trying. A flush is never worse than
+//! the same ranks in mixed suits, so every wild card takes the suit of the
+//! natural cards when they share one, and any suit when they do not.
+
+use super::{Card, Rank, Suit};
+use super::backend::Backend;
+use super::value::HandValue;
+
+/// The red, black and white jokers of the Unicode Playing Cards block.
+pub const JOKERS: [char; 3] = ['\u{1F0BF}', '\u{1F0CF}', '\u{1F0DF}'];
+
+/// Cards and a count of jokers from playing-card glyphs, ready for
+/// `WildCards::evaluate`. Whitespace is skipped. None on any other
+/// character, knights included, see `Card::from_char`.
+pub fn parse_glyphs(s: &str) -> Option<(Vec<Card>, usize)> {
+    let mut cards = vec![];
+    let mut jokers = 0;
+    for c in s.chars().filter(|c| !c.is_whitespace()) {
+        if JOKERS.contains(&c) {
+            jokers += 1;
+        } else {
+            cards.push(Card::from_char(c)?);
+        }
+    }
+    Some((cards, jokers))
+}
+
+/// Which cards are wild.
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+pub struct WildCards {
+    cards: Vec<Card>,
+}
+
+impl WildCards {
+    /// No wild cards, only jokers.
+    pub fn none() -> Self {
+        WildCards::default()
+    }
+
+    pub fn new(cards: &[Card]) -> Self {
+        WildCards{cards: cards.to_vec()}
+    }
+
+    /// Every card of `ranks`.
+    pub fn ranks(ranks: &[Rank]) -> Self {
+        let cards = ranks.iter()
+            .flat_map(|&rank| Suit::ALL.iter().map(move |&suit| Card{rank, suit}))
+            .collect();
+        WildCards{cards}
+    }
+
+    pub fn deuces() -> Self {
+        WildCards::ranks(&[Rank::Two])
+    }
+
+    pub fn is_wild(&self, card: &Card) -> bool {
+        self.cards.contains(card)
+    }
+
+    /// The value of the best hand that `cards` and `jokers` make, with
+    /// every card scored by `backend`.
+    ///
+    /// # Panics
+    ///
+    /// If there are not five cards and jokers in all.
+    pub fn evaluate(&self, cards: &[Card], jokers: usize, backend: Backend) -> HandValue {
+        assert_eq!(cards.len() + jokers, 5, "five cards and jokers");
+
+        let naturals: Vec<Card> = cards.iter().filter(|c| !self.is_wild(c)).copied().collect();
+        if naturals.len() == 5 {
+            return backend.evaluate(&[naturals[0], naturals[1], naturals[2], naturals[3], naturals[4]]);
+        }
+
+        let suit = naturals.first().map_or(Suit::Spades, |c| c.suit);
+        let mut hand = [Card{rank: Rank::Ace, suit}; 5];
+        hand[..naturals.len()].copy_from_slice(&naturals);
+
+        let mut best = None;
+        fill(&mut hand, naturals.len(), 0, backend, &mut best);
+        best.unwrap()
+    }
+}
+
+// Tries the stand-ins from `at` on as ranks in order from `Rank::DECK[from]`,
+// since which wild card stands for which rank makes no difference.
+fn fill(hand: &mut [Card; 5], at: usize, from: usize, backend: Backend, best: &mut Option<HandValue>) {
+    if at == 5 {
+        *best = (*best).max(Some(backend.evaluate(hand)));
+        return;
+    }
+    for i in from..Rank::DECK.len() {
+        hand[at].rank = Rank::DECK[i];
+        fill(hand, at + 1, i, backend, best);
+    }
+}
+
+#[cfg(test)]
+mod wild_tests {
+    use super::*;
+    use super::super::{Category, Hand};
+
+    fn cards(s: &str) -> Vec<Card> {
+        s.split_whitespace().map(|c| Card::from_code(c).unwrap()).collect()
+    }
+
+    fn each_backend(wild: &WildCards, s: &str, jokers: usize) -> HandValue {
+        let values: Vec<HandValue> = Backend::ALL.iter().map(|&b| wild.evaluate(&cards(s), jokers, b)).collect();
+        assert!(values.windows(2).all(|w| w[0] == w[1]), "backends differ on {}", s);
+        values[0]
+    }
+
+    #[test]
+    fn test_deuces() {
+        let deuces = WildCards::deuces();
+
+        let five = each_backend(&deuces, "2H 2D AS AC AD", 0);
+        assert_eq!(five.category(), Category::FiveOfAKind);
+        assert_eq!(five.tiebreaks(), &[Rank::Ace]);
+        assert_eq!(each_backend(&deuces, "2C KH QH JH TH", 0).category(), Category::RoyalFlush);
+
+        let straight_flush = each_backend(&deuces, "2C 3H 4H 5H 6H", 0);
+        assert_eq!(straight_flush.category(), Category::StraightFlush);
+        assert_eq!(straight_flush.tiebreaks(), &[Rank::Seven]);
+
+        assert_eq!(each_backend(&deuces, "2C 9D 9H 5S 5H", 0).category(), Category::FullHouse);
+        assert_eq!(each_backend(&deuces, "2C 9D 7H 5S 4H", 0).category(), Category::OnePair);
+        assert_eq!(each_backend(&deuces, "2C 9D 9H 5S 4H", 0).category(), Category::ThreeOfAKind);
+        assert_eq!(each_backend(&deuces, "2C 2D 2H 2S 4H", 0).category(), Category::FiveOfAKind);
+    }
+
+    #[test]
+    fn test_jokers() {
+        let none = WildCards::none();
+
+        assert_eq!(each_backend(&none, "AH KH QH JH", 1).category(), Category::RoyalFlush);
+        assert_eq!(each_backend(&none, "9C 9D 4S", 2).category(), Category::FourOfAKind);
+        assert_eq!(each_backend(&none, "", 5), HandValue::new(Category::FiveOfAKind, &[Rank::Ace]));
+
+        let hand = "KC 9D 7H 5S 4H";
+        assert_eq!(each_backend(&none, hand, 0), hand.parse::<Hand>().unwrap().value());
+        assert!(!WildCards::ranks(&[Rank::Two, Rank::Three]).is_wild(&Card::from_code("4H").unwrap()));
+    }
+
+    #[test]
+    fn test_parse_glyphs() {
+        for &joker in &JOKERS {
+            assert_eq!(parse_glyphs(&joker.to_string()), Some((vec![], 1)));
+        }
+
+        let (naturals, jokers) = parse_glyphs("🂱 🂾 🂽 🂻 \u{1F0CF}").unwrap();
+        assert_eq!(naturals, cards("AH KH QH JH"));
+        assert_eq!(jokers, 1);
+        assert_eq!(each_backend(&WildCards::none(), "AH KH QH JH", jokers).category(), Category::RoyalFlush);
+
+        assert_eq!(parse_glyphs("\u{1F0BF}\u{1F0DF}🂡"), Some((cards("AS"), 2)));
+        assert_eq!(parse_glyphs("🂡\u{1F0AC}"), None);
+        assert_eq!(parse_glyphs("AS"), None);
+    }
+}